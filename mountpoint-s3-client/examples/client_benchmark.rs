@@ -35,7 +35,7 @@ fn run_benchmark(client: impl ObjectClient + Clone, num_iterations: usize, bucke
         let start = Instant::now();
         futures::executor::block_on(async move {
             let mut request = client
-                .get_object(bucket, key, None, None)
+                .get_object(bucket, key, &Default::default())
                 .await
                 .expect("couldn't create get request");
             let mut request = pin!(request);
@@ -117,6 +117,7 @@ fn main() {
                 bucket: BUCKET.to_owned(),
                 part_size: args.part_size,
                 unordered_list_seed: None,
+                ..Default::default()
             };
             let client = ThroughputMockClient::new(config, args.throughput_target_gbps);
             let client = Arc::new(client);
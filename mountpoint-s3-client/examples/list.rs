@@ -1,4 +1,5 @@
 use mountpoint_s3_client::config::{EndpointConfig, S3ClientConfig};
+use mountpoint_s3_client::types::ListObjectsParams;
 use mountpoint_s3_client::{ObjectClient, S3CrtClient};
 use mountpoint_s3_crt::common::rust_log_adapter::RustLogAdapter;
 
@@ -38,7 +39,16 @@ fn main() {
     let client = S3CrtClient::new(S3ClientConfig::new().endpoint_config(EndpointConfig::new(region)))
         .expect("couldn't create client");
 
-    let result = futures::executor::block_on(client.list_objects(bucket, None, delimiter, 500, prefix)).unwrap();
+    let result = futures::executor::block_on(
+        client.list_objects(
+            bucket,
+            &ListObjectsParams::new()
+                .prefix(prefix)
+                .delimiter(delimiter)
+                .max_keys(500),
+        ),
+    )
+    .unwrap();
 
     for object in result.objects {
         println!("{object:?}");
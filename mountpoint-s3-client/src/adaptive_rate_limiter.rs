@@ -0,0 +1,227 @@
+//! A client-side rate limiter that adapts to throttling responses from S3.
+//!
+//! This follows the "adaptive" retry mode of the AWS SDKs. The limiter does nothing until S3
+//! throttles a request. From then on, it limits the rate that new requests start to a fraction of
+//! the rate they were being sent when the throttling happened, and then grows the rate again along
+//! a cubic curve (as in TCP CUBIC) as requests succeed, so that the client backs off quickly but
+//! recovers its previous rate after a while.
+//!
+//! The CRT splits each meta request into as many HTTP requests as it needs (one per part, plus
+//! retries), and we can only delay the start of the whole meta request. So rather than taking a
+//! token when a meta request starts, every HTTP request is charged a token when S3 responds to it,
+//! which can leave the bucket in debt, and new meta requests wait until the debt is paid off. This
+//! keeps the rate in the same unit (HTTP requests per second) that the limiter measures and S3
+//! throttles.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_io::Timer;
+
+/// Smoothing factor for the measured send rate
+const SMOOTH: f64 = 0.8;
+/// How much to reduce the rate by when throttled
+const BETA: f64 = 0.7;
+/// How aggressively to grow the rate after throttling
+const SCALE_CONSTANT: f64 = 0.4;
+/// The lowest rate the limiter will throttle down to, in requests per second
+const MIN_FILL_RATE: f64 = 0.5;
+/// The smallest burst the limiter allows
+const MIN_CAPACITY: f64 = 1.0;
+
+#[derive(Debug)]
+pub(crate) struct AdaptiveRateLimiter {
+    start: Instant,
+    state: Mutex<State>,
+}
+
+impl AdaptiveRateLimiter {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Wait until a new meta request can be started
+    pub(crate) async fn acquire(&self) {
+        let mut total_wait = Duration::ZERO;
+        // Other requests can be charged while we wait, so check again after every wait
+        loop {
+            let wait = self.state.lock().unwrap().acquire(self.now());
+            let Some(wait) = wait else { break };
+            total_wait += wait;
+            Timer::after(wait).await;
+        }
+        if !total_wait.is_zero() {
+            metrics::histogram!("s3.client.adaptive_throttling_delay_us").record(total_wait.as_micros() as f64);
+        }
+    }
+
+    /// Charge an HTTP request that S3 responded to, and update the allowed rate. The request was
+    /// throttled if S3 returned a 503 Slow Down error.
+    pub(crate) fn update(&self, throttled: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.update(self.now(), throttled);
+        if state.enabled {
+            metrics::gauge!("s3.client.adaptive_throttling_rate").set(state.fill_rate);
+        }
+    }
+
+    fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+}
+
+/// The state of the limiter. All times are in seconds since the limiter was created.
+#[derive(Debug, Default)]
+struct State {
+    /// Whether the limiter has seen any throttling yet
+    enabled: bool,
+    /// Rate at which the token bucket refills, in requests per second
+    fill_rate: f64,
+    max_capacity: f64,
+    /// Tokens in the bucket, which is negative if requests used more tokens than were available
+    current_capacity: f64,
+    last_refill: Option<f64>,
+    /// Smoothed measurement of how many requests per second we're sending
+    measured_tx_rate: f64,
+    last_tx_rate_bucket: f64,
+    request_count: u64,
+    /// The rate we were sending at when we were last throttled
+    last_max_rate: f64,
+    last_throttle_time: f64,
+    /// How long after being throttled the cubic curve gets back to `last_max_rate`
+    time_window: f64,
+}
+
+impl State {
+    /// Check whether a new meta request can start, returning how long the caller needs to wait
+    /// for the bucket's debt to be paid off if it can't. Starting doesn't take a token itself; the
+    /// HTTP requests it makes are charged as they complete.
+    fn acquire(&mut self, now: f64) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        self.refill(now);
+        if self.current_capacity >= 0.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(-self.current_capacity / self.fill_rate))
+        }
+    }
+
+    fn refill(&mut self, now: f64) {
+        if let Some(last_refill) = self.last_refill {
+            let fill_amount = (now - last_refill) * self.fill_rate;
+            self.current_capacity = (self.current_capacity + fill_amount).min(self.max_capacity);
+        }
+        self.last_refill = Some(now);
+    }
+
+    fn update(&mut self, now: f64, throttled: bool) {
+        self.update_measured_rate(now);
+
+        let calculated_rate = if throttled {
+            let rate_to_use = if self.enabled {
+                self.measured_tx_rate.min(self.fill_rate)
+            } else {
+                self.measured_tx_rate
+            };
+            self.last_max_rate = rate_to_use;
+            self.time_window = (self.last_max_rate * (1.0 - BETA) / SCALE_CONSTANT).cbrt();
+            self.last_throttle_time = now;
+            self.enabled = true;
+            rate_to_use * BETA
+        } else {
+            if !self.enabled {
+                return;
+            }
+            let elapsed = now - self.last_throttle_time - self.time_window;
+            SCALE_CONSTANT * elapsed.powi(3) + self.last_max_rate
+        };
+
+        let new_rate = calculated_rate.min(2.0 * self.measured_tx_rate);
+        self.refill(now);
+        self.fill_rate = new_rate.max(MIN_FILL_RATE);
+        self.max_capacity = new_rate.max(MIN_CAPACITY);
+        self.current_capacity = self.current_capacity.min(self.max_capacity) - 1.0;
+    }
+
+    /// Measure the rate requests are sent in half-second buckets
+    fn update_measured_rate(&mut self, now: f64) {
+        let time_bucket = (now * 2.0).floor() / 2.0;
+        self.request_count += 1;
+        if time_bucket > self.last_tx_rate_bucket {
+            let current_rate = self.request_count as f64 / (time_bucket - self.last_tx_rate_bucket);
+            self.measured_tx_rate = current_rate * SMOOTH + self.measured_tx_rate * (1.0 - SMOOTH);
+            self.request_count = 0;
+            self.last_tx_rate_bucket = time_bucket;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Send `rate` requests per second for `seconds`, updating the limiter with each response
+    fn send(state: &mut State, start: f64, seconds: u32, rate: u32, throttled: impl Fn(f64) -> bool) -> f64 {
+        let mut now = start;
+        for _ in 0..seconds * rate {
+            now += 1.0 / rate as f64;
+            state.update(now, throttled(now));
+        }
+        now
+    }
+
+    #[test]
+    fn disabled_until_throttled() {
+        let mut state = State::default();
+        let now = send(&mut state, 0.0, 5, 100, |_| false);
+        assert!(!state.enabled);
+        assert_eq!(state.acquire(now), None);
+    }
+
+    #[test]
+    fn backs_off_and_recovers() {
+        let mut state = State::default();
+        let now = send(&mut state, 0.0, 5, 100, |_| false);
+
+        // Throttling cuts the rate to a fraction of what we were sending
+        state.update(now, true);
+        assert!(state.enabled);
+        assert!(
+            (60.0..80.0).contains(&state.fill_rate),
+            "rate should be reduced: {}",
+            state.fill_rate
+        );
+        let throttled_rate = state.fill_rate;
+
+        // Every response is charged, so a burst of them leaves the bucket in debt and new requests
+        // have to wait, however few meta requests they belonged to
+        for _ in 0..(throttled_rate as usize + 10) {
+            state.update(now, false);
+        }
+        let wait = state.acquire(now).expect("bucket should be in debt");
+        assert!(state.acquire(now + wait.as_secs_f64() + 0.001).is_none());
+
+        // The rate recovers after requests keep succeeding
+        send(&mut state, now, 10, throttled_rate as u32, |_| false);
+        assert!(
+            state.fill_rate > throttled_rate,
+            "rate should have grown: {} <= {}",
+            state.fill_rate,
+            throttled_rate
+        );
+    }
+
+    #[test]
+    fn repeated_throttling() {
+        let mut state = State::default();
+        let now = send(&mut state, 0.0, 5, 100, |_| false);
+        // Every response is throttled, so the rate keeps dropping until it hits the minimum
+        send(&mut state, now, 20, 10, |_| true);
+        assert_eq!(state.fill_rate, MIN_FILL_RATE);
+    }
+}
@@ -1,10 +1,16 @@
-//! Provides base64 encoding/decoding for CRC32C checksums.
-use mountpoint_s3_crt::checksums::crc32c::Crc32c;
+//! Provides base64 encoding/decoding for CRC32C and CRC32 checksums, and incremental computation of
+//! checksums with any of the algorithms S3 supports.
+use mountpoint_s3_crt::checksums::crc32::{self, Crc32};
+use mountpoint_s3_crt::checksums::crc32c::{self, Crc32c};
 
 use base64ct::Base64;
 use base64ct::Encoding;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+use crate::object_client::ChecksumAlgorithm;
+
 /// The base64 encoding for this CRC32C checksum value.
 pub fn crc32c_to_base64(checksum: &Crc32c) -> String {
     Base64::encode_string(&checksum.value().to_be_bytes())
@@ -17,6 +23,85 @@ pub fn crc32c_from_base64(base64_str: &str) -> Result<Crc32c, ParseError> {
     Ok(Crc32c::new(u32::from_be_bytes(dec_buf)))
 }
 
+/// The base64 encoding for this CRC32 checksum value.
+pub fn crc32_to_base64(checksum: &Crc32) -> String {
+    Base64::encode_string(&checksum.value().to_be_bytes())
+}
+
+/// Create a CRC32 checksum from a base64 encoding.
+pub fn crc32_from_base64(base64_str: &str) -> Result<Crc32, ParseError> {
+    let mut dec_buf = [0u8; std::mem::size_of::<u32>()];
+    let _ = Base64::decode(base64_str, &mut dec_buf)?;
+    Ok(Crc32::new(u32::from_be_bytes(dec_buf)))
+}
+
+/// The name S3 uses for a checksum algorithm.
+pub(crate) fn checksum_algorithm_name(algorithm: ChecksumAlgorithm) -> &'static str {
+    match algorithm {
+        ChecksumAlgorithm::Crc32c => "CRC32C",
+        ChecksumAlgorithm::Crc32 => "CRC32",
+        ChecksumAlgorithm::Sha1 => "SHA1",
+        ChecksumAlgorithm::Sha256 => "SHA256",
+    }
+}
+
+/// Incrementally computes a checksum with one of the algorithms S3 supports, and encodes it in base64
+/// the way S3 does in `x-amz-checksum-*` headers and upload reviews.
+#[derive(Debug, Clone)]
+pub enum ChecksumHasher {
+    Crc32c(crc32c::Hasher),
+    Crc32(crc32::Hasher),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl ChecksumHasher {
+    /// Create a new hasher for the given algorithm.
+    pub fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => Self::Crc32c(crc32c::Hasher::new()),
+            ChecksumAlgorithm::Crc32 => Self::Crc32(crc32::Hasher::new()),
+            ChecksumAlgorithm::Sha1 => Self::Sha1(Sha1::new()),
+            ChecksumAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+        }
+    }
+
+    /// The algorithm this hasher computes.
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        match self {
+            Self::Crc32c(_) => ChecksumAlgorithm::Crc32c,
+            Self::Crc32(_) => ChecksumAlgorithm::Crc32,
+            Self::Sha1(_) => ChecksumAlgorithm::Sha1,
+            Self::Sha256(_) => ChecksumAlgorithm::Sha256,
+        }
+    }
+
+    /// Update the hash state with the given bytes slice.
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Crc32c(hasher) => hasher.update(data),
+            Self::Crc32(hasher) => hasher.update(data),
+            Self::Sha1(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Finalize the hash state and return the checksum in big-endian bytes.
+    pub fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Crc32c(hasher) => hasher.finalize().value().to_be_bytes().to_vec(),
+            Self::Crc32(hasher) => hasher.finalize().value().to_be_bytes().to_vec(),
+            Self::Sha1(hasher) => hasher.finalize().to_vec(),
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+
+    /// Finalize the hash state and return the base64 encoding of the checksum.
+    pub fn finalize_base64(self) -> String {
+        Base64::encode_string(&self.finalize())
+    }
+}
+
 /// Error parsing CRC32C checksums.
 #[derive(Error, Debug)]
 pub enum ParseError {
@@ -44,6 +129,27 @@ mod tests {
         assert_eq!(crc.value(), 1234);
     }
 
+    #[test]
+    fn test_crc32_base64_round_trip() {
+        let crc = Crc32::new(1234);
+        let base64 = crc32_to_base64(&crc);
+        assert_eq!(&base64, "AAAE0g==");
+        let crc = crc32_from_base64(&base64).expect("parsing should succeeed");
+        assert_eq!(crc.value(), 1234);
+    }
+
+    #[test_case(ChecksumAlgorithm::Crc32c, "yZRlqg=="; "crc32c")]
+    #[test_case(ChecksumAlgorithm::Crc32, "DUoRhQ=="; "crc32")]
+    #[test_case(ChecksumAlgorithm::Sha1, "Kq5sNclPz7QV2+lfQIuc6R7oRu0="; "sha1")]
+    #[test_case(ChecksumAlgorithm::Sha256, "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek="; "sha256")]
+    fn test_checksum_hasher(algorithm: ChecksumAlgorithm, expected: &str) {
+        let mut hasher = ChecksumHasher::new(algorithm);
+        assert_eq!(hasher.algorithm(), algorithm);
+        hasher.update(b"hello ");
+        hasher.update(b"world");
+        assert_eq!(hasher.finalize_base64(), expected);
+    }
+
     #[test_case("AAA")]
     #[test_case("AAAE0g")]
     #[test_case("AAAE0gAA==")]
@@ -30,7 +30,8 @@ impl AuthScheme {
         &self.signing_name
     }
 
-    /// Get the signing region from [AuthScheme]
+    /// Get the signing region from [AuthScheme]. For SigV4A schemes, this is a comma-separated
+    /// set of regions, or `*` for all regions.
     pub fn signing_region(&self) -> &str {
         &self.signing_region
     }
@@ -227,17 +228,23 @@ impl ResolvedEndpointInfo {
         let signing_name = auth_scheme_value["signingName"]
             .as_str()
             .ok_or_else(|| EndpointError::MissingAuthSchemeField("signingName"))?;
-        let signing_region = auth_scheme_value
-            .get("signingRegion")
-            .or_else(|| auth_scheme_value["signingRegionSet"].get(0))
-            .and_then(|t| t.as_str())
-            .ok_or_else(|| EndpointError::MissingAuthSchemeField("signingRegion or signingRegionSet"))?;
+        // SigV4A schemes (e.g. for Multi-Region Access Points) have a set of regions instead of a
+        // single region, which the signer takes as a comma-separated list.
+        let signing_region = match auth_scheme_value.get("signingRegion").and_then(|t| t.as_str()) {
+            Some(region) => region.to_owned(),
+            None => auth_scheme_value["signingRegionSet"]
+                .as_array()
+                .and_then(|regions| regions.iter().map(|t| t.as_str()).collect::<Option<Vec<_>>>())
+                .filter(|regions| !regions.is_empty())
+                .map(|regions| regions.join(","))
+                .ok_or_else(|| EndpointError::MissingAuthSchemeField("signingRegion or signingRegionSet"))?,
+        };
 
         Ok(AuthScheme {
             disable_double_encoding,
             scheme_name,
             signing_name: signing_name.to_owned(),
-            signing_region: signing_region.to_owned(),
+            signing_region,
         })
     }
 }
@@ -428,6 +435,24 @@ mod test {
         assert_eq!(signing_region, "*");
         let signing_name = endpoint_auth_scheme.signing_name();
         assert_eq!(signing_name, "s3");
+        assert_eq!(endpoint_auth_scheme.scheme_name(), SigningAlgorithm::SigV4A);
+    }
+
+    #[test]
+    fn test_auth_scheme_for_object_lambda_arn() {
+        let endpoint_config = EndpointConfig::new("eu-west-1");
+        let resolved_endpoint = endpoint_config
+            .resolve_for_bucket("arn:aws:s3-object-lambda:us-west-2:123456789012:accesspoint/olap-test")
+            .unwrap();
+        let endpoint_uri = resolved_endpoint.uri().unwrap();
+        assert_eq!(
+            "https://olap-test-123456789012.s3-object-lambda.us-west-2.amazonaws.com",
+            endpoint_uri.as_os_str()
+        );
+        let endpoint_auth_scheme = resolved_endpoint.auth_scheme().unwrap();
+        assert_eq!(endpoint_auth_scheme.signing_region(), "us-west-2");
+        assert_eq!(endpoint_auth_scheme.signing_name(), "s3-object-lambda");
+        assert_eq!(endpoint_auth_scheme.scheme_name(), SigningAlgorithm::SigV4);
     }
 
     #[test]
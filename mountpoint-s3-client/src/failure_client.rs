@@ -4,7 +4,6 @@
 
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::ops::Range;
 use std::pin::Pin;
 use std::sync::Mutex;
 use std::task::{Context, Poll};
@@ -14,10 +13,15 @@ use futures::Stream;
 use pin_project::pin_project;
 
 use crate::object_client::{
-    DeleteObjectError, DeleteObjectResult, ETag, GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult,
-    GetObjectError, HeadObjectError, HeadObjectResult, ListObjectsError, ListObjectsResult, ObjectAttribute,
-    ObjectClientError, ObjectClientResult, PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult,
-    UploadReview,
+    AbortMultipartUploadError, AbortMultipartUploadResult, CompleteMultipartUploadError, CompleteMultipartUploadResult,
+    CompletedPart, CopyObjectError, CopyObjectParams, CopyObjectResult, CreateMultipartUploadError,
+    CreateMultipartUploadResult, DeleteObjectError, DeleteObjectResult, DeleteObjectsError, DeleteObjectsResult,
+    GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult, GetObjectError, GetObjectParams,
+    GetObjectRequest, GetObjectResponse, HeadObjectError, HeadObjectParams, HeadObjectResult,
+    ListMultipartUploadsError, ListMultipartUploadsResult, ListObjectVersionsError, ListObjectVersionsResult,
+    ListObjectsError, ListObjectsParams, ListObjectsResult, ObjectAttribute, ObjectClientError, ObjectClientResult,
+    ObjectIdentifier, PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult, RestoreObjectError,
+    RestoreObjectResult, RestoreTier, UploadPartError, UploadPartResult, UploadReview,
 };
 use crate::ObjectClient;
 
@@ -31,12 +35,13 @@ pub struct FailureRequestWrapper<Client: ObjectClient, RequestWrapperState> {
 pub struct FailureClient<Client: ObjectClient, State, RequestWrapperState> {
     pub client: Client,
     pub state: Mutex<State>,
+    pub copy_object_cb:
+        fn(&mut State, &str, &str, &str, &str) -> Result<(), ObjectClientError<CopyObjectError, Client::ClientError>>,
     pub get_object_cb: fn(
         &mut State,
         &str,
         &str,
-        Option<Range<u64>>,
-        Option<ETag>,
+        &GetObjectParams,
     ) -> Result<
         FailureRequestWrapper<Client, RequestWrapperState>,
         ObjectClientError<GetObjectError, Client::ClientError>,
@@ -46,11 +51,10 @@ pub struct FailureClient<Client: ObjectClient, State, RequestWrapperState> {
     pub list_objects_cb: fn(
         &mut State,
         &str,
-        Option<&str>,
-        &str,
-        usize,
-        &str,
+        &ListObjectsParams,
     ) -> Result<(), ObjectClientError<ListObjectsError, Client::ClientError>>,
+    pub list_object_versions_cb:
+        fn(&mut State, &str, &str) -> Result<(), ObjectClientError<ListObjectVersionsError, Client::ClientError>>,
     pub put_object_cb: fn(
         &mut State,
         &str,
@@ -77,6 +81,26 @@ where
         self.client.part_size()
     }
 
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        (self.copy_object_cb)(
+            &mut *self.state.lock().unwrap(),
+            source_bucket,
+            source_key,
+            destination_bucket,
+            destination_key,
+        )?;
+        self.client
+            .copy_object(source_bucket, source_key, destination_bucket, destination_key, params)
+            .await
+    }
+
     async fn delete_object(
         &self,
         bucket: &str,
@@ -86,21 +110,23 @@ where
         self.client.delete_object(bucket, key).await
     }
 
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        // TODO failure hook for delete_objects
+        self.client.delete_objects(bucket, objects).await
+    }
+
     async fn get_object(
         &self,
         bucket: &str,
         key: &str,
-        range: Option<Range<u64>>,
-        if_match: Option<ETag>,
+        params: &GetObjectParams,
     ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
-        let wrapper = (self.get_object_cb)(
-            &mut *self.state.lock().unwrap(),
-            bucket,
-            key,
-            range.clone(),
-            if_match.clone(),
-        )?;
-        let get_result = self.client.get_object(bucket, key, range, if_match).await?;
+        let wrapper = (self.get_object_cb)(&mut *self.state.lock().unwrap(), bucket, key, params)?;
+        let get_result = self.client.get_object(bucket, key, params).await?;
         Ok(FailureGetResult {
             state: wrapper.state,
             result_fn: wrapper.result_fn,
@@ -111,22 +137,41 @@ where
     async fn list_objects(
         &self,
         bucket: &str,
-        continuation_token: Option<&str>,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+        (self.list_objects_cb)(&mut *self.state.lock().unwrap(), bucket, params)?;
+
+        self.client.list_objects(bucket, params).await
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
         delimiter: &str,
         max_keys: usize,
         prefix: &str,
-    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
-        (self.list_objects_cb)(
-            &mut *self.state.lock().unwrap(),
-            bucket,
-            continuation_token,
-            delimiter,
-            max_keys,
-            prefix,
-        )?;
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        (self.list_object_versions_cb)(&mut *self.state.lock().unwrap(), bucket, prefix)?;
+
+        self.client
+            .list_object_versions(bucket, key_marker, version_id_marker, delimiter, max_keys, prefix)
+            .await
+    }
 
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
+        // TODO failure hook for list_multipart_uploads
         self.client
-            .list_objects(bucket, continuation_token, delimiter, max_keys, prefix)
+            .list_multipart_uploads(bucket, key_marker, upload_id_marker, delimiter, max_uploads, prefix)
             .await
     }
 
@@ -134,9 +179,10 @@ where
         &self,
         bucket: &str,
         key: &str,
+        params: &HeadObjectParams,
     ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
         (self.head_object_cb)(&mut *self.state.lock().unwrap(), bucket, key)?;
-        self.client.head_object(bucket, key).await
+        self.client.head_object(bucket, key, params).await
     }
 
     async fn put_object(
@@ -154,6 +200,53 @@ where
         })
     }
 
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        // TODO failure hook for create_multipart_upload
+        self.client.create_multipart_upload(bucket, key, params).await
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        // TODO failure hook for upload_part
+        self.client
+            .upload_part(bucket, key, upload_id, part_number, contents)
+            .await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        // TODO failure hook for complete_multipart_upload
+        self.client
+            .complete_multipart_upload(bucket, key, upload_id, parts)
+            .await
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        // TODO failure hook for abort_multipart_upload
+        self.client.abort_multipart_upload(bucket, key, upload_id).await
+    }
+
     async fn get_object_attributes(
         &self,
         bucket: &str,
@@ -167,6 +260,17 @@ where
             .get_object_attributes(bucket, key, max_parts, part_number_marker, object_attributes)
             .await
     }
+
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        // TODO failure hook for restore_object
+        self.client.restore_object(bucket, key, days, tier).await
+    }
 }
 
 #[pin_project]
@@ -187,6 +291,22 @@ impl<Client: ObjectClient, FailState> Stream for FailureGetResult<Client, FailSt
     }
 }
 
+impl<Client: ObjectClient, FailState: Send> GetObjectRequest for FailureGetResult<Client, FailState> {
+    type ClientError = Client::ClientError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        self.get_result.get_object_response()
+    }
+
+    fn increment_read_window(self: Pin<&mut Self>, len: usize) {
+        self.project().get_result.increment_read_window(len);
+    }
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        self.project_ref().get_result.read_window_end_offset()
+    }
+}
+
 pub struct FailurePutObjectRequest<Client: ObjectClient, PutWrapperState> {
     request: Client::PutObjectRequest,
     state: PutWrapperState,
@@ -285,7 +405,8 @@ pub fn countdown_failure_client<Client: ObjectClient>(
     FailureClient {
         client,
         state,
-        get_object_cb: |state, _bucket, _key, _range, _if_match| {
+        copy_object_cb: |_state, _source_bucket, _source_key, _destination_bucket, _destination_key| Ok(()),
+        get_object_cb: |state, _bucket, _key, _params| {
             state.get_count += 1;
             let (fail_count, error) = if let Some(result) = state.get_results.remove(&state.get_count) {
                 let (fail_count, error) = result?;
@@ -317,7 +438,7 @@ pub fn countdown_failure_client<Client: ObjectClient>(
                 Ok(())
             }
         },
-        list_objects_cb: |state, _bucket, _ct, _delim, _max_keys, _prefix| {
+        list_objects_cb: |state, _bucket, _params| {
             state.list_count += 1;
             if let Some(error) = state.list_failures.remove(&state.list_count) {
                 Err(error)
@@ -325,6 +446,7 @@ pub fn countdown_failure_client<Client: ObjectClient>(
                 Ok(())
             }
         },
+        list_object_versions_cb: |_state, _bucket, _prefix| Ok(()),
         put_object_cb: |state, _bucket, _key, _params| {
             state.put_count += 1;
             let (fail_count, error) = if let Some(result) = state.put_results.remove(&state.put_count) {
@@ -356,6 +478,7 @@ pub fn countdown_failure_client<Client: ObjectClient>(
 mod tests {
     use super::*;
     use crate::mock_client::{MockClient, MockClientConfig, MockClientError, MockObject};
+    use crate::object_client::ETag;
     use std::collections::HashSet;
 
     #[tokio::test]
@@ -367,6 +490,7 @@ mod tests {
             bucket: bucket.to_string(),
             part_size: 128,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let body = vec![0u8; 50];
@@ -393,7 +517,7 @@ mod tests {
 
         let fail_set = HashSet::from([2, 4, 5]);
         for i in 1..=6 {
-            let r = fail_client.get_object(bucket, key, None, None).await;
+            let r = fail_client.get_object(bucket, key, &Default::default()).await;
             if fail_set.contains(&i) {
                 assert!(r.is_err());
             } else {
@@ -122,6 +122,12 @@ impl LeakyBucket {
     pub async fn acquire(&self, amount: u32) {
         self.inner.acquire(amount).await;
     }
+
+    /// The maximum number of tokens this bucket can hold, which is the most that can be acquired at
+    /// once.
+    pub fn max(&self) -> u32 {
+        self.inner.max
+    }
 }
 
 /// Builder for a leaky bucket.
@@ -21,7 +21,7 @@
 //!
 //! let client = S3CrtClient::new(Default::default()).expect("client construction failed");
 //!
-//! let response = client.get_object("my-bucket", "my-key", None, None).await.expect("get_object failed");
+//! let response = client.get_object("my-bucket", "my-key", &Default::default()).await.expect("get_object failed");
 //! let body = response.map_ok(|(offset, body)| body.to_vec()).try_concat().await.expect("body streaming failed");
 //! # }
 //! ```
@@ -43,6 +43,7 @@
 // Make async trait docs not-ugly on docs.rs (https://github.com/dtolnay/async-trait/issues/213)
 #![cfg_attr(docs_rs, feature(async_fn_in_trait))]
 
+mod adaptive_rate_limiter;
 mod build_info;
 pub mod checksums;
 mod endpoint_config;
@@ -50,30 +51,42 @@ mod endpoint_config;
 pub mod failure_client;
 pub mod imds_crt_client;
 pub mod instance_info;
+mod leaky_bucket;
 #[doc(hidden)]
 pub mod mock_client;
 mod object_client;
+pub mod rate_limited_client;
+pub mod recording_client;
+pub mod replay_client;
 mod s3_crt_client;
 #[doc(hidden)]
 pub mod user_agent;
 mod util;
 
-pub use object_client::{ObjectClient, PutObjectRequest};
+pub use object_client::{GetObjectRequest, ObjectClient, PutObjectRequest};
 
 pub use s3_crt_client::{get_object::S3GetObjectRequest, put_object::S3PutObjectRequest, S3CrtClient, S3RequestError};
 
 /// Configuration for the S3 client
 pub mod config {
     pub use super::endpoint_config::{AddressingStyle, EndpointConfig};
-    pub use super::s3_crt_client::{S3ClientAuthConfig, S3ClientConfig};
+    pub use super::s3_crt_client::{RetryConfig, S3ClientAuthConfig, S3ClientConfig, TlsConfig};
+    pub use mountpoint_s3_crt::http::proxy::{ProxyConnectionType, ProxyOptions};
+    pub use mountpoint_s3_crt::io::retry_strategy::ExponentialBackoffJitterMode;
+    pub use mountpoint_s3_crt::io::tls::{TlsContext, TlsContextOptions};
 }
 
 /// Types used by all object clients
 pub mod types {
     pub use super::object_client::{
-        Checksum, DeleteObjectResult, ETag, GetBodyPart, GetObjectAttributesParts, GetObjectAttributesResult,
-        HeadObjectResult, ListObjectsResult, ObjectAttribute, ObjectClientResult, ObjectInfo, ObjectPart,
-        PutObjectParams, PutObjectResult, RestoreStatus, UploadReview, UploadReviewPart,
+        AbortMultipartUploadResult, Checksum, ChecksumAlgorithm, CompleteMultipartUploadResult, CompletedPart,
+        CopyObjectParams, CopyObjectResult, CreateMultipartUploadResult, DeleteMarkerInfo, DeleteObjectResult,
+        DeleteObjectsKeyError, DeleteObjectsResult, ETag, GetBodyPart, GetObjectAttributesParts,
+        GetObjectAttributesResult, GetObjectParams, GetObjectResponse, HeadObjectParams, HeadObjectResult,
+        ListMultipartUploadsResult, ListObjectVersionsResult, ListObjectsEntry, ListObjectsParams, ListObjectsResult,
+        MultipartUploadInfo, ObjectAttribute, ObjectClientResult, ObjectIdentifier, ObjectInfo, ObjectMetadata,
+        ObjectPart, ObjectVersionInfo, Owner, PutObjectParams, PutObjectResult, RestoreObjectResult, RestoreStatus,
+        RestoreTier, SseCustomerKey, UploadPartResult, UploadReview, UploadReviewPart, MAX_DELETE_OBJECTS_KEYS,
     };
 }
 
@@ -84,8 +97,10 @@ pub mod types {
 /// client errors. See its documentation for more details.
 pub mod error {
     pub use super::object_client::{
-        DeleteObjectError, GetObjectAttributesError, GetObjectError, HeadObjectError, ListObjectsError,
-        ObjectClientError, PutObjectError,
+        AbortMultipartUploadError, CompleteMultipartUploadError, CopyObjectError, CreateMultipartUploadError,
+        DeleteObjectError, DeleteObjectsError, GetObjectAttributesError, GetObjectError, HeadObjectError,
+        ListMultipartUploadsError, ListObjectVersionsError, ListObjectsError, ObjectClientError, PutObjectError,
+        RestoreObjectError, SseCustomerKeyError, UploadPartError,
     };
     #[doc(hidden)]
     pub use super::s3_crt_client::HeadBucketError;
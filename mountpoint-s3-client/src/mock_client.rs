@@ -3,9 +3,10 @@
 #![cfg(feature = "mock")]
 
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::ops::Range;
 use std::pin::Pin;
+use std::sync::atomic::{self, AtomicU64};
 use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll};
 use std::time::{Duration, SystemTime};
@@ -13,7 +14,6 @@ use std::time::{Duration, SystemTime};
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 use lazy_static::lazy_static;
-use mountpoint_s3_crt::checksums::crc32c;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
@@ -21,15 +21,22 @@ use thiserror::Error;
 use time::OffsetDateTime;
 use tracing::trace;
 
-use crate::checksums::crc32c_to_base64;
+use crate::checksums::{checksum_algorithm_name, ChecksumHasher};
 use crate::object_client::{
-    Checksum, ChecksumAlgorithm, DeleteObjectError, DeleteObjectResult, ETag, GetBodyPart, GetObjectAttributesError,
-    GetObjectAttributesResult, GetObjectError, HeadObjectError, HeadObjectResult, ListObjectsError, ListObjectsResult,
-    ObjectAttribute, ObjectClient, ObjectClientError, ObjectClientResult, ObjectInfo, PutObjectError, PutObjectParams,
-    PutObjectRequest, PutObjectResult, RestoreStatus, UploadReview, UploadReviewPart,
+    AbortMultipartUploadError, AbortMultipartUploadResult, Checksum, ChecksumAlgorithm, CompleteMultipartUploadError,
+    CompleteMultipartUploadResult, CompletedPart, CopyObjectError, CopyObjectParams, CopyObjectResult,
+    CreateMultipartUploadError, CreateMultipartUploadResult, DeleteMarkerInfo, DeleteObjectError, DeleteObjectResult,
+    DeleteObjectsError, DeleteObjectsKeyError, DeleteObjectsResult, ETag, GetBodyPart, GetObjectAttributesError,
+    GetObjectAttributesResult, GetObjectError, GetObjectParams, GetObjectRequest, GetObjectResponse, HeadObjectError,
+    HeadObjectParams, HeadObjectResult, ListMultipartUploadsError, ListMultipartUploadsResult, ListObjectVersionsError,
+    ListObjectVersionsResult, ListObjectsError, ListObjectsParams, ListObjectsResult, MultipartUploadInfo,
+    ObjectAttribute, ObjectClient, ObjectClientError, ObjectClientResult, ObjectIdentifier, ObjectInfo, ObjectMetadata,
+    ObjectVersionInfo, Owner, PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult, RestoreObjectError,
+    RestoreObjectResult, RestoreStatus, RestoreTier, SseCustomerKey, UploadPartError, UploadPartResult, UploadReview,
+    UploadReviewPart, MAX_DELETE_OBJECTS_KEYS,
 };
 
-mod leaky_bucket;
+pub mod mock_server;
 pub mod throughput_client;
 
 pub const RAMP_MODULUS: usize = 251; // Largest prime under 256
@@ -38,6 +45,15 @@ static_assertions::const_assert!((RAMP_MODULUS > 0) && (RAMP_MODULUS <= 256));
 const RAMP_BUFFER_SIZE: usize = 4 * 1024 * RAMP_MODULUS; // around 1 MiB
 static_assertions::const_assert!(RAMP_BUFFER_SIZE % RAMP_MODULUS == 0);
 
+/// Minimum size of every part of a multipart upload except the last, as enforced by S3
+const MIN_MULTIPART_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Canonical user ID of the owner of every object in a [MockClient]
+pub const MOCK_OWNER_ID: &str = "mock-owner-id";
+
+/// Display name of the owner of every object in a [MockClient]
+pub const MOCK_OWNER_DISPLAY_NAME: &str = "mock-owner";
+
 // Return a ramping pattern of bytes modulo RAMP_MODULUS.  The seed is the first byte.
 pub fn ramp_bytes(seed: usize, size: usize) -> Vec<u8> {
     let mut bytes: Vec<u8> = Vec::with_capacity(size);
@@ -61,6 +77,12 @@ pub struct MockClientConfig {
     pub part_size: usize,
     /// A seed to randomize the order of ListObjectsV2 results, or None to use ordered list
     pub unordered_list_seed: Option<u64>,
+    /// Apply backpressure to GetObject requests, which then fail if they're polled when their
+    /// read window is empty, rather than waiting for it to be extended. This helps tests catch
+    /// callers that don't extend the window.
+    pub enable_backpressure: bool,
+    /// The initial size of the read window of GetObject requests if backpressure is enabled
+    pub initial_read_window_size: usize,
 }
 
 /// A mock implementation of an object client that we can manually add objects to, and then query
@@ -69,12 +91,97 @@ pub struct MockClientConfig {
 pub struct MockClient {
     config: MockClientConfig,
     objects: Arc<RwLock<BTreeMap<String, MockObject>>>,
+    versions: Arc<RwLock<BTreeMap<String, Vec<MockObjectVersion>>>>,
     in_progress_uploads: Arc<RwLock<BTreeSet<String>>>,
+    multipart_uploads: Arc<RwLock<BTreeMap<String, MockMultipartUpload>>>,
     operation_counts: Arc<RwLock<HashMap<Operation, u64>>>,
 }
 
-fn add_object(objects: &Arc<RwLock<BTreeMap<String, MockObject>>>, key: &str, value: MockObject) {
-    objects.write().unwrap().insert(key.to_owned(), value);
+/// A multipart upload created by [MockClient::create_multipart_upload], keyed by its upload ID.
+#[derive(Debug)]
+struct MockMultipartUpload {
+    key: String,
+    initiated: OffsetDateTime,
+    params: PutObjectParams,
+    parts: BTreeMap<usize, (ETag, Box<[u8]>)>,
+}
+
+/// The checksum S3 stores for an object created by a multipart upload with checksums, which is a
+/// checksum of the checksums of its parts, suffixed with the number of parts
+fn composite_checksum(algorithm: ChecksumAlgorithm, parts: &[&[u8]]) -> Checksum {
+    let mut hasher = ChecksumHasher::new(algorithm);
+    for part in parts {
+        let mut part_hasher = ChecksumHasher::new(algorithm);
+        part_hasher.update(part);
+        hasher.update(&part_hasher.finalize());
+    }
+    let value = Some(format!("{}-{}", hasher.finalize_base64(), parts.len()));
+    match algorithm {
+        ChecksumAlgorithm::Crc32c => Checksum {
+            checksum_crc32c: value,
+            ..Default::default()
+        },
+        ChecksumAlgorithm::Crc32 => Checksum {
+            checksum_crc32: value,
+            ..Default::default()
+        },
+        ChecksumAlgorithm::Sha1 => Checksum {
+            checksum_sha1: value,
+            ..Default::default()
+        },
+        ChecksumAlgorithm::Sha256 => Checksum {
+            checksum_sha256: value,
+            ..Default::default()
+        },
+    }
+}
+
+fn next_upload_id() -> String {
+    static NEXT_UPLOAD_ID: AtomicU64 = AtomicU64::new(1);
+    format!("upload-{:016x}", NEXT_UPLOAD_ID.fetch_add(1, atomic::Ordering::SeqCst))
+}
+
+/// A version of an object in the mock client's bucket. The bucket always has versioning enabled,
+/// so every write and every delete of an existing object creates a new version.
+#[derive(Debug, Clone)]
+struct MockObjectVersion {
+    version_id: String,
+    /// The object at this version, or `None` if this version is a delete marker
+    object: Option<MockObject>,
+    last_modified: OffsetDateTime,
+}
+
+fn next_version_id() -> String {
+    static NEXT_VERSION_ID: AtomicU64 = AtomicU64::new(1);
+    format!("{:032x}", NEXT_VERSION_ID.fetch_add(1, atomic::Ordering::SeqCst))
+}
+
+fn mock_owner() -> Owner {
+    Owner::new(MOCK_OWNER_ID.to_owned(), Some(MOCK_OWNER_DISPLAY_NAME.to_owned()))
+}
+
+/// Add an object to the bucket as a new version, and return the new version's ID
+fn add_object(
+    objects: &Arc<RwLock<BTreeMap<String, MockObject>>>,
+    versions: &Arc<RwLock<BTreeMap<String, Vec<MockObjectVersion>>>>,
+    key: &str,
+    value: MockObject,
+) -> String {
+    let version_id = next_version_id();
+    let version = MockObjectVersion {
+        version_id: version_id.clone(),
+        object: Some(value.clone()),
+        last_modified: value.last_modified,
+    };
+    let mut objects = objects.write().unwrap();
+    objects.insert(key.to_owned(), value);
+    versions
+        .write()
+        .unwrap()
+        .entry(key.to_owned())
+        .or_default()
+        .push(version);
+    version_id
 }
 
 impl MockClient {
@@ -83,19 +190,35 @@ impl MockClient {
         Self {
             config,
             objects: Default::default(),
+            versions: Default::default(),
             in_progress_uploads: Default::default(),
+            multipart_uploads: Default::default(),
             operation_counts: Default::default(),
         }
     }
 
     /// Add an object to this mock client's bucket
     pub fn add_object(&self, key: &str, value: MockObject) {
-        add_object(&self.objects, key, value);
+        add_object(&self.objects, &self.versions, key, value);
     }
 
-    /// Remove object for the mock client's bucket
+    /// Remove object for the mock client's bucket. Like DeleteObject, this leaves a delete marker
+    /// in the object's version history.
     pub fn remove_object(&self, key: &str) {
-        self.objects.write().unwrap().remove(key);
+        let mut objects = self.objects.write().unwrap();
+        if objects.remove(key).is_some() {
+            let delete_marker = MockObjectVersion {
+                version_id: next_version_id(),
+                object: None,
+                last_modified: OffsetDateTime::now_utc(),
+            };
+            self.versions
+                .write()
+                .unwrap()
+                .entry(key.to_owned())
+                .or_default()
+                .push(delete_marker);
+        }
     }
 
     /// Returns `true` if this mock client's bucket contains the specified key
@@ -123,8 +246,11 @@ impl MockClient {
         }
     }
 
+    /// Finish restoring an object, as if S3 had completed a restore started by
+    /// [restore_object](ObjectClient::restore_object). The restored copy expires in an hour.
+    ///
     /// Returns error if object does not exist
-    pub fn restore_object(&self, key: &str) -> Result<(), MockClientError> {
+    pub fn complete_restore(&self, key: &str) -> Result<(), MockClientError> {
         match self.objects.write().unwrap().get_mut(key) {
             Some(mock_object) => {
                 mock_object.restore_status = Some(RestoreStatus::Restored {
@@ -159,6 +285,29 @@ impl MockClient {
         }
     }
 
+    /// Find an object in the bucket, either its current version or the given version
+    fn get_object_version(&self, key: &str, version_id: Option<&str>) -> Option<MockObject> {
+        match version_id {
+            Some(version_id) => self
+                .versions
+                .read()
+                .unwrap()
+                .get(key)?
+                .iter()
+                .find(|version| version.version_id == version_id)?
+                .object
+                .clone(),
+            None => self.objects.read().unwrap().get(key).cloned(),
+        }
+    }
+
+    /// Return the ID of the current version of an object, if it is not a delete marker
+    fn current_version_id(&self, key: &str) -> Option<String> {
+        let versions = self.versions.read().unwrap();
+        let current = versions.get(key)?.last()?;
+        current.object.as_ref().map(|_| current.version_id.clone())
+    }
+
     /// Track number of operations for verifying API calls made by the client in testing.
     fn inc_op_count(&self, operation: Operation) {
         let mut op_counts = self.operation_counts.write().unwrap();
@@ -166,15 +315,12 @@ impl MockClient {
     }
 
     /// Ordered list implementation
-    fn list_objects_ordered(
-        &self,
-        continuation_token: Option<&str>,
-        delimiter: &str,
-        max_keys: usize,
-        prefix: &str,
-    ) -> ListObjectsResult {
+    fn list_objects_ordered(&self, params: &ListObjectsParams) -> ListObjectsResult {
+        let continuation_token = params.continuation_token.as_deref();
+        let max_keys = params.max_keys;
+        let prefix = params.prefix.as_str();
         // TODO delimiter and prefix should be optional in the API
-        let delimiter = (!delimiter.is_empty()).then_some(delimiter);
+        let delimiter = (!params.delimiter.is_empty()).then_some(params.delimiter.as_str());
 
         let objects = self.objects.read().unwrap();
 
@@ -205,8 +351,14 @@ impl MockClient {
                 break;
             }
 
-            // Skip keys that do not start with the specified prefix
-            if !key.starts_with(prefix) {
+            // Skip keys that do not start with the specified prefix, or don't sort after the
+            // start-after key
+            if !key.starts_with(prefix)
+                || !params
+                    .start_after
+                    .as_ref()
+                    .map_or(true, |start_after| key > start_after)
+            {
                 continue;
             }
 
@@ -248,6 +400,7 @@ impl MockClient {
                     etag: object.etag.as_str().to_string(),
                     storage_class: object.storage_class.clone(),
                     restore_status: object.restore_status,
+                    owner: params.fetch_owner.then(mock_owner),
                 });
             }
         }
@@ -261,17 +414,211 @@ impl MockClient {
         }
     }
 
-    fn list_objects_unordered(
+    /// Ordered list implementation for object versions. Versions of the same key are returned
+    /// newest first.
+    fn list_object_versions_ordered(
         &self,
-        continuation_token: Option<&str>,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
         delimiter: &str,
         max_keys: usize,
         prefix: &str,
-        seed: u64,
-    ) -> ListObjectsResult {
+    ) -> ListObjectVersionsResult {
+        // TODO delimiter and prefix should be optional in the API
+        let delimiter = (!delimiter.is_empty()).then_some(delimiter);
+
+        let mut common_prefixes = BTreeSet::new();
+        let mut current_common_prefix: Option<String> = None;
+        let mut versions_vec = Vec::new();
+        let mut delete_markers = Vec::new();
+        let mut last_consumed: Option<(String, String)> = None;
+        let mut truncated = false;
+
+        let versions = self.versions.read().unwrap();
+        let entries = versions
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .flat_map(|(key, versions)| {
+                versions
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(move |(i, version)| (key, version, i == 0))
+            });
+
+        let mut past_marker = key_marker.is_none();
+        for (key, version, is_latest) in entries {
+            // Skip everything up to and including the markers from a previous response
+            if let Some(key_marker) = key_marker.filter(|_| !past_marker) {
+                match key.as_str().cmp(key_marker) {
+                    Ordering::Less => continue,
+                    Ordering::Equal => {
+                        if version_id_marker == Some(version.version_id.as_str()) {
+                            past_marker = true;
+                        }
+                        continue;
+                    }
+                    Ordering::Greater => past_marker = true,
+                }
+            }
+
+            // As for ListObjectsV2, make sure to get past the current common prefix before
+            // truncating the response, so it isn't returned twice.
+            let key_count = common_prefixes.len() + versions_vec.len() + delete_markers.len();
+            if key_count >= max_keys {
+                match current_common_prefix {
+                    Some(ref ccp) if key.starts_with(ccp) => {
+                        last_consumed = Some((key.to_string(), version.version_id.clone()));
+                        continue;
+                    }
+                    _ => {
+                        truncated = true;
+                        break;
+                    }
+                }
+            }
+            last_consumed = Some((key.to_string(), version.version_id.clone()));
+
+            let no_prefix_key = key.chars().skip(prefix.chars().count()).collect::<String>();
+            if let Some((pre, _)) = delimiter.and_then(|d| no_prefix_key.split_once(d)) {
+                let common_prefix = format!("{}{}{}", prefix, pre, delimiter.unwrap());
+                if common_prefixes.insert(common_prefix.clone()) {
+                    current_common_prefix = Some(common_prefix);
+                }
+            } else if let Some(object) = &version.object {
+                versions_vec.push(ObjectVersionInfo {
+                    object: ObjectInfo {
+                        key: key.to_string(),
+                        size: object.len() as u64,
+                        last_modified: object.last_modified,
+                        etag: object.etag.as_str().to_string(),
+                        storage_class: object.storage_class.clone(),
+                        restore_status: object.restore_status,
+                        owner: None,
+                    },
+                    version_id: version.version_id.clone(),
+                    is_latest,
+                });
+            } else {
+                delete_markers.push(DeleteMarkerInfo {
+                    key: key.to_string(),
+                    version_id: version.version_id.clone(),
+                    is_latest,
+                    last_modified: version.last_modified,
+                });
+            }
+        }
+
+        let (next_key_marker, next_version_id_marker) = match last_consumed {
+            Some((key, version_id)) if truncated => (Some(key), Some(version_id)),
+            _ => (None, None),
+        };
+
+        ListObjectVersionsResult {
+            versions: versions_vec,
+            delete_markers,
+            common_prefixes: common_prefixes.into_iter().collect(),
+            next_key_marker,
+            next_version_id_marker,
+        }
+    }
+
+    /// Ordered list implementation for in-progress multipart uploads. Uploads of the same key are
+    /// returned in the order they were created.
+    fn list_multipart_uploads_ordered(
+        &self,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ListMultipartUploadsResult {
         // TODO delimiter and prefix should be optional in the API
         let delimiter = (!delimiter.is_empty()).then_some(delimiter);
 
+        let mut common_prefixes = BTreeSet::new();
+        let mut current_common_prefix: Option<String> = None;
+        let mut uploads_vec = Vec::new();
+        let mut last_consumed: Option<(String, String)> = None;
+        let mut truncated = false;
+
+        // Upload IDs are allocated in increasing order, so sorting by them within a key is the
+        // same as sorting by creation time.
+        let uploads = self.multipart_uploads.read().unwrap();
+        let mut entries: Vec<_> = uploads
+            .iter()
+            .filter(|(_, upload)| upload.key.starts_with(prefix))
+            .collect();
+        entries.sort_by(|(id1, u1), (id2, u2)| (&u1.key, id1).cmp(&(&u2.key, id2)));
+
+        for (upload_id, upload) in entries {
+            let key = &upload.key;
+
+            // Skip everything up to and including the markers from a previous response
+            if let Some(key_marker) = key_marker {
+                match key.as_str().cmp(key_marker) {
+                    Ordering::Less => continue,
+                    Ordering::Equal => match upload_id_marker {
+                        Some(upload_id_marker) if upload_id.as_str() > upload_id_marker => {}
+                        _ => continue,
+                    },
+                    Ordering::Greater => {}
+                }
+            }
+
+            // As for ListObjectsV2, make sure to get past the current common prefix before
+            // truncating the response, so it isn't returned twice.
+            let key_count = common_prefixes.len() + uploads_vec.len();
+            if key_count >= max_uploads {
+                match current_common_prefix {
+                    Some(ref ccp) if key.starts_with(ccp) => {
+                        last_consumed = Some((key.to_string(), upload_id.clone()));
+                        continue;
+                    }
+                    _ => {
+                        truncated = true;
+                        break;
+                    }
+                }
+            }
+            last_consumed = Some((key.to_string(), upload_id.clone()));
+
+            let no_prefix_key = key.chars().skip(prefix.chars().count()).collect::<String>();
+            if let Some((pre, _)) = delimiter.and_then(|d| no_prefix_key.split_once(d)) {
+                let common_prefix = format!("{}{}{}", prefix, pre, delimiter.unwrap());
+                if common_prefixes.insert(common_prefix.clone()) {
+                    current_common_prefix = Some(common_prefix);
+                }
+            } else {
+                uploads_vec.push(MultipartUploadInfo {
+                    key: key.to_string(),
+                    upload_id: upload_id.clone(),
+                    initiated: upload.initiated,
+                    storage_class: upload.params.storage_class.clone(),
+                });
+            }
+        }
+
+        let (next_key_marker, next_upload_id_marker) = match last_consumed {
+            Some((key, upload_id)) if truncated => (Some(key), Some(upload_id)),
+            _ => (None, None),
+        };
+
+        ListMultipartUploadsResult {
+            uploads: uploads_vec,
+            common_prefixes: common_prefixes.into_iter().collect(),
+            next_key_marker,
+            next_upload_id_marker,
+        }
+    }
+
+    fn list_objects_unordered(&self, params: &ListObjectsParams, seed: u64) -> ListObjectsResult {
+        let continuation_token = params.continuation_token.as_deref();
+        let max_keys = params.max_keys;
+        let prefix = params.prefix.as_str();
+        // TODO delimiter and prefix should be optional in the API
+        let delimiter = (!params.delimiter.is_empty()).then_some(params.delimiter.as_str());
+
         let mut common_prefixes: Vec<String> = Vec::new();
         let mut common_prefixes_set: HashSet<String> = HashSet::new();
         let mut object_vec: Vec<ObjectInfo> = Vec::new();
@@ -280,7 +627,16 @@ impl MockClient {
 
         // Shuffle the keys now before we construct an iterator over them. This won't be stable in
         // the presence of mutation, but that's the expected behavior anyway.
-        let mut object_keys: Vec<_> = objects.keys().filter(|key| key.starts_with(prefix)).collect();
+        let mut object_keys: Vec<_> = objects
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .filter(|key| {
+                params
+                    .start_after
+                    .as_ref()
+                    .map_or(true, |start_after| *key > start_after)
+            })
+            .collect();
         object_keys.shuffle(&mut ChaCha20Rng::seed_from_u64(seed));
 
         // Continuation tokens for unordered list will just be the index in the shuffled list. This
@@ -309,6 +665,7 @@ impl MockClient {
                     etag: object.etag.as_str().to_string(),
                     storage_class: object.storage_class.clone(),
                     restore_status: object.restore_status,
+                    owner: params.fetch_owner.then(mock_owner),
                 });
             }
             next_continuation_token += 1;
@@ -328,12 +685,21 @@ impl MockClient {
 /// Operations for use in operation counters.
 #[derive(Debug, Eq, Hash, PartialEq)]
 pub enum Operation {
+    AbortMultipartUpload,
+    CompleteMultipartUpload,
+    CopyObject,
+    CreateMultipartUpload,
     DeleteObject,
+    DeleteObjects,
     HeadObject,
     GetObject,
     GetObjectAttributes,
+    ListMultipartUploads,
+    ListObjectVersions,
     ListObjectsV2,
     PutObject,
+    RestoreObject,
+    UploadPart,
 }
 
 /// Counter for a specific client [Operation].
@@ -364,6 +730,12 @@ pub struct MockObject {
     restore_status: Option<RestoreStatus>,
     last_modified: OffsetDateTime,
     etag: ETag,
+    object_metadata: ObjectMetadata,
+    content_type: Option<String>,
+    content_encoding: Option<String>,
+    cache_control: Option<String>,
+    sse_customer_key: Option<SseCustomerKey>,
+    checksum: Checksum,
 }
 
 impl MockObject {
@@ -381,6 +753,12 @@ impl MockObject {
             restore_status: None,
             last_modified: OffsetDateTime::now_utc(),
             etag,
+            object_metadata: Default::default(),
+            content_type: None,
+            content_encoding: None,
+            cache_control: None,
+            sse_customer_key: None,
+            checksum: Default::default(),
         }
     }
 
@@ -392,6 +770,12 @@ impl MockObject {
             restore_status: None,
             last_modified: OffsetDateTime::now_utc(),
             etag,
+            object_metadata: Default::default(),
+            content_type: None,
+            content_encoding: None,
+            cache_control: None,
+            sse_customer_key: None,
+            checksum: Default::default(),
         }
     }
 
@@ -413,6 +797,12 @@ impl MockObject {
             restore_status: None,
             last_modified: OffsetDateTime::now_utc(),
             etag,
+            object_metadata: Default::default(),
+            content_type: None,
+            content_encoding: None,
+            cache_control: None,
+            sse_customer_key: None,
+            checksum: Default::default(),
         }
     }
 
@@ -428,6 +818,50 @@ impl MockObject {
         self.restore_status = restore_status;
     }
 
+    pub fn set_object_metadata(&mut self, object_metadata: ObjectMetadata) {
+        self.object_metadata = object_metadata;
+    }
+
+    /// Set the checksum that S3 stored for this object when it was uploaded. It isn't checked
+    /// against the contents of the object, so tests can use it to simulate a corrupted body.
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum = checksum;
+    }
+
+    /// Set the properties of a new object from the parameters of the request that created it
+    fn set_put_params(&mut self, params: &PutObjectParams) {
+        self.storage_class = params.storage_class.clone();
+        self.object_metadata = params.object_metadata.clone();
+        self.content_type = params.content_type.clone();
+        self.content_encoding = params.content_encoding.clone();
+        self.cache_control = params.cache_control.clone();
+    }
+
+    /// Check that a request to read this object provides the key it was encrypted with, if any
+    fn check_sse_customer_key<E>(&self, key: Option<&SseCustomerKey>) -> ObjectClientResult<(), E, MockClientError> {
+        match (&self.sse_customer_key, key) {
+            (None, None) => Ok(()),
+            (Some(_), None) => mock_client_error("object is encrypted with a customer-provided key"),
+            (None, Some(_)) => mock_client_error("object is not encrypted with a customer-provided key"),
+            (Some(expected), Some(key)) if expected == key => Ok(()),
+            (Some(_), Some(_)) => mock_client_error("customer-provided key doesn't match the object"),
+        }
+    }
+
+    fn get_object_response(&self, content_length: u64) -> GetObjectResponse {
+        GetObjectResponse {
+            etag: self.etag.clone(),
+            content_length: Some(content_length),
+            last_modified: self.last_modified,
+            checksum: Default::default(),
+            checksum_validated: false,
+            object_metadata: self.object_metadata.clone(),
+            content_type: self.content_type.clone(),
+            content_encoding: self.content_encoding.clone(),
+            cache_control: self.cache_control.clone(),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.size
     }
@@ -455,6 +889,7 @@ impl std::fmt::Debug for MockObject {
             .field("last_modified", &self.last_modified)
             .field("etag", &self.etag)
             .field("restored", &self.restore_status)
+            .field("object_metadata", &self.object_metadata)
             .finish()
     }
 }
@@ -465,6 +900,11 @@ pub struct GetObjectResult {
     next_offset: u64,
     length: usize,
     part_size: usize,
+    response: GetObjectResponse,
+    /// End offset of the read window, or `None` if backpressure is disabled
+    read_window_end_offset: Option<u64>,
+    /// Checksum of the body being validated and its expected value, until the body has been read
+    validation: Option<(ChecksumHasher, String)>,
 }
 
 impl GetObjectResult {
@@ -482,16 +922,55 @@ impl GetObjectResult {
     }
 }
 
+impl GetObjectRequest for GetObjectResult {
+    type ClientError = MockClientError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        Some(self.response.clone())
+    }
+
+    fn increment_read_window(mut self: Pin<&mut Self>, len: usize) {
+        if let Some(end_offset) = self.read_window_end_offset.as_mut() {
+            *end_offset += len as u64;
+        }
+    }
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        self.read_window_end_offset.unwrap_or(u64::MAX)
+    }
+}
+
 impl Stream for GetObjectResult {
     type Item = ObjectClientResult<GetBodyPart, GetObjectError, MockClientError>;
 
     fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         if self.length == 0 {
+            // Like the CRT, only report whether the body matched its checksum once it's all been read
+            if let Some((hasher, expected)) = self.validation.take() {
+                if hasher.finalize_base64() != expected {
+                    return Poll::Ready(Some(Err(ObjectClientError::ServiceError(
+                        GetObjectError::ChecksumMismatch {
+                            algorithm: checksum_algorithm_name(hasher.algorithm()),
+                            expected,
+                        },
+                    ))));
+                }
+                self.response.checksum_validated = true;
+            }
             return Poll::Ready(None);
         }
 
-        let next_part_size = self.part_size.min(self.length);
+        let mut next_part_size = self.part_size.min(self.length);
+        if let Some(end_offset) = self.read_window_end_offset {
+            if self.next_offset >= end_offset {
+                return Poll::Ready(Some(mock_client_error("empty read window")));
+            }
+            next_part_size = next_part_size.min((end_offset - self.next_offset) as usize);
+        }
         let next_part = self.object.read(self.next_offset, next_part_size);
+        if let Some((hasher, _)) = self.validation.as_mut() {
+            hasher.update(&next_part);
+        }
 
         let result = (self.next_offset, next_part);
         self.next_offset += next_part_size as u64;
@@ -523,6 +1002,37 @@ impl ObjectClient for MockClient {
         Some(self.config.part_size)
     }
 
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        _params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        trace!(
+            source_bucket,
+            source_key,
+            destination_bucket,
+            destination_key,
+            "CopyObject"
+        );
+        self.inc_op_count(Operation::CopyObject);
+
+        if source_bucket != self.config.bucket || destination_bucket != self.config.bucket {
+            return Err(ObjectClientError::ServiceError(CopyObjectError::NotFound));
+        }
+
+        let source = self.objects.read().unwrap().get(source_key).cloned();
+        if let Some(mut object) = source {
+            object.set_last_modified(OffsetDateTime::now_utc());
+            add_object(&self.objects, &self.versions, destination_key, object);
+            Ok(CopyObjectResult {})
+        } else {
+            Err(ObjectClientError::ServiceError(CopyObjectError::NotFound))
+        }
+    }
+
     async fn delete_object(
         &self,
         bucket: &str,
@@ -540,30 +1050,74 @@ impl ObjectClient for MockClient {
         Ok(DeleteObjectResult {})
     }
 
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        trace!(bucket, num_keys = objects.len(), "DeleteObjects");
+        self.inc_op_count(Operation::DeleteObjects);
+
+        if bucket != self.config.bucket {
+            return Err(ObjectClientError::ServiceError(DeleteObjectsError::NoSuchBucket));
+        }
+
+        if objects.len() > MAX_DELETE_OBJECTS_KEYS {
+            return Err(ObjectClientError::ServiceError(DeleteObjectsError::TooManyKeys));
+        }
+
+        let mut deleted = Vec::new();
+        let mut errors = Vec::new();
+        for object in objects {
+            if let Some(etag) = &object.etag {
+                let current_etag = self.objects.read().unwrap().get(&object.key).map(MockObject::etag);
+                if current_etag.is_some_and(|current_etag| &current_etag != etag) {
+                    errors.push(DeleteObjectsKeyError {
+                        key: object.key.clone(),
+                        code: "PreconditionFailed".to_owned(),
+                        message: "At least one of the pre-conditions you specified did not hold".to_owned(),
+                    });
+                    continue;
+                }
+            }
+            self.remove_object(&object.key);
+            deleted.push(object.key.clone());
+        }
+
+        Ok(DeleteObjectsResult { deleted, errors })
+    }
+
     async fn get_object(
         &self,
         bucket: &str,
         key: &str,
-        range: Option<Range<u64>>,
-        if_match: Option<ETag>,
+        params: &GetObjectParams,
     ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
-        trace!(bucket, key, ?range, ?if_match, "GetObject");
+        trace!(
+            bucket,
+            key,
+            range = ?params.range,
+            if_match = ?params.if_match,
+            version_id = ?params.version_id,
+            checksum_mode = params.checksum_mode,
+            "GetObject"
+        );
         self.inc_op_count(Operation::GetObject);
 
         if bucket != self.config.bucket {
             return Err(ObjectClientError::ServiceError(GetObjectError::NoSuchBucket));
         }
 
-        let objects = self.objects.read().unwrap();
+        if let Some(object) = self.get_object_version(key, params.version_id.as_deref()) {
+            object.check_sse_customer_key(params.sse_customer_key.as_ref())?;
 
-        if let Some(object) = objects.get(key) {
-            if let Some(etag_match) = if_match {
-                if etag_match != object.etag {
+            if let Some(etag_match) = params.if_match.as_ref() {
+                if etag_match != &object.etag {
                     return Err(ObjectClientError::ServiceError(GetObjectError::PreconditionFailed));
                 }
             }
 
-            let (next_offset, length) = if let Some(range) = range {
+            let (next_offset, length) = if let Some(range) = params.range.clone() {
                 if range.start >= object.len() as u64 || range.end > object.len() as u64 {
                     return mock_client_error(format!("invalid range, length={}", object.len()));
                 }
@@ -572,11 +1126,28 @@ impl ObjectClient for MockClient {
                 (0, object.len())
             };
 
+            let mut response = object.get_object_response(length as u64);
+            let mut validation = None;
+            if params.checksum_mode && params.range.is_none() {
+                // Like S3, only return the checksum when reading the whole object
+                response.checksum = object.checksum.clone();
+                validation = object
+                    .checksum
+                    .full_object_checksum()
+                    .map(|(algorithm, expected)| (ChecksumHasher::new(algorithm), expected.to_owned()));
+            }
+            let read_window_end_offset = self
+                .config
+                .enable_backpressure
+                .then(|| next_offset + self.config.initial_read_window_size as u64);
             Ok(GetObjectResult {
-                object: object.clone(),
+                object,
                 next_offset,
                 length,
                 part_size: self.config.part_size,
+                response,
+                read_window_end_offset,
+                validation,
             })
         } else {
             Err(ObjectClientError::ServiceError(GetObjectError::NoSuchKey))
@@ -587,16 +1158,19 @@ impl ObjectClient for MockClient {
         &self,
         bucket: &str,
         key: &str,
+        params: &HeadObjectParams,
     ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
-        trace!(bucket, key, "HeadObject");
+        trace!(bucket, key, version_id = ?params.version_id, "HeadObject");
         self.inc_op_count(Operation::HeadObject);
 
         if bucket != self.config.bucket {
             return Err(ObjectClientError::ServiceError(HeadObjectError::NotFound));
         }
 
-        let objects = self.objects.read().unwrap();
-        if let Some(object) = objects.get(key) {
+        if let Some(object) = self.get_object_version(key, params.version_id.as_deref()) {
+            object.check_sse_customer_key(params.sse_customer_key.as_ref())?;
+
+            let version_id = params.version_id.clone().or_else(|| self.current_version_id(key));
             Ok(HeadObjectResult {
                 bucket: bucket.to_string(),
                 object: ObjectInfo {
@@ -606,7 +1180,13 @@ impl ObjectClient for MockClient {
                     etag: object.etag.as_str().to_string(),
                     storage_class: object.storage_class.clone(),
                     restore_status: object.restore_status,
+                    owner: None,
                 },
+                version_id,
+                object_metadata: object.object_metadata.clone(),
+                content_type: object.content_type.clone(),
+                content_encoding: object.content_encoding.clone(),
+                cache_control: object.cache_control.clone(),
             })
         } else {
             Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
@@ -616,12 +1196,9 @@ impl ObjectClient for MockClient {
     async fn list_objects(
         &self,
         bucket: &str,
-        continuation_token: Option<&str>,
-        delimiter: &str,
-        max_keys: usize,
-        prefix: &str,
+        params: &ListObjectsParams,
     ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
-        trace!(bucket, ?continuation_token, delimiter, max_keys, prefix, "ListObjects");
+        trace!(bucket, ?params, "ListObjects");
         self.inc_op_count(Operation::ListObjectsV2);
 
         if bucket != self.config.bucket {
@@ -629,10 +1206,64 @@ impl ObjectClient for MockClient {
         }
 
         if let Some(seed) = self.config.unordered_list_seed {
-            Ok(self.list_objects_unordered(continuation_token, delimiter, max_keys, prefix, seed))
+            Ok(self.list_objects_unordered(params, seed))
         } else {
-            Ok(self.list_objects_ordered(continuation_token, delimiter, max_keys, prefix))
+            Ok(self.list_objects_ordered(params))
+        }
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
+        delimiter: &str,
+        max_keys: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        trace!(
+            bucket,
+            ?key_marker,
+            ?version_id_marker,
+            delimiter,
+            max_keys,
+            prefix,
+            "ListObjectVersions"
+        );
+        self.inc_op_count(Operation::ListObjectVersions);
+
+        if bucket != self.config.bucket {
+            return Err(ObjectClientError::ServiceError(ListObjectVersionsError::NoSuchBucket));
+        }
+
+        Ok(self.list_object_versions_ordered(key_marker, version_id_marker, delimiter, max_keys, prefix))
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
+        trace!(
+            bucket,
+            ?key_marker,
+            ?upload_id_marker,
+            delimiter,
+            max_uploads,
+            prefix,
+            "ListMultipartUploads"
+        );
+        self.inc_op_count(Operation::ListMultipartUploads);
+
+        if bucket != self.config.bucket {
+            return Err(ObjectClientError::ServiceError(ListMultipartUploadsError::NoSuchBucket));
         }
+
+        Ok(self.list_multipart_uploads_ordered(key_marker, upload_id_marker, delimiter, max_uploads, prefix))
     }
 
     async fn put_object(
@@ -653,11 +1284,163 @@ impl ObjectClient for MockClient {
             self.config.part_size,
             params,
             &self.objects,
+            &self.versions,
             &self.in_progress_uploads,
         );
         Ok(put_request)
     }
 
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        trace!(bucket, key, "CreateMultipartUpload");
+        self.inc_op_count(Operation::CreateMultipartUpload);
+
+        if bucket != self.config.bucket {
+            return Err(ObjectClientError::ServiceError(
+                CreateMultipartUploadError::NoSuchBucket,
+            ));
+        }
+
+        let upload_id = next_upload_id();
+        let upload = MockMultipartUpload {
+            key: key.to_owned(),
+            initiated: OffsetDateTime::now_utc(),
+            params: params.clone(),
+            parts: Default::default(),
+        };
+        self.multipart_uploads
+            .write()
+            .unwrap()
+            .insert(upload_id.clone(), upload);
+        Ok(CreateMultipartUploadResult { upload_id })
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        trace!(bucket, key, upload_id, part_number, size = contents.len(), "UploadPart");
+        self.inc_op_count(Operation::UploadPart);
+
+        if !(1..=10_000).contains(&part_number) {
+            return mock_client_error(format!("invalid part number {part_number}"));
+        }
+
+        let mut uploads = self.multipart_uploads.write().unwrap();
+        let Some(upload) = uploads
+            .get_mut(upload_id)
+            .filter(|u| bucket == self.config.bucket && u.key == key)
+        else {
+            return Err(ObjectClientError::ServiceError(UploadPartError::NoSuchUpload));
+        };
+
+        let etag = ETag::from_object_bytes(contents);
+        upload.parts.insert(part_number, (etag.clone(), contents.into()));
+        Ok(UploadPartResult { etag })
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        trace!(
+            bucket,
+            key,
+            upload_id,
+            num_parts = parts.len(),
+            "CompleteMultipartUpload"
+        );
+        self.inc_op_count(Operation::CompleteMultipartUpload);
+
+        let mut uploads = self.multipart_uploads.write().unwrap();
+        let Some(upload) = uploads
+            .get(upload_id)
+            .filter(|u| bucket == self.config.bucket && u.key == key)
+        else {
+            return Err(ObjectClientError::ServiceError(
+                CompleteMultipartUploadError::NoSuchUpload,
+            ));
+        };
+
+        if parts.is_empty() {
+            return mock_client_error("must specify at least one part");
+        }
+        if parts.windows(2).any(|w| w[0].part_number >= w[1].part_number) {
+            return Err(ObjectClientError::ServiceError(
+                CompleteMultipartUploadError::InvalidPartOrder,
+            ));
+        }
+
+        let mut buffer = Vec::new();
+        let mut part_contents = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let Some((etag, contents)) = upload.parts.get(&part.part_number) else {
+                return Err(ObjectClientError::ServiceError(
+                    CompleteMultipartUploadError::InvalidPart,
+                ));
+            };
+            if etag != &part.etag {
+                return Err(ObjectClientError::ServiceError(
+                    CompleteMultipartUploadError::InvalidPart,
+                ));
+            }
+            if i < parts.len() - 1 && contents.len() < MIN_MULTIPART_PART_SIZE {
+                return Err(ObjectClientError::ServiceError(
+                    CompleteMultipartUploadError::EntityTooSmall,
+                ));
+            }
+            buffer.extend_from_slice(contents);
+            part_contents.push(&contents[..]);
+        }
+
+        let mut object: MockObject = buffer.into();
+        object.set_put_params(&upload.params);
+        if let Some(algorithm) = upload.params.checksum_algorithm {
+            object.set_checksum(composite_checksum(algorithm, &part_contents));
+        }
+        let etag = object.etag();
+        let version_id = add_object(&self.objects, &self.versions, key, object);
+        uploads.remove(upload_id);
+
+        Ok(CompleteMultipartUploadResult {
+            etag,
+            version_id: Some(version_id),
+        })
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        trace!(bucket, key, upload_id, "AbortMultipartUpload");
+        self.inc_op_count(Operation::AbortMultipartUpload);
+
+        let mut uploads = self.multipart_uploads.write().unwrap();
+        if uploads
+            .get(upload_id)
+            .filter(|u| bucket == self.config.bucket && u.key == key)
+            .is_none()
+        {
+            return Err(ObjectClientError::ServiceError(AbortMultipartUploadError::NoSuchUpload));
+        }
+        uploads.remove(upload_id);
+
+        Ok(AbortMultipartUploadResult {})
+    }
+
     async fn get_object_attributes(
         &self,
         bucket: &str,
@@ -697,24 +1480,68 @@ impl ObjectClient for MockClient {
             Err(ObjectClientError::ServiceError(GetObjectAttributesError::NoSuchKey))
         }
     }
-}
 
-#[derive(Debug)]
-pub struct MockPutObjectRequest {
-    key: String,
-    buffer: Vec<u8>,
-    part_size: usize,
-    params: PutObjectParams,
-    objects: Arc<RwLock<BTreeMap<String, MockObject>>>,
-    in_progress_uploads: Arc<RwLock<BTreeSet<String>>>,
-}
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        trace!(bucket, key, days, ?tier, "RestoreObject");
+        self.inc_op_count(Operation::RestoreObject);
 
-impl MockPutObjectRequest {
+        if bucket != self.config.bucket {
+            return Err(ObjectClientError::ServiceError(RestoreObjectError::NoSuchBucket));
+        }
+
+        let mut objects = self.objects.write().unwrap();
+        let Some(object) = objects.get_mut(key) else {
+            return Err(ObjectClientError::ServiceError(RestoreObjectError::NoSuchKey));
+        };
+        if !matches!(object.storage_class.as_deref(), Some("GLACIER") | Some("DEEP_ARCHIVE")) {
+            return Err(ObjectClientError::ServiceError(RestoreObjectError::InvalidObjectState));
+        }
+
+        // Restores stay in progress until the test completes them with `complete_restore`
+        match object.restore_status {
+            None => {
+                object.restore_status = Some(RestoreStatus::InProgress);
+                Ok(RestoreObjectResult {
+                    already_restored: false,
+                })
+            }
+            Some(RestoreStatus::InProgress) => Err(ObjectClientError::ServiceError(
+                RestoreObjectError::RestoreAlreadyInProgress,
+            )),
+            Some(RestoreStatus::Restored { .. }) => {
+                object.restore_status = Some(RestoreStatus::Restored {
+                    expiry: SystemTime::now() + Duration::from_secs(days as u64 * 24 * 60 * 60),
+                });
+                Ok(RestoreObjectResult { already_restored: true })
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MockPutObjectRequest {
+    key: String,
+    buffer: Vec<u8>,
+    part_size: usize,
+    params: PutObjectParams,
+    objects: Arc<RwLock<BTreeMap<String, MockObject>>>,
+    versions: Arc<RwLock<BTreeMap<String, Vec<MockObjectVersion>>>>,
+    in_progress_uploads: Arc<RwLock<BTreeSet<String>>>,
+}
+
+impl MockPutObjectRequest {
     fn new(
         key: &str,
         part_size: usize,
         params: &PutObjectParams,
         objects: &Arc<RwLock<BTreeMap<String, MockObject>>>,
+        versions: &Arc<RwLock<BTreeMap<String, Vec<MockObjectVersion>>>>,
         in_progress_uploads: &Arc<RwLock<BTreeSet<String>>>,
     ) -> Self {
         in_progress_uploads.write().unwrap().insert(key.to_owned());
@@ -724,6 +1551,7 @@ impl MockPutObjectRequest {
             part_size,
             params: params.clone(),
             objects: objects.clone(),
+            versions: versions.clone(),
             in_progress_uploads: in_progress_uploads.clone(),
         }
     }
@@ -745,13 +1573,42 @@ impl PutObjectRequest for MockPutObjectRequest {
     }
 
     async fn complete(mut self) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        {
+            let objects = self.objects.read().unwrap();
+            let existing = objects.get(&self.key);
+            if let Some(etag) = self.params.if_none_match.as_deref() {
+                if existing.is_some_and(|object| etag == "*" || etag == object.etag.as_str()) {
+                    return Err(ObjectClientError::ServiceError(PutObjectError::PreconditionFailed));
+                }
+            }
+            if let Some(etag) = self.params.if_match.as_ref() {
+                if existing.map(|object| &object.etag) != Some(etag) {
+                    return Err(ObjectClientError::ServiceError(PutObjectError::PreconditionFailed));
+                }
+            }
+        }
+
         let buffer = std::mem::take(&mut self.buffer);
+        // Like the CRT, the upload is always a multipart upload, so S3 stores a composite checksum
+        let checksum = self.params.checksum_algorithm.map(|algorithm| {
+            let parts: Vec<&[u8]> = if buffer.is_empty() {
+                vec![&[]]
+            } else {
+                buffer.chunks(self.part_size).collect()
+            };
+            composite_checksum(algorithm, &parts)
+        });
         let mut object: MockObject = buffer.into();
-        object.set_storage_class(self.params.storage_class.clone());
-        add_object(&self.objects, &self.key, object);
+        if let Some(checksum) = checksum {
+            object.set_checksum(checksum);
+        }
+        object.set_put_params(&self.params);
+        object.sse_customer_key = self.params.sse_customer_key.clone();
+        let version_id = add_object(&self.objects, &self.versions, &self.key, object);
         Ok(PutObjectResult {
             sse_type: None,
             sse_kms_key_id: None,
+            version_id: Some(version_id),
         })
     }
 
@@ -759,22 +1616,17 @@ impl PutObjectRequest for MockPutObjectRequest {
         self,
         review_callback: impl FnOnce(UploadReview) -> bool + Send + 'static,
     ) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
-        let checksum_algorithm = if self.params.trailing_checksums {
-            Some(ChecksumAlgorithm::Crc32c)
-        } else {
-            None
-        };
+        let checksum_algorithm = self.params.checksum_algorithm;
         let parts: Vec<UploadReviewPart> = self
             .buffer
             .chunks(self.part_size)
             .map(|part| {
                 let size = part.len() as u64;
-                let checksum = if self.params.trailing_checksums {
-                    let checksum = crc32c::checksum(part);
-                    Some(crc32c_to_base64(&checksum))
-                } else {
-                    None
-                };
+                let checksum = checksum_algorithm.map(|algorithm| {
+                    let mut hasher = ChecksumHasher::new(algorithm);
+                    hasher.update(part);
+                    hasher.finalize_base64()
+                });
                 UploadReviewPart { size, checksum }
             })
             .collect();
@@ -791,12 +1643,17 @@ impl PutObjectRequest for MockPutObjectRequest {
 
 #[cfg(test)]
 mod tests {
+    use std::ops::Range;
+    use std::str::FromStr;
+
     use futures::StreamExt;
+    use mountpoint_s3_crt::checksums::crc32c;
     use rand::{Rng, RngCore, SeedableRng};
     use rand_chacha::ChaChaRng;
     use test_case::test_case;
 
     use super::*;
+    use crate::checksums::crc32c_to_base64;
 
     async fn test_get_object(key: &str, size: usize, range: Option<Range<u64>>) {
         let mut rng = ChaChaRng::seed_from_u64(0x12345678);
@@ -805,6 +1662,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let mut body = vec![0u8; size];
@@ -812,7 +1670,7 @@ mod tests {
         client.add_object(key, MockObject::from_bytes(&body, ETag::for_tests()));
 
         let mut get_request = client
-            .get_object("test_bucket", key, range.clone(), None)
+            .get_object("test_bucket", key, &GetObjectParams::new().range(range.clone()))
             .await
             .expect("should not fail");
 
@@ -826,7 +1684,11 @@ mod tests {
         }
         let expected_range = range.unwrap_or(0..size as u64);
         let expected_range = expected_range.start as usize..expected_range.end as usize;
-        assert_eq!(&accum[..], &body[expected_range], "body does not match");
+        assert_eq!(&accum[..], &body[expected_range.clone()], "body does not match");
+
+        let response = get_request.get_object_response().expect("response should be available");
+        assert_eq!(response.etag, ETag::for_tests());
+        assert_eq!(response.content_length, Some(expected_range.len() as u64));
     }
 
     #[tokio::test]
@@ -836,6 +1698,42 @@ mod tests {
         test_get_object("key1", 10, Some(0..10)).await;
     }
 
+    #[tokio::test]
+    async fn get_object_backpressure() {
+        let client = MockClient::new(MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024,
+            enable_backpressure: true,
+            initial_read_window_size: 1500,
+            ..Default::default()
+        });
+        client.add_object("key1", MockObject::ramp(0xaa, 5000, ETag::for_tests()));
+
+        let mut request = client
+            .get_object("test_bucket", "key1", &GetObjectParams::new().range(Some(100..5000)))
+            .await
+            .expect("get_object should succeed");
+        assert_eq!(Pin::new(&request).read_window_end_offset(), 1600);
+
+        // Parts are cut short at the end of the window
+        let (offset, body) = request.next().await.unwrap().unwrap();
+        assert_eq!((offset, body.len()), (100, 1024));
+        let (offset, body) = request.next().await.unwrap().unwrap();
+        assert_eq!((offset, body.len()), (1124, 476));
+        let err = request.next().await.unwrap().expect_err("read window should be empty");
+        assert!(matches!(err, ObjectClientError::ClientError(MockClientError(m)) if m == "empty read window"));
+
+        Pin::new(&mut request).increment_read_window(5000);
+        assert_eq!(Pin::new(&request).read_window_end_offset(), 6600);
+        let mut next_offset = 1600;
+        while let Some(part) = request.next().await {
+            let (offset, body) = part.expect("body part should succeed");
+            assert_eq!(offset, next_offset);
+            next_offset += body.len() as u64;
+        }
+        assert_eq!(next_offset, 5000);
+    }
+
     #[allow(clippy::reversed_empty_ranges)]
     #[tokio::test]
     async fn get_object_errors() {
@@ -845,6 +1743,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let mut body = vec![0u8; 2000];
@@ -864,33 +1763,45 @@ mod tests {
         }
 
         assert!(matches!(
-            client.get_object("wrong_bucket", "key1", None, None).await,
+            client.get_object("wrong_bucket", "key1", &GetObjectParams::new()).await,
             Err(ObjectClientError::ServiceError(GetObjectError::NoSuchBucket))
         ));
 
         assert!(matches!(
-            client.get_object("test_bucket", "wrong_key", None, None).await,
+            client
+                .get_object("test_bucket", "wrong_key", &GetObjectParams::new())
+                .await,
             Err(ObjectClientError::ServiceError(GetObjectError::NoSuchKey))
         ));
 
         assert_client_error!(
-            client.get_object("test_bucket", "key1", Some(0..2001), None).await,
+            client
+                .get_object("test_bucket", "key1", &GetObjectParams::new().range(Some(0..2001)))
+                .await,
             "invalid range, length=2000"
         );
         assert_client_error!(
-            client.get_object("test_bucket", "key1", Some(2000..2000), None).await,
+            client
+                .get_object("test_bucket", "key1", &GetObjectParams::new().range(Some(2000..2000)))
+                .await,
             "invalid range, length=2000"
         );
         assert_client_error!(
-            client.get_object("test_bucket", "key1", Some(500..2001), None).await,
+            client
+                .get_object("test_bucket", "key1", &GetObjectParams::new().range(Some(500..2001)))
+                .await,
             "invalid range, length=2000"
         );
         assert_client_error!(
-            client.get_object("test_bucket", "key1", Some(5000..2001), None).await,
+            client
+                .get_object("test_bucket", "key1", &GetObjectParams::new().range(Some(5000..2001)))
+                .await,
             "invalid range, length=2000"
         );
         assert_client_error!(
-            client.get_object("test_bucket", "key1", Some(5000..1), None).await,
+            client
+                .get_object("test_bucket", "key1", &GetObjectParams::new().range(Some(5000..1)))
+                .await,
             "invalid range, length=2000"
         );
     }
@@ -901,6 +1812,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let mut keys = vec![];
@@ -917,7 +1829,13 @@ mod tests {
         macro_rules! check {
             ($delimiter:expr, $max_keys:expr, $prefix:expr, $objects:expr, $prefixes:expr) => {
                 let result = client
-                    .list_objects("test_bucket", None, $delimiter, $max_keys, $prefix)
+                    .list_objects(
+                        "test_bucket",
+                        &ListObjectsParams::new()
+                            .prefix($prefix)
+                            .delimiter($delimiter)
+                            .max_keys($max_keys),
+                    )
                     .await
                     .expect("should not fail");
                 assert_eq!(
@@ -951,7 +1869,13 @@ mod tests {
         macro_rules! check_continuation {
             ($delimiter:expr, $max_keys:expr, $prefix:expr, $objects:expr, $prefixes:expr) => {
                 let result = client
-                    .list_objects("test_bucket", None, $delimiter, $max_keys, $prefix)
+                    .list_objects(
+                        "test_bucket",
+                        &ListObjectsParams::new()
+                            .prefix($prefix)
+                            .delimiter($delimiter)
+                            .max_keys($max_keys),
+                    )
                     .await
                     .expect("should not fail");
                 assert!(result.next_continuation_token.is_some());
@@ -959,10 +1883,11 @@ mod tests {
                 let result = client
                     .list_objects(
                         "test_bucket",
-                        result.next_continuation_token.as_deref(),
-                        $delimiter,
-                        $max_keys,
-                        $prefix,
+                        &ListObjectsParams::new()
+                            .prefix($prefix)
+                            .delimiter($delimiter)
+                            .max_keys($max_keys)
+                            .continuation_token(result.next_continuation_token.as_deref()),
                     )
                     .await
                     .expect("should not fail");
@@ -989,6 +1914,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let mut keys = vec![];
@@ -1005,7 +1931,10 @@ mod tests {
         macro_rules! check {
             ($delimiter:expr, $prefix:expr, $objects:expr, $prefixes:expr) => {
                 let result = client
-                    .list_objects("test_bucket", None, $delimiter, 1000, $prefix)
+                    .list_objects(
+                        "test_bucket",
+                        &ListObjectsParams::new().prefix($prefix).delimiter($delimiter),
+                    )
                     .await
                     .expect("should not fail");
                 assert_eq!(
@@ -1028,6 +1957,112 @@ mod tests {
         check!("", "dirs/😄🥹😮", &[], &[]);
     }
 
+    #[tokio::test]
+    async fn list_objects_start_after() {
+        let client = MockClient::new(MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        for key in ["a", "b", "c/1", "c/2", "d"] {
+            client.add_object(key, MockObject::constant(0u8, 5, ETag::for_tests()));
+        }
+
+        let result = client
+            .list_objects("test_bucket", &ListObjectsParams::new().start_after("b"))
+            .await
+            .expect("should not fail");
+        let keys: Vec<_> = result.objects.into_iter().map(|object| object.key).collect();
+        assert_eq!(keys, ["c/1", "c/2", "d"]);
+
+        // Keys under a common prefix that sort after the start-after key are still rolled up
+        let result = client
+            .list_objects(
+                "test_bucket",
+                &ListObjectsParams::new().delimiter("/").start_after("c/1"),
+            )
+            .await
+            .expect("should not fail");
+        let keys: Vec<_> = result.objects.into_iter().map(|object| object.key).collect();
+        assert_eq!(keys, ["d"]);
+        assert_eq!(result.common_prefixes, ["c/"]);
+    }
+
+    #[tokio::test]
+    async fn list_objects_fetch_owner() {
+        let client = MockClient::new(MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+        client.add_object("key", MockObject::constant(0u8, 5, ETag::for_tests()));
+
+        let result = client
+            .list_objects("test_bucket", &ListObjectsParams::new())
+            .await
+            .expect("should not fail");
+        assert_eq!(result.objects[0].owner, None);
+
+        let result = client
+            .list_objects("test_bucket", &ListObjectsParams::new().fetch_owner(true))
+            .await
+            .expect("should not fail");
+        let owner = result.objects[0].owner.as_ref().expect("owner should be returned");
+        assert_eq!(owner.id, MOCK_OWNER_ID);
+        assert_eq!(owner.display_name.as_deref(), Some(MOCK_OWNER_DISPLAY_NAME));
+    }
+
+    #[test_case(None; "ordered")]
+    #[test_case(Some(1234); "unordered")]
+    #[tokio::test]
+    async fn list_objects_stream(unordered_list_seed: Option<u64>) {
+        use crate::object_client::ListObjectsEntry;
+        use futures::TryStreamExt;
+
+        let client = MockClient::new(MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024,
+            unordered_list_seed,
+            ..Default::default()
+        });
+
+        let mut expected = Vec::new();
+        for i in 0..1500 {
+            let key = format!("dir/key{i:04}");
+            client.add_object(&key, MockObject::constant(0u8, 5, ETag::for_tests()));
+            expected.push(key);
+        }
+        for i in 0..10 {
+            client.add_object(
+                &format!("dir/sub{i}/file"),
+                MockObject::constant(0u8, 5, ETag::for_tests()),
+            );
+            expected.push(format!("dir/sub{i}/"));
+        }
+
+        let list_counter = client.new_counter(Operation::ListObjectsV2);
+        let entries: Vec<_> = client
+            .list_objects_stream("test_bucket", "dir/", "/")
+            .try_collect()
+            .await
+            .expect("should not fail");
+        let mut keys: Vec<_> = entries.iter().map(|entry| entry.key().to_owned()).collect();
+        if unordered_list_seed.is_some() {
+            keys.sort();
+        }
+        assert_eq!(keys, expected);
+        let common_prefix_count = entries
+            .iter()
+            .filter(|entry| matches!(entry, ListObjectsEntry::CommonPrefix(_)))
+            .count();
+        assert_eq!(common_prefix_count, 10);
+        // 1510 entries take two pages of 1000
+        assert_eq!(list_counter.count(), 2);
+    }
+
     #[test_case(""; "unprefixed")]
     #[test_case("prefix/1/2/"; "prefixed")]
     #[tokio::test]
@@ -1036,6 +2071,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: Some(1234),
+            ..Default::default()
         });
 
         for i in 0..20 {
@@ -1057,12 +2093,21 @@ mod tests {
         }
 
         let result1 = client
-            .list_objects("test_bucket", None, "/", 10, prefix)
+            .list_objects(
+                "test_bucket",
+                &ListObjectsParams::new().prefix(prefix).delimiter("/").max_keys(10),
+            )
             .await
             .expect("should not fail");
         let continuation_token = result1.next_continuation_token.expect("list should not be finished");
         let result2 = client
-            .list_objects("test_bucket", Some(&continuation_token), "/", 1000, prefix)
+            .list_objects(
+                "test_bucket",
+                &ListObjectsParams::new()
+                    .prefix(prefix)
+                    .delimiter("/")
+                    .continuation_token(Some(&continuation_token)),
+            )
             .await
             .expect("should not fail");
 
@@ -1109,6 +2154,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: Some(1234),
+            ..Default::default()
         });
 
         for i in 0..20 {
@@ -1134,7 +2180,14 @@ mod tests {
         let mut continuation_token = None;
         for _ in 0..100 {
             let result = client
-                .list_objects("test_bucket", continuation_token.as_deref(), "/", page_size, prefix)
+                .list_objects(
+                    "test_bucket",
+                    &ListObjectsParams::new()
+                        .prefix(prefix)
+                        .delimiter("/")
+                        .max_keys(page_size)
+                        .continuation_token(continuation_token.as_deref()),
+                )
                 .await
                 .expect("should not fail");
             continuation_token = result.next_continuation_token;
@@ -1176,6 +2229,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: Some(1234),
+            ..Default::default()
         });
 
         for i in 0..20 {
@@ -1201,7 +2255,13 @@ mod tests {
         let mut continuation_token = None;
         for _ in 0..100 {
             let result = client
-                .list_objects("test_bucket", continuation_token.as_deref(), "", page_size, prefix)
+                .list_objects(
+                    "test_bucket",
+                    &ListObjectsParams::new()
+                        .prefix(prefix)
+                        .max_keys(page_size)
+                        .continuation_token(continuation_token.as_deref()),
+                )
                 .await
                 .expect("should not fail");
             continuation_token = result.next_continuation_token;
@@ -1242,6 +2302,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let mut put_request = client
@@ -1261,7 +2322,7 @@ mod tests {
         put_request.complete().await.expect("put_object failed");
 
         let mut get_request = client
-            .get_object("test_bucket", "key1", None, None)
+            .get_object("test_bucket", "key1", &GetObjectParams::new())
             .await
             .expect("get_object failed");
 
@@ -1275,6 +2336,225 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_put_object_preconditions() {
+        let client = MockClient::new(MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        async fn put(client: &MockClient, params: PutObjectParams) -> Result<(), PutObjectError> {
+            let mut put_request = client.put_object("test_bucket", "key1", &params).await.unwrap();
+            put_request.write(b"hello").await.unwrap();
+            match put_request.complete().await {
+                Ok(_) => Ok(()),
+                Err(ObjectClientError::ServiceError(e)) => Err(e),
+                Err(e) => panic!("unexpected error: {e:?}"),
+            }
+        }
+
+        let if_none_match = PutObjectParams::new().if_none_match(Some("*".to_owned()));
+        put(&client, if_none_match.clone())
+            .await
+            .expect("key doesn't exist yet");
+        assert_eq!(
+            put(&client, if_none_match).await,
+            Err(PutObjectError::PreconditionFailed)
+        );
+
+        let etag = client
+            .head_object("test_bucket", "key1", &Default::default())
+            .await
+            .unwrap()
+            .object
+            .etag;
+        let stale_etag = ETag::for_tests();
+        assert_eq!(
+            put(&client, PutObjectParams::new().if_match(Some(stale_etag))).await,
+            Err(PutObjectError::PreconditionFailed)
+        );
+        put(&client, PutObjectParams::new().if_match(Some(etag.parse().unwrap())))
+            .await
+            .expect("ETag matches the current object");
+    }
+
+    #[tokio::test]
+    async fn test_object_metadata() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let object_metadata: ObjectMetadata = [("foo".to_owned(), "bar".to_owned())].into_iter().collect();
+        let params = PutObjectParams::new()
+            .object_metadata(object_metadata.clone())
+            .content_type("text/plain".to_owned())
+            .cache_control("no-cache".to_owned());
+
+        let mut put_request = client.put_object(bucket, "put", &params).await.unwrap();
+        put_request.write(b"hello").await.unwrap();
+        put_request.complete().await.unwrap();
+
+        let upload_id = client
+            .create_multipart_upload(bucket, "mpu", &params)
+            .await
+            .unwrap()
+            .upload_id;
+        let part = client
+            .upload_part(bucket, "mpu", &upload_id, 1, b"hello")
+            .await
+            .unwrap();
+        let parts = [CompletedPart {
+            part_number: 1,
+            etag: part.etag,
+        }];
+        client
+            .complete_multipart_upload(bucket, "mpu", &upload_id, &parts)
+            .await
+            .unwrap();
+
+        for key in ["put", "mpu"] {
+            let head = client.head_object(bucket, key, &Default::default()).await.unwrap();
+            assert_eq!(head.object_metadata, object_metadata);
+            assert_eq!(head.content_type.as_deref(), Some("text/plain"));
+            assert_eq!(head.content_encoding, None);
+            assert_eq!(head.cache_control.as_deref(), Some("no-cache"));
+
+            let get = client.get_object(bucket, key, &Default::default()).await.unwrap();
+            let response = get.get_object_response().expect("mock get should have a response");
+            assert_eq!(response.object_metadata, object_metadata);
+            assert_eq!(response.content_type.as_deref(), Some("text/plain"));
+            assert_eq!(response.content_encoding, None);
+            assert_eq!(response.cache_control.as_deref(), Some("no-cache"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_object_checksum_mode() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 4,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        // Objects uploaded with checksums have composite checksums that can't be validated
+        let params = PutObjectParams::new().checksum_algorithm(Some(ChecksumAlgorithm::Crc32c));
+        let mut put = client.put_object(bucket, "composite", &params).await.unwrap();
+        put.write(b"hello world").await.unwrap();
+        put.complete().await.unwrap();
+
+        let get = client
+            .get_object(bucket, "composite", &Default::default())
+            .await
+            .unwrap();
+        let response = get.get_object_response().unwrap();
+        assert_eq!(response.checksum, Checksum::default());
+
+        let params = GetObjectParams::new().checksum_mode(true);
+        let get = client.get_object(bucket, "composite", &params).await.unwrap();
+        let response = get.get_object_response().unwrap();
+        assert!(response.checksum.checksum_crc32c.unwrap().ends_with("-3"));
+        assert!(!response.checksum_validated);
+
+        let mut object = MockObject::from_bytes(b"hello world", ETag::for_tests());
+        let expected = crc32c_to_base64(&crc32c::checksum(b"hello world"));
+        object.set_checksum(Checksum {
+            checksum_crc32c: Some(expected.clone()),
+            ..Default::default()
+        });
+        client.add_object("key", object);
+
+        // The body is only validated once it's all been read
+        let mut get = client.get_object(bucket, "key", &params).await.unwrap();
+        let response = get.get_object_response().unwrap();
+        assert_eq!(response.checksum.checksum_crc32c, Some(expected));
+        assert!(!response.checksum_validated);
+        while let Some(part) = get.next().await {
+            part.expect("body should match its checksum");
+        }
+        assert!(get.get_object_response().unwrap().checksum_validated);
+
+        let ranged_params = params.clone().range(Some(0..5));
+        let get = client.get_object(bucket, "key", &ranged_params).await.unwrap();
+        let response = get.get_object_response().unwrap();
+        assert_eq!(response.checksum.checksum_crc32c, None);
+        assert!(!response.checksum_validated);
+
+        let mut object = MockObject::from_bytes(b"hello world", ETag::for_tests());
+        object.set_checksum(Checksum {
+            checksum_crc32c: Some(crc32c_to_base64(&crc32c::checksum(b"hello there"))),
+            ..Default::default()
+        });
+        client.add_object("corrupted", object);
+
+        let mut get = client
+            .get_object(bucket, "corrupted", &params)
+            .await
+            .expect("request should start before the body is validated");
+        let mut parts = Vec::new();
+        while let Some(part) = get.next().await {
+            parts.push(part);
+        }
+        let err = parts.pop().unwrap().expect_err("body should not match its checksum");
+        assert!(parts.iter().all(Result::is_ok));
+        assert!(matches!(
+            err,
+            ObjectClientError::ServiceError(GetObjectError::ChecksumMismatch {
+                algorithm: "CRC32C",
+                ..
+            })
+        ));
+        assert!(!get.get_object_response().unwrap().checksum_validated);
+    }
+
+    #[tokio::test]
+    async fn test_sse_customer_key() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let key = SseCustomerKey::new(&[1u8; SseCustomerKey::KEY_LENGTH]).unwrap();
+        let other_key = SseCustomerKey::new(&[2u8; SseCustomerKey::KEY_LENGTH]).unwrap();
+
+        let params = PutObjectParams::new().sse_customer_key(Some(key.clone()));
+        let mut put_request = client.put_object(bucket, "key1", &params).await.unwrap();
+        put_request.write(b"hello").await.unwrap();
+        put_request.complete().await.unwrap();
+
+        for sse_customer_key in [None, Some(other_key)] {
+            let params = HeadObjectParams::new().sse_customer_key(sse_customer_key.clone());
+            let result = client.head_object(bucket, "key1", &params).await;
+            assert!(matches!(result, Err(ObjectClientError::ClientError(_))));
+
+            let params = GetObjectParams::new().sse_customer_key(sse_customer_key);
+            let result = client.get_object(bucket, "key1", &params).await;
+            assert!(matches!(result, Err(ObjectClientError::ClientError(_))));
+        }
+
+        let params = HeadObjectParams::new().sse_customer_key(Some(key.clone()));
+        client.head_object(bucket, "key1", &params).await.unwrap();
+        let params = GetObjectParams::new().sse_customer_key(Some(key));
+        let body = client
+            .get_object(bucket, "key1", &params)
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"hello");
+    }
+
     proptest::proptest! {
         #[test]
         fn test_ramp(size in 1..2*RAMP_BUFFER_SIZE, read_size in 1..2*RAMP_BUFFER_SIZE, offset in 0..RAMP_BUFFER_SIZE) {
@@ -1297,6 +2577,7 @@ mod tests {
             bucket: bucket.to_owned(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let key = "key1";
@@ -1309,16 +2590,516 @@ mod tests {
         put_request.complete().await.unwrap();
 
         // head_object returns storage class
-        let head_result = client.head_object(bucket, key).await.unwrap();
+        let head_result = client.head_object(bucket, key, &HeadObjectParams::new()).await.unwrap();
         assert_eq!(head_result.object.storage_class.as_deref(), storage_class);
 
         // list_objects returns storage class
-        let list_result = client.list_objects(bucket, None, "/", 1, "").await.unwrap();
+        let list_result = client
+            .list_objects(bucket, &ListObjectsParams::new().delimiter("/").max_keys(1))
+            .await
+            .unwrap();
         assert!(
             matches!(&list_result.objects[..], [object] if object.key == key && object.storage_class.as_deref() == storage_class )
         );
     }
 
+    #[tokio::test]
+    async fn test_copy_object() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let obj = MockObject::ramp(0xaa, 2 * RAMP_BUFFER_SIZE, ETag::for_tests());
+        client.add_object("src", obj.clone());
+
+        client
+            .copy_object(bucket, "src", bucket, "dst", &Default::default())
+            .await
+            .expect("copy_object failed");
+
+        let body = client
+            .get_object(bucket, "dst", &GetObjectParams::new())
+            .await
+            .expect("get_object failed")
+            .collect()
+            .await
+            .expect("body streaming failed");
+        assert_eq!(body, obj.read(0, obj.len()));
+        assert!(client.contains_key("src"));
+
+        let result = client
+            .copy_object(bucket, "not_a_key", bucket, "dst2", &Default::default())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(CopyObjectError::NotFound))
+        ));
+        assert!(!client.contains_key("dst2"));
+
+        let result = client
+            .copy_object(bucket, "src", "not_a_bucket", "dst2", &Default::default())
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(CopyObjectError::NotFound))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let upload_id = client
+            .create_multipart_upload(bucket, "key", &Default::default())
+            .await
+            .expect("create_multipart_upload failed")
+            .upload_id;
+        assert!(!client.contains_key("key"));
+
+        // Upload the parts out of order
+        let first_part = vec![1u8; MIN_MULTIPART_PART_SIZE];
+        let second_part = vec![2u8; 1024];
+        let second = client
+            .upload_part(bucket, "key", &upload_id, 2, &second_part)
+            .await
+            .expect("upload_part failed");
+        let first = client
+            .upload_part(bucket, "key", &upload_id, 1, &first_part)
+            .await
+            .expect("upload_part failed");
+
+        let parts = [
+            CompletedPart {
+                part_number: 2,
+                etag: second.etag.clone(),
+            },
+            CompletedPart {
+                part_number: 1,
+                etag: first.etag.clone(),
+            },
+        ];
+        let result = client
+            .complete_multipart_upload(bucket, "key", &upload_id, &parts)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(
+                CompleteMultipartUploadError::InvalidPartOrder
+            ))
+        ));
+
+        let parts = [
+            CompletedPart {
+                part_number: 1,
+                etag: second.etag.clone(),
+            },
+            CompletedPart {
+                part_number: 2,
+                etag: second.etag.clone(),
+            },
+        ];
+        let result = client
+            .complete_multipart_upload(bucket, "key", &upload_id, &parts)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(
+                CompleteMultipartUploadError::InvalidPart
+            ))
+        ));
+
+        let parts = [
+            CompletedPart {
+                part_number: 1,
+                etag: first.etag,
+            },
+            CompletedPart {
+                part_number: 2,
+                etag: second.etag,
+            },
+        ];
+        client
+            .complete_multipart_upload(bucket, "key", &upload_id, &parts)
+            .await
+            .expect("complete_multipart_upload failed");
+
+        let body = client
+            .get_object(bucket, "key", &GetObjectParams::new())
+            .await
+            .expect("get_object failed")
+            .collect()
+            .await
+            .expect("body streaming failed");
+        assert_eq!(&body[..], &[first_part, second_part].concat()[..]);
+
+        // The upload no longer exists once it's completed
+        let result = client.upload_part(bucket, "key", &upload_id, 3, b"hello").await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(UploadPartError::NoSuchUpload))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_abort_multipart_upload() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let upload_id = client
+            .create_multipart_upload(bucket, "key", &Default::default())
+            .await
+            .expect("create_multipart_upload failed")
+            .upload_id;
+        let part = client
+            .upload_part(bucket, "key", &upload_id, 1, b"hello")
+            .await
+            .expect("upload_part failed");
+
+        // Upload IDs are scoped to the key they were created for
+        let result = client.abort_multipart_upload(bucket, "other_key", &upload_id).await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(AbortMultipartUploadError::NoSuchUpload))
+        ));
+
+        client
+            .abort_multipart_upload(bucket, "key", &upload_id)
+            .await
+            .expect("abort_multipart_upload failed");
+
+        let parts = [CompletedPart {
+            part_number: 1,
+            etag: part.etag,
+        }];
+        let result = client
+            .complete_multipart_upload(bucket, "key", &upload_id, &parts)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(
+                CompleteMultipartUploadError::NoSuchUpload
+            ))
+        ));
+        assert!(!client.contains_key("key"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_object() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            ..Default::default()
+        });
+
+        let mut archived = MockObject::constant(0u8, 5, ETag::for_tests());
+        archived.set_storage_class(Some("GLACIER".to_owned()));
+        client.add_object("archived", archived);
+        client.add_object("standard", MockObject::constant(0u8, 5, ETag::for_tests()));
+
+        let result = client
+            .restore_object(bucket, "standard", 1, RestoreTier::Standard)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(RestoreObjectError::InvalidObjectState))
+        ));
+        let result = client.restore_object(bucket, "missing", 1, RestoreTier::Standard).await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(RestoreObjectError::NoSuchKey))
+        ));
+
+        let result = client
+            .restore_object(bucket, "archived", 1, RestoreTier::Bulk)
+            .await
+            .expect("restore_object failed");
+        assert!(!result.already_restored);
+        assert!(!client.is_object_restored("archived").unwrap());
+
+        // A second restore can't start until the first one finishes
+        let result = client.restore_object(bucket, "archived", 1, RestoreTier::Bulk).await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(
+                RestoreObjectError::RestoreAlreadyInProgress
+            ))
+        ));
+
+        client.complete_restore("archived").unwrap();
+        assert!(client.is_object_restored("archived").unwrap());
+
+        // Restoring a restored object extends its expiry
+        let result = client
+            .restore_object(bucket, "archived", 7, RestoreTier::Standard)
+            .await
+            .expect("restore_object failed");
+        assert!(result.already_restored);
+        let head = client
+            .head_object(bucket, "archived", &HeadObjectParams::new())
+            .await
+            .expect("head_object failed");
+        let Some(RestoreStatus::Restored { expiry }) = head.object.restore_status else {
+            panic!("object should be restored");
+        };
+        assert!(expiry > SystemTime::now() + Duration::from_secs(6 * 24 * 60 * 60));
+    }
+
+    #[tokio::test]
+    async fn test_list_multipart_uploads() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let mut upload_ids = Vec::new();
+        for key in ["a", "a", "b", "dir/c", "dir/d"] {
+            let upload_id = client
+                .create_multipart_upload(bucket, key, &Default::default())
+                .await
+                .expect("create_multipart_upload failed")
+                .upload_id;
+            upload_ids.push((key.to_owned(), upload_id));
+        }
+
+        // Page through the uploads one at a time, rolling up the directory
+        let mut key_marker = None;
+        let mut upload_id_marker = None;
+        let mut uploads = Vec::new();
+        let mut common_prefixes = Vec::new();
+        loop {
+            let result = client
+                .list_multipart_uploads(bucket, key_marker.as_deref(), upload_id_marker.as_deref(), "/", 1, "")
+                .await
+                .expect("list_multipart_uploads failed");
+            uploads.extend(result.uploads.into_iter().map(|u| (u.key, u.upload_id)));
+            common_prefixes.extend(result.common_prefixes);
+            if result.next_key_marker.is_none() {
+                break;
+            }
+            key_marker = result.next_key_marker;
+            upload_id_marker = result.next_upload_id_marker;
+        }
+        assert_eq!(uploads, upload_ids[..3]);
+        assert_eq!(common_prefixes, vec!["dir/".to_owned()]);
+
+        let result = client
+            .list_multipart_uploads(bucket, None, None, "", 1000, "dir/")
+            .await
+            .expect("list_multipart_uploads failed");
+        let uploads: Vec<_> = result.uploads.into_iter().map(|u| (u.key, u.upload_id)).collect();
+        assert_eq!(uploads, upload_ids[3..]);
+
+        // Aborted uploads are no longer listed
+        let (key, upload_id) = &upload_ids[0];
+        client
+            .abort_multipart_upload(bucket, key, upload_id)
+            .await
+            .expect("abort_multipart_upload failed");
+        let result = client
+            .list_multipart_uploads(bucket, None, None, "", 1000, "a")
+            .await
+            .expect("list_multipart_uploads failed");
+        let uploads: Vec<_> = result.uploads.into_iter().map(|u| (u.key, u.upload_id)).collect();
+        assert_eq!(uploads, upload_ids[1..2]);
+
+        let result = client
+            .list_multipart_uploads("other_bucket", None, None, "", 1000, "")
+            .await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(ListMultipartUploadsError::NoSuchBucket))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_objects() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        for key in ["a", "b", "c"] {
+            client.add_object(key, MockObject::constant(0u8, 5, ETag::for_tests()));
+        }
+
+        let delete_counter = client.new_counter(Operation::DeleteObjects);
+
+        // Keys that don't exist are still reported as deleted
+        let keys = vec!["a".to_owned(), "c".to_owned(), "missing".to_owned()];
+        let objects: Vec<_> = keys.iter().map(ObjectIdentifier::new).collect();
+        let result = client
+            .delete_objects(bucket, &objects)
+            .await
+            .expect("delete_objects failed");
+        assert_eq!(result.deleted, keys);
+        assert!(result.errors.is_empty());
+        assert!(!client.contains_key("a"));
+        assert!(client.contains_key("b"));
+        assert!(!client.contains_key("c"));
+        assert_eq!(delete_counter.count(), 1);
+
+        // Objects whose ETag doesn't match aren't deleted
+        let objects = [ObjectIdentifier::new("b").etag(ETag::from_str("other_etag").unwrap())];
+        let result = client
+            .delete_objects(bucket, &objects)
+            .await
+            .expect("delete_objects failed");
+        assert!(result.deleted.is_empty());
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].code, "PreconditionFailed");
+        assert!(client.contains_key("b"));
+        let objects = [ObjectIdentifier::new("b").etag(ETag::for_tests())];
+        let result = client
+            .delete_objects(bucket, &objects)
+            .await
+            .expect("delete_objects failed");
+        assert_eq!(result.deleted, vec!["b".to_owned()]);
+        client.add_object("b", MockObject::constant(0u8, 5, ETag::for_tests()));
+
+        let too_many_keys: Vec<_> = (0..=MAX_DELETE_OBJECTS_KEYS)
+            .map(|i| ObjectIdentifier::new(format!("key{i}")))
+            .collect();
+        let result = client.delete_objects(bucket, &too_many_keys).await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(DeleteObjectsError::TooManyKeys))
+        ));
+        assert!(client.contains_key("b"));
+
+        let result = client.delete_objects("other_bucket", &objects).await;
+        assert!(matches!(
+            result,
+            Err(ObjectClientError::ServiceError(DeleteObjectsError::NoSuchBucket))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_object_versions() {
+        let bucket = "test_bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+
+        let mut version_ids = Vec::new();
+        for body in [b"first", b"secnd"] {
+            let mut put_request = client.put_object(bucket, "key1", &Default::default()).await.unwrap();
+            put_request.write(body).await.unwrap();
+            let result = put_request.complete().await.unwrap();
+            version_ids.push(result.version_id.expect("mock bucket is versioned"));
+        }
+        client.add_object("dir/key2", b"other".into());
+
+        let head_result = client
+            .head_object(bucket, "key1", &HeadObjectParams::new())
+            .await
+            .unwrap();
+        assert_eq!(head_result.version_id.as_ref(), Some(&version_ids[1]));
+
+        client.delete_object(bucket, "key1").await.unwrap();
+        assert!(matches!(
+            client.head_object(bucket, "key1", &HeadObjectParams::new()).await,
+            Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
+        ));
+
+        // Older versions are still readable
+        let body = client
+            .get_object(
+                bucket,
+                "key1",
+                &GetObjectParams::new().version_id(version_ids[0].clone()),
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"first");
+        let head_result = client
+            .head_object(
+                bucket,
+                "key1",
+                &HeadObjectParams::new().version_id(version_ids[1].clone()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(head_result.object.size, 5);
+        assert!(matches!(
+            client
+                .get_object(
+                    bucket,
+                    "key1",
+                    &GetObjectParams::new().version_id("not-a-version".to_owned())
+                )
+                .await,
+            Err(ObjectClientError::ServiceError(GetObjectError::NoSuchKey))
+        ));
+
+        let result = client
+            .list_object_versions(bucket, None, None, "/", 1000, "")
+            .await
+            .unwrap();
+        assert_eq!(result.common_prefixes, vec!["dir/".to_owned()]);
+        assert_eq!(result.delete_markers.len(), 1);
+        assert!(result.delete_markers[0].is_latest);
+        let versions: Vec<_> = result.versions.iter().map(|v| (&v.version_id, v.is_latest)).collect();
+        assert_eq!(versions, vec![(&version_ids[1], false), (&version_ids[0], false)]);
+        assert!(result.next_key_marker.is_none());
+
+        // Paginate one entry at a time
+        let mut key_marker = None;
+        let mut version_id_marker = None;
+        let mut entries = 0;
+        loop {
+            let result = client
+                .list_object_versions(bucket, key_marker.as_deref(), version_id_marker.as_deref(), "", 1, "")
+                .await
+                .unwrap();
+            entries += result.versions.len() + result.delete_markers.len();
+            if result.next_key_marker.is_none() {
+                break;
+            }
+            key_marker = result.next_key_marker;
+            version_id_marker = result.next_version_id_marker;
+        }
+        assert_eq!(entries, 4);
+
+        // Removing an object directly also leaves a delete marker
+        client.remove_object("dir/key2");
+        let result = client
+            .list_object_versions(bucket, None, None, "", 1000, "dir/")
+            .await
+            .unwrap();
+        assert_eq!(result.versions.len(), 1);
+        assert!(!result.versions[0].is_latest);
+        assert_eq!(result.delete_markers.len(), 1);
+        assert!(result.delete_markers[0].is_latest);
+    }
+
     #[tokio::test]
     async fn counter_test() {
         let bucket = "test_bucket";
@@ -1326,19 +3107,20 @@ mod tests {
             bucket: bucket.to_owned(),
             part_size: 1024,
             unordered_list_seed: None,
+            ..Default::default()
         });
 
         let head_counter_1 = client.new_counter(Operation::HeadObject);
         let delete_counter_1 = client.new_counter(Operation::DeleteObject);
 
-        let _result = client.head_object(bucket, "key").await;
+        let _result = client.head_object(bucket, "key", &HeadObjectParams::new()).await;
         assert_eq!(1, head_counter_1.count());
         assert_eq!(0, delete_counter_1.count());
 
         let head_counter_2 = client.new_counter(Operation::HeadObject);
         assert_eq!(0, head_counter_2.count());
 
-        let _result = client.head_object(bucket, "key").await;
+        let _result = client.head_object(bucket, "key", &HeadObjectParams::new()).await;
         let _result = client.delete_object(bucket, "key").await;
         let _result = client.delete_object(bucket, "key").await;
         let _result = client.delete_object(bucket, "key").await;
@@ -0,0 +1,1408 @@
+//! An in-process HTTP server that serves the subset of the S3 REST API used by
+//! [S3CrtClient](crate::S3CrtClient), backed by a [MockClient]. This lets the real client be
+//! tested end-to-end without access to S3.
+//!
+//! The server only supports path-style addressing and doesn't check request signatures, so clients
+//! should be configured to use [`MockS3Server::endpoint_url`] as their endpoint, with
+//! [`AddressingStyle::Path`](crate::config::AddressingStyle::Path).
+
+#![cfg(feature = "mock_server")]
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use base64ct::{Base64, Encoding};
+use futures::StreamExt;
+use hyper::body::Bytes;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, HeaderMap, Method, Request, Response, Server, StatusCode};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use time::OffsetDateTime;
+use tokio::sync::oneshot;
+use tracing::{debug, error};
+use xmltree::{Element, XMLNode};
+
+use crate::mock_client::{MockClient, MockClientError};
+use crate::object_client::{
+    AbortMultipartUploadError, Checksum, ChecksumAlgorithm, CompleteMultipartUploadError, CompletedPart,
+    CopyObjectError, CopyObjectParams, CreateMultipartUploadError, DeleteObjectError, DeleteObjectsError, ETag,
+    GetObjectError, GetObjectParams, GetObjectRequest, GetObjectResponse, HeadObjectError, HeadObjectParams,
+    ListMultipartUploadsError, ListObjectVersionsError, ListObjectsError, ListObjectsParams, ObjectClient,
+    ObjectClientError, ObjectIdentifier, ObjectInfo, ObjectMetadata, PutObjectError, PutObjectParams, PutObjectRequest,
+    RestoreObjectError, RestoreStatus, RestoreTier, SseCustomerKey, UploadPartError,
+};
+
+/// An HTTP server running on a background thread that serves the bucket of a [MockClient].
+///
+/// The server shuts down when dropped.
+#[derive(Debug)]
+pub struct MockS3Server {
+    addr: SocketAddr,
+    received_requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockS3Server {
+    /// Start a new server on an unused local port, serving the objects in the given client
+    pub fn start(client: Arc<MockClient>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("mock-s3-server")
+            .enable_all()
+            .build()?;
+
+        let received_requests: Arc<Mutex<Vec<ReceivedRequest>>> = Default::default();
+        let (shutdown, shutdown_receiver) = oneshot::channel::<()>();
+        let server = {
+            let received_requests = received_requests.clone();
+            // Adopting the listener registers it with the runtime's reactor
+            let _guard = runtime.enter();
+            let builder = Server::from_tcp(listener).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            let make_service = make_service_fn(move |_connection| {
+                let client = client.clone();
+                let received_requests = received_requests.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request| {
+                        handle_request(client.clone(), received_requests.clone(), request)
+                    }))
+                }
+            });
+            builder.serve(make_service).with_graceful_shutdown(async {
+                let _ = shutdown_receiver.await;
+            })
+        };
+
+        let thread = std::thread::Builder::new()
+            .name("mock-s3-server".to_owned())
+            .spawn(move || {
+                if let Err(error) = runtime.block_on(server) {
+                    error!(?error, "mock S3 server failed");
+                }
+            })?;
+
+        Ok(Self {
+            addr,
+            received_requests,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+
+    /// The local address the server is listening on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The endpoint URL to configure clients with, like `http://127.0.0.1:1234`
+    pub fn endpoint_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// The requests the server has received so far, in the order they arrived
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.received_requests.lock().unwrap().clone()
+    }
+}
+
+/// A request received by a [MockS3Server], so that tests can check what a client sent
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// The HTTP method, like `PUT`
+    pub method: String,
+    /// The key the request was for, if any
+    pub key: Option<String>,
+    /// The decoded query parameters
+    pub query: HashMap<String, String>,
+    /// The request headers, with lowercase names
+    pub headers: HashMap<String, String>,
+}
+
+impl ReceivedRequest {
+    /// The value of the given header, if the request had it
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Whether the request had the given query parameter
+    pub fn has_query_param(&self, name: &str) -> bool {
+        self.query.contains_key(name)
+    }
+}
+
+impl Drop for MockS3Server {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// An S3 error response
+#[derive(Debug)]
+struct S3Error {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl S3Error {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn no_such_bucket() -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            "NoSuchBucket",
+            "The specified bucket does not exist",
+        )
+    }
+
+    fn no_such_key() -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NoSuchKey", "The specified key does not exist.")
+    }
+
+    fn no_such_upload() -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            "NoSuchUpload",
+            "The specified multipart upload does not exist.",
+        )
+    }
+
+    fn precondition_failed() -> Self {
+        Self::new(
+            StatusCode::PRECONDITION_FAILED,
+            "PreconditionFailed",
+            "At least one of the pre-conditions you specified did not hold",
+        )
+    }
+
+    fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "InvalidRequest", message)
+    }
+
+    fn into_response(self) -> Response<Body> {
+        let mut root = Element::new("Error");
+        push_text(&mut root, "Code", self.code);
+        push_text(&mut root, "Message", &self.message);
+        let mut response = xml_response(root);
+        *response.status_mut() = self.status;
+        response
+    }
+}
+
+/// Translate the service errors of the mock client into the error responses S3 sends for them
+trait ToS3Error {
+    fn to_s3_error(&self) -> S3Error;
+}
+
+impl<E: ToS3Error> From<ObjectClientError<E, MockClientError>> for S3Error {
+    fn from(error: ObjectClientError<E, MockClientError>) -> Self {
+        match error {
+            ObjectClientError::ServiceError(error) => error.to_s3_error(),
+            // The mock client's own errors are all caused by invalid requests (bad ranges, wrong
+            // SSE-C keys, ...), and we don't want the CRT to retry them
+            ObjectClientError::ClientError(error) => S3Error::invalid_request(error.to_string()),
+        }
+    }
+}
+
+impl ToS3Error for GetObjectError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            GetObjectError::NoSuchBucket => S3Error::no_such_bucket(),
+            GetObjectError::NoSuchKey => S3Error::no_such_key(),
+            GetObjectError::PreconditionFailed => S3Error::precondition_failed(),
+            GetObjectError::ChecksumMismatch { .. } | GetObjectError::UnverifiableChecksum => {
+                S3Error::new(StatusCode::INTERNAL_SERVER_ERROR, "InternalError", self.to_string())
+            }
+        }
+    }
+}
+
+impl ToS3Error for HeadObjectError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            HeadObjectError::NotFound => S3Error::new(StatusCode::NOT_FOUND, "NotFound", "Not Found"),
+        }
+    }
+}
+
+impl ToS3Error for CopyObjectError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            CopyObjectError::NotFound => S3Error::no_such_key(),
+        }
+    }
+}
+
+impl ToS3Error for PutObjectError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            PutObjectError::NoSuchBucket => S3Error::no_such_bucket(),
+            PutObjectError::PreconditionFailed => S3Error::precondition_failed(),
+            PutObjectError::ConditionalRequestConflict => S3Error::new(
+                StatusCode::CONFLICT,
+                "ConditionalRequestConflict",
+                "A conflicting conditional operation is currently in progress against this resource.",
+            ),
+        }
+    }
+}
+
+impl ToS3Error for UploadPartError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            UploadPartError::NoSuchUpload => S3Error::no_such_upload(),
+        }
+    }
+}
+
+impl ToS3Error for CompleteMultipartUploadError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            CompleteMultipartUploadError::NoSuchUpload => S3Error::no_such_upload(),
+            CompleteMultipartUploadError::InvalidPart => {
+                S3Error::new(StatusCode::BAD_REQUEST, "InvalidPart", self.to_string())
+            }
+            CompleteMultipartUploadError::InvalidPartOrder => {
+                S3Error::new(StatusCode::BAD_REQUEST, "InvalidPartOrder", self.to_string())
+            }
+            CompleteMultipartUploadError::EntityTooSmall => {
+                S3Error::new(StatusCode::BAD_REQUEST, "EntityTooSmall", self.to_string())
+            }
+        }
+    }
+}
+
+impl ToS3Error for AbortMultipartUploadError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            AbortMultipartUploadError::NoSuchUpload => S3Error::no_such_upload(),
+        }
+    }
+}
+
+impl ToS3Error for RestoreObjectError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            RestoreObjectError::NoSuchBucket => S3Error::no_such_bucket(),
+            RestoreObjectError::NoSuchKey => S3Error::no_such_key(),
+            RestoreObjectError::InvalidObjectState => {
+                S3Error::new(StatusCode::FORBIDDEN, "InvalidObjectState", self.to_string())
+            }
+            RestoreObjectError::RestoreAlreadyInProgress => {
+                S3Error::new(StatusCode::CONFLICT, "RestoreAlreadyInProgress", self.to_string())
+            }
+        }
+    }
+}
+
+impl ToS3Error for DeleteObjectsError {
+    fn to_s3_error(&self) -> S3Error {
+        match self {
+            DeleteObjectsError::NoSuchBucket => S3Error::no_such_bucket(),
+            DeleteObjectsError::TooManyKeys => S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", self.to_string()),
+        }
+    }
+}
+
+macro_rules! no_such_bucket_error {
+    ($($error:ident),*) => {
+        $(
+            impl ToS3Error for $error {
+                fn to_s3_error(&self) -> S3Error {
+                    match self {
+                        $error::NoSuchBucket => S3Error::no_such_bucket(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+no_such_bucket_error!(
+    ListObjectsError,
+    ListObjectVersionsError,
+    ListMultipartUploadsError,
+    DeleteObjectError,
+    CreateMultipartUploadError
+);
+
+type S3Result = Result<Response<Body>, S3Error>;
+
+/// A parsed S3 request
+struct S3Request {
+    method: Method,
+    bucket: String,
+    key: Option<String>,
+    query: HashMap<String, String>,
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl S3Request {
+    fn parse(request: Request<Body>) -> Result<Self, S3Error> {
+        let (parts, body) = request.into_parts();
+
+        let path = parts.uri.path().strip_prefix('/').unwrap_or(parts.uri.path());
+        let path = percent_decode_str(path)
+            .decode_utf8()
+            .map_err(|_| S3Error::invalid_request("path is not valid UTF-8"))?;
+        // Bucket names can't contain '/', so the first one separates the bucket from the key
+        let (bucket, key) = match path.split_once('/') {
+            Some((bucket, key)) if !key.is_empty() => (bucket.to_owned(), Some(key.to_owned())),
+            Some((bucket, _)) => (bucket.to_owned(), None),
+            None => (path.into_owned(), None),
+        };
+
+        let mut query = HashMap::new();
+        for pair in parts
+            .uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|p| !p.is_empty())
+        {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |s: &str| {
+                percent_decode_str(s)
+                    .decode_utf8()
+                    .map(|s| s.into_owned())
+                    .map_err(|_| S3Error::invalid_request("query is not valid UTF-8"))
+            };
+            query.insert(decode(name)?, decode(value)?);
+        }
+
+        Ok(Self {
+            method: parts.method,
+            bucket,
+            key,
+            query,
+            headers: parts.headers,
+            body,
+        })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    fn to_received_request(&self) -> ReceivedRequest {
+        let headers = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned())))
+            .collect();
+        ReceivedRequest {
+            method: self.method.to_string(),
+            key: self.key.clone(),
+            query: self.query.clone(),
+            headers,
+        }
+    }
+
+    fn query_param(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+
+    fn query_usize(&self, name: &str, default: usize) -> Result<usize, S3Error> {
+        match self.query_param(name) {
+            Some(value) => usize::from_str(value).map_err(|_| S3Error::invalid_request(format!("invalid {name}"))),
+            None => Ok(default),
+        }
+    }
+
+    /// Read the whole request body, decoding it if it was sent with `aws-chunked` encoding
+    async fn read_body(&mut self) -> Result<Vec<u8>, S3Error> {
+        let body = hyper::body::to_bytes(std::mem::take(&mut self.body))
+            .await
+            .map_err(|e| S3Error::invalid_request(format!("failed to read body: {e}")))?;
+        let chunked = self
+            .header("content-encoding")
+            .is_some_and(|e| e.contains("aws-chunked"))
+            || self
+                .header("x-amz-content-sha256")
+                .is_some_and(|s| s.starts_with("STREAMING-"));
+        if chunked {
+            decode_aws_chunked(&body).ok_or_else(|| S3Error::invalid_request("invalid aws-chunked body"))
+        } else {
+            Ok(body.to_vec())
+        }
+    }
+
+    fn sse_customer_key(&self) -> Result<Option<SseCustomerKey>, S3Error> {
+        let Some(key) = self.header("x-amz-server-side-encryption-customer-key") else {
+            return Ok(None);
+        };
+        let key = Base64::decode_vec(key).map_err(|_| S3Error::invalid_request("invalid SSE-C key"))?;
+        let key = SseCustomerKey::new(&key).map_err(|e| S3Error::invalid_request(e.to_string()))?;
+        Ok(Some(key))
+    }
+
+    /// The checksum S3 stored for the requested object, if the request asked for checksums
+    fn checksum(&self, client: &MockClient) -> Checksum {
+        if self.header("x-amz-checksum-mode") != Some("ENABLED") {
+            return Checksum::default();
+        }
+        let key = self.key.as_deref().unwrap_or_default();
+        client
+            .get_object_version(key, self.query_param("versionId"))
+            .map(|object| object.checksum)
+            .unwrap_or_default()
+    }
+
+    /// Build the parameters for PutObject and CreateMultipartUpload from the request headers
+    fn put_object_params(&self) -> Result<PutObjectParams, S3Error> {
+        let checksum_algorithm = self
+            .header("x-amz-checksum-algorithm")
+            .or(self.header("x-amz-sdk-checksum-algorithm"))
+            .or(self
+                .header("x-amz-trailer")
+                .and_then(|t| t.strip_prefix("x-amz-checksum-")))
+            .and_then(parse_checksum_algorithm);
+
+        let mut object_metadata = ObjectMetadata::new();
+        for (name, value) in self.headers.iter() {
+            if let Some(name) = name.as_str().strip_prefix("x-amz-meta-") {
+                let value = value
+                    .to_str()
+                    .map_err(|_| S3Error::invalid_request("invalid metadata value"))?;
+                object_metadata.insert(name.to_owned(), value.to_owned());
+            }
+        }
+
+        // aws-chunked describes how the body was sent, not how the object is stored
+        let content_encoding = self
+            .header("content-encoding")
+            .map(|encoding| {
+                encoding
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|e| *e != "aws-chunked")
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .filter(|encoding| !encoding.is_empty());
+
+        Ok(PutObjectParams {
+            checksum_algorithm,
+            storage_class: self.header("x-amz-storage-class").map(str::to_owned),
+            server_side_encryption: self.header("x-amz-server-side-encryption").map(str::to_owned),
+            ssekms_key_id: self
+                .header("x-amz-server-side-encryption-aws-kms-key-id")
+                .map(str::to_owned),
+            sse_customer_key: self.sse_customer_key()?,
+            if_none_match: self.header("if-none-match").map(str::to_owned),
+            if_match: self.header("if-match").map(parse_etag),
+            object_metadata,
+            content_type: self.header("content-type").map(str::to_owned),
+            content_encoding,
+            cache_control: self.header("cache-control").map(str::to_owned),
+        })
+    }
+}
+
+async fn handle_request(
+    client: Arc<MockClient>,
+    received_requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+    let request_id = format!("{:016X}", NEXT_REQUEST_ID.fetch_add(1, Ordering::SeqCst));
+
+    let method = request.method().clone();
+    let uri = request.uri().clone();
+    let result = match S3Request::parse(request) {
+        Ok(request) => {
+            received_requests.lock().unwrap().push(request.to_received_request());
+            route(&client, request).await
+        }
+        Err(error) => Err(error),
+    };
+    let mut response = result.unwrap_or_else(|error| {
+        let mut response = error.into_response();
+        // Like S3, don't send an error document in response to a HEAD request
+        if method == Method::HEAD {
+            *response.body_mut() = Body::empty();
+            response.headers_mut().remove("content-length");
+        }
+        response
+    });
+    response
+        .headers_mut()
+        .insert("x-amz-request-id", request_id.parse().unwrap());
+
+    debug!(%method, %uri, status = %response.status(), %request_id, "mock S3 server request");
+    Ok(response)
+}
+
+async fn route(client: &MockClient, request: S3Request) -> S3Result {
+    let has = |name| request.query.contains_key(name);
+    match (&request.method, request.key.is_some()) {
+        (&Method::HEAD, false) => head_bucket(client, request).await,
+        (&Method::GET, false) if has("versions") => list_object_versions(client, request).await,
+        (&Method::GET, false) if has("uploads") => list_multipart_uploads(client, request).await,
+        (&Method::GET, false) => list_objects(client, request).await,
+        (&Method::POST, false) if has("delete") => delete_objects(client, request).await,
+        (&Method::GET, true) if has("attributes") => Err(not_implemented("GetObjectAttributes")),
+        (&Method::GET, true) => get_object(client, request).await,
+        (&Method::HEAD, true) => head_object(client, request).await,
+        (&Method::PUT, true) if has("uploadId") && request.headers.contains_key("x-amz-copy-source") => {
+            upload_part_copy(client, request).await
+        }
+        (&Method::PUT, true) if has("uploadId") => upload_part(client, request).await,
+        (&Method::PUT, true) if request.headers.contains_key("x-amz-copy-source") => copy_object(client, request).await,
+        (&Method::PUT, true) => put_object(client, request).await,
+        (&Method::POST, true) if has("uploads") => create_multipart_upload(client, request).await,
+        (&Method::POST, true) if has("uploadId") => complete_multipart_upload(client, request).await,
+        (&Method::POST, true) if has("restore") => restore_object(client, request).await,
+        (&Method::DELETE, true) if has("uploadId") => abort_multipart_upload(client, request).await,
+        (&Method::DELETE, true) => delete_object(client, request).await,
+        _ => Err(not_implemented(request.method.as_str())),
+    }
+}
+
+fn not_implemented(operation: &str) -> S3Error {
+    S3Error::new(
+        StatusCode::NOT_IMPLEMENTED,
+        "NotImplemented",
+        format!("{operation} is not supported by the mock S3 server"),
+    )
+}
+
+async fn head_bucket(client: &MockClient, request: S3Request) -> S3Result {
+    // The mock client doesn't have a HeadBucket operation, so just check the bucket exists by
+    // listing it
+    client
+        .list_objects(&request.bucket, &ListObjectsParams::new().max_keys(0))
+        .await
+        .map_err(S3Error::from)?;
+    Ok(empty_response(StatusCode::OK))
+}
+
+async fn list_objects(client: &MockClient, request: S3Request) -> S3Result {
+    if request.query_param("list-type") != Some("2") {
+        return Err(not_implemented("ListObjects (v1)"));
+    }
+    let continuation_token = request.query_param("continuation-token");
+    let delimiter = request.query_param("delimiter").unwrap_or_default();
+    let max_keys = request.query_usize("max-keys", 1000)?;
+    let prefix = request.query_param("prefix").unwrap_or_default();
+    let start_after = request.query_param("start-after");
+    let fetch_owner = request.query_param("fetch-owner") == Some("true");
+    let url_encoded = match request.query_param("encoding-type") {
+        None => false,
+        Some("url") => true,
+        Some(_) => {
+            return Err(S3Error::new(
+                StatusCode::BAD_REQUEST,
+                "InvalidArgument",
+                "Invalid Encoding Method specified in Request",
+            ))
+        }
+    };
+    let encode = |value: &str| {
+        if url_encoded {
+            url_encode(value)
+        } else {
+            value.to_owned()
+        }
+    };
+
+    let mut params = ListObjectsParams::new()
+        .prefix(prefix)
+        .delimiter(delimiter)
+        .max_keys(max_keys)
+        .continuation_token(continuation_token)
+        .fetch_owner(fetch_owner);
+    if let Some(start_after) = start_after {
+        params = params.start_after(start_after);
+    }
+    let mut result = client.list_objects(&request.bucket, &params).await?;
+
+    let mut root = Element::new("ListBucketResult");
+    push_text(&mut root, "Name", &request.bucket);
+    push_text(&mut root, "Prefix", &encode(prefix));
+    push_text(&mut root, "Delimiter", &encode(delimiter));
+    if url_encoded {
+        push_text(&mut root, "EncodingType", "url");
+    }
+    if let Some(start_after) = start_after {
+        push_text(&mut root, "StartAfter", &encode(start_after));
+    }
+    push_text(&mut root, "MaxKeys", &max_keys.to_string());
+    push_text(
+        &mut root,
+        "KeyCount",
+        &(result.objects.len() + result.common_prefixes.len()).to_string(),
+    );
+    push_text(
+        &mut root,
+        "IsTruncated",
+        &result.next_continuation_token.is_some().to_string(),
+    );
+    if let Some(token) = continuation_token {
+        push_text(&mut root, "ContinuationToken", token);
+    }
+    if let Some(token) = &result.next_continuation_token {
+        push_text(&mut root, "NextContinuationToken", token);
+    }
+    for object in result.objects.iter_mut() {
+        object.key = encode(&object.key);
+        let mut contents = Element::new("Contents");
+        push_object_info(&mut contents, object);
+        push_child(&mut root, contents);
+    }
+    let common_prefixes: Vec<_> = result.common_prefixes.iter().map(|prefix| encode(prefix)).collect();
+    push_common_prefixes(&mut root, &common_prefixes);
+
+    Ok(xml_response(root))
+}
+
+async fn list_object_versions(client: &MockClient, request: S3Request) -> S3Result {
+    let key_marker = request.query_param("key-marker");
+    let version_id_marker = request.query_param("version-id-marker");
+    let delimiter = request.query_param("delimiter").unwrap_or_default();
+    let max_keys = request.query_usize("max-keys", 1000)?;
+    let prefix = request.query_param("prefix").unwrap_or_default();
+
+    let result = client
+        .list_object_versions(
+            &request.bucket,
+            key_marker,
+            version_id_marker,
+            delimiter,
+            max_keys,
+            prefix,
+        )
+        .await?;
+
+    let mut root = Element::new("ListVersionsResult");
+    push_text(&mut root, "Name", &request.bucket);
+    push_text(&mut root, "Prefix", prefix);
+    push_text(&mut root, "Delimiter", delimiter);
+    push_text(&mut root, "MaxKeys", &max_keys.to_string());
+    push_text(&mut root, "IsTruncated", &result.next_key_marker.is_some().to_string());
+    if let Some(marker) = &result.next_key_marker {
+        push_text(&mut root, "NextKeyMarker", marker);
+    }
+    if let Some(marker) = &result.next_version_id_marker {
+        push_text(&mut root, "NextVersionIdMarker", marker);
+    }
+    for version in &result.versions {
+        let mut element = Element::new("Version");
+        push_object_info(&mut element, &version.object);
+        push_text(&mut element, "VersionId", &version.version_id);
+        push_text(&mut element, "IsLatest", &version.is_latest.to_string());
+        push_child(&mut root, element);
+    }
+    for marker in &result.delete_markers {
+        let mut element = Element::new("DeleteMarker");
+        push_text(&mut element, "Key", &marker.key);
+        push_text(&mut element, "VersionId", &marker.version_id);
+        push_text(&mut element, "IsLatest", &marker.is_latest.to_string());
+        push_text(&mut element, "LastModified", &format_rfc3339(marker.last_modified));
+        push_child(&mut root, element);
+    }
+    push_common_prefixes(&mut root, &result.common_prefixes);
+
+    Ok(xml_response(root))
+}
+
+async fn list_multipart_uploads(client: &MockClient, request: S3Request) -> S3Result {
+    let key_marker = request.query_param("key-marker");
+    let upload_id_marker = request.query_param("upload-id-marker");
+    let delimiter = request.query_param("delimiter").unwrap_or_default();
+    let max_uploads = request.query_usize("max-uploads", 1000)?;
+    let prefix = request.query_param("prefix").unwrap_or_default();
+
+    let result = client
+        .list_multipart_uploads(
+            &request.bucket,
+            key_marker,
+            upload_id_marker,
+            delimiter,
+            max_uploads,
+            prefix,
+        )
+        .await?;
+
+    let mut root = Element::new("ListMultipartUploadsResult");
+    push_text(&mut root, "Bucket", &request.bucket);
+    push_text(&mut root, "Prefix", prefix);
+    push_text(&mut root, "Delimiter", delimiter);
+    push_text(&mut root, "MaxUploads", &max_uploads.to_string());
+    push_text(&mut root, "IsTruncated", &result.next_key_marker.is_some().to_string());
+    if let Some(marker) = &result.next_key_marker {
+        push_text(&mut root, "NextKeyMarker", marker);
+    }
+    if let Some(marker) = &result.next_upload_id_marker {
+        push_text(&mut root, "NextUploadIdMarker", marker);
+    }
+    for upload in &result.uploads {
+        let mut element = Element::new("Upload");
+        push_text(&mut element, "Key", &upload.key);
+        push_text(&mut element, "UploadId", &upload.upload_id);
+        push_text(&mut element, "Initiated", &format_rfc3339(upload.initiated));
+        push_text(
+            &mut element,
+            "StorageClass",
+            upload.storage_class.as_deref().unwrap_or("STANDARD"),
+        );
+        push_child(&mut root, element);
+    }
+    push_common_prefixes(&mut root, &result.common_prefixes);
+
+    Ok(xml_response(root))
+}
+
+async fn delete_objects(client: &MockClient, mut request: S3Request) -> S3Result {
+    let body = request.read_body().await?;
+    let mut document = parse_xml(&body)?;
+    let quiet = document
+        .get_child("Quiet")
+        .and_then(|quiet| quiet.get_text())
+        .is_some_and(|quiet| quiet == "true");
+    let mut objects = Vec::new();
+    while let Some(object) = document.take_child("Object") {
+        let mut identifier = ObjectIdentifier::new(get_xml_field(&object, "Key")?);
+        if let Some(etag) = object.get_child("ETag").and_then(|etag| etag.get_text()) {
+            identifier = identifier.etag(parse_etag(&etag));
+        }
+        objects.push(identifier);
+    }
+
+    let result = client.delete_objects(&request.bucket, &objects).await?;
+
+    let mut root = Element::new("DeleteResult");
+    if !quiet {
+        for key in &result.deleted {
+            let mut element = Element::new("Deleted");
+            push_text(&mut element, "Key", key);
+            push_child(&mut root, element);
+        }
+    }
+    for error in &result.errors {
+        let mut element = Element::new("Error");
+        push_text(&mut element, "Key", &error.key);
+        push_text(&mut element, "Code", &error.code);
+        push_text(&mut element, "Message", &error.message);
+        push_child(&mut root, element);
+    }
+
+    Ok(xml_response(root))
+}
+
+async fn get_object(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    // S3 doesn't validate the body it sends against the checksum, so we don't use the mock
+    // client's checksum mode, which does
+    let mut params = GetObjectParams::new()
+        .if_match(request.header("if-match").map(parse_etag))
+        .sse_customer_key(request.sse_customer_key()?);
+    if let Some(version_id) = request.query_param("versionId") {
+        params = params.version_id(version_id.to_owned());
+    }
+
+    // Request the whole object first, which checks the preconditions and tells us its size
+    let mut result = client.get_object(&request.bucket, key, &params).await?;
+    let object_size = result
+        .get_object_response()
+        .and_then(|response| response.content_length)
+        .expect("mock client always returns the content length");
+
+    let range = match request.header("range") {
+        Some(range) => {
+            let range = parse_range(range, object_size)?;
+            result = client
+                .get_object(&request.bucket, key, &params.range(Some(range.clone())))
+                .await?;
+            Some(range)
+        }
+        None => None,
+    };
+    let mut response = result
+        .get_object_response()
+        .expect("mock client returns the response immediately");
+    if range.is_none() {
+        response.checksum = request.checksum(client);
+    }
+
+    let mut builder = Response::builder();
+    builder = match &range {
+        Some(range) => builder.status(StatusCode::PARTIAL_CONTENT).header(
+            "content-range",
+            format!("bytes {}-{}/{}", range.start, range.end - 1, object_size),
+        ),
+        None => builder.status(StatusCode::OK),
+    };
+    builder = add_object_headers(builder, &response)
+        .header("content-length", range.map_or(object_size, |r| r.end - r.start))
+        .header("accept-ranges", "bytes");
+
+    let body = result.map(|part| {
+        part.map(|(_offset, data)| Bytes::from(Vec::from(data)))
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+    });
+    Ok(builder.body(Body::wrap_stream(body)).unwrap())
+}
+
+async fn head_object(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    let mut params = HeadObjectParams::new().sse_customer_key(request.sse_customer_key()?);
+    if let Some(version_id) = request.query_param("versionId") {
+        params = params.version_id(version_id.to_owned());
+    }
+    let result = client.head_object(&request.bucket, key, &params).await?;
+    let object_size = result.object.size;
+
+    let mut builder = Response::builder()
+        .header("etag", quote_etag(&result.object.etag))
+        .header("last-modified", format_rfc2822(result.object.last_modified))
+        .header("accept-ranges", "bytes");
+    builder = add_metadata_headers(
+        builder,
+        &result.object_metadata,
+        result.content_type.as_deref(),
+        result.content_encoding.as_deref(),
+        result.cache_control.as_deref(),
+    );
+    if let Some(storage_class) = &result.object.storage_class {
+        builder = builder.header("x-amz-storage-class", storage_class);
+    }
+    if let Some(restore_status) = result.object.restore_status {
+        builder = builder.header("x-amz-restore", format_restore_status(restore_status));
+    }
+    if let Some(version_id) = &result.version_id {
+        builder = builder.header("x-amz-version-id", version_id);
+    }
+    builder = add_checksum_headers(builder, &request.checksum(client));
+
+    // Like S3, HeadObject honors ranges, which the CRT uses to learn the size of the object
+    builder = match request.header("range") {
+        Some(range) => {
+            let range = parse_range(range, object_size)?;
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(
+                    "content-range",
+                    format!("bytes {}-{}/{}", range.start, range.end - 1, object_size),
+                )
+                .header("content-length", range.end - range.start)
+        }
+        None => builder.status(StatusCode::OK).header("content-length", object_size),
+    };
+
+    Ok(builder.body(Body::empty()).unwrap())
+}
+
+async fn put_object(client: &MockClient, mut request: S3Request) -> S3Result {
+    let key = request.key.clone().unwrap();
+    let params = request.put_object_params()?;
+    let body = request.read_body().await?;
+
+    let mut put_request = client.put_object(&request.bucket, &key, &params).await?;
+    put_request.write(&body).await?;
+    let result = put_request.complete().await?;
+
+    // The mock client doesn't return the ETag of the new object, so look it up
+    let head_params = HeadObjectParams::new().sse_customer_key(params.sse_customer_key.clone());
+    let head = client.head_object(&request.bucket, &key, &head_params).await?;
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header("etag", quote_etag(&head.object.etag));
+    if let Some(version_id) = &result.version_id {
+        builder = builder.header("x-amz-version-id", version_id);
+    }
+    if let Some(sse_type) = &result.sse_type {
+        builder = builder.header("x-amz-server-side-encryption", sse_type);
+    }
+    if let Some(key_id) = &result.sse_kms_key_id {
+        builder = builder.header("x-amz-server-side-encryption-aws-kms-key-id", key_id);
+    }
+    Ok(builder.body(Body::empty()).unwrap())
+}
+
+async fn copy_object(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    let (source_bucket, source_key) = parse_copy_source(&request)?;
+    client
+        .copy_object(
+            &source_bucket,
+            &source_key,
+            &request.bucket,
+            key,
+            &CopyObjectParams::new(),
+        )
+        .await?;
+    let head = client
+        .head_object(&request.bucket, key, &HeadObjectParams::new())
+        .await?;
+
+    let mut root = Element::new("CopyObjectResult");
+    push_text(&mut root, "ETag", &quote_etag(&head.object.etag));
+    push_text(&mut root, "LastModified", &format_rfc3339(head.object.last_modified));
+    Ok(xml_response(root))
+}
+
+async fn create_multipart_upload(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    let params = request.put_object_params()?;
+    let result = client.create_multipart_upload(&request.bucket, key, &params).await?;
+
+    let mut root = Element::new("InitiateMultipartUploadResult");
+    push_text(&mut root, "Bucket", &request.bucket);
+    push_text(&mut root, "Key", key);
+    push_text(&mut root, "UploadId", &result.upload_id);
+    Ok(xml_response(root))
+}
+
+fn part_number(request: &S3Request) -> Result<usize, S3Error> {
+    request
+        .query_param("partNumber")
+        .and_then(|n| usize::from_str(n).ok())
+        .ok_or_else(|| S3Error::invalid_request("missing or invalid partNumber"))
+}
+
+async fn upload_part(client: &MockClient, mut request: S3Request) -> S3Result {
+    let key = request.key.clone().unwrap();
+    let upload_id = request.query_param("uploadId").unwrap().to_owned();
+    let part_number = part_number(&request)?;
+    let body = request.read_body().await?;
+
+    let result = client
+        .upload_part(&request.bucket, &key, &upload_id, part_number, &body)
+        .await?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("etag", quote_etag(result.etag.as_str()))
+        .body(Body::empty())
+        .unwrap())
+}
+
+async fn upload_part_copy(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    let upload_id = request.query_param("uploadId").unwrap();
+    let part_number = part_number(&request)?;
+    let (source_bucket, source_key) = parse_copy_source(&request)?;
+
+    let source = client
+        .head_object(&source_bucket, &source_key, &HeadObjectParams::new())
+        .await?;
+    let range = match request.header("x-amz-copy-source-range") {
+        Some(range) => parse_range(range, source.object.size)?,
+        None => 0..source.object.size,
+    };
+    let mut contents = Vec::with_capacity((range.end - range.start) as usize);
+    if !range.is_empty() {
+        let params = GetObjectParams::new().range(Some(range));
+        let mut body = client.get_object(&source_bucket, &source_key, &params).await?;
+        while let Some(part) = body.next().await {
+            let (_offset, data) = part?;
+            contents.extend_from_slice(&data);
+        }
+    }
+
+    let result = client
+        .upload_part(&request.bucket, key, upload_id, part_number, &contents)
+        .await?;
+
+    let mut root = Element::new("CopyPartResult");
+    push_text(&mut root, "ETag", &quote_etag(result.etag.as_str()));
+    push_text(&mut root, "LastModified", &format_rfc3339(OffsetDateTime::now_utc()));
+    Ok(xml_response(root))
+}
+
+async fn complete_multipart_upload(client: &MockClient, mut request: S3Request) -> S3Result {
+    let key = request.key.clone().unwrap();
+    let upload_id = request.query_param("uploadId").unwrap().to_owned();
+
+    let body = request.read_body().await?;
+    let mut document = parse_xml(&body)?;
+    let mut parts = Vec::new();
+    while let Some(part) = document.take_child("Part") {
+        let part_number = get_xml_field(&part, "PartNumber")?;
+        parts.push(CompletedPart {
+            part_number: usize::from_str(&part_number)
+                .map_err(|_| S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", "invalid PartNumber"))?,
+            etag: parse_etag(&get_xml_field(&part, "ETag")?),
+        });
+    }
+
+    // The mock client's CompleteMultipartUpload doesn't support preconditions, so check them here
+    if request.header("if-none-match") == Some("*") && client.contains_key(&key) {
+        return Err(S3Error::precondition_failed());
+    }
+    if let Some(if_match) = request.header("if-match").map(parse_etag) {
+        let head = client
+            .head_object(&request.bucket, &key, &HeadObjectParams::new())
+            .await
+            .map_err(|_| S3Error::no_such_key())?;
+        if head.object.etag != if_match.as_str() {
+            return Err(S3Error::precondition_failed());
+        }
+    }
+
+    let result = client
+        .complete_multipart_upload(&request.bucket, &key, &upload_id, &parts)
+        .await?;
+
+    let mut root = Element::new("CompleteMultipartUploadResult");
+    push_text(&mut root, "Bucket", &request.bucket);
+    push_text(&mut root, "Key", &key);
+    push_text(&mut root, "ETag", &quote_etag(result.etag.as_str()));
+    let mut response = xml_response(root);
+    if let Some(version_id) = result.version_id {
+        response
+            .headers_mut()
+            .insert("x-amz-version-id", version_id.parse().unwrap());
+    }
+    Ok(response)
+}
+
+async fn abort_multipart_upload(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    let upload_id = request.query_param("uploadId").unwrap();
+    client.abort_multipart_upload(&request.bucket, key, upload_id).await?;
+    Ok(empty_response(StatusCode::NO_CONTENT))
+}
+
+async fn restore_object(client: &MockClient, mut request: S3Request) -> S3Result {
+    let key = request.key.clone().unwrap();
+    let body = request.read_body().await?;
+    let document = parse_xml(&body)?;
+    let days = get_xml_field(&document, "Days")?
+        .parse()
+        .map_err(|_| S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", "invalid Days"))?;
+    let tier = match document.get_child("GlacierJobParameters") {
+        Some(parameters) => match get_xml_field(parameters, "Tier")?.as_str() {
+            "Expedited" => RestoreTier::Expedited,
+            "Standard" => RestoreTier::Standard,
+            "Bulk" => RestoreTier::Bulk,
+            tier => {
+                return Err(S3Error::new(
+                    StatusCode::BAD_REQUEST,
+                    "MalformedXML",
+                    format!("invalid Tier {tier}"),
+                ))
+            }
+        },
+        None => RestoreTier::Standard,
+    };
+
+    let result = client.restore_object(&request.bucket, &key, days, tier).await?;
+    let status = if result.already_restored {
+        StatusCode::OK
+    } else {
+        StatusCode::ACCEPTED
+    };
+    Ok(empty_response(status))
+}
+
+async fn delete_object(client: &MockClient, request: S3Request) -> S3Result {
+    let key = request.key.as_deref().unwrap();
+    client.delete_object(&request.bucket, key).await?;
+    Ok(empty_response(StatusCode::NO_CONTENT))
+}
+
+/// Parse an HTTP `Range` header (`bytes=a-b`, `bytes=a-`, or `bytes=-n`) into the range of bytes
+/// to return from an object of the given size. Like S3, the end of the range is clamped to the
+/// size of the object, and a range that starts past the end of the object is unsatisfiable.
+fn parse_range(header: &str, object_size: u64) -> Result<Range<u64>, S3Error> {
+    let invalid = || S3Error::invalid_request(format!("invalid range {header}"));
+    let (start, end) = header
+        .strip_prefix("bytes=")
+        .and_then(|range| range.split_once('-'))
+        .ok_or_else(invalid)?;
+    let parse = |s: &str| u64::from_str(s).map_err(|_| invalid());
+    let range = match (start, end) {
+        ("", suffix) => object_size.saturating_sub(parse(suffix)?)..object_size,
+        (start, "") => parse(start)?..object_size,
+        (start, end) => parse(start)?..(parse(end)? + 1).min(object_size),
+    };
+    if range.start >= object_size || range.start >= range.end {
+        return Err(S3Error::new(
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            "InvalidRange",
+            "The requested range is not satisfiable",
+        ));
+    }
+    Ok(range)
+}
+
+/// Parse the bucket and key out of an `x-amz-copy-source` header
+fn parse_copy_source(request: &S3Request) -> Result<(String, String), S3Error> {
+    let source = request.header("x-amz-copy-source").unwrap_or_default();
+    let source = percent_decode_str(source.strip_prefix('/').unwrap_or(source))
+        .decode_utf8()
+        .map_err(|_| S3Error::invalid_request("copy source is not valid UTF-8"))?;
+    let (bucket, key) = source
+        .split_once('/')
+        .ok_or_else(|| S3Error::invalid_request("invalid copy source"))?;
+    Ok((bucket.to_owned(), key.to_owned()))
+}
+
+fn parse_checksum_algorithm(name: &str) -> Option<ChecksumAlgorithm> {
+    match name.to_ascii_uppercase().as_str() {
+        "CRC32C" => Some(ChecksumAlgorithm::Crc32c),
+        "CRC32" => Some(ChecksumAlgorithm::Crc32),
+        "SHA1" => Some(ChecksumAlgorithm::Sha1),
+        "SHA256" => Some(ChecksumAlgorithm::Sha256),
+        _ => None,
+    }
+}
+
+/// S3 sends ETags in quotes, but the mock client stores them without
+fn parse_etag(etag: &str) -> ETag {
+    ETag::from_str(etag.trim_matches('"')).expect("ETag parsing is infallible")
+}
+
+fn quote_etag(etag: &str) -> String {
+    format!("\"{}\"", etag.trim_matches('"'))
+}
+
+/// Decode a body sent with `aws-chunked` content encoding, which the CRT uses to send trailing
+/// checksums. Each chunk is `<hex size>[;chunk-signature=...]\r\n<data>\r\n`, and the final
+/// zero-sized chunk is followed by the trailing headers, which we ignore.
+fn decode_aws_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    fn take_line<'a>(body: &mut &'a [u8]) -> Option<&'a [u8]> {
+        let end = body.windows(2).position(|w| w == b"\r\n")?;
+        let line = &body[..end];
+        *body = &body[end + 2..];
+        Some(line)
+    }
+
+    let mut decoded = Vec::new();
+    loop {
+        let header = std::str::from_utf8(take_line(&mut body)?).ok()?;
+        let size = header.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(body.get(..size)?);
+        body = body.get(size..)?;
+        if take_line(&mut body)? != b"" {
+            return None;
+        }
+    }
+}
+
+fn format_rfc3339(time: OffsetDateTime) -> String {
+    time.format(&Rfc3339).expect("timestamps are always formattable")
+}
+
+fn format_rfc2822(time: OffsetDateTime) -> String {
+    time.format(&Rfc2822).expect("timestamps are always formattable")
+}
+
+fn format_restore_status(restore_status: RestoreStatus) -> String {
+    match restore_status {
+        RestoreStatus::InProgress => r#"ongoing-request="true""#.to_owned(),
+        RestoreStatus::Restored { expiry } => format!(
+            r#"ongoing-request="false", expiry-date="{}""#,
+            format_rfc2822(expiry.into())
+        ),
+    }
+}
+
+fn add_metadata_headers(
+    mut builder: hyper::http::response::Builder,
+    object_metadata: &ObjectMetadata,
+    content_type: Option<&str>,
+    content_encoding: Option<&str>,
+    cache_control: Option<&str>,
+) -> hyper::http::response::Builder {
+    for (name, value) in object_metadata {
+        builder = builder.header(format!("x-amz-meta-{name}"), value);
+    }
+    if let Some(content_type) = content_type {
+        builder = builder.header("content-type", content_type);
+    }
+    if let Some(content_encoding) = content_encoding {
+        builder = builder.header("content-encoding", content_encoding);
+    }
+    if let Some(cache_control) = cache_control {
+        builder = builder.header("cache-control", cache_control);
+    }
+    builder
+}
+
+fn add_object_headers(
+    mut builder: hyper::http::response::Builder,
+    response: &GetObjectResponse,
+) -> hyper::http::response::Builder {
+    builder = builder
+        .header("etag", quote_etag(response.etag.as_str()))
+        .header("last-modified", format_rfc2822(response.last_modified));
+    builder = add_checksum_headers(builder, &response.checksum);
+    add_metadata_headers(
+        builder,
+        &response.object_metadata,
+        response.content_type.as_deref(),
+        response.content_encoding.as_deref(),
+        response.cache_control.as_deref(),
+    )
+}
+
+fn add_checksum_headers(
+    mut builder: hyper::http::response::Builder,
+    checksum: &Checksum,
+) -> hyper::http::response::Builder {
+    let checksums = [
+        ("x-amz-checksum-crc32", &checksum.checksum_crc32),
+        ("x-amz-checksum-crc32c", &checksum.checksum_crc32c),
+        ("x-amz-checksum-sha1", &checksum.checksum_sha1),
+        ("x-amz-checksum-sha256", &checksum.checksum_sha256),
+    ];
+    for (name, value) in checksums {
+        if let Some(value) = value {
+            builder = builder.header(name, value);
+        }
+    }
+    builder
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    Response::builder().status(status).body(Body::empty()).unwrap()
+}
+
+fn xml_response(root: Element) -> Response<Body> {
+    let mut body = Vec::new();
+    root.write(&mut body).expect("writing to a Vec can't fail");
+    Response::builder()
+        .header("content-type", "application/xml")
+        .header("content-length", body.len())
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn parse_xml(body: &[u8]) -> Result<Element, S3Error> {
+    Element::parse(body).map_err(|e| S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", e.to_string()))
+}
+
+fn get_xml_field(element: &Element, name: &str) -> Result<String, S3Error> {
+    element
+        .get_child(name)
+        .and_then(|child| child.get_text())
+        .map(|text| text.into_owned())
+        .ok_or_else(|| S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", format!("missing {name}")))
+}
+
+fn push_child(parent: &mut Element, child: Element) {
+    parent.children.push(XMLNode::Element(child));
+}
+
+/// Add a child element with the given text. Empty text is left out so that the element is written
+/// as `<Name/>`, which is how S3 writes empty fields.
+fn push_text(parent: &mut Element, name: &str, text: &str) {
+    let mut element = Element::new(name);
+    if !text.is_empty() {
+        element.children.push(XMLNode::Text(text.to_owned()));
+    }
+    push_child(parent, element);
+}
+
+fn push_object_info(element: &mut Element, object: &ObjectInfo) {
+    push_text(element, "Key", &object.key);
+    push_text(element, "LastModified", &format_rfc3339(object.last_modified));
+    push_text(element, "ETag", &quote_etag(&object.etag));
+    push_text(element, "Size", &object.size.to_string());
+    push_text(
+        element,
+        "StorageClass",
+        object.storage_class.as_deref().unwrap_or("STANDARD"),
+    );
+    if let Some(restore_status) = object.restore_status {
+        let mut status = Element::new("RestoreStatus");
+        match restore_status {
+            RestoreStatus::InProgress => push_text(&mut status, "IsRestoreInProgress", "true"),
+            RestoreStatus::Restored { expiry } => {
+                push_text(&mut status, "IsRestoreInProgress", "false");
+                push_text(&mut status, "RestoreExpiryDate", &format_rfc3339(expiry.into()));
+            }
+        }
+        push_child(element, status);
+    }
+    if let Some(owner) = &object.owner {
+        let mut owner_element = Element::new("Owner");
+        push_text(&mut owner_element, "ID", &owner.id);
+        if let Some(display_name) = &owner.display_name {
+            push_text(&mut owner_element, "DisplayName", display_name);
+        }
+        push_child(element, owner_element);
+    }
+}
+
+/// Characters that S3 escapes in listings requested with `encoding-type=url`
+const LIST_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~')
+    .remove(b'/');
+
+/// Encode a key or prefix the way S3 does for `encoding-type=url`, with spaces as `+`
+fn url_encode(value: &str) -> String {
+    utf8_percent_encode(value, LIST_ENCODE_SET)
+        .to_string()
+        .replace("%20", "+")
+}
+
+fn push_common_prefixes(root: &mut Element, common_prefixes: &[String]) {
+    for prefix in common_prefixes {
+        let mut element = Element::new("CommonPrefixes");
+        push_text(&mut element, "Prefix", prefix);
+        push_child(root, element);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("bytes=0-9", 100, Some(0..10); "closed range")]
+    #[test_case("bytes=90-199", 100, Some(90..100); "end past object")]
+    #[test_case("bytes=10-", 100, Some(10..100); "open range")]
+    #[test_case("bytes=-10", 100, Some(90..100); "suffix range")]
+    #[test_case("bytes=100-199", 100, None; "start past object")]
+    #[test_case("bytes=0-7", 0, None; "empty object")]
+    fn test_parse_range(header: &str, object_size: u64, expected: Option<Range<u64>>) {
+        let range = parse_range(header, object_size).ok();
+        assert_eq!(range, expected);
+    }
+
+    #[test]
+    fn test_decode_aws_chunked() {
+        let body = b"5\r\nhello\r\n6;chunk-signature=abc\r\n world\r\n0\r\nx-amz-checksum-crc32c:AAAAAA==\r\n\r\n";
+        assert_eq!(decode_aws_chunked(body).unwrap(), b"hello world");
+
+        let truncated = b"5\r\nhel";
+        assert!(decode_aws_chunked(truncated).is_none());
+    }
+}
@@ -1,21 +1,25 @@
-use std::ops::Range;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
 use async_trait::async_trait;
-use futures::stream::BoxStream;
-use futures::{Stream, StreamExt};
+use futures::future::BoxFuture;
+use futures::{ready, FutureExt, Stream};
 use pin_project::pin_project;
 
-use crate::mock_client::leaky_bucket::LeakyBucket;
-use crate::mock_client::{MockClient, MockClientConfig, MockClientError, MockObject, MockPutObjectRequest};
+use crate::leaky_bucket::LeakyBucket;
+use crate::mock_client::{self, MockClient, MockClientConfig, MockClientError, MockObject, MockPutObjectRequest};
 use crate::object_client::{
-    DeleteObjectError, DeleteObjectResult, GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult,
-    GetObjectError, HeadObjectError, HeadObjectResult, ListObjectsError, ListObjectsResult, ObjectAttribute,
-    ObjectClient, ObjectClientResult, PutObjectError, PutObjectParams,
+    AbortMultipartUploadError, AbortMultipartUploadResult, CompleteMultipartUploadError, CompleteMultipartUploadResult,
+    CompletedPart, CopyObjectError, CopyObjectParams, CopyObjectResult, CreateMultipartUploadError,
+    CreateMultipartUploadResult, DeleteObjectError, DeleteObjectResult, DeleteObjectsError, DeleteObjectsResult,
+    GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult, GetObjectError, GetObjectParams,
+    GetObjectRequest, GetObjectResponse, HeadObjectError, HeadObjectParams, HeadObjectResult,
+    ListMultipartUploadsError, ListMultipartUploadsResult, ListObjectVersionsError, ListObjectVersionsResult,
+    ListObjectsError, ListObjectsParams, ListObjectsResult, ObjectAttribute, ObjectClient, ObjectClientResult,
+    ObjectIdentifier, PutObjectError, PutObjectParams, RestoreObjectError, RestoreObjectResult, RestoreTier,
+    UploadPartError, UploadPartResult,
 };
-use crate::types::ETag;
 
 /// A [MockClient] that rate limits overall download throughput to simulate a target network
 /// performance without the jitter or service latency of targeting a real service. Note that while
@@ -59,7 +63,26 @@ impl ThroughputMockClient {
 #[pin_project]
 pub struct GetObjectResult {
     #[pin]
-    inner: BoxStream<'static, ObjectClientResult<GetBodyPart, GetObjectError, MockClientError>>,
+    request: mock_client::GetObjectResult,
+    rate_limiter: LeakyBucket,
+    /// A part we've received and are waiting for enough throughput to return
+    pending: Option<(BoxFuture<'static, ()>, GetBodyPart)>,
+}
+
+impl GetObjectRequest for GetObjectResult {
+    type ClientError = MockClientError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        self.request.get_object_response()
+    }
+
+    fn increment_read_window(self: Pin<&mut Self>, len: usize) {
+        self.project().request.increment_read_window(len);
+    }
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        self.project_ref().request.read_window_end_offset()
+    }
 }
 
 impl Stream for GetObjectResult {
@@ -67,7 +90,22 @@ impl Stream for GetObjectResult {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
-        this.inner.poll_next(cx)
+        if this.pending.is_none() {
+            let part = match ready!(this.request.poll_next(cx)) {
+                Some(Ok(part)) => part,
+                other => return Poll::Ready(other),
+            };
+            // Acquire enough tokens for the number of bytes we want to deliver
+            let rate_limiter = this.rate_limiter.clone();
+            let length = part.1.len() as u32;
+            let wait = async move { rate_limiter.acquire(length).await }.boxed();
+            *this.pending = Some((wait, part));
+        }
+
+        let (wait, _) = this.pending.as_mut().unwrap();
+        ready!(wait.poll_unpin(cx));
+        let (_, part) = this.pending.take().unwrap();
+        Poll::Ready(Some(Ok(part)))
     }
 }
 
@@ -81,6 +119,19 @@ impl ObjectClient for ThroughputMockClient {
         self.inner.part_size()
     }
 
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        self.inner
+            .copy_object(source_bucket, source_key, destination_bucket, destination_key, params)
+            .await
+    }
+
     async fn delete_object(
         &self,
         bucket: &str,
@@ -89,37 +140,61 @@ impl ObjectClient for ThroughputMockClient {
         self.inner.delete_object(bucket, key).await
     }
 
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        self.inner.delete_objects(bucket, objects).await
+    }
+
     async fn get_object(
         &self,
         bucket: &str,
         key: &str,
-        range: Option<Range<u64>>,
-        if_match: Option<ETag>,
+        params: &GetObjectParams,
     ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
-        let inner = self.inner.get_object(bucket, key, range, if_match).await?;
-        let rate_limiter = self.rate_limiter.clone();
-        let stream = inner.then(move |p| {
-            let rate_limiter = rate_limiter.clone();
-            async move {
-                let p = p?;
-                // Acquire enough tokens for the number of bytes we want to deliver
-                rate_limiter.acquire(p.1.len() as u32).await;
-                Ok(p)
-            }
-        });
-        Ok(GetObjectResult { inner: stream.boxed() })
+        let request = self.inner.get_object(bucket, key, params).await?;
+        Ok(GetObjectResult {
+            request,
+            rate_limiter: self.rate_limiter.clone(),
+            pending: None,
+        })
     }
 
     async fn list_objects(
         &self,
         bucket: &str,
-        continuation_token: Option<&str>,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+        self.inner.list_objects(bucket, params).await
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
         delimiter: &str,
         max_keys: usize,
         prefix: &str,
-    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        self.inner
+            .list_object_versions(bucket, key_marker, version_id_marker, delimiter, max_keys, prefix)
+            .await
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
         self.inner
-            .list_objects(bucket, continuation_token, delimiter, max_keys, prefix)
+            .list_multipart_uploads(bucket, key_marker, upload_id_marker, delimiter, max_uploads, prefix)
             .await
     }
 
@@ -127,8 +202,9 @@ impl ObjectClient for ThroughputMockClient {
         &self,
         bucket: &str,
         key: &str,
+        params: &HeadObjectParams,
     ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
-        self.inner.head_object(bucket, key).await
+        self.inner.head_object(bucket, key, params).await
     }
 
     async fn put_object(
@@ -140,6 +216,49 @@ impl ObjectClient for ThroughputMockClient {
         self.inner.put_object(bucket, key, params).await
     }
 
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        self.inner.create_multipart_upload(bucket, key, params).await
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        self.inner
+            .upload_part(bucket, key, upload_id, part_number, contents)
+            .await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        self.inner
+            .complete_multipart_upload(bucket, key, upload_id, parts)
+            .await
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        self.inner.abort_multipart_upload(bucket, key, upload_id).await
+    }
+
     async fn get_object_attributes(
         &self,
         bucket: &str,
@@ -152,6 +271,16 @@ impl ObjectClient for ThroughputMockClient {
             .get_object_attributes(bucket, key, max_parts, part_number_marker, object_attributes)
             .await
     }
+
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        self.inner.restore_object(bucket, key, days, tier).await
+    }
 }
 
 #[cfg(test)]
@@ -162,6 +291,7 @@ mod tests {
     use futures::StreamExt;
 
     use crate::mock_client::MockObject;
+    use crate::types::ETag;
 
     use super::*;
 
@@ -176,6 +306,7 @@ mod tests {
                     part_size: 8 * 1024 * 1024,
                     bucket: "test_bucket".to_owned(),
                     unordered_list_seed: None,
+                    ..Default::default()
                 };
                 let client = ThroughputMockClient::new(config, rate_gbps);
 
@@ -187,7 +318,10 @@ mod tests {
                 let start = Instant::now();
                 let num_bytes = block_on(async move {
                     let mut num_bytes = 0;
-                    let mut get = client.get_object("test_bucket", "testfile", None, None).await.unwrap();
+                    let mut get = client
+                        .get_object("test_bucket", "testfile", &Default::default())
+                        .await
+                        .unwrap();
                     while let Some(part) = get.next().await {
                         let (_offset, part) = part.unwrap();
                         num_bytes += part.len();
@@ -1,6 +1,11 @@
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use futures::Stream;
+use base64ct::{Base64, Encoding};
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt, TryStreamExt};
+use md5::{Digest, Md5};
+use std::collections::HashMap;
+use std::pin::Pin;
 use std::str::FromStr;
 use std::time::SystemTime;
 use std::{
@@ -15,6 +20,10 @@ use time::OffsetDateTime;
 /// offset within the object and the bytes starting at that offset.
 pub type GetBodyPart = (u64, Box<[u8]>);
 
+/// User-defined metadata of an object, which S3 stores as `x-amz-meta-*` headers. Keys don't
+/// include the `x-amz-meta-` prefix.
+pub type ObjectMetadata = HashMap<String, String>;
+
 /// An ETag (entity tag) is a unique identifier for a HTTP object.
 ///
 /// New ETags can be created with the [`FromStr`] implementation.
@@ -42,9 +51,7 @@ impl ETag {
     #[doc(hidden)]
     #[cfg(feature = "mock")]
     pub fn from_object_bytes(data: &[u8]) -> Self {
-        use md5::Digest as _;
-
-        let mut hasher = md5::Md5::new();
+        let mut hasher = Md5::new();
         hasher.update(data);
 
         let hash = hasher.finalize();
@@ -61,6 +68,59 @@ impl FromStr for ETag {
     }
 }
 
+/// A customer-provided key for server-side encryption (SSE-C).
+///
+/// Objects uploaded with an SSE-C key are encrypted by S3 with that key, which S3 doesn't store,
+/// and so every later request that reads the object must provide the same key. The key is never
+/// included in the [Debug] output of this type, so it's safe to log types that contain it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SseCustomerKey {
+    key: Box<[u8]>,
+}
+
+impl SseCustomerKey {
+    /// Length of an SSE-C key in bytes. S3 only supports 256-bit AES keys.
+    pub const KEY_LENGTH: usize = 32;
+
+    /// Create a new SSE-C key from the raw bytes of a 256-bit AES key.
+    pub fn new(key: &[u8]) -> Result<Self, SseCustomerKeyError> {
+        if key.len() != Self::KEY_LENGTH {
+            return Err(SseCustomerKeyError::InvalidLength(key.len()));
+        }
+        Ok(Self { key: key.into() })
+    }
+
+    /// The encryption algorithm to use with this key, as expected by the S3 API
+    pub fn algorithm(&self) -> &'static str {
+        "AES256"
+    }
+
+    /// The key encoded as base64, as expected by the S3 API
+    pub fn key_base64(&self) -> String {
+        Base64::encode_string(&self.key)
+    }
+
+    /// The MD5 digest of the key encoded as base64, which S3 uses to check the key wasn't
+    /// corrupted in transit
+    pub fn key_md5_base64(&self) -> String {
+        Base64::encode_string(&Md5::digest(&self.key))
+    }
+}
+
+impl Debug for SseCustomerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SseCustomerKey").finish_non_exhaustive()
+    }
+}
+
+/// Errors returned when creating an [SseCustomerKey]
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SseCustomerKeyError {
+    #[error("SSE-C key must be {} bytes, but was {0} bytes", SseCustomerKey::KEY_LENGTH)]
+    InvalidLength(usize),
+}
+
 /// A generic interface to S3-like object storage services.
 ///
 /// This trait defines the common methods that all object services implement.
@@ -71,7 +131,7 @@ impl FromStr for ETag {
 #[cfg_attr(not(docs_rs), async_trait)]
 #[auto_impl(Arc)]
 pub trait ObjectClient {
-    type GetObjectResult: Stream<Item = ObjectClientResult<GetBodyPart, GetObjectError, Self::ClientError>> + Send;
+    type GetObjectResult: GetObjectRequest<ClientError = Self::ClientError>;
     type PutObjectRequest: PutObjectRequest<ClientError = Self::ClientError>;
     type ClientError: std::error::Error + Send + Sync + 'static;
 
@@ -79,6 +139,17 @@ pub trait ObjectClient {
     /// can be `None` if the client does not do multi-part operations.
     fn part_size(&self) -> Option<usize>;
 
+    /// Copy an object from one location to another within the object store. The copy happens
+    /// entirely on the service side, and so the object's contents do not transit this client.
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError>;
+
     /// Delete a single object from the object store.
     ///
     /// DeleteObject will succeed even if the object within the bucket does not exist.
@@ -88,31 +159,102 @@ pub trait ObjectClient {
         key: &str,
     ) -> ObjectClientResult<DeleteObjectResult, DeleteObjectError, Self::ClientError>;
 
+    /// Delete multiple objects from the object store in a single request. At most
+    /// [`MAX_DELETE_OBJECTS_KEYS`] keys can be deleted per request.
+    ///
+    /// The request can succeed even if some of the keys could not be deleted, so callers should
+    /// check [`DeleteObjectsResult::errors`]. As for DeleteObject, deleting a key that does not
+    /// exist is considered a success. Objects whose [`ObjectIdentifier::etag`] no longer matches are
+    /// not deleted, and are reported as errors with the code `PreconditionFailed`.
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError>;
+
     /// Get an object from the object store. Returns a stream of body parts of the object. Parts are
     /// guaranteed to be returned by the stream in order and contiguously.
     async fn get_object(
         &self,
         bucket: &str,
         key: &str,
-        range: Option<Range<u64>>,
-        if_match: Option<ETag>,
+        params: &GetObjectParams,
     ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError>;
 
     /// List the objects in a bucket under a given prefix
     async fn list_objects(
         &self,
         bucket: &str,
-        continuation_token: Option<&str>,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError>;
+
+    /// List all the objects and common prefixes in a bucket under a given prefix, following
+    /// continuation tokens until the listing is complete. Entries are returned in key order.
+    fn list_objects_stream<'a>(
+        &'a self,
+        bucket: &'a str,
+        prefix: &'a str,
+        delimiter: &'a str,
+    ) -> BoxStream<'a, ObjectClientResult<ListObjectsEntry, ListObjectsError, Self::ClientError>>
+    where
+        Self: Sync,
+    {
+        // `None` once the last page has been listed, and `Some(None)` before the first page
+        let pages = stream::try_unfold(
+            Some(None),
+            move |continuation_token: Option<Option<String>>| async move {
+                let Some(continuation_token) = continuation_token else {
+                    return Ok(None);
+                };
+                let params = ListObjectsParams::new()
+                    .prefix(prefix)
+                    .delimiter(delimiter)
+                    .continuation_token(continuation_token.as_deref());
+                self.list_objects(bucket, &params).await.map(|result| {
+                    let entries = ListObjectsEntry::merge(result.objects, result.common_prefixes);
+                    Some((entries, result.next_continuation_token.map(Some)))
+                })
+            },
+        );
+        pages
+            .map_ok(|entries| {
+                stream::iter(entries).map(Ok::<_, ObjectClientError<ListObjectsError, Self::ClientError>>)
+            })
+            .try_flatten()
+            .boxed()
+    }
+
+    /// List the versions of objects and the delete markers in a bucket under a given prefix. To
+    /// continue a truncated listing, pass both markers returned by the previous response.
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
         delimiter: &str,
         max_keys: usize,
         prefix: &str,
-    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError>;
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError>;
+
+    /// List the in-progress multipart uploads in a bucket under a given prefix. An upload is in
+    /// progress until it is completed or aborted. To continue a truncated listing, pass both
+    /// markers returned by the previous response.
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError>;
 
     /// Retrieve object metadata without retrieving the object contents
     async fn head_object(
         &self,
         bucket: &str,
         key: &str,
+        params: &HeadObjectParams,
     ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError>;
 
     /// Put an object into the object store. Returns a [PutObjectRequest] for callers
@@ -124,6 +266,51 @@ pub trait ObjectClient {
         params: &PutObjectParams,
     ) -> ObjectClientResult<Self::PutObjectRequest, PutObjectError, Self::ClientError>;
 
+    /// Start a new multipart upload to the given key, and return its upload ID. Unlike
+    /// [put_object](Self::put_object), the caller is responsible for uploading the parts with
+    /// [upload_part](Self::upload_part) and then finishing the upload with either
+    /// [complete_multipart_upload](Self::complete_multipart_upload) or
+    /// [abort_multipart_upload](Self::abort_multipart_upload). Parts can be uploaded in any order
+    /// and concurrently, and the upload can be resumed later given its upload ID.
+    ///
+    /// Trailing checksums, preconditions, and SSE-C keys in the `params` are not supported for
+    /// multipart uploads created this way.
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError>;
+
+    /// Upload a single part of a multipart upload. Part numbers start at 1 and must be at most
+    /// 10,000. Uploading a part with the same number as an existing part replaces it.
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError>;
+
+    /// Complete a multipart upload by assembling the given parts, which must be in ascending order
+    /// of part number, into a new object.
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError>;
+
+    /// Abort a multipart upload, discarding any parts that have been uploaded.
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError>;
+
     /// Retrieves all the metadata from an object without returning the object contents.
     async fn get_object_attributes(
         &self,
@@ -133,6 +320,19 @@ pub trait ObjectClient {
         part_number_marker: Option<usize>,
         object_attributes: &[ObjectAttribute],
     ) -> ObjectClientResult<GetObjectAttributesResult, GetObjectAttributesError, Self::ClientError>;
+
+    /// Start restoring a temporary copy of an archived object, such as one in the GLACIER or
+    /// DEEP_ARCHIVE storage classes, that stays readable for the given number of days. S3 restores
+    /// the object in the background; the `restore_status` returned by
+    /// [head_object](Self::head_object) and [list_objects](Self::list_objects) reports when it has
+    /// finished. Restoring an object that is already restored extends the lifetime of its copy.
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError>;
 }
 
 /// The top-level error type returned by calls to an [`ObjectClient`].
@@ -164,6 +364,62 @@ pub enum ObjectClientError<S, C> {
 /// Shorthand type for the result of an object client request
 pub type ObjectClientResult<T, S, C> = Result<T, ObjectClientError<S, C>>;
 
+/// Parameters to a [`get_object`](ObjectClient::get_object) request
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct GetObjectParams {
+    /// Range of bytes to retrieve. If not set, the whole object is retrieved.
+    pub range: Option<Range<u64>>,
+    /// Only return the object if its ETag matches this one.
+    pub if_match: Option<ETag>,
+    /// Version of the object to retrieve. If not set, the current version is retrieved.
+    pub version_id: Option<String>,
+    /// Customer-provided key the object was encrypted with, if any
+    pub sse_customer_key: Option<SseCustomerKey>,
+    /// Ask S3 for the checksum of the object and verify the body against it. Only requests for the
+    /// whole object are verified, and only if it has a full-object checksum; ranged requests and
+    /// objects with composite multipart checksums are returned without verification, which
+    /// [`GetObjectResponse::checksum_validated`] reports.
+    pub checksum_mode: bool,
+}
+
+impl GetObjectParams {
+    /// Create a default [GetObjectParams].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the range of bytes to retrieve.
+    pub fn range(mut self, value: Option<Range<u64>>) -> Self {
+        self.range = value;
+        self
+    }
+
+    /// Set the ETag the object must match.
+    pub fn if_match(mut self, value: Option<ETag>) -> Self {
+        self.if_match = value;
+        self
+    }
+
+    /// Set the version of the object to retrieve.
+    pub fn version_id(mut self, value: String) -> Self {
+        self.version_id = Some(value);
+        self
+    }
+
+    /// Set the customer-provided key the object was encrypted with.
+    pub fn sse_customer_key(mut self, value: Option<SseCustomerKey>) -> Self {
+        self.sse_customer_key = value;
+        self
+    }
+
+    /// Set whether to verify the body of the object against its checksum.
+    pub fn checksum_mode(mut self, value: bool) -> Self {
+        self.checksum_mode = value;
+        self
+    }
+}
+
 /// Errors returned by a [`get_object`](ObjectClient::get_object) request
 #[derive(Debug, Error, PartialEq, Eq)]
 #[non_exhaustive]
@@ -176,6 +432,107 @@ pub enum GetObjectError {
 
     #[error("At least one of the preconditions specified did not hold")]
     PreconditionFailed,
+
+    #[error("The body of the object did not match its {algorithm} checksum {expected}")]
+    ChecksumMismatch { algorithm: &'static str, expected: String },
+
+    /// The body of the object failed checksum validation, but the checksum it was validated against
+    /// could not be determined.
+    #[error("The body of the object did not match its checksum, which could not be determined")]
+    UnverifiableChecksum,
+}
+
+/// Parameters to a [`list_objects`](ObjectClient::list_objects) request
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ListObjectsParams {
+    /// Only list keys that begin with this prefix.
+    pub prefix: String,
+    /// Roll up keys that contain this delimiter after the prefix into common prefixes. Empty for
+    /// no delimiter.
+    pub delimiter: String,
+    /// Maximum number of keys and common prefixes to return. S3 never returns more than 1000.
+    pub max_keys: usize,
+    /// Continuation token returned by a previous truncated listing.
+    pub continuation_token: Option<String>,
+    /// Only list keys that sort after this key.
+    pub start_after: Option<String>,
+    /// Include the owner of each object in the listing.
+    pub fetch_owner: bool,
+    /// Ask S3 to URL-encode keys and prefixes in the response, so that keys with characters that
+    /// aren't valid in XML 1.0 can be listed. Some S3-compatible services don't support this, so
+    /// it can be turned off. Responses are only decoded if they say they were encoded.
+    pub url_encoding: bool,
+}
+
+impl Default for ListObjectsParams {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            delimiter: String::new(),
+            max_keys: 1000,
+            continuation_token: None,
+            start_after: None,
+            fetch_owner: false,
+            url_encoding: true,
+        }
+    }
+}
+
+impl ListObjectsParams {
+    /// Create a default [ListObjectsParams], which lists up to 1000 keys in the whole bucket.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the prefix of the keys to list.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn prefix(mut self, value: &str) -> Self {
+        self.prefix = value.to_owned();
+        self
+    }
+
+    /// Set the delimiter to roll up keys into common prefixes.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn delimiter(mut self, value: &str) -> Self {
+        self.delimiter = value.to_owned();
+        self
+    }
+
+    /// Set the maximum number of keys and common prefixes to return.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn max_keys(mut self, value: usize) -> Self {
+        self.max_keys = value;
+        self
+    }
+
+    /// Set the continuation token to resume a truncated listing.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn continuation_token(mut self, value: Option<&str>) -> Self {
+        self.continuation_token = value.map(ToOwned::to_owned);
+        self
+    }
+
+    /// Set the key to start listing after.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn start_after(mut self, value: &str) -> Self {
+        self.start_after = Some(value.to_owned());
+        self
+    }
+
+    /// Set whether to include the owner of each object.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn fetch_owner(mut self, value: bool) -> Self {
+        self.fetch_owner = value;
+        self
+    }
+
+    /// Set whether to ask S3 to URL-encode keys and prefixes in the response.
+    #[must_use = "ListObjectsParams follows a builder pattern"]
+    pub fn url_encoding(mut self, value: bool) -> Self {
+        self.url_encoding = value;
+        self
+    }
 }
 
 /// Result of a [`list_objects`](ObjectClient::list_objects) request
@@ -193,6 +550,43 @@ pub struct ListObjectsResult {
     pub next_continuation_token: Option<String>,
 }
 
+/// A single entry of a [`list_objects_stream`](ObjectClient::list_objects_stream) listing
+#[derive(Debug, Clone)]
+pub enum ListObjectsEntry {
+    /// An object under the prefix.
+    Object(ObjectInfo),
+    /// A common prefix that rolls up all the keys that share it.
+    CommonPrefix(String),
+}
+
+impl ListObjectsEntry {
+    /// The key or common prefix of this entry.
+    pub fn key(&self) -> &str {
+        match self {
+            ListObjectsEntry::Object(object) => &object.key,
+            ListObjectsEntry::CommonPrefix(prefix) => prefix,
+        }
+    }
+
+    /// Merge the objects and common prefixes of one page of a listing into key order. S3 returns
+    /// each of them sorted, so this is a single merge pass.
+    fn merge(objects: Vec<ObjectInfo>, common_prefixes: Vec<String>) -> Vec<Self> {
+        let mut entries = Vec::with_capacity(objects.len() + common_prefixes.len());
+        let mut objects = objects.into_iter().map(Self::Object).peekable();
+        let mut common_prefixes = common_prefixes.into_iter().map(Self::CommonPrefix).peekable();
+        loop {
+            let next = match (objects.peek(), common_prefixes.peek()) {
+                (Some(object), Some(prefix)) if object.key() <= prefix.key() => objects.next(),
+                (_, Some(_)) => common_prefixes.next(),
+                (Some(_), None) => objects.next(),
+                (None, None) => break,
+            };
+            entries.extend(next);
+        }
+        entries
+    }
+}
+
 /// Errors returned by a [`list_objects`](ObjectClient::list_objects) request
 #[derive(Debug, Error, PartialEq, Eq)]
 #[non_exhaustive]
@@ -201,6 +595,90 @@ pub enum ListObjectsError {
     NoSuchBucket,
 }
 
+/// Result of a [`list_object_versions`](ObjectClient::list_object_versions) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ListObjectVersionsResult {
+    /// The list of object versions.
+    pub versions: Vec<ObjectVersionInfo>,
+
+    /// The list of delete markers.
+    pub delete_markers: Vec<DeleteMarkerInfo>,
+
+    /// The list of common prefixes. This rolls up all of the versions with a common prefix up to
+    /// the next instance of the delimiter.
+    pub common_prefixes: Vec<String>,
+
+    /// If present, the key marker to use to query more results.
+    pub next_key_marker: Option<String>,
+
+    /// If present, the version ID marker to use to query more results.
+    pub next_version_id_marker: Option<String>,
+}
+
+/// Errors returned by a [`list_object_versions`](ObjectClient::list_object_versions) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListObjectVersionsError {
+    #[error("The bucket does not exist")]
+    NoSuchBucket,
+}
+
+/// Result of a [`list_multipart_uploads`](ObjectClient::list_multipart_uploads) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ListMultipartUploadsResult {
+    /// The list of in-progress multipart uploads.
+    pub uploads: Vec<MultipartUploadInfo>,
+
+    /// The list of common prefixes. This rolls up all of the uploads with a common prefix up to
+    /// the next instance of the delimiter.
+    pub common_prefixes: Vec<String>,
+
+    /// If present, the key marker to use to query more results.
+    pub next_key_marker: Option<String>,
+
+    /// If present, the upload ID marker to use to query more results.
+    pub next_upload_id_marker: Option<String>,
+}
+
+/// Errors returned by a [`list_multipart_uploads`](ObjectClient::list_multipart_uploads) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListMultipartUploadsError {
+    #[error("The bucket does not exist")]
+    NoSuchBucket,
+}
+
+/// Parameters to a [`head_object`](ObjectClient::head_object) request
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct HeadObjectParams {
+    /// Version of the object to retrieve. If not set, the current version is retrieved.
+    pub version_id: Option<String>,
+    /// Customer-provided key the object was encrypted with, if any
+    pub sse_customer_key: Option<SseCustomerKey>,
+}
+
+impl HeadObjectParams {
+    /// Create a default [HeadObjectParams].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the version of the object to retrieve.
+    pub fn version_id(mut self, value: String) -> Self {
+        self.version_id = Some(value);
+        self
+    }
+
+    /// Set the customer-provided key the object was encrypted with.
+    pub fn sse_customer_key(mut self, value: Option<SseCustomerKey>) -> Self {
+        self.sse_customer_key = value;
+        self
+    }
+}
+
 /// Result of a [`head_object`](ObjectClient::head_object) request
 #[derive(Debug)]
 #[non_exhaustive]
@@ -210,6 +688,21 @@ pub struct HeadObjectResult {
 
     /// Object metadata
     pub object: ObjectInfo,
+
+    /// Version ID of the object, if the bucket has versioning enabled
+    pub version_id: Option<String>,
+
+    /// User-defined metadata of the object
+    pub object_metadata: ObjectMetadata,
+
+    /// Standard HTTP Content-Type of the object
+    pub content_type: Option<String>,
+
+    /// Standard HTTP Content-Encoding of the object
+    pub content_encoding: Option<String>,
+
+    /// Standard HTTP Cache-Control of the object
+    pub cache_control: Option<String>,
 }
 
 /// Errors returned by a [`head_object`](ObjectClient::head_object) request
@@ -221,6 +714,34 @@ pub enum HeadObjectError {
     NotFound,
 }
 
+/// Parameters to a [`copy_object`](ObjectClient::copy_object) request
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct CopyObjectParams {}
+
+impl CopyObjectParams {
+    /// Create a default [CopyObjectParams].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Result of a [`copy_object`](ObjectClient::copy_object) request
+// TODO: Populate this struct with return fields from the S3 API, e.g., etag.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CopyObjectResult {}
+
+/// Errors returned by a [`copy_object`](ObjectClient::copy_object) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CopyObjectError {
+    /// Note that the source and destination of a copy cannot always be distinguished, since the
+    /// service can fail to find either the bucket or the key of the source object.
+    #[error("The object was not found")]
+    NotFound,
+}
+
 /// Result of a [`delete_object`](ObjectClient::delete_object) request
 ///
 /// Note: DeleteObject requests on a non-existent object within a bucket are considered a success.
@@ -237,6 +758,73 @@ pub enum DeleteObjectError {
     NoSuchBucket,
 }
 
+/// Maximum number of keys that can be deleted by a single [`delete_objects`](ObjectClient::delete_objects) request
+pub const MAX_DELETE_OBJECTS_KEYS: usize = 1000;
+
+/// An object to delete with a [`delete_objects`](ObjectClient::delete_objects) request
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ObjectIdentifier {
+    /// Key of the object to delete
+    pub key: String,
+
+    /// Only delete the object if its ETag matches this one
+    pub etag: Option<ETag>,
+}
+
+impl ObjectIdentifier {
+    /// Identify the object with the given key, whatever its ETag
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            etag: None,
+        }
+    }
+
+    /// Only delete the object if its ETag matches the given one, so that an object that was replaced
+    /// since its ETag was read isn't deleted
+    pub fn etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+}
+
+/// Result of a [`delete_objects`](ObjectClient::delete_objects) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct DeleteObjectsResult {
+    /// Keys that were successfully deleted
+    pub deleted: Vec<String>,
+
+    /// Keys that could not be deleted, and why
+    pub errors: Vec<DeleteObjectsKeyError>,
+}
+
+/// A key that a [`delete_objects`](ObjectClient::delete_objects) request failed to delete
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeleteObjectsKeyError {
+    /// Key of the object that could not be deleted
+    pub key: String,
+
+    /// Error code returned by the service, such as `AccessDenied`
+    pub code: String,
+
+    /// Description of the error returned by the service
+    pub message: String,
+}
+
+/// Errors returned by a [`delete_objects`](ObjectClient::delete_objects) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeleteObjectsError {
+    #[error("The bucket does not exist")]
+    NoSuchBucket,
+
+    #[error("Too many keys in a single request (at most {MAX_DELETE_OBJECTS_KEYS} are allowed)")]
+    TooManyKeys,
+}
+
 /// Result of a [`get_object_attributes`](ObjectClient::get_object_attributes) request
 #[derive(Debug, Default)]
 pub struct GetObjectAttributesResult {
@@ -271,8 +859,8 @@ pub enum GetObjectAttributesError {
 #[derive(Debug, Default, Clone)]
 #[non_exhaustive]
 pub struct PutObjectParams {
-    /// Enable Crc32c trailing checksums.
-    pub trailing_checksums: bool,
+    /// Algorithm of the trailing checksums to upload with each part, if any
+    pub checksum_algorithm: Option<ChecksumAlgorithm>,
     /// Storage class to be used when creating new S3 object
     pub storage_class: Option<String>,
     /// The server-side encryption algorithm to be used for this object in Amazon S3 (for example, AES256, aws:kms, aws:kms:dsse)
@@ -280,6 +868,21 @@ pub struct PutObjectParams {
     /// If `server_side_encryption` has a valid value of aws:kms or aws:kms:dsse, this value may be used to specify AWS KMS key ID to be used
     /// when creating new S3 object
     pub ssekms_key_id: Option<String>,
+    /// Customer-provided key to encrypt the object with (SSE-C). Can't be combined with
+    /// `server_side_encryption`.
+    pub sse_customer_key: Option<SseCustomerKey>,
+    /// Only create the object if no object exists with this key. S3 only supports the value `"*"`.
+    pub if_none_match: Option<String>,
+    /// Only replace the object if its current ETag matches this one.
+    pub if_match: Option<ETag>,
+    /// User-defined metadata to store with the object
+    pub object_metadata: ObjectMetadata,
+    /// Standard HTTP Content-Type to store with the object
+    pub content_type: Option<String>,
+    /// Standard HTTP Content-Encoding to store with the object
+    pub content_encoding: Option<String>,
+    /// Standard HTTP Cache-Control to store with the object
+    pub cache_control: Option<String>,
 }
 
 impl PutObjectParams {
@@ -288,9 +891,9 @@ impl PutObjectParams {
         Self::default()
     }
 
-    /// Set Crc32c trailing checksums.
-    pub fn trailing_checksums(mut self, value: bool) -> Self {
-        self.trailing_checksums = value;
+    /// Set the algorithm of the trailing checksums.
+    pub fn checksum_algorithm(mut self, value: Option<ChecksumAlgorithm>) -> Self {
+        self.checksum_algorithm = value;
         self
     }
 
@@ -311,6 +914,48 @@ impl PutObjectParams {
         self.ssekms_key_id = value;
         self
     }
+
+    /// Set the customer-provided key to encrypt the object with.
+    pub fn sse_customer_key(mut self, value: Option<SseCustomerKey>) -> Self {
+        self.sse_customer_key = value;
+        self
+    }
+
+    /// Set the ETag that no existing object may match (`"*"` matches any object).
+    pub fn if_none_match(mut self, value: Option<String>) -> Self {
+        self.if_none_match = value;
+        self
+    }
+
+    /// Set the ETag the existing object must match.
+    pub fn if_match(mut self, value: Option<ETag>) -> Self {
+        self.if_match = value;
+        self
+    }
+
+    /// Set the user-defined metadata of the object.
+    pub fn object_metadata(mut self, value: ObjectMetadata) -> Self {
+        self.object_metadata = value;
+        self
+    }
+
+    /// Set the Content-Type of the object.
+    pub fn content_type(mut self, value: String) -> Self {
+        self.content_type = Some(value);
+        self
+    }
+
+    /// Set the Content-Encoding of the object.
+    pub fn content_encoding(mut self, value: String) -> Self {
+        self.content_encoding = Some(value);
+        self
+    }
+
+    /// Set the Cache-Control of the object.
+    pub fn cache_control(mut self, value: String) -> Self {
+        self.cache_control = Some(value);
+        self
+    }
 }
 
 /// Info for the caller to review before an upload completes.
@@ -322,6 +967,66 @@ pub type UploadReviewPart = mountpoint_s3_crt::s3::client::UploadReviewPart;
 /// Checksum algorithm.
 pub type ChecksumAlgorithm = mountpoint_s3_crt::s3::client::ChecksumAlgorithm;
 
+/// A streaming response to a [`get_object`](ObjectClient::get_object) request.
+///
+/// The body of the object is a [Stream] of [GetBodyPart]s, which are delivered in order. The
+/// properties of the object from the response headers are available from
+/// [`get_object_response`](Self::get_object_response).
+///
+/// If the client applies backpressure, the request only returns the body up to the end of its read
+/// window, which callers extend with [`increment_read_window`](Self::increment_read_window) as
+/// they consume the body.
+pub trait GetObjectRequest:
+    Stream<Item = ObjectClientResult<GetBodyPart, GetObjectError, Self::ClientError>> + Send
+{
+    type ClientError: std::error::Error + Send + Sync + 'static;
+
+    /// Get the properties of the object from the response headers. Returns `None` if the headers
+    /// have not been received yet, which can't be the case once the stream has returned a body
+    /// part or finished successfully.
+    fn get_object_response(&self) -> Option<GetObjectResponse>;
+
+    /// Extend the read window of this request by `len` bytes, so that more of the object can be
+    /// downloaded. Does nothing if the client doesn't apply backpressure.
+    fn increment_read_window(self: Pin<&mut Self>, len: usize);
+
+    /// Get the offset in the object of the end of the read window, exclusive. Once the stream has
+    /// returned the body up to this offset, it makes no further progress until the window is
+    /// extended. Returns `u64::MAX` if the client doesn't apply backpressure.
+    fn read_window_end_offset(self: Pin<&Self>) -> u64;
+}
+
+/// Properties of an object returned in the headers of a [`get_object`](ObjectClient::get_object)
+/// response
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GetObjectResponse {
+    /// Entity tag of the object that was read
+    pub etag: ETag,
+    /// Length of the body of the response, which is the length of the requested range if there was
+    /// one, or of the whole object otherwise
+    pub content_length: Option<u64>,
+    /// Last modified time of the object that was read
+    pub last_modified: OffsetDateTime,
+    /// Checksums of the whole object, which S3 only returns if the object was uploaded with
+    /// checksums and the request asked for them
+    pub checksum: Checksum,
+    /// Whether the body of the response was validated against [`checksum`](Self::checksum). This is
+    /// only set once the whole body has been read, and a body that doesn't match its checksum fails
+    /// the request with [`GetObjectError::ChecksumMismatch`] instead. Bodies can't be validated if
+    /// the request didn't enable `checksum_mode` or was for a range, or if the object has no
+    /// checksum or only a composite checksum from a multipart upload.
+    pub checksum_validated: bool,
+    /// User-defined metadata of the object
+    pub object_metadata: ObjectMetadata,
+    /// Standard HTTP Content-Type of the object
+    pub content_type: Option<String>,
+    /// Standard HTTP Content-Encoding of the object
+    pub content_encoding: Option<String>,
+    /// Standard HTTP Cache-Control of the object
+    pub cache_control: Option<String>,
+}
+
 /// A streaming put request which allows callers to asynchronously write the body of the request.
 ///
 /// You can call the [`write`](Self::write) method to write data to the object, and then call
@@ -358,6 +1063,8 @@ pub struct PutObjectResult {
     pub sse_type: Option<String>,
     /// Server-side encryption KMS key ID that was used to store new object (reported by S3)
     pub sse_kms_key_id: Option<String>,
+    /// Version ID of the new object, if the bucket has versioning enabled
+    pub version_id: Option<String>,
 }
 
 /// Errors returned by a [`put_object`](ObjectClient::put_object) request
@@ -366,6 +1073,153 @@ pub struct PutObjectResult {
 pub enum PutObjectError {
     #[error("The bucket does not exist")]
     NoSuchBucket,
+
+    #[error("At least one of the preconditions specified did not hold")]
+    PreconditionFailed,
+
+    /// Another conditional write to the same key was in progress, so the preconditions couldn't be
+    /// checked. Retrying the upload may succeed.
+    #[error("A conflicting conditional write to the same key was in progress")]
+    ConditionalRequestConflict,
+}
+
+/// Result of a [`create_multipart_upload`](ObjectClient::create_multipart_upload) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CreateMultipartUploadResult {
+    /// ID of the new multipart upload
+    pub upload_id: String,
+}
+
+/// Errors returned by a [`create_multipart_upload`](ObjectClient::create_multipart_upload) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CreateMultipartUploadError {
+    #[error("The bucket does not exist")]
+    NoSuchBucket,
+}
+
+/// Result of an [`upload_part`](ObjectClient::upload_part) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct UploadPartResult {
+    /// ETag of the uploaded part, which must be passed to
+    /// [`complete_multipart_upload`](ObjectClient::complete_multipart_upload)
+    pub etag: ETag,
+}
+
+/// Errors returned by an [`upload_part`](ObjectClient::upload_part) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UploadPartError {
+    /// Note that this error is also returned if the upload was already completed or aborted
+    #[error("The multipart upload does not exist")]
+    NoSuchUpload,
+}
+
+/// A part of a multipart upload to assemble in a
+/// [`complete_multipart_upload`](ObjectClient::complete_multipart_upload) request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedPart {
+    /// Number of the part
+    pub part_number: usize,
+
+    /// ETag of the part, as returned by [`upload_part`](ObjectClient::upload_part)
+    pub etag: ETag,
+}
+
+/// Result of a [`complete_multipart_upload`](ObjectClient::complete_multipart_upload) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CompleteMultipartUploadResult {
+    /// ETag of the new object
+    pub etag: ETag,
+    /// Version ID of the new object, if the bucket has versioning enabled
+    pub version_id: Option<String>,
+}
+
+/// Errors returned by a [`complete_multipart_upload`](ObjectClient::complete_multipart_upload) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompleteMultipartUploadError {
+    #[error("The multipart upload does not exist")]
+    NoSuchUpload,
+
+    #[error("One or more of the parts was not found or its ETag did not match")]
+    InvalidPart,
+
+    #[error("The list of parts was not in ascending order of part number")]
+    InvalidPartOrder,
+
+    #[error("A part other than the last one was smaller than the minimum part size")]
+    EntityTooSmall,
+}
+
+/// Result of an [`abort_multipart_upload`](ObjectClient::abort_multipart_upload) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct AbortMultipartUploadResult {}
+
+/// Errors returned by an [`abort_multipart_upload`](ObjectClient::abort_multipart_upload) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbortMultipartUploadError {
+    #[error("The multipart upload does not exist")]
+    NoSuchUpload,
+}
+
+/// How quickly a [`restore_object`](ObjectClient::restore_object) request should restore an
+/// object. Faster tiers cost more.
+///
+/// See [Archive retrieval
+/// options](https://docs.aws.amazon.com/AmazonS3/latest/userguide/restoring-objects-retrieval-options.html)
+/// in the *Amazon S3 User Guide* for more details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestoreTier {
+    /// Restore within minutes. Not available for the DEEP_ARCHIVE storage class.
+    Expedited,
+    /// Restore within hours.
+    #[default]
+    Standard,
+    /// Restore within hours at the lowest cost, for large amounts of data.
+    Bulk,
+}
+
+impl RestoreTier {
+    /// The name S3 uses for this tier.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestoreTier::Expedited => "Expedited",
+            RestoreTier::Standard => "Standard",
+            RestoreTier::Bulk => "Bulk",
+        }
+    }
+}
+
+/// Result of a [`restore_object`](ObjectClient::restore_object) request
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RestoreObjectResult {
+    /// Whether the object was already restored, in which case only the expiry of the restored copy
+    /// was updated.
+    pub already_restored: bool,
+}
+
+/// Errors returned by a [`restore_object`](ObjectClient::restore_object) request
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RestoreObjectError {
+    #[error("The bucket does not exist")]
+    NoSuchBucket,
+
+    #[error("The key does not exist")]
+    NoSuchKey,
+
+    #[error("The object is not in a storage class that needs to be restored")]
+    InvalidObjectState,
+
+    #[error("A restore of the object is already in progress")]
+    RestoreAlreadyInProgress,
 }
 
 /// Restoration status for S3 objects in flexible retrieval storage classes.
@@ -410,6 +1264,88 @@ pub struct ObjectInfo {
 
     /// Entity tag of this object.
     pub etag: String,
+
+    /// Owner of this object. Only returned by [`list_objects`](ObjectClient::list_objects) when
+    /// `fetch_owner` is set in its [ListObjectsParams].
+    pub owner: Option<Owner>,
+}
+
+/// The owner of an S3 object.
+///
+/// See [Owner](https://docs.aws.amazon.com/AmazonS3/latest/API/API_Owner.html) in the *Amazon S3
+/// API Reference* for more details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Owner {
+    /// Canonical user ID of the owner.
+    pub id: String,
+
+    /// Display name of the owner. Only returned in some regions.
+    pub display_name: Option<String>,
+}
+
+impl Owner {
+    /// Create a new [Owner].
+    pub fn new(id: String, display_name: Option<String>) -> Self {
+        Self { id, display_name }
+    }
+}
+
+/// Metadata about a single version of an S3 object.
+///
+/// See [ObjectVersion](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ObjectVersion.html) in
+/// the *Amazon S3 API Reference* for more details.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ObjectVersionInfo {
+    /// Metadata of this version of the object.
+    pub object: ObjectInfo,
+
+    /// Version ID of this version of the object.
+    pub version_id: String,
+
+    /// Whether this is the current version of the object.
+    pub is_latest: bool,
+}
+
+/// Metadata about an in-progress multipart upload.
+///
+/// See [MultipartUpload](https://docs.aws.amazon.com/AmazonS3/latest/API/API_MultipartUpload.html)
+/// in the *Amazon S3 API Reference* for more details.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MultipartUploadInfo {
+    /// Key of the object the upload will create.
+    pub key: String,
+
+    /// ID of the upload.
+    pub upload_id: String,
+
+    /// The time the upload was created.
+    pub initiated: OffsetDateTime,
+
+    /// Storage class the object will be stored in.
+    pub storage_class: Option<String>,
+}
+
+/// Metadata about a delete marker for an S3 object.
+///
+/// See [DeleteMarkerEntry](https://docs.aws.amazon.com/AmazonS3/latest/API/API_DeleteMarkerEntry.html)
+/// in the *Amazon S3 API Reference* for more details.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct DeleteMarkerInfo {
+    /// Key of the object this delete marker applies to.
+    pub key: String,
+
+    /// Version ID of the delete marker.
+    pub version_id: String,
+
+    /// Whether this delete marker is the current version of the object.
+    pub is_latest: bool,
+
+    /// The time the delete marker was created.
+    pub last_modified: OffsetDateTime,
 }
 
 /// All possible object attributes that can be retrived from [ObjectClient::get_object_attributes].
@@ -449,7 +1385,7 @@ impl fmt::Display for ObjectAttribute {
 ///
 /// See [Checksum](https://docs.aws.amazon.com/AmazonS3/latest/API/API_Checksum.html) in the *Amazon
 /// S3 API Reference* for more details.
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Checksum {
     /// Base64-encoded, 32-bit CRC32 checksum of the object
     pub checksum_crc32: Option<String>,
@@ -464,6 +1400,34 @@ pub struct Checksum {
     pub checksum_sha256: Option<String>,
 }
 
+impl Checksum {
+    /// The value of the checksum for the given algorithm, if the object has one
+    pub(crate) fn value(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => self.checksum_crc32c.as_deref(),
+            ChecksumAlgorithm::Crc32 => self.checksum_crc32.as_deref(),
+            ChecksumAlgorithm::Sha1 => self.checksum_sha1.as_deref(),
+            ChecksumAlgorithm::Sha256 => self.checksum_sha256.as_deref(),
+        }
+    }
+
+    /// The algorithm and value of the checksum that a response with the whole object can be
+    /// validated against, if any. Objects uploaded with multipart uploads have composite checksums
+    /// (a checksum of the part checksums, suffixed with `-<number of parts>`) that can't be.
+    pub(crate) fn full_object_checksum(&self) -> Option<(ChecksumAlgorithm, &str)> {
+        // Like the CRT, only consider the first checksum present, in order of performance
+        let (algorithm, value) = [
+            (ChecksumAlgorithm::Crc32c, &self.checksum_crc32c),
+            (ChecksumAlgorithm::Crc32, &self.checksum_crc32),
+            (ChecksumAlgorithm::Sha1, &self.checksum_sha1),
+            (ChecksumAlgorithm::Sha256, &self.checksum_sha256),
+        ]
+        .into_iter()
+        .find_map(|(algorithm, value)| Some((algorithm, value.as_deref()?)))?;
+        (!value.contains('-')).then_some((algorithm, value))
+    }
+}
+
 /// Metadata about object parts from GetObjectAttributes API.
 ///
 /// See [GetObjectAttributesParts](https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetObjectAttributesParts.html)
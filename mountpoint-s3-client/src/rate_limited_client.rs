@@ -0,0 +1,636 @@
+//! An [`ObjectClient`] that limits the rate of S3 requests and the bandwidth another client uses.
+//!
+//! Most calls to the [`ObjectClient`] are one S3 request. GetObject and PutObject calls are counted
+//! as one request for each part of the inner client's [part size](ObjectClient::part_size), since
+//! that's how the CRT client splits them into requests: a GetObject is charged for each part as it
+//! is returned, and a PutObject for its CreateMultipartUpload, for each part as it is written, and
+//! for its CompleteMultipartUpload. Copies are counted as one request regardless of their size.
+//! Requests that the inner client retries aren't counted again, so these limits can't keep a client
+//! within S3's request rate limits on their own. Use
+//! [adaptive throttling](crate::config::RetryConfig::adaptive_throttling) to back off when S3 asks
+//! for that.
+//!
+//! Requests are grouped into three classes, each with its own budgets:
+//! * GET: GetObject, HeadObject, and GetObjectAttributes
+//! * PUT: PutObject, CopyObject, DeleteObject(s), and the multipart upload operations
+//! * LIST: ListObjectsV2, ListObjectVersions, and ListMultipartUploads
+//!
+//! There is also an optional budget for the total rate of requests of any class. Budgets allow a
+//! burst of up to one second's worth of requests or bytes, after which requests wait until
+//! enough budget is available. Bandwidth is charged as data is returned from GetObject streams and
+//! as it is written to PutObject requests (and when parts are uploaded), so a stream that isn't read
+//! doesn't consume any read bandwidth. The inner client may download ahead of the reader into its
+//! own buffers, so its network bandwidth can exceed the read limit for short periods.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::{ready, FutureExt, Stream};
+use pin_project::pin_project;
+
+use crate::leaky_bucket::LeakyBucket;
+use crate::object_client::{
+    AbortMultipartUploadError, AbortMultipartUploadResult, CompleteMultipartUploadError, CompleteMultipartUploadResult,
+    CompletedPart, CopyObjectError, CopyObjectParams, CopyObjectResult, CreateMultipartUploadError,
+    CreateMultipartUploadResult, DeleteObjectError, DeleteObjectResult, DeleteObjectsError, DeleteObjectsResult,
+    GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult, GetObjectError, GetObjectParams,
+    GetObjectRequest, GetObjectResponse, HeadObjectError, HeadObjectParams, HeadObjectResult,
+    ListMultipartUploadsError, ListMultipartUploadsResult, ListObjectVersionsError, ListObjectVersionsResult,
+    ListObjectsError, ListObjectsParams, ListObjectsResult, ObjectAttribute, ObjectClient, ObjectClientResult,
+    ObjectIdentifier, PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult, RestoreObjectError,
+    RestoreObjectResult, RestoreTier, UploadPartError, UploadPartResult, UploadReview,
+};
+
+/// Limits for a [RateLimitedClient]. All limits are unset by default, meaning unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    request_rate: Option<u32>,
+    get_request_rate: Option<u32>,
+    put_request_rate: Option<u32>,
+    list_request_rate: Option<u32>,
+    read_bandwidth: Option<u64>,
+    write_bandwidth: Option<u64>,
+}
+
+impl RateLimitConfig {
+    /// Create a new [RateLimitConfig] with no limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of requests of any kind per second
+    #[must_use = "RateLimitConfig follows a builder pattern"]
+    pub fn request_rate(mut self, requests_per_second: u32) -> Self {
+        self.request_rate = Some(requests_per_second);
+        self
+    }
+
+    /// Set the maximum number of GET requests per second
+    #[must_use = "RateLimitConfig follows a builder pattern"]
+    pub fn get_request_rate(mut self, requests_per_second: u32) -> Self {
+        self.get_request_rate = Some(requests_per_second);
+        self
+    }
+
+    /// Set the maximum number of PUT requests per second
+    #[must_use = "RateLimitConfig follows a builder pattern"]
+    pub fn put_request_rate(mut self, requests_per_second: u32) -> Self {
+        self.put_request_rate = Some(requests_per_second);
+        self
+    }
+
+    /// Set the maximum number of LIST requests per second
+    #[must_use = "RateLimitConfig follows a builder pattern"]
+    pub fn list_request_rate(mut self, requests_per_second: u32) -> Self {
+        self.list_request_rate = Some(requests_per_second);
+        self
+    }
+
+    /// Set the maximum number of bytes per second returned by GET operations
+    #[must_use = "RateLimitConfig follows a builder pattern"]
+    pub fn read_bandwidth(mut self, bytes_per_second: u64) -> Self {
+        self.read_bandwidth = Some(bytes_per_second);
+        self
+    }
+
+    /// Set the maximum number of bytes per second written by PUT operations
+    #[must_use = "RateLimitConfig follows a builder pattern"]
+    pub fn write_bandwidth(mut self, bytes_per_second: u64) -> Self {
+        self.write_bandwidth = Some(bytes_per_second);
+        self
+    }
+}
+
+/// Create a bucket that refills at the given rate per second, and holds up to one second's worth
+/// of tokens. The bucket starts full.
+fn per_second_limiter(rate: u64) -> LeakyBucket {
+    let rate = rate.max(1);
+    // Refill in small increments so that waiting requests are released smoothly
+    let (refill_interval, refill_amount) = if rate >= 1000 {
+        (Duration::from_millis(1), (rate / 1000).min(u32::MAX as u64) as u32)
+    } else {
+        (Duration::from_secs_f64(1.0 / rate as f64), 1)
+    };
+    let max = rate.min(u32::MAX as u64) as u32;
+    LeakyBucket::builder()
+        .max(max)
+        .tokens(max)
+        .refill_interval(refill_interval)
+        .refill_amount(refill_amount)
+        .build()
+}
+
+/// Acquire any number of tokens from a bucket, in chunks no larger than it can hold
+async fn acquire(limiter: &LeakyBucket, mut amount: u64) {
+    while amount > 0 {
+        let chunk = amount.min(limiter.max() as u64) as u32;
+        limiter.acquire(chunk).await;
+        amount -= chunk as u64;
+    }
+}
+
+/// Number of parts of the given size needed to transfer `bytes` bytes
+fn parts(bytes: u64, part_size: Option<usize>) -> u64 {
+    match part_size {
+        Some(part_size) if part_size > 0 => bytes.div_ceil(part_size as u64),
+        _ => 1,
+    }
+}
+
+/// The budgets that requests of one class count against
+#[derive(Debug, Clone)]
+struct RequestLimiter {
+    /// Budget for requests of any class
+    total: Option<LeakyBucket>,
+    /// Budget for requests of this class
+    class: Option<LeakyBucket>,
+}
+
+impl RequestLimiter {
+    fn is_unlimited(&self) -> bool {
+        self.total.is_none() && self.class.is_none()
+    }
+
+    /// Wait until there's budget for `count` more requests
+    async fn acquire(&self, count: u64) {
+        if let Some(limiter) = &self.total {
+            acquire(limiter, count).await;
+        }
+        if let Some(limiter) = &self.class {
+            acquire(limiter, count).await;
+        }
+    }
+}
+
+/// A client that limits the rate of requests and the bandwidth of another client.
+#[derive(Debug)]
+pub struct RateLimitedClient<Client: ObjectClient> {
+    client: Client,
+    get_requests: RequestLimiter,
+    put_requests: RequestLimiter,
+    list_requests: RequestLimiter,
+    read_limiter: Option<LeakyBucket>,
+    write_limiter: Option<LeakyBucket>,
+}
+
+impl<Client: ObjectClient> RateLimitedClient<Client> {
+    /// Create a new client that forwards operations to `client` within the given limits
+    pub fn new(client: Client, config: RateLimitConfig) -> Self {
+        let limiter = |rate: Option<u64>| rate.map(per_second_limiter);
+        let total = limiter(config.request_rate.map(Into::into));
+        let requests = |rate: Option<u32>| RequestLimiter {
+            total: total.clone(),
+            class: limiter(rate.map(Into::into)),
+        };
+        Self {
+            get_requests: requests(config.get_request_rate),
+            put_requests: requests(config.put_request_rate),
+            list_requests: requests(config.list_request_rate),
+            read_limiter: limiter(config.read_bandwidth),
+            write_limiter: limiter(config.write_bandwidth),
+            client,
+        }
+    }
+
+    /// The client this client forwards requests to
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+}
+
+#[cfg_attr(not(docs_rs), async_trait)]
+impl<Client> ObjectClient for RateLimitedClient<Client>
+where
+    Client: ObjectClient + Send + Sync + 'static,
+{
+    type GetObjectResult = RateLimitedGetObjectRequest<Client>;
+    type PutObjectRequest = RateLimitedPutObjectRequest<Client>;
+    type ClientError = Client::ClientError;
+
+    fn part_size(&self) -> Option<usize> {
+        self.client.part_size()
+    }
+
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client
+            .copy_object(source_bucket, source_key, destination_bucket, destination_key, params)
+            .await
+    }
+
+    async fn delete_object(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> ObjectClientResult<DeleteObjectResult, DeleteObjectError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client.delete_object(bucket, key).await
+    }
+
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client.delete_objects(bucket, objects).await
+    }
+
+    async fn get_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &GetObjectParams,
+    ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
+        self.get_requests.acquire(1).await;
+        let get_result = self.client.get_object(bucket, key, params).await?;
+        Ok(RateLimitedGetObjectRequest {
+            get_result,
+            get_requests: self.get_requests.clone(),
+            read_limiter: self.read_limiter.clone(),
+            part_size: self.client.part_size(),
+            received: 0,
+            pending: None,
+        })
+    }
+
+    async fn list_objects(
+        &self,
+        bucket: &str,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+        self.list_requests.acquire(1).await;
+        self.client.list_objects(bucket, params).await
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
+        delimiter: &str,
+        max_keys: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        self.list_requests.acquire(1).await;
+        self.client
+            .list_object_versions(bucket, key_marker, version_id_marker, delimiter, max_keys, prefix)
+            .await
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
+        self.list_requests.acquire(1).await;
+        self.client
+            .list_multipart_uploads(bucket, key_marker, upload_id_marker, delimiter, max_uploads, prefix)
+            .await
+    }
+
+    async fn head_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &HeadObjectParams,
+    ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
+        self.get_requests.acquire(1).await;
+        self.client.head_object(bucket, key, params).await
+    }
+
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<Self::PutObjectRequest, PutObjectError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        let request = self.client.put_object(bucket, key, params).await?;
+        Ok(RateLimitedPutObjectRequest {
+            request,
+            put_requests: self.put_requests.clone(),
+            write_limiter: self.write_limiter.clone(),
+            part_size: self.client.part_size(),
+            written: 0,
+        })
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client.create_multipart_upload(bucket, key, params).await
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        if let Some(limiter) = &self.write_limiter {
+            acquire(limiter, contents.len() as u64).await;
+        }
+        self.client
+            .upload_part(bucket, key, upload_id, part_number, contents)
+            .await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client
+            .complete_multipart_upload(bucket, key, upload_id, parts)
+            .await
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client.abort_multipart_upload(bucket, key, upload_id).await
+    }
+
+    async fn get_object_attributes(
+        &self,
+        bucket: &str,
+        key: &str,
+        max_parts: Option<usize>,
+        part_number_marker: Option<usize>,
+        object_attributes: &[ObjectAttribute],
+    ) -> ObjectClientResult<GetObjectAttributesResult, GetObjectAttributesError, Self::ClientError> {
+        self.get_requests.acquire(1).await;
+        self.client
+            .get_object_attributes(bucket, key, max_parts, part_number_marker, object_attributes)
+            .await
+    }
+
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.client.restore_object(bucket, key, days, tier).await
+    }
+}
+
+/// A GetObject request that holds back each part until there's read bandwidth for it, and request
+/// budget for any new part of the part size it starts
+#[pin_project]
+pub struct RateLimitedGetObjectRequest<Client: ObjectClient> {
+    #[pin]
+    get_result: Client::GetObjectResult,
+    get_requests: RequestLimiter,
+    read_limiter: Option<LeakyBucket>,
+    part_size: Option<usize>,
+    /// Bytes returned by the inner request so far
+    received: u64,
+    /// A part we've received and are waiting for bandwidth to return
+    pending: Option<(BoxFuture<'static, ()>, GetBodyPart)>,
+}
+
+impl<Client: ObjectClient> Stream for RateLimitedGetObjectRequest<Client> {
+    type Item = ObjectClientResult<GetBodyPart, GetObjectError, Client::ClientError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if this.pending.is_none() {
+            let part = match ready!(this.get_result.poll_next(cx)) {
+                Some(Ok(part)) => part,
+                other => return Poll::Ready(other),
+            };
+            let length = part.1.len() as u64;
+            // The first part's request was charged when the GetObject started
+            let charged = parts(*this.received, *this.part_size).max(1);
+            *this.received += length;
+            let new_requests = parts(*this.received, *this.part_size).saturating_sub(charged);
+            if this.read_limiter.is_none() && (new_requests == 0 || this.get_requests.is_unlimited()) {
+                return Poll::Ready(Some(Ok(part)));
+            }
+            let get_requests = this.get_requests.clone();
+            let read_limiter = this.read_limiter.clone();
+            let wait = async move {
+                get_requests.acquire(new_requests).await;
+                if let Some(limiter) = read_limiter {
+                    acquire(&limiter, length).await;
+                }
+            }
+            .boxed();
+            *this.pending = Some((wait, part));
+        }
+
+        let (wait, _) = this.pending.as_mut().unwrap();
+        ready!(wait.poll_unpin(cx));
+        let (_, part) = this.pending.take().unwrap();
+        Poll::Ready(Some(Ok(part)))
+    }
+}
+
+impl<Client: ObjectClient> GetObjectRequest for RateLimitedGetObjectRequest<Client> {
+    type ClientError = Client::ClientError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        self.get_result.get_object_response()
+    }
+
+    fn increment_read_window(self: Pin<&mut Self>, len: usize) {
+        self.project().get_result.increment_read_window(len);
+    }
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        self.project_ref().get_result.read_window_end_offset()
+    }
+}
+
+/// A PutObject request that waits for write bandwidth before each write, and for request budget for
+/// each part it uploads and for completing the upload
+pub struct RateLimitedPutObjectRequest<Client: ObjectClient> {
+    request: Client::PutObjectRequest,
+    put_requests: RequestLimiter,
+    write_limiter: Option<LeakyBucket>,
+    part_size: Option<usize>,
+    /// Bytes written to the request so far
+    written: u64,
+}
+
+#[cfg_attr(not(docs_rs), async_trait)]
+impl<Client: ObjectClient> PutObjectRequest for RateLimitedPutObjectRequest<Client> {
+    type ClientError = Client::ClientError;
+
+    async fn write(&mut self, slice: &[u8]) -> ObjectClientResult<(), PutObjectError, Self::ClientError> {
+        let started_parts = parts(self.written, self.part_size);
+        self.written += slice.len() as u64;
+        let new_parts = parts(self.written, self.part_size) - started_parts;
+        self.put_requests.acquire(new_parts).await;
+        if let Some(limiter) = &self.write_limiter {
+            acquire(limiter, slice.len() as u64).await;
+        }
+        self.request.write(slice).await
+    }
+
+    async fn complete(self) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.request.complete().await
+    }
+
+    async fn review_and_complete(
+        self,
+        review_callback: impl FnOnce(UploadReview) -> bool + Send + 'static,
+    ) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        self.put_requests.acquire(1).await;
+        self.request.review_and_complete(review_callback).await
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::time::Instant;
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::mock_client::{MockClient, MockClientConfig, MockObject};
+    use crate::object_client::ETag;
+
+    fn mock_client() -> MockClient {
+        let client = MockClient::new(MockClientConfig {
+            bucket: "test_bucket".to_owned(),
+            part_size: 1000,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+        client.add_object("key", MockObject::constant(0, 10_000, ETag::for_tests()));
+        client
+    }
+
+    #[tokio::test]
+    async fn test_request_rate() {
+        let client = RateLimitedClient::new(mock_client(), RateLimitConfig::new().list_request_rate(20));
+
+        // The first second's worth of requests is a burst, and the next 10 wait for the budget to
+        // refill
+        let start = Instant::now();
+        for _ in 0..30 {
+            client
+                .list_objects("test_bucket", &ListObjectsParams::new().delimiter("/").max_keys(10))
+                .await
+                .unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(450));
+
+        // Other request classes have their own budget
+        let start = Instant::now();
+        for _ in 0..10 {
+            client
+                .head_object("test_bucket", "key", &HeadObjectParams::new())
+                .await
+                .unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(450));
+    }
+
+    #[tokio::test]
+    async fn test_get_object_charged_per_part() {
+        let client = RateLimitedClient::new(mock_client(), RateLimitConfig::new().get_request_rate(5));
+
+        // The object is 10 parts, so it takes 10 requests: a burst of 5, and 5 that wait for the
+        // budget to refill
+        let start = Instant::now();
+        let get = client
+            .get_object("test_bucket", "key", &GetObjectParams::new())
+            .await
+            .unwrap();
+        let parts: Vec<_> = get.collect().await;
+        assert_eq!(parts.len(), 10);
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_put_object_charged_per_part() {
+        let client = RateLimitedClient::new(mock_client(), RateLimitConfig::new().put_request_rate(5));
+
+        // CreateMultipartUpload, 6 parts, and CompleteMultipartUpload are 8 requests, 3 more than
+        // the burst
+        let start = Instant::now();
+        let mut put = client
+            .put_object("test_bucket", "new", &PutObjectParams::new())
+            .await
+            .unwrap();
+        put.write(&[0; 6_000]).await.unwrap();
+        put.complete().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_read_bandwidth() {
+        let client = RateLimitedClient::new(mock_client(), RateLimitConfig::new().read_bandwidth(10_000));
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            let get = client
+                .get_object("test_bucket", "key", &GetObjectParams::new())
+                .await
+                .unwrap();
+            let parts: Vec<_> = get.collect().await;
+            assert_eq!(parts.len(), 10);
+            assert!(parts.iter().all(|part| part.is_ok()));
+        }
+        // The first 10KB is a burst, and the second has to wait a second for the budget to refill
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_write_bandwidth() {
+        let client = RateLimitedClient::new(mock_client(), RateLimitConfig::new().write_bandwidth(4_000));
+
+        let start = Instant::now();
+        let mut put = client
+            .put_object("test_bucket", "new", &PutObjectParams::new())
+            .await
+            .unwrap();
+        // More than the bucket holds at once, which needs to be acquired in chunks
+        put.write(&[0; 6_000]).await.unwrap();
+        put.complete().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[test]
+    fn test_limiter_parameters() {
+        // Slow rates refill one token at a time, and fast rates in bigger increments
+        assert_eq!(per_second_limiter(5).max(), 5);
+        assert_eq!(per_second_limiter(100 * 1024 * 1024).max(), 100 * 1024 * 1024);
+        assert_eq!(per_second_limiter(u64::MAX).max(), u32::MAX);
+    }
+}
@@ -0,0 +1,657 @@
+//! An [`ObjectClient`] that records every request it makes and the response it got, so that a
+//! session can be replayed later with [ReplayClient](crate::replay_client::ReplayClient).
+//!
+//! Only request parameters and response metadata are recorded by default. The contents of GetObject
+//! and PutObject bodies can be recorded too, but recordings then contain object data and grow as
+//! large as the data transferred. The `format` module describes the layout of recordings.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures::Stream;
+use pin_project::{pin_project, pinned_drop};
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::object_client::{
+    AbortMultipartUploadError, AbortMultipartUploadResult, CompleteMultipartUploadError, CompleteMultipartUploadResult,
+    CompletedPart, CopyObjectError, CopyObjectParams, CopyObjectResult, CreateMultipartUploadError,
+    CreateMultipartUploadResult, DeleteObjectError, DeleteObjectResult, DeleteObjectsError, DeleteObjectsResult,
+    GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult, GetObjectError, GetObjectParams,
+    GetObjectRequest, GetObjectResponse, HeadObjectError, HeadObjectParams, HeadObjectResult,
+    ListMultipartUploadsError, ListMultipartUploadsResult, ListObjectVersionsError, ListObjectVersionsResult,
+    ListObjectsError, ListObjectsParams, ListObjectsResult, ObjectAttribute, ObjectClientResult, ObjectIdentifier,
+    PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult, RestoreObjectError, RestoreObjectResult,
+    RestoreTier, UploadPartError, UploadPartResult, UploadReview,
+};
+use crate::ObjectClient;
+
+pub(crate) mod format;
+
+use format::{encode_body, record_error, record_result, Recordable};
+
+/// Writes records of requests to the recording file
+struct Recorder {
+    writer: Mutex<Box<dyn Write + Send>>,
+    next_id: AtomicU64,
+    record_bodies: bool,
+}
+
+impl Recorder {
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn record(&self, id: u64, operation: &str, request: Value, response: Value) {
+        let record = json!({
+            "id": id,
+            "operation": operation,
+            "request": request,
+            "response": response,
+        });
+        let mut writer = self.writer.lock().unwrap();
+        // Flush after every record so that the recording is complete even if the process crashes
+        if let Err(error) = writeln!(writer, "{record}").and_then(|_| writer.flush()) {
+            warn!(?error, operation, "failed to write request recording");
+        }
+    }
+}
+
+/// A client that forwards requests to another client and records them.
+pub struct RecordingClient<Client: ObjectClient> {
+    client: Client,
+    recorder: Arc<Recorder>,
+}
+
+impl<Client: ObjectClient> RecordingClient<Client> {
+    /// Create a new client that writes its recording to the given writer. If `record_bodies` is
+    /// true, the contents of objects read and written are recorded too.
+    pub fn new(client: Client, writer: impl Write + Send + 'static, record_bodies: bool) -> Self {
+        let recorder = Recorder {
+            writer: Mutex::new(Box::new(writer)),
+            next_id: AtomicU64::new(1),
+            record_bodies,
+        };
+        Self {
+            client,
+            recorder: Arc::new(recorder),
+        }
+    }
+
+    /// Create a new client that writes its recording to a new file at the given path, replacing
+    /// any existing file.
+    pub fn create(client: Client, path: impl AsRef<Path>, record_bodies: bool) -> std::io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(client, BufWriter::new(file), record_bodies))
+    }
+
+    /// The client this client forwards requests to
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    fn record<T: Recordable, E: Recordable>(
+        &self,
+        id: u64,
+        operation: &str,
+        request: Value,
+        result: &ObjectClientResult<T, E, Client::ClientError>,
+    ) {
+        self.recorder.record(id, operation, request, record_result(result));
+    }
+}
+
+#[cfg_attr(not(docs_rs), async_trait)]
+impl<Client> ObjectClient for RecordingClient<Client>
+where
+    Client: ObjectClient + Send + Sync + 'static,
+{
+    type GetObjectResult = RecordingGetObjectRequest<Client>;
+    type PutObjectRequest = RecordingPutObjectRequest<Client>;
+    type ClientError = Client::ClientError;
+
+    fn part_size(&self) -> Option<usize> {
+        self.client.part_size()
+    }
+
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self
+            .client
+            .copy_object(source_bucket, source_key, destination_bucket, destination_key, params)
+            .await;
+        let request = json!({
+            "source_bucket": source_bucket,
+            "source_key": source_key,
+            "destination_bucket": destination_bucket,
+            "destination_key": destination_key,
+        });
+        self.record(id, "copy_object", request, &result);
+        result
+    }
+
+    async fn delete_object(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> ObjectClientResult<DeleteObjectResult, DeleteObjectError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.delete_object(bucket, key).await;
+        let request = json!({ "bucket": bucket, "key": key });
+        self.record(id, "delete_object", request, &result);
+        result
+    }
+
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.delete_objects(bucket, objects).await;
+        let request = format::delete_objects_request(bucket, objects);
+        self.record(id, "delete_objects", request, &result);
+        result
+    }
+
+    async fn get_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &GetObjectParams,
+    ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let request = format::get_object_request(bucket, key, params);
+        match self.client.get_object(bucket, key, params).await {
+            Ok(get_result) => Ok(RecordingGetObjectRequest {
+                get_result,
+                recorder: self.recorder.clone(),
+                id,
+                request: Some(request),
+                parts: Vec::new(),
+                error: None,
+            }),
+            Err(error) => {
+                self.recorder.record(id, "get_object", request, record_error(&error));
+                Err(error)
+            }
+        }
+    }
+
+    async fn list_objects(
+        &self,
+        bucket: &str,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.list_objects(bucket, params).await;
+        let request = format::list_objects_request(bucket, params);
+        self.record(id, "list_objects", request, &result);
+        result
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
+        delimiter: &str,
+        max_keys: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self
+            .client
+            .list_object_versions(bucket, key_marker, version_id_marker, delimiter, max_keys, prefix)
+            .await;
+        let request = json!({
+            "bucket": bucket,
+            "key_marker": key_marker,
+            "version_id_marker": version_id_marker,
+            "delimiter": delimiter,
+            "max_keys": max_keys,
+            "prefix": prefix,
+        });
+        self.record(id, "list_object_versions", request, &result);
+        result
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self
+            .client
+            .list_multipart_uploads(bucket, key_marker, upload_id_marker, delimiter, max_uploads, prefix)
+            .await;
+        let request = json!({
+            "bucket": bucket,
+            "key_marker": key_marker,
+            "upload_id_marker": upload_id_marker,
+            "delimiter": delimiter,
+            "max_uploads": max_uploads,
+            "prefix": prefix,
+        });
+        self.record(id, "list_multipart_uploads", request, &result);
+        result
+    }
+
+    async fn head_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &HeadObjectParams,
+    ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.head_object(bucket, key, params).await;
+        let request = format::head_object_request(bucket, key, params);
+        self.record(id, "head_object", request, &result);
+        result
+    }
+
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<Self::PutObjectRequest, PutObjectError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let request = format::put_object_request(bucket, key, params);
+        match self.client.put_object(bucket, key, params).await {
+            Ok(put_request) => Ok(RecordingPutObjectRequest {
+                request: Some(put_request),
+                recorder: self.recorder.clone(),
+                id,
+                recorded_request: Some(request),
+                length: 0,
+                body: self.recorder.record_bodies.then(Vec::new),
+                write_error: None,
+                result: None,
+            }),
+            Err(error) => {
+                self.recorder.record(id, "put_object", request, record_error(&error));
+                Err(error)
+            }
+        }
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.create_multipart_upload(bucket, key, params).await;
+        let request = format::put_object_request(bucket, key, params);
+        self.record(id, "create_multipart_upload", request, &result);
+        result
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self
+            .client
+            .upload_part(bucket, key, upload_id, part_number, contents)
+            .await;
+        // The contents aren't part of the request, so that replays match the request even when the
+        // recording doesn't include bodies
+        let request = json!({
+            "bucket": bucket,
+            "key": key,
+            "upload_id": upload_id,
+            "part_number": part_number,
+            "length": contents.len(),
+        });
+        self.record(id, "upload_part", request, &result);
+        result
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self
+            .client
+            .complete_multipart_upload(bucket, key, upload_id, parts)
+            .await;
+        let request = format::complete_multipart_upload_request(bucket, key, upload_id, parts);
+        self.record(id, "complete_multipart_upload", request, &result);
+        result
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.abort_multipart_upload(bucket, key, upload_id).await;
+        let request = json!({ "bucket": bucket, "key": key, "upload_id": upload_id });
+        self.record(id, "abort_multipart_upload", request, &result);
+        result
+    }
+
+    async fn get_object_attributes(
+        &self,
+        bucket: &str,
+        key: &str,
+        max_parts: Option<usize>,
+        part_number_marker: Option<usize>,
+        object_attributes: &[ObjectAttribute],
+    ) -> ObjectClientResult<GetObjectAttributesResult, GetObjectAttributesError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self
+            .client
+            .get_object_attributes(bucket, key, max_parts, part_number_marker, object_attributes)
+            .await;
+        let request =
+            format::get_object_attributes_request(bucket, key, max_parts, part_number_marker, object_attributes);
+        self.record(id, "get_object_attributes", request, &result);
+        result
+    }
+
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        let id = self.recorder.next_id();
+        let result = self.client.restore_object(bucket, key, days, tier).await;
+        let request = format::restore_object_request(bucket, key, days, tier);
+        self.record(id, "restore_object", request, &result);
+        result
+    }
+}
+
+/// A GetObject request that records the parts it returns. The request is recorded when it's
+/// dropped, so that partially read requests are recorded too.
+#[pin_project(PinnedDrop)]
+pub struct RecordingGetObjectRequest<Client: ObjectClient> {
+    #[pin]
+    get_result: Client::GetObjectResult,
+    recorder: Arc<Recorder>,
+    id: u64,
+    request: Option<Value>,
+    parts: Vec<Value>,
+    error: Option<Value>,
+}
+
+impl<Client: ObjectClient> Stream for RecordingGetObjectRequest<Client> {
+    type Item = ObjectClientResult<GetBodyPart, GetObjectError, Client::ClientError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let next = this.get_result.poll_next(cx);
+        match &next {
+            Poll::Ready(Some(Ok((offset, body)))) => {
+                let mut part = json!({ "offset": offset, "length": body.len() });
+                if this.recorder.record_bodies {
+                    part["body"] = encode_body(body);
+                }
+                this.parts.push(part);
+            }
+            Poll::Ready(Some(Err(error))) => *this.error = Some(record_error(error)),
+            Poll::Ready(None) | Poll::Pending => {}
+        }
+        next
+    }
+}
+
+impl<Client: ObjectClient> GetObjectRequest for RecordingGetObjectRequest<Client> {
+    type ClientError = Client::ClientError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        self.get_result.get_object_response()
+    }
+
+    fn increment_read_window(self: Pin<&mut Self>, len: usize) {
+        self.project().get_result.increment_read_window(len);
+    }
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        self.project_ref().get_result.read_window_end_offset()
+    }
+}
+
+#[pinned_drop]
+impl<Client: ObjectClient> PinnedDrop for RecordingGetObjectRequest<Client> {
+    fn drop(self: Pin<&mut Self>) {
+        let response = self.get_result.get_object_response();
+        let this = self.project();
+        let response = json!({
+            "ok": {
+                "response": response.as_ref().map(Recordable::to_recording),
+                "parts": std::mem::take(this.parts),
+                "error": this.error.take(),
+            }
+        });
+        let request = this.request.take().unwrap_or_default();
+        this.recorder.record(*this.id, "get_object", request, response);
+    }
+}
+
+/// A PutObject request that records the data written to it. The request is recorded when it's
+/// completed or dropped.
+pub struct RecordingPutObjectRequest<Client: ObjectClient> {
+    request: Option<Client::PutObjectRequest>,
+    recorder: Arc<Recorder>,
+    id: u64,
+    recorded_request: Option<Value>,
+    length: u64,
+    body: Option<Vec<u8>>,
+    write_error: Option<Value>,
+    result: Option<Value>,
+}
+
+#[cfg_attr(not(docs_rs), async_trait)]
+impl<Client: ObjectClient> PutObjectRequest for RecordingPutObjectRequest<Client>
+where
+    Client::PutObjectRequest: Send,
+{
+    type ClientError = Client::ClientError;
+
+    async fn write(&mut self, slice: &[u8]) -> ObjectClientResult<(), PutObjectError, Self::ClientError> {
+        let request = self.request.as_mut().expect("request is only taken when completing");
+        let result = request.write(slice).await;
+        match &result {
+            Ok(()) => {
+                self.length += slice.len() as u64;
+                if let Some(body) = &mut self.body {
+                    body.extend_from_slice(slice);
+                }
+            }
+            Err(error) => self.write_error = Some(record_error(error)),
+        }
+        result
+    }
+
+    async fn complete(mut self) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        let request = self.request.take().expect("request is only taken when completing");
+        let result = request.complete().await;
+        self.result = Some(record_result(&result));
+        result
+    }
+
+    async fn review_and_complete(
+        mut self,
+        review_callback: impl FnOnce(UploadReview) -> bool + Send + 'static,
+    ) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        let request = self.request.take().expect("request is only taken when completing");
+        let result = request.review_and_complete(review_callback).await;
+        self.result = Some(record_result(&result));
+        result
+    }
+}
+
+impl<Client: ObjectClient> Drop for RecordingPutObjectRequest<Client> {
+    fn drop(&mut self) {
+        let response = json!({
+            "ok": {
+                "length": self.length,
+                "body": self.body.as_deref().map(encode_body),
+                "write_error": self.write_error.take(),
+                "complete": self.result.take(),
+            }
+        });
+        let request = self.recorded_request.take().unwrap_or_default();
+        self.recorder.record(self.id, "put_object", request, response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::mock_client::{MockClient, MockClientConfig, MockObject};
+    use crate::object_client::{ETag, ObjectClientError};
+
+    /// A writer whose contents can be inspected after it's moved into a client
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn records(&self) -> Vec<Value> {
+            let contents = self.0.lock().unwrap();
+            std::str::from_utf8(&contents)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recording() {
+        let bucket = "test_bucket";
+        let mock_client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 4,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+        mock_client.add_object("hello", MockObject::from_bytes(b"hello world", ETag::for_tests()));
+
+        let buffer = SharedBuffer::default();
+        let client = RecordingClient::new(mock_client, buffer.clone(), true);
+
+        let head = client.head_object(bucket, "missing", &HeadObjectParams::new()).await;
+        assert!(matches!(
+            head,
+            Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
+        ));
+
+        let mut get = client
+            .get_object(bucket, "hello", &GetObjectParams::new())
+            .await
+            .unwrap();
+        while let Some(part) = get.next().await {
+            part.unwrap();
+        }
+        drop(get);
+
+        let mut put = client.put_object(bucket, "new", &PutObjectParams::new()).await.unwrap();
+        put.write(b"abc").await.unwrap();
+        put.complete().await.unwrap();
+
+        let records = buffer.records();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0]["id"], 1);
+        assert_eq!(records[0]["operation"], "head_object");
+        assert_eq!(records[0]["response"]["service_error"], "NotFound");
+
+        assert_eq!(records[1]["operation"], "get_object");
+        let parts = records[1]["response"]["ok"]["parts"].as_array().unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[2]["offset"], 8);
+        assert_eq!(parts[2]["length"], 3);
+        assert_eq!(parts[2]["body"], "cmxk");
+        assert_eq!(records[1]["response"]["ok"]["response"]["content_length"], 11);
+
+        assert_eq!(records[2]["operation"], "put_object");
+        assert_eq!(records[2]["request"]["key"], "new");
+        assert_eq!(records[2]["response"]["ok"]["length"], 3);
+        assert_eq!(records[2]["response"]["ok"]["body"], "YWJj");
+        assert!(records[2]["response"]["ok"]["complete"]["ok"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_recording_delete_objects() {
+        let bucket = "test_bucket";
+        let mock_client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 4,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+        mock_client.add_object("a", MockObject::from_bytes(b"a", ETag::for_tests()));
+        mock_client.add_object("b", MockObject::from_bytes(b"b", ETag::for_tests()));
+
+        let buffer = SharedBuffer::default();
+        let client = RecordingClient::new(mock_client, buffer.clone(), false);
+
+        let stale_etag: ETag = "stale_etag".parse().unwrap();
+        let objects = [ObjectIdentifier::new("a"), ObjectIdentifier::new("b").etag(stale_etag)];
+        let result = client.delete_objects(bucket, &objects).await.unwrap();
+        assert_eq!(result.deleted, vec!["a".to_owned()]);
+        assert_eq!(result.errors.len(), 1);
+
+        let records = buffer.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["operation"], "delete_objects");
+        let request_objects = records[0]["request"]["objects"].as_array().unwrap();
+        assert_eq!(request_objects.len(), 2);
+        assert_eq!(request_objects[0]["key"], "a");
+        assert!(request_objects[0]["etag"].is_null());
+        assert_eq!(request_objects[1]["key"], "b");
+        assert_eq!(request_objects[1]["etag"], "stale_etag");
+        assert_eq!(records[0]["response"]["ok"]["deleted"], json!(["a"]));
+        assert_eq!(records[0]["response"]["ok"]["errors"][0]["key"], "b");
+        assert_eq!(records[0]["response"]["ok"]["errors"][0]["code"], "PreconditionFailed");
+    }
+}
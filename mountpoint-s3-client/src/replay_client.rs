@@ -0,0 +1,747 @@
+//! An [`ObjectClient`] that serves the responses of a session recorded by
+//! [RecordingClient](crate::recording_client::RecordingClient), without making any network
+//! requests.
+//!
+//! Each request is matched with a recorded request that had exactly the same parameters, and gets
+//! the response recorded for it. Requests that were made several times in the recording get the
+//! recorded responses in the order they were originally made. A request that wasn't recorded at all
+//! fails with [`ReplayClientError::NoRecording`], and one that's made more times than it was
+//! recorded fails with [`ReplayClientError::RecordingExhausted`], so that a replay that diverges
+//! from the recording is noticed rather than served stale responses.
+//!
+//! GetObject bodies are replayed from the recording if it includes them, and as zeroes of the
+//! recorded length otherwise. PutObject bodies are accepted and discarded.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tracing::trace;
+
+use crate::checksums::ChecksumHasher;
+use crate::object_client::{
+    AbortMultipartUploadError, AbortMultipartUploadResult, ChecksumAlgorithm, CompleteMultipartUploadError,
+    CompleteMultipartUploadResult, CompletedPart, CopyObjectError, CopyObjectParams, CopyObjectResult,
+    CreateMultipartUploadError, CreateMultipartUploadResult, DeleteObjectError, DeleteObjectResult, DeleteObjectsError,
+    DeleteObjectsResult, GetBodyPart, GetObjectAttributesError, GetObjectAttributesResult, GetObjectError,
+    GetObjectParams, GetObjectRequest, GetObjectResponse, HeadObjectError, HeadObjectParams, HeadObjectResult,
+    ListMultipartUploadsError, ListMultipartUploadsResult, ListObjectVersionsError, ListObjectVersionsResult,
+    ListObjectsError, ListObjectsParams, ListObjectsResult, ObjectAttribute, ObjectClientError, ObjectClientResult,
+    ObjectIdentifier, PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult, RestoreObjectError,
+    RestoreObjectResult, RestoreTier, UploadPartError, UploadPartResult, UploadReview, UploadReviewPart,
+};
+use crate::recording_client::format::{self, decode_body, replay_result, Recordable};
+use crate::ObjectClient;
+
+/// Errors returned by a [ReplayClient]
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ReplayClientError {
+    #[error("no recorded response for {operation} request {request}")]
+    NoRecording { operation: String, request: String },
+
+    #[error("all {recorded} recorded responses for {operation} request {request} were already replayed")]
+    RecordingExhausted {
+        operation: String,
+        request: String,
+        recorded: usize,
+    },
+
+    #[error("invalid recording of {0} response")]
+    InvalidRecording(&'static str),
+
+    #[error("PutObject request was not completed in the recording")]
+    IncompleteUpload,
+
+    /// A client error that happened during the recording, with the message it had then
+    #[error("recorded client error: {0}")]
+    Recorded(String),
+}
+
+/// Errors loading a recording
+#[derive(Debug, Error)]
+pub enum ReplayLoadError {
+    #[error("failed to read recording")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid record on line {line}")]
+    InvalidRecord {
+        line: usize,
+        #[source]
+        source: Option<serde_json::Error>,
+    },
+}
+
+/// A recorded request
+struct Record {
+    id: u64,
+    key: String,
+    response: Value,
+}
+
+/// The recorded responses to one request, in the order they were made
+#[derive(Default)]
+struct RecordedResponses {
+    remaining: VecDeque<Value>,
+    recorded: usize,
+}
+
+/// A client that replays a recorded session.
+pub struct ReplayClient {
+    part_size: usize,
+    responses: Mutex<HashMap<String, RecordedResponses>>,
+}
+
+impl ReplayClient {
+    /// Load a recording from a reader. `part_size` is only used to split PutObject bodies into parts
+    /// for review.
+    pub fn new(part_size: usize, reader: impl BufRead) -> Result<Self, ReplayLoadError> {
+        let mut records = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |source| ReplayLoadError::InvalidRecord {
+                line: index + 1,
+                source,
+            };
+            let mut record: Value = serde_json::from_str(&line).map_err(|e| invalid(Some(e)))?;
+            let (Some(id), Some(operation)) = (record["id"].as_u64(), record["operation"].as_str()) else {
+                return Err(invalid(None));
+            };
+            records.push(Record {
+                id,
+                key: request_key(operation, &record["request"]),
+                response: record["response"].take(),
+            });
+        }
+
+        // Records are written as requests finish, so put them back in the order they were made
+        records.sort_by_key(|record| record.id);
+        let mut responses: HashMap<String, RecordedResponses> = HashMap::new();
+        for record in records {
+            let entry = responses.entry(record.key).or_default();
+            entry.remaining.push_back(record.response);
+            entry.recorded += 1;
+        }
+
+        Ok(Self {
+            part_size,
+            responses: Mutex::new(responses),
+        })
+    }
+
+    /// Load a recording from a file
+    pub fn open(part_size: usize, path: impl AsRef<Path>) -> Result<Self, ReplayLoadError> {
+        let file = File::open(path)?;
+        Self::new(part_size, BufReader::new(file))
+    }
+
+    /// Find the next recorded response to a request
+    fn next_response(&self, operation: &str, request: &Value) -> Result<Value, ReplayClientError> {
+        let key = request_key(operation, request);
+        let mut responses = self.responses.lock().unwrap();
+        let Some(recorded) = responses.get_mut(&key) else {
+            return Err(ReplayClientError::NoRecording {
+                operation: operation.to_owned(),
+                request: request.to_string(),
+            });
+        };
+        trace!(
+            operation,
+            ?request,
+            remaining = recorded.remaining.len(),
+            "replaying request"
+        );
+        recorded
+            .remaining
+            .pop_front()
+            .ok_or_else(|| ReplayClientError::RecordingExhausted {
+                operation: operation.to_owned(),
+                request: request.to_string(),
+                recorded: recorded.recorded,
+            })
+    }
+
+    /// Replay a request whose response was recorded in full
+    fn replay<T: Recordable, E: Recordable>(
+        &self,
+        operation: &'static str,
+        request: Value,
+    ) -> ObjectClientResult<T, E, ReplayClientError> {
+        let response = self
+            .next_response(operation, &request)
+            .map_err(ObjectClientError::ClientError)?;
+        replay_response(operation, &response)
+    }
+}
+
+fn request_key(operation: &str, request: &Value) -> String {
+    format!("{operation} {request}")
+}
+
+fn replay_response<T: Recordable, E: Recordable>(
+    operation: &'static str,
+    response: &Value,
+) -> ObjectClientResult<T, E, ReplayClientError> {
+    match replay_result(response) {
+        Some(Ok(result)) => Ok(result),
+        Some(Err(ObjectClientError::ServiceError(error))) => Err(ObjectClientError::ServiceError(error)),
+        Some(Err(ObjectClientError::ClientError(message))) => {
+            Err(ObjectClientError::ClientError(ReplayClientError::Recorded(message)))
+        }
+        None => Err(ObjectClientError::ClientError(ReplayClientError::InvalidRecording(
+            operation,
+        ))),
+    }
+}
+
+/// Replay a recorded error response
+fn replay_error<E: Recordable>(operation: &'static str, error: &Value) -> ObjectClientError<E, ReplayClientError> {
+    // Use an empty result type so that only the error is parsed
+    match replay_response::<CopyObjectResult, E>(operation, error) {
+        Ok(_) => ObjectClientError::ClientError(ReplayClientError::InvalidRecording(operation)),
+        Err(error) => error,
+    }
+}
+
+#[cfg_attr(not(docs_rs), async_trait)]
+impl ObjectClient for ReplayClient {
+    type GetObjectResult = ReplayGetObjectRequest;
+    type PutObjectRequest = ReplayPutObjectRequest;
+    type ClientError = ReplayClientError;
+
+    fn part_size(&self) -> Option<usize> {
+        Some(self.part_size)
+    }
+
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        _params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        let request = json!({
+            "source_bucket": source_bucket,
+            "source_key": source_key,
+            "destination_bucket": destination_bucket,
+            "destination_key": destination_key,
+        });
+        self.replay("copy_object", request)
+    }
+
+    async fn delete_object(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> ObjectClientResult<DeleteObjectResult, DeleteObjectError, Self::ClientError> {
+        self.replay("delete_object", json!({ "bucket": bucket, "key": key }))
+    }
+
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        self.replay("delete_objects", format::delete_objects_request(bucket, objects))
+    }
+
+    async fn get_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &GetObjectParams,
+    ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
+        const OPERATION: &str = "get_object";
+        let request = format::get_object_request(bucket, key, params);
+        let response = self
+            .next_response(OPERATION, &request)
+            .map_err(ObjectClientError::ClientError)?;
+        let Some(recorded) = response.get("ok") else {
+            return Err(replay_error(OPERATION, &response));
+        };
+
+        let invalid = || ObjectClientError::ClientError(ReplayClientError::InvalidRecording(OPERATION));
+        let get_object_response = match &recorded["response"] {
+            Value::Null => None,
+            response => Some(GetObjectResponse::from_recording(response).ok_or_else(invalid)?),
+        };
+        let mut parts = VecDeque::new();
+        for part in recorded["parts"].as_array().ok_or_else(invalid)? {
+            let offset = part["offset"].as_u64().ok_or_else(invalid)?;
+            let body = match part.get("body") {
+                Some(body) => decode_body(body).ok_or_else(invalid)?,
+                None => vec![0; part["length"].as_u64().ok_or_else(invalid)? as usize],
+            };
+            parts.push_back((offset, body.into_boxed_slice()));
+        }
+        let error = match &recorded["error"] {
+            Value::Null => None,
+            error => Some(replay_error(OPERATION, error)),
+        };
+
+        Ok(ReplayGetObjectRequest {
+            response: get_object_response,
+            parts,
+            error,
+        })
+    }
+
+    async fn list_objects(
+        &self,
+        bucket: &str,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+        self.replay("list_objects", format::list_objects_request(bucket, params))
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
+        delimiter: &str,
+        max_keys: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        let request = json!({
+            "bucket": bucket,
+            "key_marker": key_marker,
+            "version_id_marker": version_id_marker,
+            "delimiter": delimiter,
+            "max_keys": max_keys,
+            "prefix": prefix,
+        });
+        self.replay("list_object_versions", request)
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
+        let request = json!({
+            "bucket": bucket,
+            "key_marker": key_marker,
+            "upload_id_marker": upload_id_marker,
+            "delimiter": delimiter,
+            "max_uploads": max_uploads,
+            "prefix": prefix,
+        });
+        self.replay("list_multipart_uploads", request)
+    }
+
+    async fn head_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &HeadObjectParams,
+    ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
+        self.replay("head_object", format::head_object_request(bucket, key, params))
+    }
+
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<Self::PutObjectRequest, PutObjectError, Self::ClientError> {
+        const OPERATION: &str = "put_object";
+        let request = format::put_object_request(bucket, key, params);
+        let response = self
+            .next_response(OPERATION, &request)
+            .map_err(ObjectClientError::ClientError)?;
+        let Some(recorded) = response.get("ok") else {
+            return Err(replay_error(OPERATION, &response));
+        };
+        let recorded_length =
+            recorded["length"]
+                .as_u64()
+                .ok_or(ObjectClientError::ClientError(ReplayClientError::InvalidRecording(
+                    OPERATION,
+                )))?;
+
+        Ok(ReplayPutObjectRequest {
+            part_size: self.part_size,
+            checksum_algorithm: params.checksum_algorithm,
+            buffer: Vec::new(),
+            recorded_length,
+            write_error: recorded.get("write_error").filter(|e| !e.is_null()).cloned(),
+            complete: recorded.get("complete").filter(|c| !c.is_null()).cloned(),
+        })
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        self.replay(
+            "create_multipart_upload",
+            format::put_object_request(bucket, key, params),
+        )
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        let request = json!({
+            "bucket": bucket,
+            "key": key,
+            "upload_id": upload_id,
+            "part_number": part_number,
+            "length": contents.len(),
+        });
+        self.replay("upload_part", request)
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        self.replay(
+            "complete_multipart_upload",
+            format::complete_multipart_upload_request(bucket, key, upload_id, parts),
+        )
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        let request = json!({ "bucket": bucket, "key": key, "upload_id": upload_id });
+        self.replay("abort_multipart_upload", request)
+    }
+
+    async fn get_object_attributes(
+        &self,
+        bucket: &str,
+        key: &str,
+        max_parts: Option<usize>,
+        part_number_marker: Option<usize>,
+        object_attributes: &[ObjectAttribute],
+    ) -> ObjectClientResult<GetObjectAttributesResult, GetObjectAttributesError, Self::ClientError> {
+        let request =
+            format::get_object_attributes_request(bucket, key, max_parts, part_number_marker, object_attributes);
+        self.replay("get_object_attributes", request)
+    }
+
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        self.replay(
+            "restore_object",
+            format::restore_object_request(bucket, key, days, tier),
+        )
+    }
+}
+
+/// A replayed GetObject request, which returns the recorded parts and then the recorded error, if
+/// there was one
+#[derive(Debug)]
+pub struct ReplayGetObjectRequest {
+    response: Option<GetObjectResponse>,
+    parts: VecDeque<GetBodyPart>,
+    error: Option<ObjectClientError<GetObjectError, ReplayClientError>>,
+}
+
+impl Stream for ReplayGetObjectRequest {
+    type Item = ObjectClientResult<GetBodyPart, GetObjectError, ReplayClientError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(part) = self.parts.pop_front() {
+            return Poll::Ready(Some(Ok(part)));
+        }
+        Poll::Ready(self.error.take().map(Err))
+    }
+}
+
+impl GetObjectRequest for ReplayGetObjectRequest {
+    type ClientError = ReplayClientError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        self.response.clone()
+    }
+
+    // Replayed requests return the recorded parts without any flow control
+    fn increment_read_window(self: Pin<&mut Self>, _len: usize) {}
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        u64::MAX
+    }
+}
+
+/// A replayed PutObject request, which accepts writes until the point where they failed in the
+/// recording, and then completes with the recorded result
+#[derive(Debug)]
+pub struct ReplayPutObjectRequest {
+    part_size: usize,
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    buffer: Vec<u8>,
+    recorded_length: u64,
+    write_error: Option<Value>,
+    complete: Option<Value>,
+}
+
+impl ReplayPutObjectRequest {
+    fn replay_complete(self) -> ObjectClientResult<PutObjectResult, PutObjectError, ReplayClientError> {
+        match &self.complete {
+            Some(complete) => replay_response("put_object", complete),
+            None => Err(ObjectClientError::ClientError(ReplayClientError::IncompleteUpload)),
+        }
+    }
+}
+
+#[cfg_attr(not(docs_rs), async_trait)]
+impl PutObjectRequest for ReplayPutObjectRequest {
+    type ClientError = ReplayClientError;
+
+    async fn write(&mut self, slice: &[u8]) -> ObjectClientResult<(), PutObjectError, Self::ClientError> {
+        if let Some(error) = &self.write_error {
+            if self.buffer.len() as u64 + slice.len() as u64 > self.recorded_length {
+                return Err(replay_error("put_object", error));
+            }
+        }
+        self.buffer.extend_from_slice(slice);
+        Ok(())
+    }
+
+    async fn complete(self) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        self.replay_complete()
+    }
+
+    async fn review_and_complete(
+        self,
+        review_callback: impl FnOnce(UploadReview) -> bool + Send + 'static,
+    ) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
+        // Build the review from the data actually written, like the mock client does, so that callers
+        // that check it against their own checksums see it match
+        let checksum_algorithm = self.checksum_algorithm;
+        let parts = self
+            .buffer
+            .chunks(self.part_size)
+            .map(|part| {
+                let checksum = checksum_algorithm.map(|algorithm| {
+                    let mut hasher = ChecksumHasher::new(algorithm);
+                    hasher.update(part);
+                    hasher.finalize_base64()
+                });
+                UploadReviewPart {
+                    size: part.len() as u64,
+                    checksum,
+                }
+            })
+            .collect();
+        let review = UploadReview {
+            parts,
+            checksum_algorithm,
+        };
+        if !review_callback(review) {
+            return Err(ObjectClientError::ClientError(ReplayClientError::Recorded(
+                "upload review failed, aborting".to_owned(),
+            )));
+        }
+        self.replay_complete()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    use std::sync::Arc;
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::mock_client::{MockClient, MockClientConfig, MockObject};
+    use crate::object_client::ETag;
+    use crate::recording_client::RecordingClient;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn record_session(record_bodies: bool) -> Vec<u8> {
+        let bucket = "test_bucket";
+        let mock_client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 4,
+            unordered_list_seed: None,
+            ..Default::default()
+        });
+        mock_client.add_object("dir/hello", MockObject::from_bytes(b"hello world", ETag::for_tests()));
+
+        let buffer = SharedBuffer::default();
+        let client = RecordingClient::new(mock_client, buffer.clone(), record_bodies);
+
+        client
+            .list_objects(bucket, &ListObjectsParams::new().delimiter("/"))
+            .await
+            .unwrap();
+        client
+            .list_objects(bucket, &ListObjectsParams::new().prefix("dir/").delimiter("/"))
+            .await
+            .unwrap();
+        let get = client
+            .get_object(bucket, "dir/hello", &GetObjectParams::new())
+            .await
+            .unwrap();
+        get.collect::<Vec<_>>().await;
+        client.delete_object(bucket, "dir/hello").await.unwrap();
+        let _ = client.head_object(bucket, "dir/hello", &HeadObjectParams::new()).await;
+
+        let mut put = client.put_object(bucket, "new", &PutObjectParams::new()).await.unwrap();
+        put.write(b"abc").await.unwrap();
+        put.complete().await.unwrap();
+        client
+            .delete_objects(
+                bucket,
+                &[ObjectIdentifier::new("new"), ObjectIdentifier::new("missing")],
+            )
+            .await
+            .unwrap();
+
+        let recording = buffer.0.lock().unwrap().clone();
+        recording
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let recording = record_session(true).await;
+        let client = ReplayClient::new(4, Cursor::new(recording)).unwrap();
+        let bucket = "test_bucket";
+
+        let list = client
+            .list_objects(bucket, &ListObjectsParams::new().delimiter("/"))
+            .await
+            .unwrap();
+        assert_eq!(list.common_prefixes, ["dir/"]);
+        let list = client
+            .list_objects(bucket, &ListObjectsParams::new().prefix("dir/").delimiter("/"))
+            .await
+            .unwrap();
+        assert_eq!(list.objects[0].key, "dir/hello");
+        assert_eq!(list.objects[0].size, 11);
+
+        let mut get = client
+            .get_object(bucket, "dir/hello", &GetObjectParams::new())
+            .await
+            .unwrap();
+        assert_eq!(get.get_object_response().unwrap().content_length, Some(11));
+        let mut body = Vec::new();
+        while let Some(part) = get.next().await {
+            body.extend_from_slice(&part.unwrap().1);
+        }
+        assert_eq!(body, b"hello world");
+
+        // The HeadObject request was made after the object was deleted
+        client.delete_object(bucket, "dir/hello").await.unwrap();
+        let head = client.head_object(bucket, "dir/hello", &HeadObjectParams::new()).await;
+        assert!(matches!(
+            head,
+            Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
+        ));
+
+        let mut put = client.put_object(bucket, "new", &PutObjectParams::new()).await.unwrap();
+        put.write(b"xyz").await.unwrap();
+        let result = put.complete().await.unwrap();
+        assert!(result.version_id.is_some());
+
+        let delete = client
+            .delete_objects(
+                bucket,
+                &[ObjectIdentifier::new("new"), ObjectIdentifier::new("missing")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(delete.deleted, ["new", "missing"]);
+        assert!(delete.errors.is_empty());
+
+        // A DeleteObjects request for different keys wasn't recorded
+        let delete = client.delete_objects(bucket, &[ObjectIdentifier::new("new")]).await;
+        assert!(matches!(
+            delete,
+            Err(ObjectClientError::ClientError(ReplayClientError::NoRecording { .. }))
+        ));
+
+        // Requests that weren't recorded fail
+        let head = client.head_object(bucket, "other", &HeadObjectParams::new()).await;
+        assert!(matches!(
+            head,
+            Err(ObjectClientError::ClientError(ReplayClientError::NoRecording { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replay_without_bodies() {
+        let recording = record_session(false).await;
+        let client = ReplayClient::new(4, Cursor::new(recording)).unwrap();
+
+        let get = client
+            .get_object("test_bucket", "dir/hello", &GetObjectParams::new())
+            .await
+            .unwrap();
+        let parts: Vec<_> = get.map(|part| part.unwrap()).collect().await;
+        let offsets: Vec<_> = parts.iter().map(|(offset, body)| (*offset, body.len())).collect();
+        assert_eq!(offsets, [(0, 4), (4, 4), (8, 3)]);
+        assert!(parts.iter().all(|(_, body)| body.iter().all(|b| *b == 0)));
+
+        // Requests can't be replayed more times than they were recorded
+        let get = client
+            .get_object("test_bucket", "dir/hello", &GetObjectParams::new())
+            .await;
+        assert!(matches!(
+            get,
+            Err(ObjectClientError::ClientError(ReplayClientError::RecordingExhausted {
+                recorded: 1,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_recording() {
+        let recording = "{\"id\":1,\"operation\":\"head_object\"}\nnot json\n";
+        let result = ReplayClient::new(4, Cursor::new(recording));
+        assert!(matches!(result, Err(ReplayLoadError::InvalidRecord { line: 2, .. })));
+    }
+}
@@ -10,32 +10,39 @@ use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use mountpoint_s3_crt::auth::credentials::{
-    CredentialsProvider, CredentialsProviderChainDefaultOptions, CredentialsProviderProfileOptions,
+    CredentialsProvider, CredentialsProviderCachedOptions, CredentialsProviderChainDefaultOptions,
+    CredentialsProviderEcsOptions, CredentialsProviderProcessOptions, CredentialsProviderProfileOptions,
+    CredentialsProviderStsOptions, CredentialsProviderStsWebIdentityOptions,
 };
 use mountpoint_s3_crt::auth::signing_config::SigningConfig;
 use mountpoint_s3_crt::common::allocator::Allocator;
 use mountpoint_s3_crt::common::string::AwsString;
 use mountpoint_s3_crt::common::uri::Uri;
+use mountpoint_s3_crt::http::proxy::ProxyOptions;
 use mountpoint_s3_crt::http::request_response::{Header, Headers, Message};
-use mountpoint_s3_crt::io::async_stream::AsyncInputStream;
+use mountpoint_s3_crt::io::async_stream::{AsyncInputStream, AsyncStreamWriter};
 use mountpoint_s3_crt::io::channel_bootstrap::{ClientBootstrap, ClientBootstrapOptions};
 use mountpoint_s3_crt::io::event_loop::EventLoopGroup;
 use mountpoint_s3_crt::io::host_resolver::{AddressKinds, HostResolver, HostResolverDefaultOptions};
 use mountpoint_s3_crt::io::retry_strategy::{ExponentialBackoffJitterMode, RetryStrategy, StandardRetryOptions};
+use mountpoint_s3_crt::io::tls::{TlsConnectionOptions, TlsContext};
 use mountpoint_s3_crt::s3::client::{
     init_signing_config, ChecksumConfig, Client, ClientConfig, MetaRequest, MetaRequestOptions, MetaRequestResult,
-    MetaRequestType, RequestType,
+    MetaRequestType, RequestType, TlsMode,
 };
 
+use async_io::Timer;
 use async_trait::async_trait;
 use futures::channel::oneshot;
+use futures::{select_biased, FutureExt as _};
 use percent_encoding::{percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use pin_project::{pin_project, pinned_drop};
 use thiserror::Error;
-use tracing::{debug, error, trace, Span};
+use tracing::{debug, error, trace, warn, Span};
 
 use self::get_object::S3GetObjectRequest;
 use self::put_object::S3PutObjectRequest;
+use crate::adaptive_rate_limiter::AdaptiveRateLimiter;
 use crate::endpoint_config::EndpointConfig;
 use crate::endpoint_config::EndpointError;
 use crate::object_client::*;
@@ -54,12 +61,21 @@ macro_rules! request_span {
     ($self:expr, $method:expr) => { request_span!($self, $method,) };
 }
 
+pub(crate) mod abort_multipart_upload;
+pub(crate) mod complete_multipart_upload;
+pub(crate) mod copy_object;
+pub(crate) mod create_multipart_upload;
 pub(crate) mod delete_object;
+pub(crate) mod delete_objects;
 pub(crate) mod get_object;
 pub(crate) mod get_object_attributes;
 pub(crate) mod head_object;
+pub(crate) mod list_multipart_uploads;
+pub(crate) mod list_object_versions;
 pub(crate) mod list_objects;
 pub(crate) mod put_object;
+pub(crate) mod restore_object;
+pub(crate) mod upload_part;
 
 pub(crate) mod head_bucket;
 pub use head_bucket::HeadBucketError;
@@ -88,6 +104,14 @@ pub struct S3ClientConfig {
     user_agent: Option<UserAgent>,
     request_payer: Option<String>,
     bucket_owner: Option<String>,
+    retry_config: RetryConfig,
+    proxy_options: Option<ProxyOptions>,
+    tls_config: TlsConfig,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    first_byte_timeout: Option<Duration>,
+    read_backpressure: bool,
+    initial_read_window: usize,
 }
 
 impl Default for S3ClientConfig {
@@ -100,6 +124,14 @@ impl Default for S3ClientConfig {
             user_agent: None,
             request_payer: None,
             bucket_owner: None,
+            retry_config: Default::default(),
+            proxy_options: None,
+            tls_config: Default::default(),
+            connect_timeout: None,
+            request_timeout: None,
+            first_byte_timeout: None,
+            read_backpressure: false,
+            initial_read_window: 8 * 1024 * 1024,
         }
     }
 }
@@ -157,6 +189,149 @@ impl S3ClientConfig {
         self.bucket_owner = Some(bucket_owner.to_owned());
         self
     }
+
+    /// Set the configuration for retrying failed requests
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Set an HTTP proxy to connect to S3 through
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn proxy_options(mut self, proxy_options: ProxyOptions) -> Self {
+        self.proxy_options = Some(proxy_options);
+        self
+    }
+
+    /// Set the TLS configuration for connections to S3
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn tls_config(mut self, tls_config: TlsConfig) -> Self {
+        self.tls_config = tls_config;
+        self
+    }
+
+    /// Set the timeout for establishing new connections to S3
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Set the timeout for requests that S3 serves with a single HTTP request, such as HeadObject,
+    /// ListObjectsV2, or a GetObject for less than one part. Requests that take longer are
+    /// canceled and started again, up to the [RetryConfig::max_retries] limit, after which they
+    /// fail with [S3RequestError::RequestTimedOut]. Only idempotent requests are retried, and
+    /// GetObject requests only if they haven't returned any of the body yet.
+    ///
+    /// Larger GetObject requests are split into many HTTP requests, so the timeout only applies
+    /// until the first part of the body is received. Other large transfers aren't covered, and are
+    /// instead protected by the [first_byte_timeout](Self::first_byte_timeout).
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Set how long an HTTP request to S3 can go without receiving any bytes, including while
+    /// waiting for the first byte of the response. The connection of a stalled request is closed
+    /// and the request is retried on a new connection. The timeout is rounded up to whole seconds.
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn first_byte_timeout(mut self, first_byte_timeout: Duration) -> Self {
+        self.first_byte_timeout = Some(first_byte_timeout);
+        self
+    }
+
+    /// Enable flow control for GetObject requests. Each request only downloads the object up to
+    /// the end of its read window, which starts [initial_read_window](Self::initial_read_window)
+    /// bytes long, and callers must extend it with
+    /// [GetObjectRequest::increment_read_window](crate::GetObjectRequest::increment_read_window)
+    /// to keep reading. Without backpressure, requests download as fast as possible and buffer
+    /// any data the caller hasn't consumed yet in memory.
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn read_backpressure(mut self, read_backpressure: bool) -> Self {
+        self.read_backpressure = read_backpressure;
+        self
+    }
+
+    /// Set the initial size in bytes of the read window of GetObject requests when
+    /// [read_backpressure](Self::read_backpressure) is enabled. Defaults to 8MiB.
+    #[must_use = "S3ClientConfig follows a builder pattern"]
+    pub fn initial_read_window(mut self, initial_read_window: usize) -> Self {
+        self.initial_read_window = initial_read_window;
+        self
+    }
+}
+
+/// Configuration for how the CRT-based S3 client retries failed requests
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    max_retries: usize,
+    backoff_scale: Duration,
+    jitter_mode: ExponentialBackoffJitterMode,
+    adaptive_throttling: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        // Match the SDK "legacy" retry strategies
+        Self {
+            max_retries: 3,
+            backoff_scale: Duration::from_millis(500),
+            jitter_mode: ExponentialBackoffJitterMode::Full,
+            adaptive_throttling: false,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of times to retry a failed request
+    #[must_use = "RetryConfig follows a builder pattern"]
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the scale factor for the exponential backoff between retries. The `n`th retry waits
+    /// for up to `backoff_scale * 2^n`, depending on the jitter mode.
+    #[must_use = "RetryConfig follows a builder pattern"]
+    pub fn backoff_scale(mut self, backoff_scale: Duration) -> Self {
+        self.backoff_scale = backoff_scale;
+        self
+    }
+
+    /// Set how the backoff between retries is randomized
+    #[must_use = "RetryConfig follows a builder pattern"]
+    pub fn jitter_mode(mut self, jitter_mode: ExponentialBackoffJitterMode) -> Self {
+        self.jitter_mode = jitter_mode;
+        self
+    }
+
+    /// Set whether to slow down the rate of new requests when S3 responds with 503 Slow Down
+    /// errors. Disabled by default. When enabled, the client keeps sending at full rate until the
+    /// first such error, and then limits the rate of HTTP requests by delaying new meta requests.
+    #[must_use = "RetryConfig follows a builder pattern"]
+    pub fn adaptive_throttling(mut self, adaptive_throttling: bool) -> Self {
+        self.adaptive_throttling = adaptive_throttling;
+        self
+    }
+}
+
+/// TLS configuration for the CRT-based S3 client
+#[derive(Debug, Clone, Default)]
+pub enum TlsConfig {
+    /// Verify S3 with the system's default trust store
+    #[default]
+    Default,
+    /// Use a custom TLS context, for example to trust a private certificate authority or to
+    /// authenticate with a client certificate
+    Context(TlsContext),
+    /// Do not use TLS. Only valid with an `http://` endpoint.
+    Disabled,
 }
 
 /// Authentication configuration for the CRT-based S3 client
@@ -171,6 +346,44 @@ pub enum S3ClientAuthConfig {
     Profile(String),
     /// Use a custom credentials provider
     Provider(CredentialsProvider),
+    /// Assume the given role using credentials from another auth configuration, such as
+    /// [Default](Self::Default). The role's credentials are cached and refreshed before they expire.
+    AssumeRole {
+        /// ARN of the role to assume
+        role_arn: String,
+        /// Name of the role session
+        session_name: String,
+        /// Where to get the credentials used to assume the role
+        source: Box<S3ClientAuthConfig>,
+    },
+    /// Exchange a web identity token for credentials with STS, for example with IAM roles for
+    /// service accounts on Amazon EKS. Fields left unset are read from the `AWS_ROLE_ARN`,
+    /// `AWS_WEB_IDENTITY_TOKEN_FILE`, and `AWS_ROLE_SESSION_NAME` environment variables.
+    WebIdentity {
+        /// ARN of the role to assume
+        role_arn: Option<String>,
+        /// Path to the file containing the web identity token
+        token_file: Option<String>,
+        /// Name of the role session
+        session_name: Option<String>,
+    },
+    /// Fetch credentials from a container credentials endpoint, such as the one provided by
+    /// Amazon ECS or Amazon EKS Pod Identity
+    Ecs {
+        /// Host name of the credentials endpoint
+        host: String,
+        /// Path and query of the credentials endpoint
+        path_and_query: String,
+        /// Port of the credentials endpoint. The default HTTP or HTTPS port if not set.
+        port: Option<u16>,
+        /// Authorization token to send to the endpoint
+        auth_token: Option<String>,
+        /// Connect to the endpoint with HTTPS
+        use_tls: bool,
+    },
+    /// Run the `credential_process` command configured in the given profile, or in the default
+    /// profile if not set
+    Process(Option<String>),
 }
 
 /// An S3 client that uses the [AWS Common Runtime (CRT)][crt] to make requests.
@@ -221,6 +434,106 @@ struct S3CrtClientInner {
     bucket_owner: Option<String>,
     credentials_provider: Option<CredentialsProvider>,
     host_resolver: HostResolver,
+    adaptive_rate_limiter: Option<Arc<AdaptiveRateLimiter>>,
+    request_timeout: Option<Duration>,
+    /// How many times to start a request again after it hits the request timeout
+    max_timeout_retries: usize,
+    read_backpressure: bool,
+    initial_read_window: usize,
+}
+
+/// Create the credentials provider for an [S3ClientAuthConfig]. Providers that fetch temporary
+/// credentials are wrapped in a cached provider so that we don't fetch new ones for every request.
+fn new_credentials_provider(
+    allocator: &Allocator,
+    client_bootstrap: &mut ClientBootstrap,
+    region: &str,
+    auth_config: S3ClientAuthConfig,
+) -> Result<CredentialsProvider, mountpoint_s3_crt::common::error::Error> {
+    let cached = |source: CredentialsProvider| {
+        let cached_options = CredentialsProviderCachedOptions {
+            source: &source,
+            refresh_time: None,
+        };
+        CredentialsProvider::new_cached(allocator, cached_options)
+    };
+
+    match auth_config {
+        S3ClientAuthConfig::Default => {
+            let credentials_chain_default_options = CredentialsProviderChainDefaultOptions {
+                bootstrap: client_bootstrap,
+            };
+            CredentialsProvider::new_chain_default(allocator, credentials_chain_default_options)
+        }
+        S3ClientAuthConfig::NoSigning => CredentialsProvider::new_anonymous(allocator),
+        S3ClientAuthConfig::Profile(profile_name) => {
+            let credentials_profile_options = CredentialsProviderProfileOptions {
+                bootstrap: client_bootstrap,
+                profile_name_override: &profile_name,
+            };
+            CredentialsProvider::new_profile(allocator, credentials_profile_options)
+        }
+        S3ClientAuthConfig::Provider(provider) => Ok(provider),
+        S3ClientAuthConfig::AssumeRole {
+            role_arn,
+            session_name,
+            source,
+        } => {
+            let source = new_credentials_provider(allocator, client_bootstrap, region, *source)?;
+            let tls_context = TlsContext::new_client_default(allocator)?;
+            let sts_options = CredentialsProviderStsOptions {
+                bootstrap: client_bootstrap,
+                tls_context: &tls_context,
+                source: &source,
+                role_arn: &role_arn,
+                session_name: &session_name,
+                duration: None,
+            };
+            cached(CredentialsProvider::new_sts(allocator, sts_options)?)
+        }
+        S3ClientAuthConfig::WebIdentity {
+            role_arn,
+            token_file,
+            session_name,
+        } => {
+            let web_identity_options = CredentialsProviderStsWebIdentityOptions {
+                bootstrap: client_bootstrap,
+                tls_context: None,
+                region: Some(region),
+                role_arn: role_arn.as_deref(),
+                role_session_name: session_name.as_deref(),
+                token_file_path: token_file.as_deref(),
+            };
+            cached(CredentialsProvider::new_sts_web_identity(
+                allocator,
+                web_identity_options,
+            )?)
+        }
+        S3ClientAuthConfig::Ecs {
+            host,
+            path_and_query,
+            port,
+            auth_token,
+            use_tls,
+        } => {
+            let tls_context = use_tls.then(|| TlsContext::new_client_default(allocator)).transpose()?;
+            let ecs_options = CredentialsProviderEcsOptions {
+                bootstrap: client_bootstrap,
+                tls_context: tls_context.as_ref(),
+                host: &host,
+                path_and_query: &path_and_query,
+                auth_token: auth_token.as_deref(),
+                port,
+            };
+            cached(CredentialsProvider::new_ecs(allocator, ecs_options)?)
+        }
+        S3ClientAuthConfig::Process(profile_name) => {
+            let process_options = CredentialsProviderProcessOptions {
+                profile_name_override: profile_name.as_deref(),
+            };
+            cached(CredentialsProvider::new_process(allocator, process_options)?)
+        }
+    }
 }
 
 impl S3CrtClientInner {
@@ -245,35 +558,21 @@ impl S3CrtClientInner {
 
         let mut client_config = ClientConfig::new();
 
+        let retry_config = config.retry_config;
         let mut retry_strategy_options = StandardRetryOptions::default(&mut event_loop_group);
-        // Match the SDK "legacy" retry strategies
-        retry_strategy_options.backoff_retry_options.max_retries = 3;
-        retry_strategy_options.backoff_retry_options.backoff_scale_factor = Duration::from_millis(500);
-        retry_strategy_options.backoff_retry_options.jitter_mode = ExponentialBackoffJitterMode::Full;
+        retry_strategy_options.backoff_retry_options.max_retries = retry_config.max_retries;
+        retry_strategy_options.backoff_retry_options.backoff_scale_factor = retry_config.backoff_scale;
+        retry_strategy_options.backoff_retry_options.jitter_mode = retry_config.jitter_mode;
         let retry_strategy = RetryStrategy::standard(&allocator, &retry_strategy_options).unwrap();
 
         trace!("constructing client with auth config {:?}", config.auth_config);
-        let credentials_provider = match config.auth_config {
-            S3ClientAuthConfig::Default => {
-                let credentials_chain_default_options = CredentialsProviderChainDefaultOptions {
-                    bootstrap: &mut client_bootstrap,
-                };
-                CredentialsProvider::new_chain_default(&allocator, credentials_chain_default_options)
-                    .map_err(NewClientError::ProviderFailure)?
-            }
-            S3ClientAuthConfig::NoSigning => {
-                CredentialsProvider::new_anonymous(&allocator).map_err(NewClientError::ProviderFailure)?
-            }
-            S3ClientAuthConfig::Profile(profile_name) => {
-                let credentials_profile_options = CredentialsProviderProfileOptions {
-                    bootstrap: &mut client_bootstrap,
-                    profile_name_override: &profile_name,
-                };
-                CredentialsProvider::new_profile(&allocator, credentials_profile_options)
-                    .map_err(NewClientError::ProviderFailure)?
-            }
-            S3ClientAuthConfig::Provider(provider) => provider,
-        };
+        let credentials_provider = new_credentials_provider(
+            &allocator,
+            &mut client_bootstrap,
+            config.endpoint_config.get_region(),
+            config.auth_config,
+        )
+        .map_err(NewClientError::ProviderFailure)?;
 
         let endpoint_config = config.endpoint_config;
         client_config.region(endpoint_config.get_region());
@@ -293,6 +592,43 @@ impl S3CrtClientInner {
 
         client_config.throughput_target_gbps(config.throughput_target_gbps);
 
+        if let Some(proxy_options) = config.proxy_options {
+            trace!(?proxy_options, "connecting through proxy");
+            client_config.proxy_options(proxy_options);
+        }
+
+        if let Some(connect_timeout) = config.connect_timeout {
+            client_config.connect_timeout(connect_timeout);
+        }
+        if let Some(first_byte_timeout) = config.first_byte_timeout {
+            // A stalled connection receives nothing at all, so any progress counts as throughput
+            client_config.throughput_monitoring(1, first_byte_timeout);
+        }
+
+        if config.read_backpressure {
+            client_config
+                .read_backpressure(true)
+                .initial_read_window(config.initial_read_window);
+        }
+
+        match config.tls_config {
+            TlsConfig::Default => {}
+            TlsConfig::Context(tls_context) => {
+                client_config.tls_connection_options(TlsConnectionOptions::new(&tls_context));
+            }
+            TlsConfig::Disabled => {
+                let is_http = endpoint_config
+                    .get_endpoint()
+                    .is_some_and(|endpoint| endpoint.scheme().eq_ignore_ascii_case("http"));
+                if !is_http {
+                    return Err(NewClientError::InvalidConfiguration(
+                        "TLS can only be disabled with an http:// endpoint".into(),
+                    ));
+                }
+                client_config.tls_mode(TlsMode::Disabled);
+            }
+        }
+
         if !(5 * 1024 * 1024..=5 * 1024 * 1024 * 1024).contains(&config.part_size) {
             return Err(NewClientError::InvalidConfiguration(
                 "part size must be at between 5MiB and 5GiB".into(),
@@ -317,9 +653,23 @@ impl S3CrtClientInner {
             bucket_owner: config.bucket_owner,
             credentials_provider: Some(credentials_provider),
             host_resolver,
+            adaptive_rate_limiter: retry_config
+                .adaptive_throttling
+                .then(|| Arc::new(AdaptiveRateLimiter::new())),
+            request_timeout: config.request_timeout,
+            max_timeout_retries: retry_config.max_retries,
+            read_backpressure: config.read_backpressure,
+            initial_read_window: config.initial_read_window,
         })
     }
 
+    /// Wait until the adaptive rate limiter, if enabled, allows a new request
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.adaptive_rate_limiter {
+            limiter.acquire().await;
+        }
+    }
+
     /// Create a new HTTP request template for the given HTTP method and S3 bucket name.
     /// Pre-populates common headers used across all requests. Sets the "accept" header assuming the
     /// response should be XML; this header should be overwritten for requests like GET that return
@@ -441,8 +791,14 @@ impl S3CrtClientInner {
         let span_finish = request_span;
 
         let endpoint = options.get_endpoint().expect("S3Message always has an endpoint");
+        // Auto-ranged GetObject requests disarm the timeout once they receive body data
+        let timeout = match options.get_request_type() {
+            MetaRequestType::Default | MetaRequestType::GetObject => self.request_timeout,
+            _ => None,
+        };
         let hostname = endpoint.host_name().to_str().unwrap().to_owned();
         let host_resolver = self.host_resolver.clone();
+        let adaptive_rate_limiter = self.adaptive_rate_limiter.clone();
 
         let start_time = Instant::now();
         let first_body_part = Arc::new(AtomicBool::new(true));
@@ -485,6 +841,31 @@ impl S3CrtClientInner {
                 } else if request_canceled {
                     metrics::counter!("s3.requests.canceled", "op" => op, "type" => request_type).increment(1);
                 }
+
+                if let (Some(limiter), Some(status)) = (&adaptive_rate_limiter, http_status) {
+                    limiter.update(status == 503);
+                }
+
+                // Requests that S3 responded to are billed whether they succeeded or not. GET and
+                // HEAD responses report the storage class of non-Standard objects, which affects
+                // what reading them costs, so record it alongside the request and bytes read.
+                if http_status.is_some() {
+                    let response_headers = metrics.response_headers();
+                    let storage_class = response_headers
+                        .as_ref()
+                        .and_then(|headers| put_object::try_get_header_value(headers, "x-amz-storage-class"))
+                        .unwrap_or_default();
+                    metrics::counter!("s3.requests.billed", "op" => op, "type" => request_type, "storage_class" => storage_class.clone()).increment(1);
+                    if !request_failure && matches!(metrics.request_type(), RequestType::GetObject) {
+                        let length = response_headers
+                            .as_ref()
+                            .and_then(|headers| put_object::try_get_header_value(headers, "Content-Length"))
+                            .and_then(|length| length.parse::<u64>().ok());
+                        if let Some(length) = length {
+                            metrics::counter!("s3.requests.downloaded_bytes", "storage_class" => storage_class).increment(length);
+                        }
+                    }
+                }
             })
             .on_headers(move |headers, response_status| {
                 (on_headers)(headers, response_status);
@@ -593,6 +974,7 @@ impl S3CrtClientInner {
         Ok(S3HttpRequest {
             receiver: rx,
             meta_request,
+            timeout: timeout.map(|timeout| (timeout, Timer::after(timeout))),
         })
     }
 
@@ -756,6 +1138,40 @@ impl S3Message {
     fn set_checksum_config(&mut self, checksum_config: Option<ChecksumConfig>) {
         self.checksum_config = checksum_config;
     }
+
+    /// Add the headers that set the user-defined metadata, content type, content encoding, and
+    /// cache control of a new object.
+    fn set_object_headers(&mut self, params: &PutObjectParams) -> Result<(), mountpoint_s3_crt::common::error::Error> {
+        for (name, value) in &params.object_metadata {
+            self.set_header(&Header::new(format!("x-amz-meta-{name}"), value))?;
+        }
+        if let Some(content_type) = params.content_type.as_ref() {
+            self.set_header(&Header::new("Content-Type", content_type))?;
+        }
+        if let Some(content_encoding) = params.content_encoding.as_ref() {
+            self.set_header(&Header::new("Content-Encoding", content_encoding))?;
+        }
+        if let Some(cache_control) = params.cache_control.as_ref() {
+            self.set_header(&Header::new("Cache-Control", cache_control))?;
+        }
+        Ok(())
+    }
+
+    /// Add the headers that provide a customer-provided key for server-side encryption (SSE-C).
+    fn set_sse_customer_key(&mut self, key: &SseCustomerKey) -> Result<(), mountpoint_s3_crt::common::error::Error> {
+        self.set_header(&Header::new(
+            "x-amz-server-side-encryption-customer-algorithm",
+            key.algorithm(),
+        ))?;
+        self.set_header(&Header::new(
+            "x-amz-server-side-encryption-customer-key",
+            key.key_base64(),
+        ))?;
+        self.set_header(&Header::new(
+            "x-amz-server-side-encryption-customer-key-MD5",
+            key.key_md5_base64(),
+        ))
+    }
 }
 
 #[derive(Debug)]
@@ -764,6 +1180,8 @@ struct S3HttpRequest<T, E> {
     #[pin]
     receiver: oneshot::Receiver<ObjectClientResult<T, E, S3RequestError>>,
     meta_request: MetaRequest,
+    /// If set, cancel the request if it hasn't finished by the time this timer fires
+    timeout: Option<(Duration, Timer)>,
 }
 
 impl<T: Send, E: Send> Future for S3HttpRequest<T, E> {
@@ -771,13 +1189,35 @@ impl<T: Send, E: Send> Future for S3HttpRequest<T, E> {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
-        this.receiver.poll(cx).map(|result| {
-            result.unwrap_or_else(|err| {
+        if let Poll::Ready(result) = this.receiver.poll(cx) {
+            return Poll::Ready(result.unwrap_or_else(|err| {
                 Err(ObjectClientError::ClientError(S3RequestError::InternalError(Box::new(
                     err,
                 ))))
-            })
-        })
+            }));
+        }
+
+        if let Some((timeout, timer)) = this.timeout.as_mut() {
+            if Pin::new(timer).poll(cx).is_ready() {
+                let timeout = *timeout;
+                warn!(?timeout, "request timed out, canceling");
+                metrics::counter!("s3.meta_requests.timeouts").increment(1);
+                this.meta_request.cancel();
+                *this.timeout = None;
+                return Poll::Ready(Err(ObjectClientError::ClientError(S3RequestError::RequestTimedOut(
+                    timeout,
+                ))));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T, E> S3HttpRequest<T, E> {
+    /// Stop applying the request timeout to this request
+    fn disarm_timeout(self: Pin<&mut Self>) {
+        *self.project().timeout = None;
     }
 }
 
@@ -788,6 +1228,39 @@ impl<T, E> PinnedDrop for S3HttpRequest<T, E> {
     }
 }
 
+impl<T: Send, E: Send> S3HttpRequest<T, E> {
+    /// Write the entire body of this request to the given writer, and then wait for the request to
+    /// finish. The writer must be the other end of the body stream set on the request's message.
+    async fn write_body_and_wait(
+        self,
+        mut writer: AsyncStreamWriter,
+        body: &[u8],
+    ) -> ObjectClientResult<T, E, S3RequestError> {
+        let mut request = self.fuse();
+        let write_body = async move {
+            writer.write(body).await?;
+            writer.complete().await
+        };
+
+        // Check if the request has already finished (which can only happen because of an error in
+        // the request) while we're still writing. Ordering here is significant: if both futures are
+        // ready, we want to proceed with the write path, as the request may have succeeded
+        // _because_ the body was completed.
+        select_biased! {
+            result = write_body.fuse() => {
+                result.map_err(|e| S3RequestError::InternalError(Box::new(e)))?;
+                request.await
+            }
+
+            // Request can't have succeeded if the body is still being written
+            result = request => match result {
+                Ok(_) => Err(S3RequestError::InternalError("request finished before its body was written".into()).into()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+}
+
 /// Failures to construct a new S3 client
 #[derive(Error, Debug)]
 #[non_exhaustive]
@@ -840,6 +1313,10 @@ pub enum S3RequestError {
     /// The request was canceled
     #[error("Request canceled")]
     RequestCanceled,
+
+    /// The request didn't finish within the configured timeout, so was canceled
+    #[error("Request timed out after {0:?}")]
+    RequestTimedOut(Duration),
 }
 
 impl S3RequestError {
@@ -977,6 +1454,32 @@ fn emit_throughput_metric(bytes: u64, duration: Duration, op: &'static str) {
     metrics::histogram!("s3.meta_requests.throughput_mibs", "op" => op, "size" => bucket).record(throughput_mbps);
 }
 
+impl S3CrtClient {
+    /// Run an idempotent operation, and start it again if it hits the request timeout, up to the
+    /// configured number of retries. Every attempt waits for the adaptive rate limiter.
+    async fn retry_timeouts<T, E, Fut>(
+        &self,
+        mut operation: impl FnMut() -> Fut + Send,
+    ) -> ObjectClientResult<T, E, S3RequestError>
+    where
+        Fut: Future<Output = ObjectClientResult<T, E, S3RequestError>> + Send,
+    {
+        let mut attempt = 0;
+        loop {
+            self.inner.throttle().await;
+            match operation().await {
+                Err(ObjectClientError::ClientError(S3RequestError::RequestTimedOut(timeout)))
+                    if attempt < self.inner.max_timeout_retries =>
+                {
+                    attempt += 1;
+                    warn!(?timeout, attempt, "request timed out, retrying");
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
 #[cfg_attr(not(docs_rs), async_trait)]
 impl ObjectClient for S3CrtClient {
     type GetObjectResult = S3GetObjectRequest;
@@ -990,44 +1493,90 @@ impl ObjectClient for S3CrtClient {
         Some(self.inner.part_size)
     }
 
+    async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        destination_bucket: &str,
+        destination_key: &str,
+        params: &CopyObjectParams,
+    ) -> ObjectClientResult<CopyObjectResult, CopyObjectError, Self::ClientError> {
+        self.inner.throttle().await;
+        self.copy_object(source_bucket, source_key, destination_bucket, destination_key, params)
+            .await
+    }
+
     async fn delete_object(
         &self,
         bucket: &str,
         key: &str,
     ) -> ObjectClientResult<DeleteObjectResult, DeleteObjectError, Self::ClientError> {
-        self.delete_object(bucket, key).await
+        self.retry_timeouts(move || self.delete_object(bucket, key)).await
+    }
+
+    async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, Self::ClientError> {
+        self.retry_timeouts(move || self.delete_objects(bucket, objects)).await
     }
 
     async fn get_object(
         &self,
         bucket: &str,
         key: &str,
-        range: Option<Range<u64>>,
-        if_match: Option<ETag>,
-        // TODO: If more arguments are added to get object, make a request struct having those arguments
-        // along with bucket and key.
+        params: &GetObjectParams,
     ) -> ObjectClientResult<Self::GetObjectResult, GetObjectError, Self::ClientError> {
-        self.get_object(bucket, key, range, if_match)
+        self.inner.throttle().await;
+        self.get_object(bucket, key, params)
     }
 
     async fn list_objects(
         &self,
         bucket: &str,
-        continuation_token: Option<&str>,
+        params: &ListObjectsParams,
+    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
+        self.retry_timeouts(move || self.list_objects(bucket, params)).await
+    }
+
+    async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
         delimiter: &str,
         max_keys: usize,
         prefix: &str,
-    ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, Self::ClientError> {
-        self.list_objects(bucket, continuation_token, delimiter, max_keys, prefix)
-            .await
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, Self::ClientError> {
+        self.retry_timeouts(move || {
+            self.list_object_versions(bucket, key_marker, version_id_marker, delimiter, max_keys, prefix)
+        })
+        .await
+    }
+
+    async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, Self::ClientError> {
+        self.retry_timeouts(move || {
+            self.list_multipart_uploads(bucket, key_marker, upload_id_marker, delimiter, max_uploads, prefix)
+        })
+        .await
     }
 
     async fn head_object(
         &self,
         bucket: &str,
         key: &str,
+        params: &HeadObjectParams,
     ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, Self::ClientError> {
-        self.head_object(bucket, key).await
+        self.retry_timeouts(move || self.head_object(bucket, key, params)).await
     }
 
     async fn put_object(
@@ -1036,9 +1585,59 @@ impl ObjectClient for S3CrtClient {
         key: &str,
         params: &PutObjectParams,
     ) -> ObjectClientResult<Self::PutObjectRequest, PutObjectError, Self::ClientError> {
+        self.inner.throttle().await;
         self.put_object(bucket, key, params).await
     }
 
+    async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, Self::ClientError> {
+        // Parts uploaded with `upload_part` don't carry checksums or SSE-C keys
+        let params = PutObjectParams {
+            checksum_algorithm: None,
+            sse_customer_key: None,
+            ..params.clone()
+        };
+        self.inner.throttle().await;
+        self.create_multipart_upload(bucket, key, &params).await
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, Self::ClientError> {
+        self.retry_timeouts(move || self.upload_part(bucket, key, upload_id, part_number, contents))
+            .await
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, Self::ClientError> {
+        self.inner.throttle().await;
+        self.complete_multipart_upload(bucket, key, upload_id, parts).await
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, Self::ClientError> {
+        self.retry_timeouts(move || self.abort_multipart_upload(bucket, key, upload_id))
+            .await
+    }
+
     async fn get_object_attributes(
         &self,
         bucket: &str,
@@ -1047,8 +1646,21 @@ impl ObjectClient for S3CrtClient {
         part_number_marker: Option<usize>,
         object_attributes: &[ObjectAttribute],
     ) -> ObjectClientResult<GetObjectAttributesResult, GetObjectAttributesError, Self::ClientError> {
-        self.get_object_attributes(bucket, key, max_parts, part_number_marker, object_attributes)
-            .await
+        self.retry_timeouts(move || {
+            self.get_object_attributes(bucket, key, max_parts, part_number_marker, object_attributes)
+        })
+        .await
+    }
+
+    async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, Self::ClientError> {
+        self.inner.throttle().await;
+        self.restore_object(bucket, key, days, tier).await
     }
 }
 
@@ -1071,6 +1683,18 @@ mod tests {
         S3CrtClient::new(config).expect_err("creating a new client should fail");
     }
 
+    #[test]
+    fn client_new_fails_with_tls_disabled_for_https() {
+        let config = S3ClientConfig::new().tls_config(TlsConfig::Disabled);
+        S3CrtClient::new(config).expect_err("disabling TLS without an http:// endpoint should fail");
+
+        let endpoint = Uri::new_from_str(&Allocator::default(), "http://localhost:9000").unwrap();
+        let config = S3ClientConfig::new()
+            .endpoint_config(EndpointConfig::new("us-east-1").endpoint(endpoint))
+            .tls_config(TlsConfig::Disabled);
+        S3CrtClient::new(config).expect("disabling TLS with an http:// endpoint should succeed");
+    }
+
     /// Test if the prefix is added correctly to the User-Agent header
     #[test]
     fn test_user_agent_with_prefix() {
@@ -1165,6 +1789,38 @@ mod tests {
             .starts_with(expected_bucket_owner));
     }
 
+    #[test]
+    fn test_sse_customer_key_headers() {
+        let key = SseCustomerKey::new(&[0x42; 32]).expect("key should be valid");
+        let key_base64 = "QkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkJCQkI=";
+
+        let client = S3CrtClient::new(Default::default()).expect("Create test client");
+        let mut message = client
+            .inner
+            .new_request_template("GET", "doc-example-bucket")
+            .expect("new request template expected");
+        message.set_sse_customer_key(&key).expect("failed to set headers");
+
+        let headers = message.inner.get_headers().expect("Expected a block of HTTP headers");
+        let get_header = |name: &str| headers.get(name).unwrap().value().to_string_lossy().to_string();
+        assert_eq!(get_header("x-amz-server-side-encryption-customer-algorithm"), "AES256");
+        assert_eq!(get_header("x-amz-server-side-encryption-customer-key"), key_base64);
+        assert_eq!(
+            get_header("x-amz-server-side-encryption-customer-key-MD5"),
+            "8NB6psqPvuXCjIqE3J2m5Q=="
+        );
+
+        // The key must never be printed
+        let debug = format!("{:?}", GetObjectParams::new().sse_customer_key(Some(key)));
+        assert!(!debug.contains(key_base64), "{debug}");
+        assert!(!debug.contains("66, 66"), "{debug}");
+
+        assert_eq!(
+            SseCustomerKey::new(&[0x42; 16]),
+            Err(SseCustomerKeyError::InvalidLength(16))
+        );
+    }
+
     fn make_result(
         response_status: i32,
         body: impl Into<OsString>,
@@ -1180,6 +1836,8 @@ mod tests {
             crt_error: 1i32.into(),
             error_response_headers,
             error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
         }
     }
 
@@ -1268,6 +1926,8 @@ mod tests {
             crt_error,
             error_response_headers: None,
             error_response_body: None,
+            did_validate: false,
+            validation_algorithm: None,
         }
     }
 
@@ -0,0 +1,83 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+
+use crate::object_client::{AbortMultipartUploadError, AbortMultipartUploadResult, ObjectClientResult};
+use crate::s3_crt_client::{S3CrtClient, S3RequestError};
+
+impl S3CrtClient {
+    /// Create and begin a new AbortMultipartUpload request.
+    pub(super) async fn abort_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+    ) -> ObjectClientResult<AbortMultipartUploadResult, AbortMultipartUploadError, S3RequestError> {
+        let span = request_span!(self.inner, "abort_multipart_upload", bucket, key, upload_id);
+
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let request = {
+            let mut message = self
+                .inner
+                .new_request_template("DELETE", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_request_path_and_query(format!("/{key}"), [("uploadId", upload_id)])
+                .map_err(S3RequestError::construction_failure)?;
+
+            self.inner.make_simple_http_request(
+                message,
+                MetaRequestType::Default,
+                span,
+                parse_abort_multipart_upload_error,
+            )?
+        };
+
+        let _body = request.await?;
+
+        Ok(AbortMultipartUploadResult {})
+    }
+}
+
+fn parse_abort_multipart_upload_error(result: &MetaRequestResult) -> Option<AbortMultipartUploadError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchUpload" => Some(AbortMultipartUploadError::NoSuchUpload),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_upload() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchUpload</Code><Message>The specified upload does not exist. The upload ID may be invalid, or the upload may have been aborted or completed.</Message><UploadId>VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId><RequestId>CQ7FMXPZ3P5BNF4N</RequestId><HostId>yyzvVi9oF9iZUcVzPNCTmazSJkoYl1plKUhlXm/8y6qJqbnvDqbPzeUoO9G3e8jIDKH7BIj9ccw=</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_abort_multipart_upload_error(&result);
+        assert_eq!(result, Some(AbortMultipartUploadError::NoSuchUpload));
+    }
+}
@@ -0,0 +1,314 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use mountpoint_s3_crt::http::request_response::{Header, Headers};
+use mountpoint_s3_crt::io::async_stream;
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use xmltree::{Element, XMLNode};
+
+use crate::checksums::checksum_algorithm_name;
+use crate::object_client::{
+    ChecksumAlgorithm, CompleteMultipartUploadError, CompleteMultipartUploadResult, CompletedPart, ETag,
+    ObjectClientError, ObjectClientResult, SseCustomerKey,
+};
+use crate::s3_crt_client::list_objects::{get_field, ParseError};
+use crate::s3_crt_client::put_object::{try_get_header_value, VERSION_ID_HEADER_NAME};
+use crate::s3_crt_client::{S3CrtClient, S3CrtClientInner, S3RequestError};
+
+/// Parts of a CompleteMultipartUpload request that only the multipart uploads driven by
+/// [S3CrtClient::put_object] use
+#[derive(Debug, Default)]
+pub(super) struct CompleteOptions<'a> {
+    /// Algorithm and checksums of the parts, in the same order as the parts
+    pub checksums: Option<(ChecksumAlgorithm, &'a [String])>,
+    pub sse_customer_key: Option<&'a SseCustomerKey>,
+    pub if_none_match: Option<&'a str>,
+    pub if_match: Option<&'a ETag>,
+}
+
+/// Build the CompleteMultipartUpload XML document listing the parts to assemble
+fn build_request_body(parts: &[CompletedPart], checksums: Option<(ChecksumAlgorithm, &[String])>) -> Vec<u8> {
+    fn text_element(name: &str, text: String) -> XMLNode {
+        let mut element = Element::new(name);
+        element.children.push(XMLNode::Text(text));
+        XMLNode::Element(element)
+    }
+
+    let mut root = Element::new("CompleteMultipartUpload");
+    for (index, part) in parts.iter().enumerate() {
+        let mut element = Element::new("Part");
+        element
+            .children
+            .push(text_element("ETag", part.etag.as_str().to_owned()));
+        element
+            .children
+            .push(text_element("PartNumber", format!("{}", part.part_number)));
+        if let Some((algorithm, checksums)) = checksums {
+            let name = format!("Checksum{}", checksum_algorithm_name(algorithm));
+            element.children.push(text_element(&name, checksums[index].clone()));
+        }
+        root.children.push(XMLNode::Element(element));
+    }
+
+    let mut body = Vec::new();
+    root.write(&mut body).expect("writing to a Vec can't fail");
+    body
+}
+
+/// The body of a CompleteMultipartUpload response with a 200 OK status. S3 can fail the request after
+/// it has already sent the status, in which case the body is an error document rather than a result.
+#[derive(Debug, PartialEq, Eq)]
+enum CompleteMultipartUploadResponse {
+    Completed { etag: ETag },
+    Failed { code: String },
+}
+
+fn parse_result_from_bytes(bytes: &[u8]) -> Result<CompleteMultipartUploadResponse, ParseError> {
+    let element = Element::parse(bytes)?;
+    if element.name == "Error" {
+        let code = get_field(&element, "Code")?;
+        return Ok(CompleteMultipartUploadResponse::Failed { code });
+    }
+    let etag = get_field(&element, "ETag")?;
+    let etag = ETag::from_str(&etag).expect("ETag parsing is infallible");
+    Ok(CompleteMultipartUploadResponse::Completed { etag })
+}
+
+impl S3CrtClient {
+    /// Create and begin a new CompleteMultipartUpload request.
+    pub(super) async fn complete_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> ObjectClientResult<CompleteMultipartUploadResult, CompleteMultipartUploadError, S3RequestError> {
+        let (result, _headers) = self
+            .complete_multipart_upload_with(bucket, key, upload_id, parts, &Default::default(), error_from_code)
+            .await?;
+        Ok(result)
+    }
+
+    /// Create and begin a new CompleteMultipartUpload request with the given options, mapping the
+    /// error codes of failed requests with `error_from_code`. Also returns the response headers.
+    pub(super) async fn complete_multipart_upload_with<E: std::error::Error + Send + Sync + 'static>(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+        options: &CompleteOptions<'_>,
+        error_from_code: fn(&str) -> Option<E>,
+    ) -> ObjectClientResult<(CompleteMultipartUploadResult, Headers), E, S3RequestError> {
+        let span = request_span!(
+            self.inner,
+            "complete_multipart_upload",
+            bucket,
+            key,
+            upload_id,
+            num_parts = parts.len()
+        );
+
+        let body = build_request_body(parts, options.checksums);
+        let response_headers: Arc<Mutex<Option<Headers>>> = Default::default();
+
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let (request, writer) = {
+            let mut message = self
+                .inner
+                .new_request_template("POST", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_request_path_and_query(format!("/{key}"), [("uploadId", upload_id)])
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Length", format!("{}", body.len())))
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Type", "application/xml"))
+                .map_err(S3RequestError::construction_failure)?;
+            // S3 needs the SSE-C key to check the checksums of the parts against the object
+            if let (Some(sse_customer_key), Some(_)) = (options.sse_customer_key, options.checksums) {
+                message
+                    .set_sse_customer_key(sse_customer_key)
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            if let Some(etag) = options.if_none_match {
+                message
+                    .set_header(&Header::new("If-None-Match", etag))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            if let Some(etag) = options.if_match {
+                message
+                    .set_header(&Header::new("If-Match", etag.as_str()))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+
+            let (body_async_stream, writer) = async_stream::new_stream(&self.inner.allocator);
+            message.set_body_stream(Some(body_async_stream));
+
+            let response_headers_writer = response_headers.clone();
+            let on_headers = move |headers: &Headers, _: i32| {
+                *response_headers_writer.lock().unwrap() = Some(headers.clone());
+            };
+            let meta_request_options = S3CrtClientInner::new_meta_request_options(message, MetaRequestType::Default);
+            let request = self.inner.make_simple_http_request_from_options(
+                meta_request_options,
+                span,
+                move |result| parse_complete_multipart_upload_error(result, error_from_code),
+                on_headers,
+            )?;
+            (request, writer)
+        };
+
+        let response_body = request.write_body_and_wait(writer, &body).await?;
+
+        let etag = match parse_result_from_bytes(&response_body) {
+            Ok(CompleteMultipartUploadResponse::Completed { etag }) => etag,
+            Ok(CompleteMultipartUploadResponse::Failed { code }) => {
+                return Err(match error_from_code(&code) {
+                    Some(error) => ObjectClientError::ServiceError(error),
+                    None => ObjectClientError::ClientError(S3RequestError::InternalError(
+                        format!("CompleteMultipartUpload failed with error code {code}").into(),
+                    )),
+                });
+            }
+            Err(e) => return Err(ObjectClientError::ClientError(S3RequestError::InternalError(e.into()))),
+        };
+
+        let response_headers = response_headers
+            .lock()
+            .expect("must be able to acquire headers lock")
+            .take()
+            .expect("CompleteMultipartUpload response headers must be available at this point");
+
+        let result = CompleteMultipartUploadResult {
+            etag,
+            version_id: try_get_header_value(&response_headers, VERSION_ID_HEADER_NAME),
+        };
+        Ok((result, response_headers))
+    }
+}
+
+fn error_from_code(code: &str) -> Option<CompleteMultipartUploadError> {
+    match code {
+        "NoSuchUpload" => Some(CompleteMultipartUploadError::NoSuchUpload),
+        "InvalidPart" => Some(CompleteMultipartUploadError::InvalidPart),
+        "InvalidPartOrder" => Some(CompleteMultipartUploadError::InvalidPartOrder),
+        "EntityTooSmall" => Some(CompleteMultipartUploadError::EntityTooSmall),
+        _ => None,
+    }
+}
+
+fn parse_complete_multipart_upload_error<E>(
+    result: &MetaRequestResult,
+    error_from_code: fn(&str) -> Option<E>,
+) -> Option<E> {
+    let body = result.error_response_body.as_ref()?;
+    let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+    let error_code = root.get_child("Code")?;
+    let error_str = error_code.get_text()?;
+    error_from_code(error_str.deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_upload() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchUpload</Code><Message>The specified upload does not exist. The upload ID may be invalid, or the upload may have been aborted or completed.</Message><UploadId>VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId><RequestId>CQ7FMXPZ3P5BNF4N</RequestId><HostId>yyzvVi9oF9iZUcVzPNCTmazSJkoYl1plKUhlXm/8y6qJqbnvDqbPzeUoO9G3e8jIDKH7BIj9ccw=</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_complete_multipart_upload_error(&result, error_from_code);
+        assert_eq!(result, Some(CompleteMultipartUploadError::NoSuchUpload));
+    }
+
+    #[test]
+    fn parse_400_invalid_part() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>InvalidPart</Code><Message>One or more of the specified parts could not be found.  The part may not have been uploaded, or the specified entity tag may not match the part's entity tag.</Message><UploadId>VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId><PartNumber>2</PartNumber><ETag>"7778aef83f66abc1fa1e8477f296d394"</ETag><RequestId>MVGPB0A3JNKEJ0SB</RequestId><HostId>5ZkcRbAKvGfBWT+Z9fcGzCa7fmrDR9ZUAfmKUdUrnOtJ1tYo8iC2l7e+k4fPu4NAdkh0MakYjzU=</HostId></Error>"#;
+        let result = make_result(400, OsStr::from_bytes(&body[..]));
+        let result = parse_complete_multipart_upload_error(&result, error_from_code);
+        assert_eq!(result, Some(CompleteMultipartUploadError::InvalidPart));
+    }
+
+    #[test]
+    fn parse_completed() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<CompleteMultipartUploadResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Location>https://DOC-EXAMPLE-BUCKET.s3.amazonaws.com/example-object</Location>
+    <Bucket>DOC-EXAMPLE-BUCKET</Bucket>
+    <Key>example-object</Key>
+    <ETag>"3858f62230ac3c915f300c664312c11f-9"</ETag>
+</CompleteMultipartUploadResult>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+        assert_eq!(
+            result,
+            CompleteMultipartUploadResponse::Completed {
+                etag: ETag::from_str("\"3858f62230ac3c915f300c664312c11f-9\"").unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_error_after_200() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+    <Code>InternalError</Code>
+    <Message>We encountered an internal error. Please try again.</Message>
+    <RequestId>656c76696e6727732072657175657374</RequestId>
+    <HostId>Uuag1LuByRx9e6j5Onimru9pO4ZVKnJ2Qz7/C1NPcfTWAtRPfTaOFg==</HostId>
+</Error>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+        assert_eq!(
+            result,
+            CompleteMultipartUploadResponse::Failed {
+                code: "InternalError".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn request_body_lists_parts() {
+        let parts = [
+            CompletedPart {
+                part_number: 1,
+                etag: ETag::from_str("\"a54357aff0632cce46d942af68356b38\"").unwrap(),
+            },
+            CompletedPart {
+                part_number: 2,
+                etag: ETag::from_str("\"0c78aef83f66abc1fa1e8477f296d394\"").unwrap(),
+            },
+        ];
+        let body = build_request_body(&parts, None);
+        let root = Element::parse(&body[..]).expect("body should be valid XML");
+        assert_eq!(root.name, "CompleteMultipartUpload");
+        let parsed: Vec<_> = root
+            .children
+            .iter()
+            .filter_map(|node| node.as_element())
+            .map(|part| (get_field(part, "PartNumber").unwrap(), get_field(part, "ETag").unwrap()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("1".to_owned(), "\"a54357aff0632cce46d942af68356b38\"".to_owned()),
+                ("2".to_owned(), "\"0c78aef83f66abc1fa1e8477f296d394\"".to_owned()),
+            ]
+        );
+    }
+}
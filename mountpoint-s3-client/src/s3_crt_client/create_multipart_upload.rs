@@ -0,0 +1,145 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+
+use mountpoint_s3_crt::http::request_response::Header;
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+
+use crate::checksums::checksum_algorithm_name;
+use crate::object_client::{
+    CreateMultipartUploadError, CreateMultipartUploadResult, ObjectClientError, ObjectClientResult, PutObjectParams,
+};
+use crate::s3_crt_client::list_objects::{get_field, ParseError};
+use crate::s3_crt_client::put_object::{SSE_KEY_ID_HEADER_NAME, SSE_TYPE_HEADER_NAME};
+use crate::s3_crt_client::{S3CrtClient, S3RequestError};
+
+fn parse_result_from_bytes(bytes: &[u8]) -> Result<CreateMultipartUploadResult, ParseError> {
+    let element = xmltree::Element::parse(bytes)?;
+    let upload_id = get_field(&element, "UploadId")?;
+    Ok(CreateMultipartUploadResult { upload_id })
+}
+
+impl S3CrtClient {
+    /// Create and begin a new CreateMultipartUpload request. Unlike the [ObjectClient](crate::ObjectClient)
+    /// API, this sends the checksum algorithm and SSE-C key of the `params` if they have one, which
+    /// the multipart uploads driven by [S3CrtClient::put_object] need.
+    pub(super) async fn create_multipart_upload(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<CreateMultipartUploadResult, CreateMultipartUploadError, S3RequestError> {
+        let span = request_span!(self.inner, "create_multipart_upload", bucket, key);
+
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let request = {
+            let mut message = self
+                .inner
+                .new_request_template("POST", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_request_path_and_query(format!("/{key}"), [("uploads", "")])
+                .map_err(S3RequestError::construction_failure)?;
+
+            if let Some(storage_class) = params.storage_class.as_ref() {
+                message
+                    .set_header(&Header::new("x-amz-storage-class", storage_class))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            if let Some(sse) = params.server_side_encryption.as_ref() {
+                message
+                    .set_header(&Header::new(SSE_TYPE_HEADER_NAME, sse))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            if let Some(key_id) = params.ssekms_key_id.as_ref() {
+                message
+                    .set_header(&Header::new(SSE_KEY_ID_HEADER_NAME, key_id))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            message
+                .set_object_headers(params)
+                .map_err(S3RequestError::construction_failure)?;
+            if let Some(algorithm) = params.checksum_algorithm {
+                message
+                    .set_header(&Header::new(
+                        "x-amz-checksum-algorithm",
+                        checksum_algorithm_name(algorithm),
+                    ))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            if let Some(sse_customer_key) = params.sse_customer_key.as_ref() {
+                message
+                    .set_sse_customer_key(sse_customer_key)
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+
+            self.inner.make_simple_http_request(
+                message,
+                MetaRequestType::Default,
+                span,
+                parse_create_multipart_upload_error,
+            )?
+        };
+
+        let body = request.await?;
+
+        parse_result_from_bytes(&body)
+            .map_err(|e| ObjectClientError::ClientError(S3RequestError::InternalError(e.into())))
+    }
+}
+
+fn parse_create_multipart_upload_error(result: &MetaRequestResult) -> Option<CreateMultipartUploadError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchBucket" => Some(CreateMultipartUploadError::NoSuchBucket),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_bucket() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_create_multipart_upload_error(&result);
+        assert_eq!(result, Some(CreateMultipartUploadError::NoSuchBucket));
+    }
+
+    #[test]
+    fn parse_upload_id() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<InitiateMultipartUploadResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Bucket>DOC-EXAMPLE-BUCKET</Bucket>
+    <Key>example-object</Key>
+    <UploadId>VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId>
+</InitiateMultipartUploadResult>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+        assert_eq!(
+            result.upload_id,
+            "VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+
+use mountpoint_s3_crt::checksums::crc32c;
+use mountpoint_s3_crt::http::request_response::Header;
+use mountpoint_s3_crt::io::async_stream;
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use xmltree::{Element, XMLNode};
+
+use crate::checksums::crc32c_to_base64;
+use crate::object_client::{
+    DeleteObjectsError, DeleteObjectsKeyError, DeleteObjectsResult, ObjectClientError, ObjectClientResult,
+    ObjectIdentifier, MAX_DELETE_OBJECTS_KEYS,
+};
+use crate::s3_crt_client::list_objects::{get_field, ParseError};
+use crate::s3_crt_client::{S3CrtClient, S3CrtClientInner, S3RequestError};
+
+/// Build the DeleteObjects XML document listing the objects to delete
+fn build_request_body(objects: &[ObjectIdentifier]) -> Vec<u8> {
+    fn text_element(name: &str, text: String) -> XMLNode {
+        let mut element = Element::new(name);
+        element.children.push(XMLNode::Text(text));
+        XMLNode::Element(element)
+    }
+
+    let mut root = Element::new("Delete");
+    for object in objects {
+        let mut element = Element::new("Object");
+        element.children.push(text_element("Key", object.key.clone()));
+        if let Some(etag) = &object.etag {
+            element.children.push(text_element("ETag", etag.as_str().to_owned()));
+        }
+        root.children.push(XMLNode::Element(element));
+    }
+    // We want to know which keys were deleted, not just the ones that failed
+    root.children.push(text_element("Quiet", "false".to_owned()));
+
+    let mut body = Vec::new();
+    root.write(&mut body).expect("writing to a Vec can't fail");
+    body
+}
+
+fn parse_result_from_bytes(bytes: &[u8]) -> Result<DeleteObjectsResult, ParseError> {
+    parse_result_from_xml(&mut Element::parse(bytes)?)
+}
+
+fn parse_result_from_xml(element: &mut Element) -> Result<DeleteObjectsResult, ParseError> {
+    let mut deleted = Vec::new();
+    while let Some(object) = element.take_child("Deleted") {
+        deleted.push(get_field(&object, "Key")?);
+    }
+
+    let mut errors = Vec::new();
+    while let Some(error) = element.take_child("Error") {
+        errors.push(DeleteObjectsKeyError {
+            key: get_field(&error, "Key")?,
+            code: get_field(&error, "Code")?,
+            message: get_field(&error, "Message").unwrap_or_default(),
+        });
+    }
+
+    Ok(DeleteObjectsResult { deleted, errors })
+}
+
+impl S3CrtClient {
+    /// Create and begin a new DeleteObjects request.
+    pub(super) async fn delete_objects(
+        &self,
+        bucket: &str,
+        objects: &[ObjectIdentifier],
+    ) -> ObjectClientResult<DeleteObjectsResult, DeleteObjectsError, S3RequestError> {
+        // S3 would reject the request anyway, so don't bother sending it
+        if objects.len() > MAX_DELETE_OBJECTS_KEYS {
+            return Err(ObjectClientError::ServiceError(DeleteObjectsError::TooManyKeys));
+        }
+
+        let span = request_span!(self.inner, "delete_objects", bucket, num_keys = objects.len());
+
+        let body = build_request_body(objects);
+
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let (request, writer) = {
+            let mut message = self
+                .inner
+                .new_request_template("POST", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_request_path_and_query("/", [("delete", "")])
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Length", format!("{}", body.len())))
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Type", "application/xml"))
+                .map_err(S3RequestError::construction_failure)?;
+
+            // DeleteObjects requires an integrity check on the request body. S3 accepts a
+            // flexible checksum instead of Content-MD5, and we already have CRC32C available.
+            let checksum = crc32c_to_base64(&crc32c::checksum(&body));
+            message
+                .set_header(&Header::new("x-amz-sdk-checksum-algorithm", "CRC32C"))
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("x-amz-checksum-crc32c", checksum))
+                .map_err(S3RequestError::construction_failure)?;
+
+            let (body_async_stream, writer) = async_stream::new_stream(&self.inner.allocator);
+            message.set_body_stream(Some(body_async_stream));
+
+            let options = S3CrtClientInner::new_meta_request_options(message, MetaRequestType::Default);
+            let request = self.inner.make_simple_http_request_from_options(
+                options,
+                span,
+                parse_delete_objects_error,
+                |_, _| (),
+            )?;
+            (request, writer)
+        };
+
+        let response_body = request.write_body_and_wait(writer, &body).await?;
+
+        parse_result_from_bytes(&response_body)
+            .map_err(|e| ObjectClientError::ClientError(S3RequestError::InternalError(e.into())))
+    }
+}
+
+fn parse_delete_objects_error(result: &MetaRequestResult) -> Option<DeleteObjectsError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchBucket" => Some(DeleteObjectsError::NoSuchBucket),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::object_client::ETag;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_bucket() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_delete_objects_error(&result);
+        assert_eq!(result, Some(DeleteObjectsError::NoSuchBucket));
+    }
+
+    #[test]
+    fn parse_partial_failure() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<DeleteResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Deleted>
+        <Key>sample1.txt</Key>
+    </Deleted>
+    <Error>
+        <Key>sample2.txt</Key>
+        <Code>AccessDenied</Code>
+        <Message>Access Denied</Message>
+    </Error>
+</DeleteResult>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+        assert_eq!(result.deleted, vec!["sample1.txt".to_owned()]);
+        assert_eq!(
+            result.errors,
+            vec![DeleteObjectsKeyError {
+                key: "sample2.txt".to_owned(),
+                code: "AccessDenied".to_owned(),
+                message: "Access Denied".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn request_body_lists_keys() {
+        let objects = [
+            ObjectIdentifier::new("a"),
+            ObjectIdentifier::new("dir/b & c").etag(ETag::from_str("\"etag\"").unwrap()),
+        ];
+        let body = build_request_body(&objects);
+        let root = Element::parse(&body[..]).expect("body should be valid XML");
+        assert_eq!(root.name, "Delete");
+        let parsed: Vec<_> = root
+            .children
+            .iter()
+            .filter_map(|node| node.as_element())
+            .filter(|element| element.name == "Object")
+            .map(|object| (get_field(object, "Key").unwrap(), get_field(object, "ETag").ok()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("a".to_owned(), None),
+                ("dir/b & c".to_owned(), Some("\"etag\"".to_owned()))
+            ]
+        );
+        assert_eq!(get_field(&root, "Quiet").unwrap(), "false");
+    }
+}
@@ -1,20 +1,59 @@
+use std::fmt::Debug;
 use std::future::Future;
 use std::ops::Deref;
-use std::ops::Range;
 use std::os::unix::prelude::OsStrExt;
 use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 
 use futures::channel::mpsc::UnboundedReceiver;
-use futures::Stream;
+use futures::future::BoxFuture;
+use futures::{ready, FutureExt, Stream};
 use mountpoint_s3_crt::common::error::Error;
-use mountpoint_s3_crt::http::request_response::Header;
-use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use mountpoint_s3_crt::http::request_response::{Header, Headers};
+use mountpoint_s3_crt::s3::client::{ChecksumConfig, MetaRequestResult, MetaRequestType};
 use pin_project::pin_project;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+use tracing::{debug, error, warn};
 
-use crate::object_client::{ETag, GetBodyPart, GetObjectError, ObjectClientError, ObjectClientResult};
+use crate::checksums::checksum_algorithm_name;
+use crate::object_client::{
+    Checksum, ETag, GetBodyPart, GetObjectError, GetObjectParams, GetObjectRequest, GetObjectResponse,
+    ObjectClientError, ObjectClientResult,
+};
+use crate::s3_crt_client::head_object::{get_field, get_optional_field, parse_object_metadata, ParseError};
 use crate::s3_crt_client::{S3CrtClient, S3HttpRequest, S3RequestError};
 
+impl GetObjectResponse {
+    fn parse_from_hdr(headers: &Headers) -> Result<Self, ParseError> {
+        let etag = ETag::from_str(&get_field(headers, "ETag")?).expect("ETag parsing is infallible");
+        let content_length = get_optional_field(headers, "Content-Length")?
+            .map(|length| u64::from_str(&length).map_err(|e| ParseError::Int(e, "ContentLength".into())))
+            .transpose()?;
+        let last_modified = OffsetDateTime::parse(&get_field(headers, "Last-Modified")?, &Rfc2822)
+            .map_err(|e| ParseError::OffsetDateTime(e, "LastModified".into()))?;
+        let checksum = Checksum {
+            checksum_crc32: get_optional_field(headers, "x-amz-checksum-crc32")?,
+            checksum_crc32c: get_optional_field(headers, "x-amz-checksum-crc32c")?,
+            checksum_sha1: get_optional_field(headers, "x-amz-checksum-sha1")?,
+            checksum_sha256: get_optional_field(headers, "x-amz-checksum-sha256")?,
+        };
+        Ok(Self {
+            etag,
+            content_length,
+            last_modified,
+            checksum,
+            object_metadata: parse_object_metadata(headers)?,
+            content_type: get_optional_field(headers, "Content-Type")?,
+            content_encoding: get_optional_field(headers, "Content-Encoding")?,
+            cache_control: get_optional_field(headers, "Cache-Control")?,
+            checksum_validated: false,
+        })
+    }
+}
+
 impl S3CrtClient {
     /// Create and begin a new GetObject request. The returned [GetObjectRequest] is a [Stream] of
     /// body parts of the object, which will be delivered in order.
@@ -22,10 +61,18 @@ impl S3CrtClient {
         &self,
         bucket: &str,
         key: &str,
-        range: Option<Range<u64>>,
-        if_match: Option<ETag>,
+        params: &GetObjectParams,
     ) -> Result<S3GetObjectRequest, ObjectClientError<GetObjectError, S3RequestError>> {
-        let span = request_span!(self.inner, "get_object", bucket, key, ?range, ?if_match);
+        let span = request_span!(
+            self.inner,
+            "get_object",
+            bucket,
+            key,
+            range = ?params.range,
+            if_match = ?params.if_match,
+            version_id = ?params.version_id,
+            checksum_mode = params.checksum_mode
+        );
 
         let mut message = self
             .inner
@@ -37,19 +84,31 @@ impl S3CrtClient {
             .set_header(&Header::new("accept", "*/*"))
             .map_err(S3RequestError::construction_failure)?;
 
-        if let Some(etag) = if_match {
+        if let Some(etag) = params.if_match.as_ref() {
             // Return the object only if its entity tag (ETag) is matched
             message
                 .set_header(&Header::new("If-Match", etag.as_str()))
                 .map_err(S3RequestError::construction_failure)?;
         }
 
+        if let Some(sse_customer_key) = params.sse_customer_key.as_ref() {
+            message
+                .set_sse_customer_key(sse_customer_key)
+                .map_err(S3RequestError::construction_failure)?;
+        }
+
+        if params.checksum_mode {
+            // The CRT adds the `x-amz-checksum-mode` header, and validates the body if the response has
+            // a checksum it can validate against
+            message.set_checksum_config(Some(ChecksumConfig::validate_response()));
+        }
+
         // Only use the CRT auto-ranged-get machinery for requests larger than the part size, or
         // unknown lengths. This avoids the machinery's HeadObject requests for small/random
         // requests. For auto-ranged-gets, the CRT takes care of adjusting the offset returned to
         // the body callback to include the range start, but for manual requests we need to do it
         // ourselves with `range_start`.
-        let (request_type, range_start) = if let Some(range) = range {
+        let (request_type, range_start) = if let Some(range) = params.range.clone() {
             // Range HTTP header is bounded below *inclusive*
             let range_value = format!("bytes={}-{}", range.start, range.end.saturating_sub(1));
             message
@@ -67,37 +126,129 @@ impl S3CrtClient {
         };
 
         let key = format!("/{key}");
+        let mut query = Vec::new();
+        if let Some(version_id) = params.version_id.as_deref() {
+            query.push(("versionId", version_id));
+        }
         message
-            .set_request_path(key)
+            .set_request_path_and_query(key, query)
             .map_err(S3RequestError::construction_failure)?;
 
+        // With backpressure, the CRT only downloads up to the end of the read window, so the
+        // channel holds at most a window's worth of parts that the caller hasn't consumed yet.
+        let read_window_end_offset = self.inner.read_backpressure.then(|| {
+            let start = params.range.as_ref().map_or(0, |range| range.start);
+            start + self.inner.initial_read_window as u64
+        });
         let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let checksum_mode = params.checksum_mode;
+
+        let response: Arc<Mutex<Option<GetObjectResponse>>> = Default::default();
+        let response_writer = response.clone();
+        let response_finisher = response.clone();
 
         let request = self.inner.make_meta_request(
             message,
             request_type,
             span,
-            |_, _| (),
+            move |headers, _status| match GetObjectResponse::parse_from_hdr(headers) {
+                Ok(parsed) => *response_writer.lock().unwrap() = Some(parsed),
+                Err(error) => error!(?error, "failed to parse GetObject response headers"),
+            },
             move |offset, data| {
                 let _ = sender.unbounded_send(Ok((range_start + offset, data.into())));
             },
             move |result| {
+                let mut response = response_finisher.lock().unwrap();
+                if result.is_checksum_mismatch() {
+                    let error = match checksum_mismatch(result, response.as_ref()) {
+                        Some((algorithm, expected)) => GetObjectError::ChecksumMismatch { algorithm, expected },
+                        None => GetObjectError::UnverifiableChecksum,
+                    };
+                    return Err(Some(ObjectClientError::ServiceError(error)));
+                }
                 if result.is_err() {
                     Err(parse_get_object_error(result).map(ObjectClientError::ServiceError))
                 } else {
+                    if result.did_validate {
+                        if let Some(response) = response.as_mut() {
+                            response.checksum_validated = true;
+                        }
+                    } else if checksum_mode {
+                        debug!("the CRT did not validate the body against a checksum");
+                    }
                     Ok(())
                 }
             },
         )?;
 
+        // Requests can only be started again if they time out, which needs a timeout to be set
+        let retry = self.inner.request_timeout.map(|_| GetObjectRetry {
+            client: self.clone(),
+            bucket: bucket.to_owned(),
+            key: key.to_owned(),
+            params: params.clone(),
+            remaining: self.inner.max_timeout_retries,
+        });
+
         Ok(S3GetObjectRequest {
             request,
             finish_receiver: receiver,
             finished: false,
+            response,
+            read_window_end_offset,
+            disarm_timeout_on_body: request_type == MetaRequestType::GetObject,
+            retry,
+            restart: None,
         })
     }
 }
 
+/// The algorithm and expected value of the checksum the CRT found the body didn't match, if the CRT
+/// reported the algorithm and the response headers had a checksum for it
+fn checksum_mismatch(
+    result: &MetaRequestResult,
+    response: Option<&GetObjectResponse>,
+) -> Option<(&'static str, String)> {
+    let algorithm = result.validation_algorithm?;
+    let expected = response?.checksum.value(algorithm)?;
+    Some((checksum_algorithm_name(algorithm), expected.to_owned()))
+}
+
+/// What we need to start a GetObject request again after it timed out
+#[derive(Debug)]
+struct GetObjectRetry {
+    client: S3CrtClient,
+    bucket: String,
+    key: String,
+    params: GetObjectParams,
+    /// How many more times the request can be started again
+    remaining: usize,
+}
+
+impl GetObjectRetry {
+    /// Start the request again, once the client's adaptive throttling allows a new request
+    async fn restart(self) -> Result<S3GetObjectRequest, ObjectClientError<GetObjectError, S3RequestError>> {
+        self.client.inner.throttle().await;
+        let mut request = self.client.get_object(&self.bucket, &self.key, &self.params)?;
+        if let Some(retry) = request.retry.as_mut() {
+            retry.remaining = self.remaining.saturating_sub(1);
+        }
+        Ok(request)
+    }
+}
+
+/// A GetObject request that is waiting to be started again after it timed out
+struct PendingRestart(
+    BoxFuture<'static, Result<S3GetObjectRequest, ObjectClientError<GetObjectError, S3RequestError>>>,
+);
+
+impl Debug for PendingRestart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingRestart").finish_non_exhaustive()
+    }
+}
+
 /// A streaming response to a GetObject request.
 ///
 /// This struct implements [`futures::Stream`], which you can use to read the body of the object.
@@ -111,19 +262,98 @@ pub struct S3GetObjectRequest {
     #[pin]
     finish_receiver: UnboundedReceiver<Result<GetBodyPart, Error>>,
     finished: bool,
+    /// Properties of the object from the response headers, available once they've been received
+    response: Arc<Mutex<Option<GetObjectResponse>>>,
+    /// End offset of the read window, or `None` if the client doesn't apply backpressure
+    read_window_end_offset: Option<u64>,
+    /// Whether the request timeout stops applying once body data arrives, as it does for
+    /// auto-ranged requests that can take arbitrarily long to download the whole object
+    disarm_timeout_on_body: bool,
+    /// Set until the request returns any of the body, if it can be started again after a timeout
+    retry: Option<GetObjectRetry>,
+    /// Set while the request is being started again after a timeout
+    restart: Option<PendingRestart>,
+}
+
+impl GetObjectRequest for S3GetObjectRequest {
+    type ClientError = S3RequestError;
+
+    fn get_object_response(&self) -> Option<GetObjectResponse> {
+        self.response.lock().unwrap().clone()
+    }
+
+    fn increment_read_window(self: Pin<&mut Self>, len: usize) {
+        let this = self.project();
+        if let Some(end_offset) = this.read_window_end_offset {
+            *end_offset += len as u64;
+            this.request.meta_request.increment_read_window(len as u64);
+        }
+    }
+
+    fn read_window_end_offset(self: Pin<&Self>) -> u64 {
+        self.read_window_end_offset.unwrap_or(u64::MAX)
+    }
 }
 
 impl Stream for S3GetObjectRequest {
     type Item = ObjectClientResult<GetBodyPart, GetObjectError, S3RequestError>;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(PendingRestart(restart)) = self.as_mut().project().restart.as_mut() {
+                let result = ready!(restart.poll_unpin(cx));
+                *self.as_mut().project().restart = None;
+                let mut request = match result {
+                    Ok(request) => request,
+                    Err(e) => {
+                        *self.as_mut().project().finished = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                };
+                // Carry over the read window the caller already granted to the request that timed out
+                if let (Some(old_end), Some(new_end)) = (self.read_window_end_offset, request.read_window_end_offset) {
+                    if old_end > new_end {
+                        request.request.meta_request.increment_read_window(old_end - new_end);
+                        request.read_window_end_offset = Some(old_end);
+                    }
+                }
+                self.set(request);
+                continue;
+            }
+
+            let result = ready!(self.as_mut().poll_attempt(cx));
+            let Some(Err(ObjectClientError::ClientError(S3RequestError::RequestTimedOut(timeout)))) = &result else {
+                return Poll::Ready(result);
+            };
+            let timeout = *timeout;
+            let Some(retry) = self.as_mut().project().retry.take().filter(|retry| retry.remaining > 0) else {
+                return Poll::Ready(result);
+            };
+            warn!(
+                ?timeout,
+                remaining = retry.remaining,
+                "GetObject request timed out, retrying"
+            );
+            *self.as_mut().project().restart = Some(PendingRestart(retry.restart().boxed()));
+        }
+    }
+}
+
+impl S3GetObjectRequest {
+    /// Poll the current attempt at the request
+    fn poll_attempt(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<<Self as Stream>::Item>> {
         if self.finished {
             return Poll::Ready(None);
         }
 
-        let this = self.project();
+        let mut this = self.project();
 
         if let Poll::Ready(Some(val)) = this.finish_receiver.poll_next(cx) {
+            // We can't take back the body we return, so the request can't be started again
+            *this.retry = None;
+            if *this.disarm_timeout_on_body {
+                this.request.as_mut().disarm_timeout();
+            }
             return Poll::Ready(Some(val.map_err(|e| ObjectClientError::ClientError(e.into()))));
         }
 
@@ -150,10 +380,12 @@ fn parse_get_object_error(result: &MetaRequestResult) -> Option<GetObjectError>
             let error_str = error_code.get_text()?;
             match error_str.deref() {
                 "NoSuchBucket" => Some(GetObjectError::NoSuchBucket),
-                "NoSuchKey" => Some(GetObjectError::NoSuchKey),
+                "NoSuchKey" | "NoSuchVersion" => Some(GetObjectError::NoSuchKey),
                 _ => None,
             }
         }
+        // Requesting a specific version that is a delete marker fails with 405
+        405 => Some(GetObjectError::NoSuchKey),
         412 => Some(GetObjectError::PreconditionFailed),
         _ => None,
     }
@@ -163,7 +395,10 @@ fn parse_get_object_error(result: &MetaRequestResult) -> Option<GetObjectError>
 mod tests {
     use std::ffi::{OsStr, OsString};
 
+    use mountpoint_s3_crt::common::allocator::Allocator;
+
     use super::*;
+    use crate::object_client::ChecksumAlgorithm;
 
     fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
         MetaRequestResult {
@@ -171,9 +406,98 @@ mod tests {
             crt_error: 1i32.into(),
             error_response_headers: None,
             error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
         }
     }
 
+    #[test]
+    fn test_parse_get_object_response() {
+        let mut headers = Headers::new(&Allocator::default()).unwrap();
+        headers
+            .add_header(&Header::new("ETag", "\"3858f62230ac3c915f300c664312c11f\""))
+            .unwrap();
+        headers.add_header(&Header::new("Content-Length", "1024")).unwrap();
+        headers
+            .add_header(&Header::new("Last-Modified", "Wed, 12 Oct 2009 17:50:00 GMT"))
+            .unwrap();
+        headers.add_header(&Header::new("Content-Type", "text/plain")).unwrap();
+        headers
+            .add_header(&Header::new("x-amz-checksum-crc32c", "yZRlqg=="))
+            .unwrap();
+        headers.add_header(&Header::new("x-amz-meta-owner", "alice")).unwrap();
+
+        let response = GetObjectResponse::parse_from_hdr(&headers).expect("failed to parse headers");
+        assert_eq!(response.etag.as_str(), "\"3858f62230ac3c915f300c664312c11f\"");
+        assert_eq!(response.content_length, Some(1024));
+        assert_eq!(
+            response.last_modified,
+            OffsetDateTime::parse("Wed, 12 Oct 2009 17:50:00 GMT", &Rfc2822).unwrap()
+        );
+        assert_eq!(response.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(response.checksum.checksum_crc32c.as_deref(), Some("yZRlqg=="));
+        assert_eq!(response.checksum.checksum_sha256, None);
+        assert_eq!(response.object_metadata.get("owner").map(String::as_str), Some("alice"));
+    }
+
+    #[test]
+    fn test_parse_get_object_response_missing_etag() {
+        let mut headers = Headers::new(&Allocator::default()).unwrap();
+        headers
+            .add_header(&Header::new("Last-Modified", "Wed, 12 Oct 2009 17:50:00 GMT"))
+            .unwrap();
+        GetObjectResponse::parse_from_hdr(&headers).expect_err("ETag is required");
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut headers = Headers::new(&Allocator::default()).unwrap();
+        headers.add_header(&Header::new("ETag", "\"etag\"")).unwrap();
+        headers
+            .add_header(&Header::new("Last-Modified", "Wed, 12 Oct 2009 17:50:00 GMT"))
+            .unwrap();
+        headers
+            .add_header(&Header::new("x-amz-checksum-crc32c", "yZRlqg=="))
+            .unwrap();
+        let response = GetObjectResponse::parse_from_hdr(&headers).unwrap();
+        assert!(!response.checksum_validated);
+
+        let mut result = make_result(200, "");
+        result.validation_algorithm = Some(ChecksumAlgorithm::Crc32c);
+        assert_eq!(
+            checksum_mismatch(&result, Some(&response)),
+            Some(("CRC32C", "yZRlqg==".to_owned()))
+        );
+
+        // Without the algorithm or the checksum, the mismatch can't be explained
+        result.validation_algorithm = Some(ChecksumAlgorithm::Sha256);
+        assert_eq!(checksum_mismatch(&result, Some(&response)), None);
+        result.validation_algorithm = None;
+        assert_eq!(checksum_mismatch(&result, Some(&response)), None);
+        result.validation_algorithm = Some(ChecksumAlgorithm::Crc32c);
+        assert_eq!(checksum_mismatch(&result, None), None);
+    }
+
+    #[test]
+    fn test_full_object_checksum() {
+        let checksum = Checksum {
+            checksum_crc32c: Some("yZRlqg==".to_owned()),
+            checksum_sha256: Some("uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            checksum.full_object_checksum(),
+            Some((ChecksumAlgorithm::Crc32c, "yZRlqg=="))
+        );
+
+        let checksum = Checksum {
+            checksum_crc32c: Some("6JKWfg==-3".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(checksum.full_object_checksum(), None);
+        assert_eq!(Checksum::default().full_object_checksum(), None);
+    }
+
     #[test]
     fn parse_404_no_such_key() {
         let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message><Key>not-a-real-key</Key><RequestId>NTKJWKHQBYNS73A9</RequestId><HostId>Nc9kWNrf4kGoq5NIUnQ4t7u04ZZXGm/i463v+jwCI8sIrZBqeYI8uffLHQ+/qusdMWNuUwqeXHU=</HostId></Error>"#;
@@ -190,6 +514,22 @@ mod tests {
         assert_eq!(result, Some(GetObjectError::NoSuchBucket));
     }
 
+    #[test]
+    fn parse_404_no_such_version() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchVersion</Code><Message>The specified version does not exist.</Message><Key>hello</Key><VersionId>3HL4kqtJlcpXroDTDmjVBH40Nrjfkd</VersionId><RequestId>4X6XZ7K1C5N5Z3YB</RequestId><HostId>uTZvbfw8vIhzDd2lJNQzC3dMPtT9QN2mOYUQ6PPSW0cTa4PH6m4fWCRqIzHIYjMdHlzMYpN7FD4=</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_get_object_error(&result);
+        assert_eq!(result, Some(GetObjectError::NoSuchKey));
+    }
+
+    #[test]
+    fn parse_405_delete_marker() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>MethodNotAllowed</Code><Message>The specified method is not allowed against this resource.</Message><Method>GET</Method><ResourceType>DeleteMarker</ResourceType><RequestId>V0GXDCMKQ1MJSHTJ</RequestId><HostId>AGvz3kBL3k2kX1eNX3lQWwUQbMVF4yU7Fv4Mly5hzx0d2fdm6UPwPvJh3/cOd3lvcFQwNgLfPGE=</HostId></Error>"#;
+        let result = make_result(405, OsStr::from_bytes(&body[..]));
+        let result = parse_get_object_error(&result);
+        assert_eq!(result, Some(GetObjectError::NoSuchKey));
+    }
+
     #[test]
     fn parse_403_glacier_storage_class() {
         let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>InvalidObjectState</Code><Message>The action is not valid for the object's storage class</Message><RequestId>9FEFFF118E15B86F</RequestId><HostId>WVQ5kzhiT+oiUfDCOiOYv8W4Tk9eNcxWi/MK+hTS/av34Xy4rBU3zsavf0aaaaa</HostId></Error>"#;
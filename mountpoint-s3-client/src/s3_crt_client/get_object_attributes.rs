@@ -226,6 +226,8 @@ mod tests {
             crt_error: 1i32.into(),
             error_response_headers: None,
             error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
         }
     }
 
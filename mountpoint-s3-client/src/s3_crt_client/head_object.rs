@@ -1,4 +1,5 @@
 use std::ffi::OsString;
+use std::os::unix::prelude::OsStrExt;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
@@ -12,7 +13,8 @@ use time::OffsetDateTime;
 use tracing::error;
 
 use crate::object_client::{
-    HeadObjectError, HeadObjectResult, ObjectClientError, ObjectClientResult, ObjectInfo, RestoreStatus,
+    HeadObjectError, HeadObjectParams, HeadObjectResult, ObjectClientError, ObjectClientResult, ObjectInfo,
+    ObjectMetadata, RestoreStatus,
 };
 use crate::s3_crt_client::{S3CrtClient, S3RequestError};
 
@@ -35,7 +37,7 @@ pub enum ParseError {
     InvalidRestore(String),
 }
 
-fn get_field(headers: &Headers, name: &str) -> Result<String, ParseError> {
+pub(crate) fn get_field(headers: &Headers, name: &str) -> Result<String, ParseError> {
     let header = headers.get(name)?;
     let value = header.value();
     if let Some(s) = value.to_str() {
@@ -45,7 +47,7 @@ fn get_field(headers: &Headers, name: &str) -> Result<String, ParseError> {
     }
 }
 
-fn get_optional_field(headers: &Headers, name: &str) -> Result<Option<String>, ParseError> {
+pub(crate) fn get_optional_field(headers: &Headers, name: &str) -> Result<Option<String>, ParseError> {
     Ok(if headers.has_header(name) {
         Some(get_field(headers, name)?)
     } else {
@@ -53,6 +55,25 @@ fn get_optional_field(headers: &Headers, name: &str) -> Result<Option<String>, P
     })
 }
 
+/// Parse the user-defined metadata from the `x-amz-meta-*` headers of a response
+pub(crate) fn parse_object_metadata(headers: &Headers) -> Result<ObjectMetadata, ParseError> {
+    const PREFIX: &str = "x-amz-meta-";
+    let mut metadata = ObjectMetadata::new();
+    for (name, value) in headers.iter() {
+        let is_metadata = name
+            .as_bytes()
+            .get(..PREFIX.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(PREFIX.as_bytes()));
+        if !is_metadata {
+            continue;
+        }
+        let name = name.into_string().map_err(ParseError::Invalid)?;
+        let value = value.into_string().map_err(ParseError::Invalid)?;
+        metadata.insert(name[PREFIX.len()..].to_owned(), value);
+    }
+    Ok(metadata)
+}
+
 lazy_static! {
     // Example: ongoing-request="true"
     static ref RESTORE_IN_PROGRESS_RE: Regex = Regex::new(r#"^ongoing-request="(?<ongoing>[^"]*)"$"#).unwrap();
@@ -93,6 +114,11 @@ impl HeadObjectResult {
         let etag = get_field(headers, "Etag")?;
         let storage_class = get_optional_field(headers, "x-amz-storage-class")?;
         let restore_status = Self::parse_restore_status(headers)?;
+        let version_id = get_optional_field(headers, "x-amz-version-id")?;
+        let object_metadata = parse_object_metadata(headers)?;
+        let content_type = get_optional_field(headers, "Content-Type")?;
+        let content_encoding = get_optional_field(headers, "Content-Encoding")?;
+        let cache_control = get_optional_field(headers, "Cache-Control")?;
         let object = ObjectInfo {
             key,
             size,
@@ -101,7 +127,15 @@ impl HeadObjectResult {
             restore_status,
             etag,
         };
-        Ok(HeadObjectResult { bucket, object })
+        Ok(HeadObjectResult {
+            bucket,
+            object,
+            version_id,
+            object_metadata,
+            content_type,
+            content_encoding,
+            cache_control,
+        })
     }
 }
 
@@ -110,6 +144,7 @@ impl S3CrtClient {
         &self,
         bucket: &str,
         key: &str,
+        params: &HeadObjectParams,
     ) -> ObjectClientResult<HeadObjectResult, HeadObjectError, S3RequestError> {
         // Stash the response from the head_object in this lock during the on_headers
         // callback, and pull them out once the request is done.
@@ -123,13 +158,22 @@ impl S3CrtClient {
                 .map_err(S3RequestError::construction_failure)?;
 
             let key = key.to_string();
+            let mut query = Vec::new();
+            if let Some(version_id) = params.version_id.as_deref() {
+                query.push(("versionId", version_id));
+            }
             message
-                .set_request_path(format!("/{key}"))
+                .set_request_path_and_query(format!("/{key}"), query)
                 .map_err(S3RequestError::construction_failure)?;
+            if let Some(sse_customer_key) = params.sse_customer_key.as_ref() {
+                message
+                    .set_sse_customer_key(sse_customer_key)
+                    .map_err(S3RequestError::construction_failure)?;
+            }
 
             let bucket = bucket.to_owned();
 
-            let span = request_span!(self.inner, "head_object", bucket, key);
+            let span = request_span!(self.inner, "head_object", bucket, key, version_id = ?params.version_id);
 
             self.inner.make_meta_request(
                 message,
@@ -164,6 +208,8 @@ impl S3CrtClient {
 fn parse_head_object_error(result: &MetaRequestResult) -> Option<HeadObjectError> {
     match result.response_status {
         404 => Some(HeadObjectError::NotFound),
+        // Requesting a specific version that is a delete marker fails with 405
+        405 => Some(HeadObjectError::NotFound),
         _ => None,
     }
 }
@@ -183,6 +229,8 @@ mod tests {
             crt_error: 1i32.into(),
             error_response_headers: None,
             error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
         }
     }
 
@@ -193,6 +241,30 @@ mod tests {
         assert_eq!(result, Some(HeadObjectError::NotFound));
     }
 
+    #[test]
+    fn parse_405_delete_marker() {
+        let result = make_result(405, "");
+        let result = parse_head_object_error(&result);
+        assert_eq!(result, Some(HeadObjectError::NotFound));
+    }
+
+    #[test]
+    fn test_parse_object_metadata() {
+        let mut headers = Headers::new(&Allocator::default()).unwrap();
+        headers.add_header(&Header::new("x-amz-meta-owner", "alice")).unwrap();
+        headers.add_header(&Header::new("X-Amz-Meta-Job-Id", "1234")).unwrap();
+        headers.add_header(&Header::new("x-amz-version-id", "abc")).unwrap();
+        headers.add_header(&Header::new("Content-Type", "text/plain")).unwrap();
+        let metadata = parse_object_metadata(&headers).expect("failed to parse headers");
+        assert_eq!(
+            metadata,
+            ObjectMetadata::from([
+                ("owner".to_owned(), "alice".to_owned()),
+                ("Job-Id".to_owned(), "1234".to_owned()),
+            ])
+        );
+    }
+
     #[test_case(r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#; "from documentation")]
     #[test_case(r#"ongoing-request="false",expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#; "no whitespace")]
     #[test_case("ongoing-request=\"false\",   \t   \t  expiry-date=\"Fri, 21 Dec 2012 00:00:00 GMT\""; "lots of whitespaces")]
@@ -0,0 +1,245 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+use std::str::FromStr;
+
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::object_client::{
+    ListMultipartUploadsError, ListMultipartUploadsResult, MultipartUploadInfo, ObjectClientError, ObjectClientResult,
+};
+use crate::s3_crt_client::list_objects::{get_field, get_text, ParseError};
+use crate::s3_crt_client::{S3CrtClient, S3RequestError};
+
+fn parse_result_from_bytes(bytes: &[u8]) -> Result<ListMultipartUploadsResult, ParseError> {
+    parse_result_from_xml(&mut xmltree::Element::parse(bytes)?)
+}
+
+fn parse_result_from_xml(element: &mut xmltree::Element) -> Result<ListMultipartUploadsResult, ParseError> {
+    let mut uploads = Vec::new();
+
+    while let Some(upload) = element.take_child("Upload") {
+        uploads.push(parse_upload_from_xml(&upload)?);
+    }
+
+    let mut common_prefixes = Vec::new();
+
+    while let Some(common_prefix) = element.take_child("CommonPrefixes") {
+        let prefix = get_field(&common_prefix, "Prefix")?;
+        common_prefixes.push(prefix);
+    }
+
+    let is_truncated = get_field(element, "IsTruncated")?;
+    let is_truncated = bool::from_str(&is_truncated).map_err(|e| ParseError::Bool(e, "IsTruncated".to_string()))?;
+
+    // Unlike ListObjectsV2, S3 returns the next markers (possibly empty) even when the listing
+    // isn't truncated, so only use them if there are more results.
+    let (next_key_marker, next_upload_id_marker) = if is_truncated {
+        let next_key_marker = get_field(element, "NextKeyMarker")?;
+        let next_upload_id_marker = match element.get_child("NextUploadIdMarker") {
+            Some(elem) => Some(get_text(elem)?),
+            None => None,
+        };
+        (Some(next_key_marker), next_upload_id_marker)
+    } else {
+        (None, None)
+    };
+
+    Ok(ListMultipartUploadsResult {
+        uploads,
+        common_prefixes,
+        next_key_marker,
+        next_upload_id_marker,
+    })
+}
+
+fn parse_upload_from_xml(element: &xmltree::Element) -> Result<MultipartUploadInfo, ParseError> {
+    let key = get_field(element, "Key")?;
+    let upload_id = get_field(element, "UploadId")?;
+
+    let initiated = get_field(element, "Initiated")?;
+    let initiated = OffsetDateTime::parse(&initiated, &Rfc3339)
+        .map_err(|e| ParseError::OffsetDateTime(e, "Initiated".to_string()))?;
+
+    let storage_class = match element.get_child("StorageClass") {
+        Some(elem) => Some(get_text(elem)?),
+        None => None,
+    };
+
+    Ok(MultipartUploadInfo {
+        key,
+        upload_id,
+        initiated,
+        storage_class,
+    })
+}
+
+impl S3CrtClient {
+    pub(super) async fn list_multipart_uploads(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        upload_id_marker: Option<&str>,
+        delimiter: &str,
+        max_uploads: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListMultipartUploadsResult, ListMultipartUploadsError, S3RequestError> {
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let body = {
+            let mut message = self
+                .inner
+                .new_request_template("GET", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            let max_uploads = format!("{max_uploads}");
+            let mut query = vec![
+                ("uploads", ""),
+                ("delimiter", delimiter),
+                ("max-uploads", &max_uploads),
+                ("prefix", prefix),
+            ];
+            if let Some(key_marker) = key_marker {
+                query.push(("key-marker", key_marker));
+            }
+            if let Some(upload_id_marker) = upload_id_marker {
+                query.push(("upload-id-marker", upload_id_marker));
+            }
+
+            message
+                .set_request_path_and_query("/", query)
+                .map_err(S3RequestError::construction_failure)?;
+
+            let span = request_span!(
+                self.inner,
+                "list_multipart_uploads",
+                bucket,
+                continued = key_marker.is_some(),
+                delimiter,
+                max_uploads,
+                prefix
+            );
+
+            self.inner.make_simple_http_request(
+                message,
+                MetaRequestType::Default,
+                span,
+                parse_list_multipart_uploads_error,
+            )?
+        };
+
+        let body = body.await?;
+
+        parse_result_from_bytes(&body)
+            .map_err(|e| ObjectClientError::ClientError(S3RequestError::InternalError(e.into())))
+    }
+}
+
+fn parse_list_multipart_uploads_error(result: &MetaRequestResult) -> Option<ListMultipartUploadsError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchBucket" => Some(ListMultipartUploadsError::NoSuchBucket),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_bucket() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_list_multipart_uploads_error(&result);
+        assert_eq!(result, Some(ListMultipartUploadsError::NoSuchBucket));
+    }
+
+    #[test]
+    fn parse_truncated_uploads() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<ListMultipartUploadsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Bucket>DOC-EXAMPLE-BUCKET</Bucket>
+    <KeyMarker></KeyMarker>
+    <UploadIdMarker></UploadIdMarker>
+    <NextKeyMarker>my-movie.m2ts</NextKeyMarker>
+    <NextUploadIdMarker>YW55IGlkZWEgd2h5IGVsdmluZydzIHVwbG9hZCBmYWlsZWQ</NextUploadIdMarker>
+    <MaxUploads>2</MaxUploads>
+    <IsTruncated>true</IsTruncated>
+    <Upload>
+        <Key>my-divisor</Key>
+        <UploadId>XMgbGlrZSBlbHZpbmcncyBub3QgaGF2aW5nIG11Y2ggbHVjaw</UploadId>
+        <StorageClass>REDUCED_REDUNDANCY</StorageClass>
+        <Initiated>2010-11-10T20:48:33.000Z</Initiated>
+    </Upload>
+    <Upload>
+        <Key>my-movie.m2ts</Key>
+        <UploadId>YW55IGlkZWEgd2h5IGVsdmluZydzIHVwbG9hZCBmYWlsZWQ</UploadId>
+        <StorageClass>STANDARD</StorageClass>
+        <Initiated>2010-11-10T20:48:33.000Z</Initiated>
+    </Upload>
+</ListMultipartUploadsResult>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+
+        assert_eq!(result.uploads.len(), 2);
+        assert_eq!(result.uploads[0].key, "my-divisor");
+        assert_eq!(
+            result.uploads[0].upload_id,
+            "XMgbGlrZSBlbHZpbmcncyBub3QgaGF2aW5nIG11Y2ggbHVjaw"
+        );
+        assert_eq!(result.uploads[0].storage_class.as_deref(), Some("REDUCED_REDUNDANCY"));
+        assert_eq!(result.uploads[0].initiated.unix_timestamp(), 1289422113);
+        assert_eq!(result.uploads[1].key, "my-movie.m2ts");
+        assert!(result.common_prefixes.is_empty());
+        assert_eq!(result.next_key_marker.as_deref(), Some("my-movie.m2ts"));
+        assert_eq!(
+            result.next_upload_id_marker.as_deref(),
+            Some("YW55IGlkZWEgd2h5IGVsdmluZydzIHVwbG9hZCBmYWlsZWQ")
+        );
+    }
+
+    #[test]
+    fn parse_not_truncated_ignores_markers() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<ListMultipartUploadsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Bucket>DOC-EXAMPLE-BUCKET</Bucket>
+    <KeyMarker></KeyMarker>
+    <UploadIdMarker></UploadIdMarker>
+    <NextKeyMarker></NextKeyMarker>
+    <NextUploadIdMarker></NextUploadIdMarker>
+    <Delimiter>/</Delimiter>
+    <Prefix>photos/</Prefix>
+    <MaxUploads>1000</MaxUploads>
+    <IsTruncated>false</IsTruncated>
+    <CommonPrefixes>
+        <Prefix>photos/2006/</Prefix>
+    </CommonPrefixes>
+</ListMultipartUploadsResult>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+
+        assert!(result.uploads.is_empty());
+        assert_eq!(result.common_prefixes, vec!["photos/2006/".to_owned()]);
+        assert!(result.next_key_marker.is_none());
+        assert!(result.next_upload_id_marker.is_none());
+    }
+}
@@ -0,0 +1,282 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+use std::str::FromStr;
+
+use mountpoint_s3_crt::http::request_response::Header;
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::object_client::{
+    DeleteMarkerInfo, ListObjectVersionsError, ListObjectVersionsResult, ObjectClientError, ObjectClientResult,
+    ObjectVersionInfo,
+};
+use crate::s3_crt_client::list_objects::{get_field, get_text, parse_object_info_from_xml, ParseError};
+use crate::s3_crt_client::{S3CrtClient, S3RequestError};
+
+fn parse_result_from_bytes(bytes: &[u8]) -> Result<ListObjectVersionsResult, ParseError> {
+    parse_result_from_xml(&mut xmltree::Element::parse(bytes)?)
+}
+
+fn parse_result_from_xml(element: &mut xmltree::Element) -> Result<ListObjectVersionsResult, ParseError> {
+    let mut versions = Vec::new();
+
+    while let Some(version) = element.take_child("Version") {
+        versions.push(parse_object_version_from_xml(&version)?);
+    }
+
+    let mut delete_markers = Vec::new();
+
+    while let Some(delete_marker) = element.take_child("DeleteMarker") {
+        delete_markers.push(parse_delete_marker_from_xml(&delete_marker)?);
+    }
+
+    let mut common_prefixes = Vec::new();
+
+    while let Some(common_prefix) = element.take_child("CommonPrefixes") {
+        let prefix = get_field(&common_prefix, "Prefix")?;
+        common_prefixes.push(prefix);
+    }
+
+    let mut next_key_marker = None;
+    if let Some(elem) = element.get_child("NextKeyMarker") {
+        next_key_marker = Some(get_text(elem)?);
+    }
+
+    let mut next_version_id_marker = None;
+    if let Some(elem) = element.get_child("NextVersionIdMarker") {
+        next_version_id_marker = Some(get_text(elem)?);
+    }
+
+    let is_truncated = get_field(element, "IsTruncated")?;
+    let is_truncated = bool::from_str(&is_truncated).map_err(|e| ParseError::Bool(e, "IsTruncated".to_string()))?;
+
+    if is_truncated != next_key_marker.is_some() {
+        return Err(ParseError::InvalidResponse(
+            element.clone(),
+            "IsTruncated doesn't match NextKeyMarker".to_string(),
+        ));
+    }
+
+    Ok(ListObjectVersionsResult {
+        versions,
+        delete_markers,
+        common_prefixes,
+        next_key_marker,
+        next_version_id_marker,
+    })
+}
+
+fn parse_is_latest(element: &xmltree::Element) -> Result<bool, ParseError> {
+    let is_latest = get_field(element, "IsLatest")?;
+    bool::from_str(&is_latest).map_err(|e| ParseError::Bool(e, "IsLatest".to_string()))
+}
+
+fn parse_object_version_from_xml(element: &xmltree::Element) -> Result<ObjectVersionInfo, ParseError> {
+    let object = parse_object_info_from_xml(element)?;
+    let version_id = get_field(element, "VersionId")?;
+    let is_latest = parse_is_latest(element)?;
+
+    Ok(ObjectVersionInfo {
+        object,
+        version_id,
+        is_latest,
+    })
+}
+
+fn parse_delete_marker_from_xml(element: &xmltree::Element) -> Result<DeleteMarkerInfo, ParseError> {
+    let key = get_field(element, "Key")?;
+    let version_id = get_field(element, "VersionId")?;
+    let is_latest = parse_is_latest(element)?;
+
+    let last_modified = get_field(element, "LastModified")?;
+    let last_modified = OffsetDateTime::parse(&last_modified, &Rfc3339)
+        .map_err(|e| ParseError::OffsetDateTime(e, "LastModified".to_string()))?;
+
+    Ok(DeleteMarkerInfo {
+        key,
+        version_id,
+        is_latest,
+        last_modified,
+    })
+}
+
+impl S3CrtClient {
+    pub(super) async fn list_object_versions(
+        &self,
+        bucket: &str,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
+        delimiter: &str,
+        max_keys: usize,
+        prefix: &str,
+    ) -> ObjectClientResult<ListObjectVersionsResult, ListObjectVersionsError, S3RequestError> {
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let body = {
+            let mut message = self
+                .inner
+                .new_request_template("GET", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("x-amz-optional-object-attributes", "RestoreStatus"))
+                .map_err(S3RequestError::construction_failure)?;
+            let max_keys = format!("{max_keys}");
+            let mut query = vec![
+                ("versions", ""),
+                ("delimiter", delimiter),
+                ("max-keys", &max_keys),
+                ("prefix", prefix),
+            ];
+            if let Some(key_marker) = key_marker {
+                query.push(("key-marker", key_marker));
+            }
+            if let Some(version_id_marker) = version_id_marker {
+                query.push(("version-id-marker", version_id_marker));
+            }
+
+            message
+                .set_request_path_and_query("/", query)
+                .map_err(S3RequestError::construction_failure)?;
+
+            let span = request_span!(
+                self.inner,
+                "list_object_versions",
+                bucket,
+                continued = key_marker.is_some(),
+                delimiter,
+                max_keys,
+                prefix
+            );
+
+            self.inner.make_simple_http_request(
+                message,
+                MetaRequestType::Default,
+                span,
+                parse_list_object_versions_error,
+            )?
+        };
+
+        let body = body.await?;
+
+        parse_result_from_bytes(&body)
+            .map_err(|e| ObjectClientError::ClientError(S3RequestError::InternalError(e.into())))
+    }
+}
+
+fn parse_list_object_versions_error(result: &MetaRequestResult) -> Option<ListObjectVersionsError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchBucket" => Some(ListObjectVersionsError::NoSuchBucket),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_bucket() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_list_object_versions_error(&result);
+        assert_eq!(result, Some(ListObjectVersionsError::NoSuchBucket));
+    }
+
+    #[test]
+    fn parse_versions_and_delete_markers() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<ListVersionsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>DOC-EXAMPLE-BUCKET</Name>
+    <Prefix>my</Prefix>
+    <KeyMarker></KeyMarker>
+    <VersionIdMarker></VersionIdMarker>
+    <NextKeyMarker>my-third-image.jpg</NextKeyMarker>
+    <NextVersionIdMarker>03jpff543dhffds434rfdsFDN943fdsFkdmqnh892</NextVersionIdMarker>
+    <MaxKeys>3</MaxKeys>
+    <IsTruncated>true</IsTruncated>
+    <DeleteMarker>
+        <Key>my-second-image.jpg</Key>
+        <VersionId>03jpff543dhffds434rfdsFDN943fdsFkdmqnh892</VersionId>
+        <IsLatest>true</IsLatest>
+        <LastModified>2009-11-12T17:50:30.000Z</LastModified>
+    </DeleteMarker>
+    <Version>
+        <Key>my-image.jpg</Key>
+        <VersionId>3/L4kqtJl40Nr8X8gdRQBpUMLUo</VersionId>
+        <IsLatest>true</IsLatest>
+        <LastModified>2009-10-12T17:50:30.000Z</LastModified>
+        <ETag>"fba9dede5f27731c9771645a39863328"</ETag>
+        <Size>434234</Size>
+        <StorageClass>STANDARD</StorageClass>
+    </Version>
+    <Version>
+        <Key>my-second-image.jpg</Key>
+        <VersionId>QUpfdndhfd8438MNFDN93jdnJFkdmqnh893</VersionId>
+        <IsLatest>false</IsLatest>
+        <LastModified>2009-10-10T17:50:30.000Z</LastModified>
+        <ETag>"9b2cf535f27731c974343645a3985328"</ETag>
+        <Size>166434</Size>
+        <StorageClass>STANDARD</StorageClass>
+    </Version>
+</ListVersionsResult>"#;
+        let result = parse_result_from_bytes(body).expect("parsing should succeed");
+
+        assert_eq!(result.versions.len(), 2);
+        assert_eq!(result.versions[0].object.key, "my-image.jpg");
+        assert_eq!(result.versions[0].object.size, 434234);
+        assert_eq!(result.versions[0].version_id, "3/L4kqtJl40Nr8X8gdRQBpUMLUo");
+        assert!(result.versions[0].is_latest);
+        assert_eq!(result.versions[1].object.key, "my-second-image.jpg");
+        assert!(!result.versions[1].is_latest);
+
+        assert_eq!(result.delete_markers.len(), 1);
+        assert_eq!(result.delete_markers[0].key, "my-second-image.jpg");
+        assert_eq!(
+            result.delete_markers[0].version_id,
+            "03jpff543dhffds434rfdsFDN943fdsFkdmqnh892"
+        );
+        assert!(result.delete_markers[0].is_latest);
+
+        assert!(result.common_prefixes.is_empty());
+        assert_eq!(result.next_key_marker.as_deref(), Some("my-third-image.jpg"));
+        assert_eq!(
+            result.next_version_id_marker.as_deref(),
+            Some("03jpff543dhffds434rfdsFDN943fdsFkdmqnh892")
+        );
+    }
+
+    #[test]
+    fn parse_truncated_without_marker() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?>
+<ListVersionsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>DOC-EXAMPLE-BUCKET</Name>
+    <Prefix></Prefix>
+    <MaxKeys>1000</MaxKeys>
+    <IsTruncated>true</IsTruncated>
+</ListVersionsResult>"#;
+        let result = parse_result_from_bytes(body);
+        assert!(matches!(result, Err(ParseError::InvalidResponse(_, _))));
+    }
+}
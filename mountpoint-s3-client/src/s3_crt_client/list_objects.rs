@@ -4,13 +4,15 @@ use std::str::FromStr;
 
 use mountpoint_s3_crt::http::request_response::Header;
 use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use percent_encoding::percent_decode_str;
 use thiserror::Error;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 use tracing::error;
 
 use crate::object_client::{
-    ListObjectsError, ListObjectsResult, ObjectClientError, ObjectClientResult, ObjectInfo, RestoreStatus,
+    ListObjectsError, ListObjectsParams, ListObjectsResult, ObjectClientError, ObjectClientResult, ObjectInfo, Owner,
+    RestoreStatus,
 };
 use crate::s3_crt_client::{S3CrtClient, S3RequestError};
 
@@ -37,7 +39,7 @@ pub enum ParseError {
 }
 
 /// Copy text out of an XML element, with the right error type.
-fn get_text(element: &xmltree::Element) -> Result<String, ParseError> {
+pub(super) fn get_text(element: &xmltree::Element) -> Result<String, ParseError> {
     Ok(element
         .get_text()
         .ok_or_else(|| ParseError::InvalidResponse(element.clone(), "field has no text".to_string()))?
@@ -45,14 +47,14 @@ fn get_text(element: &xmltree::Element) -> Result<String, ParseError> {
 }
 
 /// Wrapper to get child with some name out of an XML element, with the right error type.
-fn get_child<'a>(element: &'a xmltree::Element, name: &str) -> Result<&'a xmltree::Element, ParseError> {
+pub(super) fn get_child<'a>(element: &'a xmltree::Element, name: &str) -> Result<&'a xmltree::Element, ParseError> {
     element
         .get_child(name)
         .ok_or_else(|| ParseError::MissingField(element.clone(), name.to_string()))
 }
 
 /// Get the text out of a child node, with the right error type.
-fn get_field(element: &xmltree::Element, name: &str) -> Result<String, ParseError> {
+pub(super) fn get_field(element: &xmltree::Element, name: &str) -> Result<String, ParseError> {
     get_text(get_child(element, name)?)
 }
 
@@ -74,6 +76,22 @@ fn parse_result_from_xml(element: &mut xmltree::Element) -> Result<ListObjectsRe
         common_prefixes.push(prefix);
     }
 
+    // Keys and prefixes are URL-encoded if we asked for it, so that keys with characters that
+    // aren't valid in XML 1.0 (like most control characters) can be listed. Some S3-compatible
+    // services ignore the request, so only decode if the response says it's encoded.
+    let url_encoded = element
+        .get_child("EncodingType")
+        .and_then(|elem| elem.get_text())
+        .is_some_and(|encoding| encoding == "url");
+    if url_encoded {
+        for object in objects.iter_mut() {
+            object.key = url_decode(element, &object.key)?;
+        }
+        for prefix in common_prefixes.iter_mut() {
+            *prefix = url_decode(element, prefix)?;
+        }
+    }
+
     let mut next_continuation_token = None;
     if let Some(elem) = element.get_child("NextContinuationToken") {
         next_continuation_token = Some(get_text(elem)?);
@@ -96,6 +114,26 @@ fn parse_result_from_xml(element: &mut xmltree::Element) -> Result<ListObjectsRe
     })
 }
 
+/// Decode a value of a listing that was URL-encoded with `encoding-type=url`. S3 encodes spaces as
+/// `+`, so those need to be decoded before the percent-encoded bytes.
+fn url_decode(element: &xmltree::Element, value: &str) -> Result<String, ParseError> {
+    let value = value.replace('+', " ");
+    percent_decode_str(&value)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| ParseError::InvalidResponse(element.clone(), format!("{value:?} is not valid UTF-8 when decoded")))
+}
+
+fn parse_owner(element: &xmltree::Element) -> Result<Option<Owner>, ParseError> {
+    let Some(owner) = element.get_child("Owner") else {
+        return Ok(None);
+    };
+
+    let id = get_field(owner, "ID")?;
+    let display_name = get_field(owner, "DisplayName").ok();
+    Ok(Some(Owner { id, display_name }))
+}
+
 fn parse_restore_status(element: &xmltree::Element) -> Result<Option<RestoreStatus>, ParseError> {
     let Some(restore_status) = element.get_child("RestoreStatus") else {
         return Ok(None);
@@ -114,7 +152,7 @@ fn parse_restore_status(element: &xmltree::Element) -> Result<Option<RestoreStat
     }))
 }
 
-fn parse_object_info_from_xml(element: &xmltree::Element) -> Result<ObjectInfo, ParseError> {
+pub(super) fn parse_object_info_from_xml(element: &xmltree::Element) -> Result<ObjectInfo, ParseError> {
     let key = get_field(element, "Key")?;
 
     let size = get_field(element, "Size")?;
@@ -134,6 +172,8 @@ fn parse_object_info_from_xml(element: &xmltree::Element) -> Result<ObjectInfo,
 
     let etag = get_field(element, "ETag")?;
 
+    let owner = parse_owner(element)?;
+
     Ok(ObjectInfo {
         key,
         size,
@@ -141,6 +181,7 @@ fn parse_object_info_from_xml(element: &xmltree::Element) -> Result<ObjectInfo,
         storage_class,
         restore_status,
         etag,
+        owner,
     })
 }
 
@@ -148,10 +189,7 @@ impl S3CrtClient {
     pub(super) async fn list_objects(
         &self,
         bucket: &str,
-        continuation_token: Option<&str>,
-        delimiter: &str,
-        max_keys: usize,
-        prefix: &str,
+        params: &ListObjectsParams,
     ) -> ObjectClientResult<ListObjectsResult, ListObjectsError, S3RequestError> {
         // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
         let body = {
@@ -162,16 +200,22 @@ impl S3CrtClient {
             message
                 .set_header(&Header::new("x-amz-optional-object-attributes", "RestoreStatus"))
                 .map_err(S3RequestError::construction_failure)?;
-            let max_keys = format!("{max_keys}");
-            let mut query = vec![
-                ("list-type", "2"),
-                ("delimiter", delimiter),
-                ("max-keys", &max_keys),
-                ("prefix", prefix),
-            ];
-            if let Some(continuation_token) = continuation_token {
+            let max_keys = format!("{}", params.max_keys);
+            let mut query = vec![("list-type", "2"), ("delimiter", params.delimiter.as_str())];
+            if params.url_encoding {
+                query.push(("encoding-type", "url"));
+            }
+            query.push(("max-keys", &max_keys));
+            query.push(("prefix", params.prefix.as_str()));
+            if let Some(continuation_token) = params.continuation_token.as_deref() {
                 query.push(("continuation-token", continuation_token));
             }
+            if let Some(start_after) = params.start_after.as_deref() {
+                query.push(("start-after", start_after));
+            }
+            if params.fetch_owner {
+                query.push(("fetch-owner", "true"));
+            }
 
             message
                 .set_request_path_and_query("/", query)
@@ -181,10 +225,12 @@ impl S3CrtClient {
                 self.inner,
                 "list_objects",
                 bucket,
-                continued = continuation_token.is_some(),
-                delimiter,
-                max_keys,
-                prefix
+                continued = params.continuation_token.is_some(),
+                delimiter = params.delimiter,
+                max_keys = params.max_keys,
+                prefix = params.prefix,
+                start_after = params.start_after,
+                fetch_owner = params.fetch_owner
             );
 
             self.inner
@@ -226,9 +272,21 @@ mod tests {
             crt_error: 1i32.into(),
             error_response_headers: None,
             error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
         }
     }
 
+    #[test]
+    fn parse_url_encoded_listing() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><ListBucketResult><Name>test-bucket</Name><Prefix>dir%2F</Prefix><KeyCount>2</KeyCount><MaxKeys>1000</MaxKeys><Delimiter>%2F</Delimiter><EncodingType>url</EncodingType><IsTruncated>false</IsTruncated><Contents><Key>dir%2Fa+b%01c</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><ETag>&quot;abc&quot;</ETag><Size>3</Size><Owner><ID>1234</ID></Owner><StorageClass>STANDARD</StorageClass></Contents><CommonPrefixes><Prefix>dir%2F%E2%9C%93%2F</Prefix></CommonPrefixes></ListBucketResult>"#;
+        let result = parse_result_from_bytes(&body[..]).expect("should parse");
+        assert_eq!(result.objects.len(), 1);
+        assert_eq!(result.objects[0].key, "dir/a b\u{1}c");
+        assert_eq!(result.objects[0].owner, Some(Owner::new("1234".to_owned(), None)));
+        assert_eq!(result.common_prefixes, vec!["dir/\u{2713}/".to_owned()]);
+    }
+
     #[test]
     fn parse_404_no_such_bucket() {
         let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
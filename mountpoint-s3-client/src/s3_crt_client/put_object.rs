@@ -1,19 +1,34 @@
+use std::collections::VecDeque;
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
-use crate::object_client::{ObjectClientResult, PutObjectError, PutObjectParams, PutObjectRequest, PutObjectResult};
+use crate::checksums::ChecksumHasher;
+use crate::object_client::{
+    CompletedPart, CreateMultipartUploadError, ETag, ObjectClientError, ObjectClientResult, PutObjectError,
+    PutObjectParams, PutObjectRequest, PutObjectResult,
+};
+use crate::s3_crt_client::complete_multipart_upload::CompleteOptions;
 use crate::s3_crt_client::{emit_throughput_metric, S3CrtClient, S3RequestError};
 use async_trait::async_trait;
 use futures::{select_biased, FutureExt as _};
 use mountpoint_s3_crt::http::request_response::{Header, Headers};
 use mountpoint_s3_crt::io::async_stream::{self, AsyncStreamWriter};
-use mountpoint_s3_crt::s3::client::{ChecksumConfig, MetaRequestType, UploadReview};
-use tracing::error;
+use mountpoint_s3_crt::io::futures::{FutureJoinHandle, FutureSpawner};
+use mountpoint_s3_crt::s3::client::{
+    ChecksumConfig, MetaRequestResult, MetaRequestType, UploadReview, UploadReviewPart,
+};
+use tracing::{debug, error, warn};
 
 use super::{S3CrtClientInner, S3HttpRequest};
 
-const SSE_TYPE_HEADER_NAME: &str = "x-amz-server-side-encryption";
-const SSE_KEY_ID_HEADER_NAME: &str = "x-amz-server-side-encryption-aws-kms-key-id";
+pub(super) const SSE_TYPE_HEADER_NAME: &str = "x-amz-server-side-encryption";
+pub(super) const SSE_KEY_ID_HEADER_NAME: &str = "x-amz-server-side-encryption-aws-kms-key-id";
+pub(super) const VERSION_ID_HEADER_NAME: &str = "x-amz-version-id";
+
+/// How many parts of a conditional upload can be uploading at once
+const MAX_CONDITIONAL_PARTS_IN_FLIGHT: usize = 4;
 
 impl S3CrtClient {
     pub(super) async fn put_object(
@@ -22,6 +37,26 @@ impl S3CrtClient {
         key: &str,
         params: &PutObjectParams,
     ) -> ObjectClientResult<S3PutObjectRequest, PutObjectError, S3RequestError> {
+        let upload = if params.if_none_match.is_some() || params.if_match.is_some() {
+            let upload = ConditionalUpload::new(self, bucket, key, params).await?;
+            PutObjectUpload::Conditional(upload)
+        } else {
+            self.start_put_object_meta_request(bucket, key, params)?
+        };
+
+        Ok(S3PutObjectRequest {
+            upload,
+            start_time: Instant::now(),
+            total_bytes: 0,
+        })
+    }
+
+    fn start_put_object_meta_request(
+        &self,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> Result<PutObjectUpload, S3RequestError> {
         let span = request_span!(self.inner, "put_object", bucket, key);
         let mut message = self
             .inner
@@ -33,8 +68,8 @@ impl S3CrtClient {
             .set_request_path(&key)
             .map_err(S3RequestError::construction_failure)?;
 
-        if params.trailing_checksums {
-            let checksum_config = ChecksumConfig::trailing_crc32c();
+        if let Some(algorithm) = params.checksum_algorithm {
+            let checksum_config = ChecksumConfig::trailing(algorithm);
             message.set_checksum_config(Some(checksum_config));
         }
 
@@ -59,6 +94,14 @@ impl S3CrtClient {
                 .set_header(&Header::new(SSE_KEY_ID_HEADER_NAME, key_id))
                 .map_err(S3RequestError::construction_failure)?;
         }
+        if let Some(sse_customer_key) = params.sse_customer_key.as_ref() {
+            message
+                .set_sse_customer_key(sse_customer_key)
+                .map_err(S3RequestError::construction_failure)?;
+        }
+        message
+            .set_object_headers(params)
+            .map_err(S3RequestError::construction_failure)?;
         // Variable `response_headers` will be accessed from different threads: from CRT thread which executes `on_headers` callback
         // and from our thread which executes `review_and_complete`. Callback `on_headers` is guaranteed to finish before this
         // variable is accessed in `review_and_complete` (see `S3HttpRequest::poll` implementation).
@@ -69,21 +112,49 @@ impl S3CrtClient {
         };
         let mut options = S3CrtClientInner::new_meta_request_options(message, MetaRequestType::PutObject);
         options.on_upload_review(move |review| callback.invoke(review));
-        let body = self
-            .inner
-            .make_simple_http_request_from_options(options, span, |_| None, on_headers)?;
+        let body =
+            self.inner
+                .make_simple_http_request_from_options(options, span, parse_put_object_error, on_headers)?;
 
-        Ok(S3PutObjectRequest {
+        Ok(PutObjectUpload::MetaRequest {
             body,
             writer,
             review_callback,
-            start_time: Instant::now(),
-            total_bytes: 0,
             response_headers,
         })
     }
 }
 
+fn parse_put_object_error(result: &MetaRequestResult) -> Option<PutObjectError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchBucket" => Some(PutObjectError::NoSuchBucket),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Map the error code of a failed CompleteMultipartUpload request of a conditional upload
+fn conditional_put_object_error_from_code(code: &str) -> Option<PutObjectError> {
+    match code {
+        "NoSuchBucket" => Some(PutObjectError::NoSuchBucket),
+        "PreconditionFailed" => Some(PutObjectError::PreconditionFailed),
+        // An If-Match precondition can't hold if there's no object to match
+        "NoSuchKey" => Some(PutObjectError::PreconditionFailed),
+        // A concurrent conditional write to the same key was in progress, so S3 couldn't check the
+        // preconditions
+        "ConditionalRequestConflict" => Some(PutObjectError::ConditionalRequestConflict),
+        _ => None,
+    }
+}
+
 type ReviewCallback = dyn FnOnce(UploadReview) -> bool + Send;
 
 /// Holder for the upload review callback.
@@ -123,16 +194,26 @@ impl ReviewCallbackBox {
 /// object.
 #[derive(Debug)]
 pub struct S3PutObjectRequest {
-    body: S3HttpRequest<Vec<u8>, PutObjectError>,
-    writer: AsyncStreamWriter,
-    review_callback: ReviewCallbackBox,
+    upload: PutObjectUpload,
     start_time: Instant,
     total_bytes: u64,
-    /// Headers of the CompleteMultipartUpload response, available after the request was finished
-    response_headers: Arc<Mutex<Option<Headers>>>,
 }
 
-fn try_get_header_value(headers: &Headers, key: &str) -> Option<String> {
+#[derive(Debug)]
+enum PutObjectUpload {
+    /// An upload made by a CRT PutObject meta request
+    MetaRequest {
+        body: S3HttpRequest<Vec<u8>, PutObjectError>,
+        writer: AsyncStreamWriter,
+        review_callback: ReviewCallbackBox,
+        /// Headers of the CompleteMultipartUpload response, available after the request was finished
+        response_headers: Arc<Mutex<Option<Headers>>>,
+    },
+    /// An upload with preconditions, whose requests we make ourselves
+    Conditional(ConditionalUpload),
+}
+
+pub(super) fn try_get_header_value(headers: &Headers, key: &str) -> Option<String> {
     headers.get(key).ok()?.value().clone().into_string().ok()
 }
 
@@ -141,19 +222,25 @@ impl PutObjectRequest for S3PutObjectRequest {
     type ClientError = S3RequestError;
 
     async fn write(&mut self, slice: &[u8]) -> ObjectClientResult<(), PutObjectError, Self::ClientError> {
-        // Check if the request has already finished (which can only happen because of an error in
-        // the request), and fail the write if so. Ordering doesn't matter here as it should be
-        // impossible for the request to succeed while we still hold `&mut self`, as no one can call
-        // `complete()`.
-        select_biased! {
-            result = self.writer.write(slice).fuse() => {
-                self.total_bytes += slice.len() as u64;
-                result.map_err(|e| S3RequestError::InternalError(Box::new(e)).into())
-            }
+        match &mut self.upload {
+            PutObjectUpload::MetaRequest { body, writer, .. } => {
+                // Check if the request has already finished (which can only happen because of an error in
+                // the request), and fail the write if so. Ordering doesn't matter here as it should be
+                // impossible for the request to succeed while we still hold `&mut self`, as no one can call
+                // `complete()`.
+                select_biased! {
+                    result = writer.write(slice).fuse() => {
+                        result.map_err(|e| S3RequestError::InternalError(Box::new(e)))?;
+                    }
 
-            // Request can't have succeeded if we still own `&mut self`
-            result = (&mut self.body).fuse() => Err(result.expect_err("request can't succeed while still writing")),
+                    // Request can't have succeeded if we still own `&mut self`
+                    result = body.fuse() => return Err(result.expect_err("request can't succeed while still writing")),
+                }
+            }
+            PutObjectUpload::Conditional(upload) => upload.write(slice).await?,
         }
+        self.total_bytes += slice.len() as u64;
+        Ok(())
     }
 
     async fn complete(self) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
@@ -161,42 +248,341 @@ impl PutObjectRequest for S3PutObjectRequest {
     }
 
     async fn review_and_complete(
-        mut self,
-        review_callback: impl FnOnce(UploadReview) -> bool + Send + 'static,
+        self,
+        review_callback_fn: impl FnOnce(UploadReview) -> bool + Send + 'static,
     ) -> ObjectClientResult<PutObjectResult, PutObjectError, Self::ClientError> {
-        self.review_callback.set(review_callback);
+        let response_headers = match self.upload {
+            PutObjectUpload::MetaRequest {
+                body,
+                writer,
+                mut review_callback,
+                response_headers,
+            } => {
+                review_callback.set(review_callback_fn);
 
-        let mut request = self.body.fuse();
+                let mut request = body.fuse();
 
-        // Check if the request has already finished (because of an error), and fail the write if
-        // so. Ordering here is significant: if both futures are ready (which could happen if we
-        // were not polled for a long time, and so the request succeeded _because_ of the
-        // `self.writer.complete()` call), we want to proceed with the complete path.
-        select_biased! {
-            result = self.writer.complete().fuse() => {
-                let _ = result.map_err(|e| S3RequestError::InternalError(Box::new(e)))?;
+                // Check if the request has already finished (because of an error), and fail the write if
+                // so. Ordering here is significant: if both futures are ready (which could happen if we
+                // were not polled for a long time, and so the request succeeded _because_ of the
+                // `writer.complete()` call), we want to proceed with the complete path.
+                select_biased! {
+                    result = writer.complete().fuse() => {
+                        let _ = result.map_err(|e| S3RequestError::InternalError(Box::new(e)))?;
 
-                // Now wait for the request to finish.
-                let _ = request.await?;
-            }
+                        // Now wait for the request to finish.
+                        let _ = request.await?;
+                    }
 
-            // Request can't have succeeded if we still own it and the `complete()` future is still
-            // incomplete, which we know it is because this is a biased select.
-            result = request => return Err(result.expect_err("request can't succeed while still completing")),
+                    // Request can't have succeeded if we still own it and the `complete()` future is still
+                    // incomplete, which we know it is because this is a biased select.
+                    result = request => return Err(result.expect_err("request can't succeed while still completing")),
+                };
+
+                let response_headers = response_headers
+                    .lock()
+                    .expect("must be able to acquire headers lock")
+                    .take()
+                    .expect("PUT response headers must be available at this point");
+                response_headers
+            }
+            PutObjectUpload::Conditional(upload) => upload.complete(review_callback_fn).await?,
         };
 
         let elapsed = self.start_time.elapsed();
         emit_throughput_metric(self.total_bytes, elapsed, "put_object");
+        metrics::counter!("s3.requests.uploaded_bytes", "op" => "put_object").increment(self.total_bytes);
 
-        let response_headers = self
-            .response_headers
-            .lock()
-            .expect("must be able to acquire headers lock")
-            .take()
-            .expect("PUT response headers must be available at this point");
         Ok(PutObjectResult {
             sse_type: try_get_header_value(&response_headers, SSE_TYPE_HEADER_NAME),
             sse_kms_key_id: try_get_header_value(&response_headers, SSE_KEY_ID_HEADER_NAME),
+            version_id: try_get_header_value(&response_headers, VERSION_ID_HEADER_NAME),
         })
     }
 }
+
+/// A part of a conditional upload that was uploaded
+#[derive(Debug)]
+struct UploadedPart {
+    part_number: usize,
+    etag: ETag,
+    size: u64,
+    checksum: Option<String>,
+}
+
+type UploadPartHandle = FutureJoinHandle<ObjectClientResult<UploadedPart, PutObjectError, S3RequestError>>;
+
+/// A PutObject request with preconditions, which we upload with our own multipart upload requests
+/// rather than with a CRT PutObject meta request.
+///
+/// S3 only checks the preconditions of a multipart upload on its CompleteMultipartUpload request.
+/// The CRT copies the headers of a PutObject meta request into every request of its multipart
+/// upload, including the UploadPart requests, and has no way to add headers to
+/// CompleteMultipartUpload alone. So we split the object into parts of the client's part size
+/// ourselves, and only send the preconditions with CompleteMultipartUpload. Parts are uploaded in
+/// the background, at most [MAX_CONDITIONAL_PARTS_IN_FLIGHT] at a time.
+///
+/// If the upload is dropped before it completes, it's aborted in the background.
+struct ConditionalUpload {
+    client: S3CrtClient,
+    bucket: String,
+    key: String,
+    upload_id: String,
+    params: PutObjectParams,
+    part_size: usize,
+    /// Data for the next part
+    buffer: Vec<u8>,
+    /// Parts being uploaded, in order of part number
+    in_flight: VecDeque<UploadPartHandle>,
+    /// Parts that finished uploading, in order of part number
+    parts: Vec<UploadedPart>,
+    /// Whether the upload was completed, and so mustn't be aborted when dropped
+    completed: bool,
+}
+
+impl std::fmt::Debug for ConditionalUpload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConditionalUpload")
+            .field("bucket", &self.bucket)
+            .field("key", &self.key)
+            .field("upload_id", &self.upload_id)
+            .field("buffered", &self.buffer.len())
+            .field("in_flight", &self.in_flight.len())
+            .field("parts", &self.parts)
+            .finish()
+    }
+}
+
+impl ConditionalUpload {
+    async fn new(
+        client: &S3CrtClient,
+        bucket: &str,
+        key: &str,
+        params: &PutObjectParams,
+    ) -> ObjectClientResult<Self, PutObjectError, S3RequestError> {
+        let result = client
+            .retry_timeouts(|| client.create_multipart_upload(bucket, key, params))
+            .await
+            .map_err(|e| match e {
+                ObjectClientError::ServiceError(CreateMultipartUploadError::NoSuchBucket) => {
+                    ObjectClientError::ServiceError(PutObjectError::NoSuchBucket)
+                }
+                ObjectClientError::ClientError(e) => ObjectClientError::ClientError(e),
+            })?;
+        debug!(bucket, key, upload_id = result.upload_id, "started conditional upload");
+
+        Ok(Self {
+            client: client.clone(),
+            bucket: bucket.to_owned(),
+            key: key.to_owned(),
+            upload_id: result.upload_id,
+            params: params.clone(),
+            part_size: client.inner.part_size,
+            buffer: Vec::new(),
+            in_flight: VecDeque::new(),
+            parts: Vec::new(),
+            completed: false,
+        })
+    }
+
+    async fn write(&mut self, mut slice: &[u8]) -> ObjectClientResult<(), PutObjectError, S3RequestError> {
+        while !slice.is_empty() {
+            let len = slice.len().min(self.part_size - self.buffer.len());
+            self.buffer.extend_from_slice(&slice[..len]);
+            slice = &slice[len..];
+            if self.buffer.len() == self.part_size {
+                self.upload_buffer().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start uploading the buffered data as the next part, after waiting for an earlier part to
+    /// finish if too many are in flight
+    async fn upload_buffer(&mut self) -> ObjectClientResult<(), PutObjectError, S3RequestError> {
+        if self.in_flight.len() >= MAX_CONDITIONAL_PARTS_IN_FLIGHT {
+            self.wait_for_part().await?;
+        }
+
+        let contents = std::mem::take(&mut self.buffer);
+        let part_number = self.parts.len() + self.in_flight.len() + 1;
+        let algorithm = self.params.checksum_algorithm;
+        let checksum = algorithm.map(|algorithm| {
+            let mut hasher = ChecksumHasher::new(algorithm);
+            hasher.update(&contents);
+            hasher.finalize_base64()
+        });
+
+        let client = self.client.clone();
+        let bucket = self.bucket.clone();
+        let key = self.key.clone();
+        let upload_id = self.upload_id.clone();
+        let sse_customer_key = self.params.sse_customer_key.clone();
+        let handle = self.client.inner.event_loop_group.spawn_future(async move {
+            let part_checksum = algorithm.zip(checksum.as_deref());
+            let result = client
+                .retry_timeouts(|| {
+                    client.upload_part_with(
+                        &bucket,
+                        &key,
+                        &upload_id,
+                        part_number,
+                        &contents,
+                        part_checksum,
+                        sse_customer_key.as_ref(),
+                    )
+                })
+                .await
+                .map_err(|e| match e {
+                    ObjectClientError::ServiceError(e) => {
+                        ObjectClientError::ClientError(S3RequestError::InternalError(Box::new(e)))
+                    }
+                    ObjectClientError::ClientError(e) => ObjectClientError::ClientError(e),
+                })?;
+            Ok(UploadedPart {
+                part_number,
+                etag: result.etag,
+                size: contents.len() as u64,
+                checksum,
+            })
+        });
+        self.in_flight.push_back(handle);
+        Ok(())
+    }
+
+    /// Wait for the oldest part in flight to finish uploading
+    async fn wait_for_part(&mut self) -> ObjectClientResult<(), PutObjectError, S3RequestError> {
+        let Some(handle) = self.in_flight.pop_front() else {
+            return Ok(());
+        };
+        let part = handle
+            .into_future()
+            .await
+            .map_err(|e| S3RequestError::InternalError(Box::new(e)))??;
+        self.parts.push(part);
+        Ok(())
+    }
+
+    /// Upload the remaining data, let the `review_callback` review the parts, and complete the
+    /// upload with its preconditions. Returns the headers of the CompleteMultipartUpload response.
+    async fn complete(
+        mut self,
+        review_callback: impl FnOnce(UploadReview) -> bool,
+    ) -> ObjectClientResult<Headers, PutObjectError, S3RequestError> {
+        // An empty object is uploaded as a single empty part
+        if !self.buffer.is_empty() || self.parts.is_empty() && self.in_flight.is_empty() {
+            self.upload_buffer().await?;
+        }
+        while !self.in_flight.is_empty() {
+            self.wait_for_part().await?;
+        }
+
+        let review = UploadReview {
+            parts: self
+                .parts
+                .iter()
+                .map(|part| UploadReviewPart {
+                    size: part.size,
+                    checksum: part.checksum.clone(),
+                })
+                .collect(),
+            checksum_algorithm: self.params.checksum_algorithm,
+        };
+        if !review_callback(review) {
+            return Err(ObjectClientError::ClientError(S3RequestError::RequestCanceled));
+        }
+
+        let completed_parts: Vec<_> = self
+            .parts
+            .iter()
+            .map(|part| CompletedPart {
+                part_number: part.part_number,
+                etag: part.etag.clone(),
+            })
+            .collect();
+        let checksums: Vec<_> = self.parts.iter().filter_map(|part| part.checksum.clone()).collect();
+        let options = CompleteOptions {
+            checksums: self
+                .params
+                .checksum_algorithm
+                .map(|algorithm| (algorithm, &checksums[..])),
+            sse_customer_key: self.params.sse_customer_key.as_ref(),
+            if_none_match: self.params.if_none_match.as_deref(),
+            if_match: self.params.if_match.as_ref(),
+        };
+        self.client.inner.throttle().await;
+        let (_result, response_headers) = self
+            .client
+            .complete_multipart_upload_with(
+                &self.bucket,
+                &self.key,
+                &self.upload_id,
+                &completed_parts,
+                &options,
+                conditional_put_object_error_from_code,
+            )
+            .await?;
+        self.completed = true;
+        Ok(response_headers)
+    }
+}
+
+impl Drop for ConditionalUpload {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        // S3 keeps the parts of an upload that's neither completed nor aborted, so clean them up.
+        // Parts still in flight will fail once the upload is aborted.
+        let client = self.client.clone();
+        let bucket = std::mem::take(&mut self.bucket);
+        let key = std::mem::take(&mut self.key);
+        let upload_id = std::mem::take(&mut self.upload_id);
+        let _ = self.client.inner.event_loop_group.spawn_future(async move {
+            let result = client
+                .retry_timeouts(|| client.abort_multipart_upload(&bucket, &key, &upload_id))
+                .await;
+            match result {
+                Ok(_) => debug!(bucket, key, upload_id, "aborted conditional upload"),
+                Err(error) => warn!(bucket, key, upload_id, ?error, "failed to abort conditional upload"),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use test_case::test_case;
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_bucket() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_put_object_error(&result);
+        assert_eq!(result, Some(PutObjectError::NoSuchBucket));
+    }
+
+    #[test_case("PreconditionFailed", Some(PutObjectError::PreconditionFailed); "precondition failed")]
+    #[test_case("NoSuchKey", Some(PutObjectError::PreconditionFailed); "if-match on missing key")]
+    #[test_case("ConditionalRequestConflict", Some(PutObjectError::ConditionalRequestConflict); "conflicting conditional write")]
+    #[test_case("NoSuchBucket", Some(PutObjectError::NoSuchBucket); "no such bucket")]
+    #[test_case("InternalError", None; "other error")]
+    fn parse_conditional_put_object_error(code: &str, expected: Option<PutObjectError>) {
+        assert_eq!(conditional_put_object_error_from_code(code), expected);
+    }
+}
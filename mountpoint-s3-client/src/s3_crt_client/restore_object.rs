@@ -0,0 +1,155 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+
+use mountpoint_s3_crt::http::request_response::{Header, Headers};
+use mountpoint_s3_crt::io::async_stream;
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+use xmltree::{Element, XMLNode};
+
+use crate::object_client::{ObjectClientResult, RestoreObjectError, RestoreObjectResult, RestoreTier};
+use crate::s3_crt_client::{S3CrtClient, S3CrtClientInner, S3RequestError};
+
+/// Build the RestoreObject XML document for a restore of the given duration and tier
+fn build_request_body(days: u32, tier: RestoreTier) -> Vec<u8> {
+    fn text_element(name: &str, text: String) -> XMLNode {
+        let mut element = Element::new(name);
+        element.children.push(XMLNode::Text(text));
+        XMLNode::Element(element)
+    }
+
+    let mut job_parameters = Element::new("GlacierJobParameters");
+    job_parameters
+        .children
+        .push(text_element("Tier", tier.as_str().to_owned()));
+
+    let mut root = Element::new("RestoreRequest");
+    root.children.push(text_element("Days", format!("{days}")));
+    root.children.push(XMLNode::Element(job_parameters));
+
+    let mut body = Vec::new();
+    root.write(&mut body).expect("writing to a Vec can't fail");
+    body
+}
+
+impl S3CrtClient {
+    /// Create and begin a new RestoreObject request.
+    pub(super) async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: u32,
+        tier: RestoreTier,
+    ) -> ObjectClientResult<RestoreObjectResult, RestoreObjectError, S3RequestError> {
+        let span = request_span!(self.inner, "restore_object", bucket, key, days, tier = tier.as_str());
+
+        let body = build_request_body(days, tier);
+        // S3 responds 202 Accepted when it starts a new restore, and 200 OK when the object was
+        // already restored and it only updated the expiry of the restored copy
+        let response_status = Arc::new(AtomicI32::new(0));
+
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let (request, writer) = {
+            let mut message = self
+                .inner
+                .new_request_template("POST", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_request_path_and_query(format!("/{key}"), [("restore", "")])
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Length", format!("{}", body.len())))
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Type", "application/xml"))
+                .map_err(S3RequestError::construction_failure)?;
+
+            let (body_async_stream, writer) = async_stream::new_stream(&self.inner.allocator);
+            message.set_body_stream(Some(body_async_stream));
+
+            let response_status_writer = response_status.clone();
+            let on_headers = move |_: &Headers, status: i32| {
+                response_status_writer.store(status, Ordering::SeqCst);
+            };
+            let options = S3CrtClientInner::new_meta_request_options(message, MetaRequestType::Default);
+            let request = self.inner.make_simple_http_request_from_options(
+                options,
+                span,
+                parse_restore_object_error,
+                on_headers,
+            )?;
+            (request, writer)
+        };
+
+        let _body = request.write_body_and_wait(writer, &body).await?;
+
+        Ok(RestoreObjectResult {
+            already_restored: response_status.load(Ordering::SeqCst) == 200,
+        })
+    }
+}
+
+fn parse_restore_object_error(result: &MetaRequestResult) -> Option<RestoreObjectError> {
+    match result.response_status {
+        403 | 404 | 409 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchBucket" => Some(RestoreObjectError::NoSuchBucket),
+                "NoSuchKey" => Some(RestoreObjectError::NoSuchKey),
+                "InvalidObjectState" => Some(RestoreObjectError::InvalidObjectState),
+                "RestoreAlreadyInProgress" => Some(RestoreObjectError::RestoreAlreadyInProgress),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use test_case::test_case;
+
+    use super::*;
+    use crate::s3_crt_client::list_objects::get_field;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test_case(404, "NoSuchKey", Some(RestoreObjectError::NoSuchKey); "no such key")]
+    #[test_case(404, "NoSuchBucket", Some(RestoreObjectError::NoSuchBucket); "no such bucket")]
+    #[test_case(403, "InvalidObjectState", Some(RestoreObjectError::InvalidObjectState); "invalid object state")]
+    #[test_case(403, "AccessDenied", None; "access denied")]
+    #[test_case(409, "RestoreAlreadyInProgress", Some(RestoreObjectError::RestoreAlreadyInProgress); "already in progress")]
+    fn parse_error(status: i32, code: &str, expected: Option<RestoreObjectError>) {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>{code}</Code><Message>message</Message><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#
+        );
+        let result = make_result(status, OsStr::new(&body));
+        let result = parse_restore_object_error(&result);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn request_body_has_days_and_tier() {
+        let body = build_request_body(7, RestoreTier::Bulk);
+        let root = Element::parse(&body[..]).expect("body should be valid XML");
+        assert_eq!(root.name, "RestoreRequest");
+        assert_eq!(get_field(&root, "Days").unwrap(), "7");
+        let job_parameters = root.get_child("GlacierJobParameters").unwrap();
+        assert_eq!(get_field(job_parameters, "Tier").unwrap(), "Bulk");
+    }
+}
@@ -0,0 +1,165 @@
+use std::ops::Deref;
+use std::os::unix::prelude::OsStrExt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use mountpoint_s3_crt::http::request_response::{Header, Headers};
+use mountpoint_s3_crt::io::async_stream;
+use mountpoint_s3_crt::s3::client::{MetaRequestResult, MetaRequestType};
+
+use crate::checksums::checksum_algorithm_name;
+use crate::object_client::{
+    ChecksumAlgorithm, ETag, ObjectClientError, ObjectClientResult, SseCustomerKey, UploadPartError, UploadPartResult,
+};
+use crate::s3_crt_client::put_object::try_get_header_value;
+use crate::s3_crt_client::{S3CrtClient, S3CrtClientInner, S3RequestError};
+
+impl S3CrtClient {
+    /// Create and begin a new UploadPart request.
+    pub(super) async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, S3RequestError> {
+        self.upload_part_with(bucket, key, upload_id, part_number, contents, None, None)
+            .await
+    }
+
+    /// Create and begin a new UploadPart request that also sends the given checksum of the part and
+    /// SSE-C key, which the multipart uploads driven by [S3CrtClient::put_object] need.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn upload_part_with(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        contents: &[u8],
+        checksum: Option<(ChecksumAlgorithm, &str)>,
+        sse_customer_key: Option<&SseCustomerKey>,
+    ) -> ObjectClientResult<UploadPartResult, UploadPartError, S3RequestError> {
+        let span = request_span!(
+            self.inner,
+            "upload_part",
+            bucket,
+            key,
+            upload_id,
+            part_number,
+            size = contents.len()
+        );
+
+        let response_headers: Arc<Mutex<Option<Headers>>> = Default::default();
+
+        // Scope the endpoint, message, etc. since otherwise rustc thinks we use Message across the await.
+        let (request, writer) = {
+            let mut message = self
+                .inner
+                .new_request_template("PUT", bucket)
+                .map_err(S3RequestError::construction_failure)?;
+            let part_number = format!("{part_number}");
+            message
+                .set_request_path_and_query(
+                    format!("/{key}"),
+                    [("partNumber", part_number.as_str()), ("uploadId", upload_id)],
+                )
+                .map_err(S3RequestError::construction_failure)?;
+            message
+                .set_header(&Header::new("Content-Length", format!("{}", contents.len())))
+                .map_err(S3RequestError::construction_failure)?;
+            if let Some((algorithm, checksum)) = checksum {
+                let name = format!("x-amz-checksum-{}", checksum_algorithm_name(algorithm).to_lowercase());
+                message
+                    .set_header(&Header::new(name, checksum))
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+            if let Some(sse_customer_key) = sse_customer_key {
+                message
+                    .set_sse_customer_key(sse_customer_key)
+                    .map_err(S3RequestError::construction_failure)?;
+            }
+
+            let (body_async_stream, writer) = async_stream::new_stream(&self.inner.allocator);
+            message.set_body_stream(Some(body_async_stream));
+
+            let response_headers_writer = response_headers.clone();
+            let on_headers = move |headers: &Headers, _: i32| {
+                *response_headers_writer.lock().unwrap() = Some(headers.clone());
+            };
+            let options = S3CrtClientInner::new_meta_request_options(message, MetaRequestType::Default);
+            let request =
+                self.inner
+                    .make_simple_http_request_from_options(options, span, parse_upload_part_error, on_headers)?;
+            (request, writer)
+        };
+
+        let _body = request.write_body_and_wait(writer, contents).await?;
+        metrics::counter!("s3.requests.uploaded_bytes", "op" => "upload_part").increment(contents.len() as u64);
+
+        let response_headers = response_headers
+            .lock()
+            .expect("must be able to acquire headers lock")
+            .take()
+            .expect("UploadPart response headers must be available at this point");
+        let etag = try_get_header_value(&response_headers, "ETag").ok_or_else(|| {
+            ObjectClientError::ClientError(S3RequestError::InternalError(
+                "missing ETag in UploadPart response".into(),
+            ))
+        })?;
+        let etag = ETag::from_str(&etag).expect("ETag parsing is infallible");
+
+        Ok(UploadPartResult { etag })
+    }
+}
+
+fn parse_upload_part_error(result: &MetaRequestResult) -> Option<UploadPartError> {
+    match result.response_status {
+        404 => {
+            let body = result.error_response_body.as_ref()?;
+            let root = xmltree::Element::parse(body.as_bytes()).ok()?;
+            let error_code = root.get_child("Code")?;
+            let error_str = error_code.get_text()?;
+            match error_str.deref() {
+                "NoSuchUpload" => Some(UploadPartError::NoSuchUpload),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use super::*;
+
+    fn make_result(response_status: i32, body: impl Into<OsString>) -> MetaRequestResult {
+        MetaRequestResult {
+            response_status,
+            crt_error: 1i32.into(),
+            error_response_headers: None,
+            error_response_body: Some(body.into()),
+            did_validate: false,
+            validation_algorithm: None,
+        }
+    }
+
+    #[test]
+    fn parse_404_no_such_upload() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchUpload</Code><Message>The specified upload does not exist. The upload ID may be invalid, or the upload may have been aborted or completed.</Message><UploadId>VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId><RequestId>CQ7FMXPZ3P5BNF4N</RequestId><HostId>yyzvVi9oF9iZUcVzPNCTmazSJkoYl1plKUhlXm/8y6qJqbnvDqbPzeUoO9G3e8jIDKH7BIj9ccw=</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_upload_part_error(&result);
+        assert_eq!(result, Some(UploadPartError::NoSuchUpload));
+    }
+
+    #[test]
+    fn parse_404_no_such_bucket() {
+        let body = br#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message><BucketName>DOC-EXAMPLE-BUCKET</BucketName><RequestId>4YAYHJ0E82DDDNF0</RequestId><HostId>Ajn9+i3d3VWQi339YrGqBbJqQlj5HaX2vplXp9IlDPAxsJ4vsIAsje0P2gJ0of/mTKKz/fv9pNy9RqhbLUBc/g==</HostId></Error>"#;
+        let result = make_result(404, OsStr::from_bytes(&body[..]));
+        let result = parse_upload_part_error(&result);
+        assert_eq!(result, None);
+    }
+}
@@ -4,6 +4,9 @@ pub mod common;
 
 use std::io::Write;
 use std::option::Option::None;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use aws_config::default_provider::credentials::DefaultCredentialsChain;
 use aws_credential_types::provider::ProvideCredentials;
@@ -15,10 +18,11 @@ use futures::StreamExt;
 use mountpoint_s3_client::config::{EndpointConfig, S3ClientAuthConfig, S3ClientConfig};
 #[cfg(not(feature = "s3express_tests"))]
 use mountpoint_s3_client::error::ObjectClientError;
+use mountpoint_s3_client::types::ListObjectsParams;
 #[cfg(not(feature = "s3express_tests"))]
 use mountpoint_s3_client::S3RequestError;
 use mountpoint_s3_client::{ObjectClient, S3CrtClient};
-use mountpoint_s3_crt::auth::credentials::{CredentialsProvider, CredentialsProviderStaticOptions};
+use mountpoint_s3_crt::auth::credentials::{Credentials, CredentialsProvider, CredentialsProviderStaticOptions};
 use mountpoint_s3_crt::common::allocator::Allocator;
 use rusty_fork::rusty_fork_test;
 use tempfile::NamedTempFile;
@@ -63,7 +67,7 @@ async fn test_static_provider() {
     let client = S3CrtClient::new(config).unwrap();
 
     let result = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should succeed");
     check_get_result(result, None, &body[..]).await;
@@ -83,7 +87,7 @@ async fn test_static_provider() {
     let client = S3CrtClient::new(config).unwrap();
 
     let mut request = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object request should be sent");
 
@@ -94,6 +98,101 @@ async fn test_static_provider() {
         .expect_err("bogus credentials should not work");
 }
 
+/// Test creating a client with a credentials provider that delegates to a Rust closure
+#[tokio::test]
+async fn test_delegate_provider() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_delegate_provider");
+
+    let key = format!("{prefix}/hello");
+    let body = b"hello world!";
+    sdk_client
+        .put_object()
+        .bucket(&bucket)
+        .key(&key)
+        .body(ByteStream::from(Bytes::from_static(body)))
+        .send()
+        .await
+        .unwrap();
+
+    // Get some static credentials by just using the SDK's default provider, which we know works
+    let sdk_provider = DefaultCredentialsChain::builder()
+        .region(Region::new(get_test_region()))
+        .build()
+        .await;
+    let credentials = sdk_provider
+        .provide_credentials()
+        .await
+        .expect("static credentials should be available");
+
+    // Hand out those credentials from a delegate, counting how often it's called
+    let calls = Arc::new(AtomicUsize::new(0));
+    let delegate_credentials = Credentials {
+        access_key_id: credentials.access_key_id().to_owned(),
+        secret_access_key: credentials.secret_access_key().to_owned(),
+        session_token: credentials.session_token().map(str::to_owned),
+        expiration: Some(SystemTime::now() + Duration::from_secs(3600)),
+    };
+    let provider = {
+        let calls = calls.clone();
+        CredentialsProvider::new_delegate(&Allocator::default(), move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(delegate_credentials.clone())
+        })
+        .unwrap()
+    };
+    let config = S3ClientConfig::new()
+        .auth_config(S3ClientAuthConfig::Provider(provider))
+        .endpoint_config(EndpointConfig::new(&get_test_region()));
+    let client = S3CrtClient::new(config).unwrap();
+
+    for _ in 0..2 {
+        let result = client
+            .get_object(&bucket, &key, &Default::default())
+            .await
+            .expect("get_object should succeed");
+        check_get_result(result, None, &body[..]).await;
+    }
+    // The credentials don't expire for an hour, so they should have been cached
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // A delegate that fails should fail the request
+    let provider = CredentialsProvider::new_delegate(&Allocator::default(), || Err("no credentials".into())).unwrap();
+    let config = S3ClientConfig::new()
+        .auth_config(S3ClientAuthConfig::Provider(provider))
+        .endpoint_config(EndpointConfig::new(&get_test_region()));
+    let client = S3CrtClient::new(config).unwrap();
+
+    let mut request = client
+        .get_object(&bucket, &key, &Default::default())
+        .await
+        .expect("get_object request should be sent");
+
+    let _error = request
+        .next()
+        .await
+        .unwrap()
+        .expect_err("failing delegate should not work");
+
+    // A delegate that panics should also fail the request, rather than unwinding into the CRT
+    let provider = CredentialsProvider::new_delegate(&Allocator::default(), || panic!("delegate panicked")).unwrap();
+    let config = S3ClientConfig::new()
+        .auth_config(S3ClientAuthConfig::Provider(provider))
+        .endpoint_config(EndpointConfig::new(&get_test_region()));
+    let client = S3CrtClient::new(config).unwrap();
+
+    let mut request = client
+        .get_object(&bucket, &key, &Default::default())
+        .await
+        .expect("get_object request should be sent");
+
+    let _error = request
+        .next()
+        .await
+        .unwrap()
+        .expect_err("panicking delegate should not work");
+}
+
 /// Test creating a client with the profile credentials provider
 ///
 /// This is complicated because CLI profiles are inherently global state, but we want to isolate the
@@ -152,7 +251,7 @@ async fn test_profile_provider_async() {
     let client = S3CrtClient::new(config).unwrap();
 
     let result = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should succeed");
     check_get_result(result, None, &body[..]).await;
@@ -168,7 +267,7 @@ async fn test_profile_provider_async() {
     let client = S3CrtClient::new(config).unwrap();
 
     let mut request = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should be sent");
 
@@ -256,17 +355,23 @@ async fn test_scoped_credentials() {
 
     // Inside the prefix, things should be fine
     let _result = client
-        .get_object(&bucket, &format!("{prefix}foo/foo.txt"), None, None)
+        .get_object(&bucket, &format!("{prefix}foo/foo.txt"), &Default::default())
         .await
         .expect("get_object should succeed");
     let _result = client
-        .list_objects(&bucket, None, "/", 10, &format!("{prefix}foo/"))
+        .list_objects(
+            &bucket,
+            &ListObjectsParams::new()
+                .prefix(&format!("{prefix}foo/"))
+                .delimiter("/")
+                .max_keys(10),
+        )
         .await
         .expect("list_objects_should_succeed");
 
     // Outside the prefix, requests should fail with permissions errors
     let mut request = client
-        .get_object(&bucket, &format!("{prefix}baz.txt"), None, None)
+        .get_object(&bucket, &format!("{prefix}baz.txt"), &Default::default())
         .await
         .expect("request should be sent");
     let err = request
@@ -279,7 +384,13 @@ async fn test_scoped_credentials() {
         ObjectClientError::ClientError(S3RequestError::Forbidden(_))
     ));
     let err = client
-        .list_objects(&bucket, None, "/", 10, &format!("{prefix}/"))
+        .list_objects(
+            &bucket,
+            &ListObjectsParams::new()
+                .prefix(&format!("{prefix}/"))
+                .delimiter("/")
+                .max_keys(10),
+        )
         .await
         .expect_err("should fail in different prefix");
     assert!(matches!(
@@ -287,3 +398,106 @@ async fn test_scoped_credentials() {
         ObjectClientError::ClientError(S3RequestError::Forbidden(_))
     ));
 }
+
+/// Test creating a client that assumes a role using the default credentials as its source
+#[tokio::test]
+#[cfg(not(feature = "s3express_tests"))]
+async fn test_assume_role_provider() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_assume_role_provider");
+
+    let key = format!("{prefix}/hello");
+    let body = b"hello world!";
+    sdk_client
+        .put_object()
+        .bucket(&bucket)
+        .key(&key)
+        .body(ByteStream::from(Bytes::from_static(body)))
+        .send()
+        .await
+        .unwrap();
+
+    let auth_config = S3ClientAuthConfig::AssumeRole {
+        role_arn: get_subsession_iam_role(),
+        session_name: "test_assume_role_provider".to_owned(),
+        source: Box::new(S3ClientAuthConfig::Default),
+    };
+    let config = S3ClientConfig::new()
+        .auth_config(auth_config)
+        .endpoint_config(EndpointConfig::new(&get_test_region()));
+    let client = S3CrtClient::new(config).unwrap();
+
+    let result = client
+        .get_object(&bucket, &key, &Default::default())
+        .await
+        .expect("get_object should succeed");
+    check_get_result(result, None, &body[..]).await;
+}
+
+/// Test creating a client with the credential_process provider. Like [test_profile_provider_async],
+/// this test needs to modify environment variables, so it runs in a forked process.
+async fn test_process_provider_async() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_process_provider");
+
+    let key = format!("{prefix}/hello");
+    let body = b"hello world!";
+    sdk_client
+        .put_object()
+        .bucket(&bucket)
+        .key(&key)
+        .body(ByteStream::from(Bytes::from_static(body)))
+        .send()
+        .await
+        .unwrap();
+
+    // Get some static credentials by just using the SDK's default provider chain
+    let sdk_provider = DefaultCredentialsChain::builder()
+        .region(Region::new(get_test_region()))
+        .build()
+        .await;
+    let credentials = sdk_provider
+        .provide_credentials()
+        .await
+        .expect("static credentials should be available");
+
+    // Configure a profile whose credential_process just echoes those credentials back
+    let mut process_output = format!(
+        r#"{{"Version": 1, "AccessKeyId": "{}", "SecretAccessKey": "{}""#,
+        credentials.access_key_id(),
+        credentials.secret_access_key()
+    );
+    if let Some(session_token) = credentials.session_token() {
+        process_output.push_str(&format!(r#", "SessionToken": "{}""#, session_token));
+    }
+    process_output.push('}');
+
+    let profile_name = "mountpoint-process-profile";
+    let mut config_file = NamedTempFile::new().unwrap();
+    writeln!(config_file, "[profile {}]", profile_name).unwrap();
+    writeln!(config_file, "credential_process = echo '{}'", process_output).unwrap();
+
+    // Set up the environment variables to use this new config file. This is only OK to do because
+    // this test is run in a forked process, so won't affect any other concurrently running tests.
+    std::env::set_var("AWS_CONFIG_FILE", config_file.path().as_os_str());
+
+    let config = S3ClientConfig::new()
+        .auth_config(S3ClientAuthConfig::Process(Some(profile_name.to_owned())))
+        .endpoint_config(EndpointConfig::new(&get_test_region()));
+    let client = S3CrtClient::new(config).unwrap();
+
+    let result = client
+        .get_object(&bucket, &key, &Default::default())
+        .await
+        .expect("get_object should succeed");
+    check_get_result(result, None, &body[..]).await;
+}
+
+rusty_fork_test! {
+    #[test]
+    fn test_process_provider() {
+        // rusty_fork doesn't support async tests, so build an SDK-usable runtime manually
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(test_process_provider_async());
+    }
+}
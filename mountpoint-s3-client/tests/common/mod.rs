@@ -244,6 +244,7 @@ macro_rules! object_client_test {
                     bucket: bucket.to_string(),
                     part_size: 1024,
                     unordered_list_seed: None,
+                    ..Default::default()
                 });
 
                 let key = format!("{prefix}hello");
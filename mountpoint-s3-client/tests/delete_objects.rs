@@ -0,0 +1,106 @@
+#![cfg(feature = "s3_tests")]
+
+pub mod common;
+
+use std::str::FromStr;
+
+use common::*;
+use mountpoint_s3_client::error::{DeleteObjectsError, ObjectClientError};
+use mountpoint_s3_client::types::{
+    ETag, HeadObjectParams, ListObjectsParams, ObjectIdentifier, MAX_DELETE_OBJECTS_KEYS,
+};
+use mountpoint_s3_client::{ObjectClient, S3CrtClient};
+
+#[tokio::test]
+async fn test_delete_objects() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_delete_objects");
+    create_objects_for_test(&sdk_client, &bucket, &prefix, &["hello", "dir/a", "dir/b & c"]).await;
+
+    let client: S3CrtClient = get_test_client();
+
+    // Deleting a key that doesn't exist is still a success
+    let keys: Vec<_> = ["hello", "dir/b & c", "nonexistent_key"]
+        .iter()
+        .map(|key| format!("{prefix}{key}"))
+        .collect();
+    let objects: Vec<_> = keys.iter().map(ObjectIdentifier::new).collect();
+    let mut result = client
+        .delete_objects(&bucket, &objects)
+        .await
+        .expect("delete_objects should succeed");
+    assert!(result.errors.is_empty(), "unexpected errors: {:?}", result.errors);
+    result.deleted.sort();
+    let mut expected = keys.clone();
+    expected.sort();
+    assert_eq!(result.deleted, expected);
+
+    let result = client
+        .list_objects(&bucket, &ListObjectsParams::new().prefix(&prefix))
+        .await
+        .expect("list_objects should succeed");
+    let remaining: Vec<_> = result.objects.into_iter().map(|object| object.key).collect();
+    assert_eq!(remaining, vec![format!("{prefix}dir/a")]);
+}
+
+#[tokio::test]
+async fn test_delete_objects_etag() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_delete_objects_etag");
+    create_objects_for_test(&sdk_client, &bucket, &prefix, &["hello"]).await;
+    let key = format!("{prefix}hello");
+
+    let client: S3CrtClient = get_test_client();
+
+    let objects = [ObjectIdentifier::new(&key).etag(ETag::from_str("\"wrong-etag\"").unwrap())];
+    let result = client
+        .delete_objects(&bucket, &objects)
+        .await
+        .expect("delete_objects should succeed");
+    assert!(result.deleted.is_empty());
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].code, "PreconditionFailed");
+
+    let head = client
+        .head_object(&bucket, &key, &HeadObjectParams::new())
+        .await
+        .expect("object should not have been deleted");
+    let etag = ETag::from_str(&head.object.etag).unwrap();
+    let objects = [ObjectIdentifier::new(&key).etag(etag)];
+    let result = client
+        .delete_objects(&bucket, &objects)
+        .await
+        .expect("delete_objects should succeed");
+    assert!(result.errors.is_empty(), "unexpected errors: {:?}", result.errors);
+    assert_eq!(result.deleted, vec![key]);
+}
+
+#[tokio::test]
+async fn test_delete_objects_too_many_keys() {
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_delete_objects_too_many_keys");
+
+    let client: S3CrtClient = get_test_client();
+
+    let objects: Vec<_> = (0..=MAX_DELETE_OBJECTS_KEYS)
+        .map(|i| ObjectIdentifier::new(format!("{prefix}{i}")))
+        .collect();
+    let result = client.delete_objects(&bucket, &objects).await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(DeleteObjectsError::TooManyKeys))
+    ));
+}
+
+#[tokio::test]
+async fn test_delete_objects_404_bucket() {
+    let (_bucket, prefix) = get_test_bucket_and_prefix("test_delete_objects_404_bucket");
+
+    let client: S3CrtClient = get_test_client();
+
+    let objects = [ObjectIdentifier::new(format!("{prefix}hello"))];
+    let result = client.delete_objects("DOC-EXAMPLE-BUCKET", &objects).await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(DeleteObjectsError::NoSuchBucket))
+    ));
+}
@@ -6,6 +6,7 @@ use aws_sdk_s3::primitives::ByteStream;
 use bytes::Bytes;
 use common::*;
 use mountpoint_s3_client::config::{AddressingStyle, EndpointConfig, S3ClientConfig};
+use mountpoint_s3_client::types::ListObjectsParams;
 use mountpoint_s3_client::{ObjectClient, S3CrtClient};
 use test_case::test_case;
 
@@ -30,7 +31,7 @@ async fn run_test<F: FnOnce(&str) -> EndpointConfig>(f: F, prefix: &str, bucket:
     let client = S3CrtClient::new(config).expect("could not create test client");
 
     let result = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should succeed");
     check_get_result(result, None, &body[..]).await;
@@ -136,7 +137,10 @@ async fn run_list_objects_test<F: FnOnce(&str) -> EndpointConfig>(f: F, prefix:
     let client = S3CrtClient::new(config).expect("could not create test client");
 
     client
-        .list_objects(bucket, None, "/", 10, prefix)
+        .list_objects(
+            bucket,
+            &ListObjectsParams::new().prefix(prefix).delimiter("/").max_keys(10),
+        )
         .await
         .expect("list_object should succeed");
 }
@@ -7,12 +7,13 @@ use std::option::Option::None;
 use std::str::FromStr;
 
 use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::ChecksumAlgorithm;
 use bytes::Bytes;
 use common::*;
 use futures::stream::StreamExt;
 use mountpoint_s3_client::error::{GetObjectError, ObjectClientError};
-use mountpoint_s3_client::types::ETag;
-use mountpoint_s3_client::{ObjectClient, S3CrtClient};
+use mountpoint_s3_client::types::{ETag, GetObjectParams};
+use mountpoint_s3_client::{GetObjectRequest, ObjectClient, S3CrtClient};
 
 use test_case::test_case;
 
@@ -42,7 +43,7 @@ async fn test_get_object(size: usize, range: Option<Range<u64>>) {
     let client: S3CrtClient = get_test_client();
 
     let result = client
-        .get_object(&bucket, &key, range.clone(), None)
+        .get_object(&bucket, &key, &GetObjectParams::new().range(range.clone()))
         .await
         .expect("get_object should succeed");
     let expected = match range {
@@ -61,7 +62,7 @@ async fn test_get_object_404_key() {
     let client: S3CrtClient = get_test_client();
 
     let mut result = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should succeed");
     let next = StreamExt::next(&mut result).await.expect("stream needs to return Err");
@@ -82,7 +83,7 @@ async fn test_get_object_404_bucket() {
     let client: S3CrtClient = get_test_client();
 
     let mut result = client
-        .get_object("DOC-EXAMPLE-BUCKET", &key, None, None)
+        .get_object("DOC-EXAMPLE-BUCKET", &key, &Default::default())
         .await
         .expect("get_object failed");
     let next = StreamExt::next(&mut result).await.expect("stream needs to return Err");
@@ -92,6 +93,103 @@ async fn test_get_object_404_bucket() {
     ));
 }
 
+#[test_case(10, None; "small object")]
+#[test_case(10, Some(2..6); "small object with range")]
+#[test_case(30000000, None; "large object")]
+#[test_case(30000000, Some(10000000..30000000); "large object with large range")]
+#[tokio::test]
+async fn test_get_object_response(size: usize, range: Option<Range<u64>>) {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_get_object_response");
+
+    let key = format!("{prefix}/test");
+    let body = vec![0x42; size];
+    sdk_client
+        .put_object()
+        .bucket(&bucket)
+        .key(&key)
+        .body(ByteStream::from(body))
+        .content_type("application/octet-stream")
+        .send()
+        .await
+        .unwrap();
+
+    let client: S3CrtClient = get_test_client();
+    let head = client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect("head_object should succeed");
+
+    let mut result = client
+        .get_object(&bucket, &key, &GetObjectParams::new().range(range.clone()))
+        .await
+        .expect("get_object should succeed");
+    let mut received = 0;
+    while let Some(part) = result.next().await {
+        let (_offset, body) = part.expect("get_object body should succeed");
+        received += body.len() as u64;
+    }
+
+    let response = result
+        .get_object_response()
+        .expect("response headers should be captured");
+    assert_eq!(response.etag.as_str(), head.object.etag);
+    assert_eq!(response.last_modified, head.object.last_modified);
+    assert_eq!(response.content_length, Some(received));
+    assert_eq!(response.content_type.as_deref(), Some("application/octet-stream"));
+    let expected_length = range.map(|range| range.end - range.start).unwrap_or(size as u64);
+    assert_eq!(received, expected_length);
+}
+
+#[test_case(ChecksumAlgorithm::Crc32C; "crc32c")]
+#[test_case(ChecksumAlgorithm::Crc32; "crc32")]
+#[test_case(ChecksumAlgorithm::Sha1; "sha1")]
+#[test_case(ChecksumAlgorithm::Sha256; "sha256")]
+#[tokio::test]
+async fn test_get_object_checksum_mode(checksum_algorithm: ChecksumAlgorithm) {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_get_object_checksum_mode");
+
+    let key = format!("{prefix}/test");
+    let body = vec![0x42; 1000];
+    sdk_client
+        .put_object()
+        .bucket(&bucket)
+        .key(&key)
+        .body(ByteStream::from(body.clone()))
+        .checksum_algorithm(checksum_algorithm.clone())
+        .send()
+        .await
+        .unwrap();
+
+    let client: S3CrtClient = get_test_client();
+
+    let mut result = client
+        .get_object(&bucket, &key, &GetObjectParams::new().checksum_mode(true))
+        .await
+        .expect("get_object should succeed");
+    let mut received = Vec::new();
+    while let Some(part) = result.next().await {
+        let (_offset, part) = part.expect("body should pass checksum validation");
+        received.extend_from_slice(&part);
+    }
+    assert_eq!(received, body);
+
+    let response = result
+        .get_object_response()
+        .expect("response headers should be captured");
+    assert!(response.checksum_validated, "body should be validated");
+    let checksum = response.checksum;
+    let returned = match checksum_algorithm {
+        ChecksumAlgorithm::Crc32C => checksum.checksum_crc32c,
+        ChecksumAlgorithm::Crc32 => checksum.checksum_crc32,
+        ChecksumAlgorithm::Sha1 => checksum.checksum_sha1,
+        ChecksumAlgorithm::Sha256 => checksum.checksum_sha256,
+        _ => unreachable!("unsupported checksum algorithm"),
+    };
+    assert!(returned.is_some(), "checksum should be returned in checksum mode");
+}
+
 #[tokio::test]
 async fn test_get_object_success_if_match() {
     let sdk_client = get_test_sdk_client().await;
@@ -114,7 +212,7 @@ async fn test_get_object_success_if_match() {
     let etag = Some(ETag::from_str(response.e_tag().expect("E-Tag should be set")).unwrap());
 
     let result = client
-        .get_object(&bucket, &key, None, etag)
+        .get_object(&bucket, &key, &GetObjectParams::new().if_match(etag))
         .await
         .expect("get_object should succeed");
     check_get_result(result, None, &body[..]).await;
@@ -141,7 +239,7 @@ async fn test_get_object_412_if_match() {
     let etag = Some(ETag::from_str("incorrect_etag").unwrap());
 
     let mut result = client
-        .get_object(&bucket, &key, None, etag)
+        .get_object(&bucket, &key, &GetObjectParams::new().if_match(etag))
         .await
         .expect("get_object should succeed");
 
@@ -177,7 +275,7 @@ async fn test_get_object_cancel(read: bool) {
     let client: S3CrtClient = get_test_client();
 
     let mut request = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should succeed");
 
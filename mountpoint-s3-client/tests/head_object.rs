@@ -34,7 +34,10 @@ async fn test_head_object() {
         .unwrap();
 
     let client: S3CrtClient = get_test_client();
-    let result = client.head_object(&bucket, &key).await.expect("head_object failed");
+    let result = client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect("head_object failed");
 
     assert_eq!(result.bucket, bucket);
     assert_eq!(result.object.key, key);
@@ -63,7 +66,10 @@ async fn test_head_object_storage_class(storage_class: &str) {
         .unwrap();
 
     let client: S3CrtClient = get_test_client();
-    let result = client.head_object(&bucket, &key).await.expect("head_object failed");
+    let result = client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect("head_object failed");
 
     assert_eq!(result.bucket, bucket);
     assert_eq!(result.object.key, key);
@@ -80,7 +86,7 @@ async fn test_head_object_404_key() {
 
     let client: S3CrtClient = get_test_client();
 
-    let result = client.head_object(&bucket, &key).await;
+    let result = client.head_object(&bucket, &key, &Default::default()).await;
     assert!(matches!(
         result,
         Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
@@ -95,7 +101,9 @@ async fn test_head_object_404_bucket() {
 
     let client: S3CrtClient = get_test_client();
 
-    let result = client.head_object("DOC-EXAMPLE-BUCKET", &key).await;
+    let result = client
+        .head_object("DOC-EXAMPLE-BUCKET", &key, &Default::default())
+        .await;
     assert!(matches!(
         result,
         Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
@@ -111,7 +119,7 @@ async fn test_head_object_no_perm() {
 
     let client: S3CrtClient = get_test_client();
 
-    let result = client.head_object(&bucket, &key).await;
+    let result = client.head_object(&bucket, &key, &Default::default()).await;
     assert!(matches!(
         result,
         Err(ObjectClientError::ClientError(S3RequestError::Forbidden(_)))
@@ -138,7 +146,10 @@ async fn test_head_object_restored() {
         .unwrap();
 
     let client: S3CrtClient = get_test_client();
-    let result = client.head_object(&bucket, &key).await.expect("head_object failed");
+    let result = client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect("head_object failed");
 
     assert_eq!(result.bucket, bucket);
     assert_eq!(result.object.key, key);
@@ -171,7 +182,7 @@ async fn test_head_object_restored() {
     let mut timeouted = true;
     while start.elapsed() < timeout {
         let object = client
-            .head_object(&bucket, &key)
+            .head_object(&bucket, &key, &Default::default())
             .await
             .expect("head_object failed")
             .object;
@@ -0,0 +1,124 @@
+#![cfg(feature = "s3_tests")]
+
+pub mod common;
+
+use common::*;
+use mountpoint_s3_client::error::{ListMultipartUploadsError, ObjectClientError};
+use mountpoint_s3_client::{ObjectClient, S3CrtClient};
+
+#[tokio::test]
+async fn test_list_multipart_uploads() {
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_list_multipart_uploads");
+
+    let client: S3CrtClient = get_test_client();
+
+    let mut upload_ids = Vec::new();
+    for key in ["hello", "dir/a"] {
+        let key = format!("{prefix}{key}");
+        let upload_id = client
+            .create_multipart_upload(&bucket, &key, &Default::default())
+            .await
+            .expect("create_multipart_upload failed")
+            .upload_id;
+        upload_ids.push((key, upload_id));
+    }
+
+    let result = client
+        .list_multipart_uploads(&bucket, None, None, "/", 1000, &prefix)
+        .await
+        .expect("ListMultipartUploads failed");
+
+    println!("{result:?}");
+    assert!(result.next_key_marker.is_none());
+    assert!(result.next_upload_id_marker.is_none());
+    assert_eq!(result.uploads.len(), 1);
+    assert_eq!(result.uploads[0].key, upload_ids[0].0);
+    assert_eq!(result.uploads[0].upload_id, upload_ids[0].1);
+    assert_eq!(result.common_prefixes, vec![format!("{prefix}dir/")]);
+
+    for (key, upload_id) in &upload_ids {
+        client
+            .abort_multipart_upload(&bucket, key, upload_id)
+            .await
+            .expect("abort_multipart_upload failed");
+    }
+
+    let result = client
+        .list_multipart_uploads(&bucket, None, None, "", 1000, &prefix)
+        .await
+        .expect("ListMultipartUploads failed");
+    assert!(result.uploads.is_empty());
+}
+
+#[tokio::test]
+async fn test_list_multipart_uploads_markers() {
+    // Max uploads to get per request
+    const MAX_UPLOADS_PER_REQUEST: usize = 2;
+    // Total number of uploads, including two for the same key
+    const TOTAL_UPLOADS: usize = 5;
+
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_list_multipart_uploads_markers");
+
+    let client: S3CrtClient = get_test_client();
+
+    let mut upload_ids = Vec::new();
+    for i in 0..TOTAL_UPLOADS {
+        let key = format!("{prefix}object_{}", i.min(TOTAL_UPLOADS - 2));
+        let upload_id = client
+            .create_multipart_upload(&bucket, &key, &Default::default())
+            .await
+            .expect("create_multipart_upload failed")
+            .upload_id;
+        upload_ids.push((key, upload_id));
+    }
+
+    let mut key_marker = None;
+    let mut upload_id_marker = None;
+    let mut listed = Vec::new();
+    loop {
+        let result = client
+            .list_multipart_uploads(
+                &bucket,
+                key_marker.as_deref(),
+                upload_id_marker.as_deref(),
+                "/",
+                MAX_UPLOADS_PER_REQUEST,
+                &prefix,
+            )
+            .await
+            .expect("ListMultipartUploads failed");
+        assert!(result.uploads.len() <= MAX_UPLOADS_PER_REQUEST);
+        listed.extend(result.uploads.into_iter().map(|u| (u.key, u.upload_id)));
+        if result.next_key_marker.is_none() {
+            break;
+        }
+        key_marker = result.next_key_marker;
+        upload_id_marker = result.next_upload_id_marker;
+    }
+
+    listed.sort();
+    upload_ids.sort();
+    assert_eq!(listed, upload_ids);
+
+    for (key, upload_id) in &upload_ids {
+        client
+            .abort_multipart_upload(&bucket, key, upload_id)
+            .await
+            .expect("abort_multipart_upload failed");
+    }
+}
+
+#[tokio::test]
+async fn test_list_multipart_uploads_404_bucket() {
+    let (_bucket, prefix) = get_test_bucket_and_prefix("test_list_multipart_uploads_404_bucket");
+
+    let client: S3CrtClient = get_test_client();
+
+    let result = client
+        .list_multipart_uploads("DOC-EXAMPLE-BUCKET", None, None, "/", 1000, &prefix)
+        .await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(ListMultipartUploadsError::NoSuchBucket))
+    ));
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "s3_tests")]
+
+pub mod common;
+
+use common::*;
+use mountpoint_s3_client::error::{ListObjectVersionsError, ObjectClientError};
+use mountpoint_s3_client::types::{GetObjectParams, HeadObjectParams};
+use mountpoint_s3_client::{ObjectClient, S3CrtClient};
+
+#[tokio::test]
+async fn test_list_object_versions() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_list_object_versions");
+    create_objects_for_test(&sdk_client, &bucket, &prefix, &["hello", "dir/a", "dir/b"]).await;
+
+    let client: S3CrtClient = get_test_client();
+
+    let result = client
+        .list_object_versions(&bucket, None, None, "/", 1000, &prefix)
+        .await
+        .expect("ListObjectVersions failed");
+
+    println!("{result:?}");
+    assert!(result.next_key_marker.is_none());
+    assert!(result.next_version_id_marker.is_none());
+    assert!(result.delete_markers.is_empty());
+    assert_eq!(result.versions.len(), 1);
+    assert_eq!(result.versions[0].object.key, format!("{}{}", prefix, "hello"));
+    assert!(result.versions[0].is_latest);
+    assert_eq!(result.common_prefixes.len(), 1);
+    assert_eq!(result.common_prefixes[0], format!("{}{}", prefix, "dir/"));
+
+    // Reading the object at the listed version should work whether or not the bucket is versioned,
+    // since unversioned objects have the version ID "null".
+    let version_id = result.versions[0].version_id.clone();
+    let key = &result.versions[0].object.key;
+    let head_result = client
+        .head_object(&bucket, key, &HeadObjectParams::new().version_id(version_id.clone()))
+        .await
+        .expect("head_object failed");
+    assert_eq!(head_result.object.size, result.versions[0].object.size);
+    let get_result = client
+        .get_object(&bucket, key, &GetObjectParams::new().version_id(version_id))
+        .await
+        .expect("get_object failed");
+    check_get_result(get_result, None, b".").await;
+}
+
+#[tokio::test]
+async fn test_list_object_versions_markers() {
+    // Max keys to get per request
+    const MAX_KEYS_PER_REQUEST: usize = 4;
+    // Total number of keys in directory
+    const TOTAL_KEYS: usize = 13;
+
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_list_object_versions_markers");
+
+    let keys: Vec<String> = (0..TOTAL_KEYS).map(|i| format!("object_{i}")).collect();
+    create_objects_for_test(&sdk_client, &bucket, &prefix, &keys[..]).await;
+
+    let client: S3CrtClient = get_test_client();
+
+    let mut key_marker: Option<String> = None;
+    let mut version_id_marker: Option<String> = None;
+    let mut versions = Vec::new();
+    loop {
+        let result = client
+            .list_object_versions(
+                &bucket,
+                key_marker.as_deref(),
+                version_id_marker.as_deref(),
+                "/",
+                MAX_KEYS_PER_REQUEST,
+                &prefix,
+            )
+            .await
+            .expect("ListObjectVersions failed");
+        assert!(result.versions.len() <= MAX_KEYS_PER_REQUEST);
+        versions.extend(result.versions.into_iter().map(|v| v.object.key));
+
+        if result.next_key_marker.is_none() {
+            break;
+        }
+        key_marker = result.next_key_marker;
+        version_id_marker = result.next_version_id_marker;
+    }
+
+    let mut expected: Vec<_> = keys.iter().map(|key| format!("{prefix}{key}")).collect();
+    expected.sort();
+    assert_eq!(versions, expected);
+}
+
+#[tokio::test]
+async fn test_list_object_versions_404_bucket() {
+    let client: S3CrtClient = get_test_client();
+
+    let result = client
+        .list_object_versions("DOC-EXAMPLE-BUCKET", None, None, "/", 1000, "")
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(ListObjectVersionsError::NoSuchBucket))
+    ));
+}
@@ -4,6 +4,7 @@ pub mod common;
 
 use common::*;
 use mountpoint_s3_client::error::{ListObjectsError, ObjectClientError};
+use mountpoint_s3_client::types::ListObjectsParams;
 use mountpoint_s3_client::{ObjectClient, S3CrtClient};
 
 #[tokio::test]
@@ -15,7 +16,7 @@ async fn test_list_objects() {
     let client: S3CrtClient = get_test_client();
 
     let result = client
-        .list_objects(&bucket, None, "/", 1000, &prefix)
+        .list_objects(&bucket, &ListObjectsParams::new().prefix(&prefix).delimiter("/"))
         .await
         .expect("ListObjects failed");
 
@@ -54,10 +55,11 @@ async fn test_max_keys_continuation_token() {
         let result = client
             .list_objects(
                 &bucket,
-                continuation_token.as_deref(),
-                "/",
-                MAX_KEYS_PER_REQUEST,
-                &prefix,
+                &ListObjectsParams::new()
+                    .prefix(&prefix)
+                    .delimiter("/")
+                    .max_keys(MAX_KEYS_PER_REQUEST)
+                    .continuation_token(continuation_token.as_deref()),
             )
             .await
             .expect("ListObjects failed");
@@ -97,7 +99,13 @@ async fn test_invalid_list_objects() {
     // Make a ListObjects request using some made-up continuation token.
     let continuation_token = Some("Made-up invalid token here");
     let result = client
-        .list_objects(&bucket, continuation_token, "/", 1000, &prefix)
+        .list_objects(
+            &bucket,
+            &ListObjectsParams::new()
+                .prefix(&prefix)
+                .delimiter("/")
+                .continuation_token(continuation_token),
+        )
         .await;
 
     let err = result.expect_err("this request should have failed: we made up an invalid continuation token");
@@ -111,7 +119,10 @@ async fn test_list_objects_404_bucket() {
     let client: S3CrtClient = get_test_client();
 
     let result = client
-        .list_objects("DOC-EXAMPLE-BUCKET", None, "/", 1000, &prefix)
+        .list_objects(
+            "DOC-EXAMPLE-BUCKET",
+            &ListObjectsParams::new().prefix(&prefix).delimiter("/"),
+        )
         .await;
     assert!(matches!(
         result,
@@ -137,14 +148,23 @@ async fn test_interesting_keys() {
     let client: S3CrtClient = get_test_client();
 
     let result = client
-        .list_objects(&bucket, None, "/", 2, &prefix)
+        .list_objects(
+            &bucket,
+            &ListObjectsParams::new().prefix(&prefix).delimiter("/").max_keys(2),
+        )
         .await
         .expect("ListObjects failed");
     assert_eq!(result.common_prefixes[0], format!("{prefix}{}/", keys[0]));
     assert_eq!(result.objects[0].key, format!("{prefix}{}", keys[0]));
 
     let result = client
-        .list_objects(&bucket, None, "/", 1, &format!("{prefix}{}/", keys[0]))
+        .list_objects(
+            &bucket,
+            &ListObjectsParams::new()
+                .prefix(&format!("{prefix}{}/", keys[0]))
+                .delimiter("/")
+                .max_keys(1),
+        )
         .await
         .expect("ListObjects failed");
     assert_eq!(result.objects.len(), 1);
@@ -154,10 +174,10 @@ async fn test_interesting_keys() {
     let result = client
         .list_objects(
             &bucket,
-            result.next_continuation_token.as_deref(),
-            "/",
-            1000,
-            &format!("{prefix}{}/", keys[0]),
+            &ListObjectsParams::new()
+                .prefix(&format!("{prefix}{}/", keys[0]))
+                .delimiter("/")
+                .continuation_token(result.next_continuation_token.as_deref()),
         )
         .await
         .expect("ListObjects failed");
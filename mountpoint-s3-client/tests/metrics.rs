@@ -171,7 +171,7 @@ async fn test_get_object_metrics() {
 
     let client: S3CrtClient = get_test_client();
     let result = client
-        .get_object(&bucket, &key, None, None)
+        .get_object(&bucket, &key, &Default::default())
         .await
         .expect("get_object should succeed");
     let result = result
@@ -233,7 +233,7 @@ async fn test_head_object_403() {
 
     let client: S3CrtClient = get_test_client();
     let err = client
-        .head_object(&bucket, "some-key")
+        .head_object(&bucket, "some-key", &Default::default())
         .await
         .expect_err("head to no-permissions bucket should fail");
     assert!(matches!(
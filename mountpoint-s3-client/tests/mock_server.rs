@@ -0,0 +1,775 @@
+//! Tests that run the real [S3CrtClient] against the in-process mock S3 server, so they don't need
+//! access to S3.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64ct::{Base64, Encoding};
+
+use futures::StreamExt;
+use mountpoint_s3_client::checksums::ChecksumHasher;
+use mountpoint_s3_client::config::{
+    AddressingStyle, EndpointConfig, ProxyConnectionType, ProxyOptions, S3ClientAuthConfig, S3ClientConfig, TlsConfig,
+};
+use mountpoint_s3_client::error::{
+    GetObjectError, HeadObjectError, ListObjectsError, ObjectClientError, PutObjectError, RestoreObjectError,
+    S3RequestError,
+};
+use mountpoint_s3_client::mock_client::mock_server::MockS3Server;
+use mountpoint_s3_client::mock_client::{ramp_bytes, MockClient, MockClientConfig, MockObject};
+use mountpoint_s3_client::types::{
+    Checksum, ChecksumAlgorithm, ETag, GetObjectParams, HeadObjectParams, ListObjectsParams, ObjectIdentifier,
+    ObjectMetadata, PutObjectParams, RestoreStatus, RestoreTier,
+};
+use mountpoint_s3_client::{GetObjectRequest, ObjectClient, PutObjectRequest, S3CrtClient};
+use mountpoint_s3_crt::common::allocator::Allocator;
+use mountpoint_s3_crt::common::uri::Uri;
+use test_case::test_case;
+
+const BUCKET: &str = "test-bucket";
+const PART_SIZE: usize = 5 * 1024 * 1024;
+
+fn setup() -> (Arc<MockClient>, MockS3Server, S3CrtClient) {
+    setup_with_config(|config| config)
+}
+
+fn setup_with_config(f: impl FnOnce(S3ClientConfig) -> S3ClientConfig) -> (Arc<MockClient>, MockS3Server, S3CrtClient) {
+    let mock_client = Arc::new(MockClient::new(MockClientConfig {
+        bucket: BUCKET.to_owned(),
+        part_size: 1024 * 1024,
+        unordered_list_seed: None,
+        ..Default::default()
+    }));
+    let server = MockS3Server::start(mock_client.clone()).expect("server should start");
+
+    let endpoint = Uri::new_from_str(&Allocator::default(), server.endpoint_url()).unwrap();
+    let endpoint_config = EndpointConfig::new("us-east-1")
+        .endpoint(endpoint)
+        .addressing_style(AddressingStyle::Path);
+    let config = S3ClientConfig::new()
+        .endpoint_config(endpoint_config)
+        .auth_config(S3ClientAuthConfig::NoSigning)
+        .part_size(PART_SIZE);
+    let client = S3CrtClient::new(f(config)).expect("could not create test client");
+
+    (mock_client, server, client)
+}
+
+async fn collect_body(client: &S3CrtClient, key: &str, params: &GetObjectParams) -> Vec<u8> {
+    let mut request = client
+        .get_object(BUCKET, key, params)
+        .await
+        .expect("get should succeed");
+    let mut body = Vec::new();
+    while let Some(part) = request.next().await {
+        let (offset, data) = part.expect("body part should succeed");
+        assert_eq!(offset, params.range.as_ref().map_or(0, |r| r.start) + body.len() as u64);
+        body.extend_from_slice(&data);
+    }
+    body
+}
+
+#[tokio::test]
+async fn test_get_object() {
+    let (mock_client, _server, client) = setup();
+    let contents = ramp_bytes(7, 3 * PART_SIZE / 2);
+    mock_client.add_object("dir/hello", MockObject::from(&contents));
+
+    let body = collect_body(&client, "dir/hello", &GetObjectParams::new()).await;
+    assert_eq!(body, contents);
+
+    let range = 1000..PART_SIZE as u64 + 1000;
+    let body = collect_body(&client, "dir/hello", &GetObjectParams::new().range(Some(range.clone()))).await;
+    assert_eq!(body, &contents[range.start as usize..range.end as usize]);
+
+    let result = client.get_object(BUCKET, "dir/missing", &GetObjectParams::new()).await;
+    let result = match result {
+        Ok(mut request) => request.next().await.unwrap().map(|_| ()),
+        Err(e) => Err(e),
+    };
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(GetObjectError::NoSuchKey))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_object_backpressure() {
+    let (mock_client, _server, client) =
+        setup_with_config(|config| config.read_backpressure(true).initial_read_window(PART_SIZE));
+    let contents = ramp_bytes(3, 3 * PART_SIZE);
+    mock_client.add_object("hello", MockObject::from(&contents));
+
+    let mut request = client
+        .get_object(BUCKET, "hello", &GetObjectParams::new())
+        .await
+        .expect("get should succeed");
+    assert_eq!(Pin::new(&request).read_window_end_offset(), PART_SIZE as u64);
+
+    // The first part fills the initial window, and then the request stalls until it's extended
+    let mut body = Vec::new();
+    while body.len() < PART_SIZE {
+        let (offset, data) = request.next().await.unwrap().expect("body part should succeed");
+        assert_eq!(offset, body.len() as u64);
+        body.extend_from_slice(&data);
+    }
+    let stalled = tokio::time::timeout(Duration::from_millis(500), request.next()).await;
+    assert!(
+        stalled.is_err(),
+        "request should wait for the read window to be extended"
+    );
+
+    // Extend the window as we consume the body, like a streaming reader would
+    Pin::new(&mut request).increment_read_window(PART_SIZE);
+    while let Some(part) = request.next().await {
+        let (offset, data) = part.expect("body part should succeed");
+        assert_eq!(offset, body.len() as u64);
+        body.extend_from_slice(&data);
+        Pin::new(&mut request).increment_read_window(data.len());
+    }
+    assert_eq!(body, contents);
+}
+
+#[test_case(1024; "single part")]
+#[test_case(3 * PART_SIZE / 2; "multiple parts")]
+#[tokio::test]
+async fn test_get_object_checksum_mode(size: usize) {
+    let (mock_client, _server, client) = setup();
+    let contents = ramp_bytes(5, size);
+    let params = GetObjectParams::new().checksum_mode(true);
+
+    let mut hasher = ChecksumHasher::new(ChecksumAlgorithm::Sha256);
+    hasher.update(&contents);
+    let mut object = MockObject::from(&contents);
+    object.set_checksum(Checksum {
+        checksum_sha256: Some(hasher.finalize_base64()),
+        ..Default::default()
+    });
+    mock_client.add_object("valid", object);
+
+    let body = collect_body(&client, "valid", &params).await;
+    assert_eq!(body, contents);
+
+    // S3 stored a checksum of different contents, as if the body was corrupted on the way to us
+    let mut hasher = ChecksumHasher::new(ChecksumAlgorithm::Sha256);
+    hasher.update(b"something else");
+    let mut object = MockObject::from(&contents);
+    object.set_checksum(Checksum {
+        checksum_sha256: Some(hasher.finalize_base64()),
+        ..Default::default()
+    });
+    mock_client.add_object("corrupted", object);
+
+    let mut request = client
+        .get_object(BUCKET, "corrupted", &params)
+        .await
+        .expect("get should start");
+    let mut result = Ok(());
+    while let Some(part) = request.next().await {
+        if let Err(e) = part {
+            result = Err(e);
+            break;
+        }
+    }
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(GetObjectError::ChecksumMismatch {
+            algorithm: "SHA256",
+            ..
+        }))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_object_checksum_mode_composite() {
+    let (mock_client, _server, client) = setup();
+    let contents = ramp_bytes(6, 1024);
+
+    let params = PutObjectParams::new().checksum_algorithm(Some(ChecksumAlgorithm::Crc32c));
+    let mut put = mock_client.put_object(BUCKET, "composite", &params).await.unwrap();
+    put.write(&contents).await.unwrap();
+    put.complete().await.unwrap();
+
+    let mut request = client
+        .get_object(BUCKET, "composite", &GetObjectParams::new().checksum_mode(true))
+        .await
+        .expect("get should start");
+    let mut body = Vec::new();
+    while let Some(part) = request.next().await {
+        body.extend_from_slice(&part.expect("composite checksums are not validated").1);
+    }
+    assert_eq!(body, contents);
+    let response = request.get_object_response().expect("response should be available");
+    assert!(response.checksum.checksum_crc32c.is_some());
+    assert!(!response.checksum_validated);
+}
+
+#[tokio::test]
+async fn test_head_object() {
+    let (mock_client, _server, client) = setup();
+    let mut object = MockObject::constant(1, 1234, ETag::for_tests());
+    object.set_storage_class(Some("GLACIER".to_owned()));
+    mock_client.add_object("hello", object);
+
+    let head = client
+        .head_object(BUCKET, "hello", &HeadObjectParams::new())
+        .await
+        .expect("head should succeed");
+    assert_eq!(head.object.size, 1234);
+    assert_eq!(head.object.etag, ETag::for_tests().as_str());
+    assert_eq!(head.object.storage_class.as_deref(), Some("GLACIER"));
+
+    let result = client.head_object(BUCKET, "missing", &HeadObjectParams::new()).await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(HeadObjectError::NotFound))
+    ));
+}
+
+#[tokio::test]
+async fn test_list_objects() {
+    let (mock_client, _server, client) = setup();
+    for key in ["a", "dir/b", "dir/c", "dir/sub/d", "e"] {
+        mock_client.add_object(key, MockObject::constant(0, 10, ETag::for_tests()));
+    }
+
+    let result = client
+        .list_objects(BUCKET, &ListObjectsParams::new().delimiter("/"))
+        .await
+        .expect("list should succeed");
+    let keys: Vec<_> = result.objects.iter().map(|o| o.key.as_str()).collect();
+    assert_eq!(keys, ["a", "e"]);
+    assert_eq!(result.common_prefixes, ["dir/"]);
+    assert!(result.next_continuation_token.is_none());
+
+    let mut keys = Vec::new();
+    let mut continuation_token = None;
+    loop {
+        let result = client
+            .list_objects(
+                BUCKET,
+                &ListObjectsParams::new()
+                    .prefix("dir/")
+                    .max_keys(2)
+                    .continuation_token(continuation_token.as_deref()),
+            )
+            .await
+            .expect("list should succeed");
+        keys.extend(result.objects.into_iter().map(|o| o.key));
+        continuation_token = result.next_continuation_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+    assert_eq!(keys, ["dir/b", "dir/c", "dir/sub/d"]);
+
+    let result = client
+        .list_objects("other-bucket", &ListObjectsParams::new().delimiter("/"))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(ListObjectsError::NoSuchBucket))
+    ));
+}
+
+#[tokio::test]
+async fn test_list_objects_special_characters() {
+    let (mock_client, _server, client) = setup();
+    // Control characters can't be represented in XML 1.0, so these keys can only be listed with
+    // URL encoding
+    let keys = ["dir/a b+c", "dir/bell\u{7}", "dir/tab\t/file", "dir/\u{2713}"];
+    for key in keys {
+        mock_client.add_object(key, MockObject::constant(0, 10, ETag::for_tests()));
+    }
+
+    let result = client
+        .list_objects(
+            BUCKET,
+            &ListObjectsParams::new()
+                .prefix("dir/")
+                .delimiter("/")
+                .start_after("dir/a b+c")
+                .fetch_owner(true),
+        )
+        .await
+        .expect("list should succeed");
+    let listed: Vec<_> = result.objects.iter().map(|o| o.key.as_str()).collect();
+    assert_eq!(listed, ["dir/bell\u{7}", "dir/\u{2713}"]);
+    assert_eq!(result.common_prefixes, ["dir/tab\t/"]);
+    assert!(result.objects.iter().all(|o| o.owner.is_some()));
+
+    let entries: Vec<_> = client
+        .list_objects_stream(BUCKET, "dir/", "/")
+        .map(|entry| entry.expect("list should succeed").key().to_owned())
+        .collect()
+        .await;
+    assert_eq!(entries, ["dir/a b+c", "dir/bell\u{7}", "dir/tab\t/", "dir/\u{2713}"]);
+}
+
+#[tokio::test]
+async fn test_list_objects_without_url_encoding() {
+    let (mock_client, _server, client) = setup();
+    let keys = ["dir/a b+c", "dir/%41", "dir/\u{2713}"];
+    for key in keys {
+        mock_client.add_object(key, MockObject::constant(0, 10, ETag::for_tests()));
+    }
+
+    // Without URL encoding the response isn't marked as encoded, so keys must not be decoded
+    let result = client
+        .list_objects(BUCKET, &ListObjectsParams::new().prefix("dir/").url_encoding(false))
+        .await
+        .expect("list should succeed");
+    let listed: Vec<_> = result.objects.iter().map(|o| o.key.as_str()).collect();
+    assert_eq!(listed, ["dir/%41", "dir/a b+c", "dir/\u{2713}"]);
+}
+
+#[tokio::test]
+async fn test_put_object() {
+    let (mock_client, _server, client) = setup();
+
+    // Bigger than the part size, so the CRT uploads it with a multipart upload
+    let contents = ramp_bytes(3, 2 * PART_SIZE + 17);
+    let object_metadata: ObjectMetadata = [("foo".to_owned(), "bar".to_owned())].into_iter().collect();
+    let params = PutObjectParams::new()
+        .object_metadata(object_metadata.clone())
+        .content_type("text/plain".to_owned());
+    let mut request = client
+        .put_object(BUCKET, "uploaded", &params)
+        .await
+        .expect("put should start");
+    request.write(&contents).await.unwrap();
+    request.complete().await.expect("put should succeed");
+
+    assert!(mock_client.contains_key("uploaded"));
+    let body = collect_body(&client, "uploaded", &GetObjectParams::new()).await;
+    assert_eq!(body, contents);
+
+    let head = client
+        .head_object(BUCKET, "uploaded", &HeadObjectParams::new())
+        .await
+        .expect("head should succeed");
+    assert_eq!(head.object_metadata, object_metadata);
+    assert_eq!(head.content_type.as_deref(), Some("text/plain"));
+}
+
+#[tokio::test]
+async fn test_put_object_preconditions() {
+    let (mock_client, server, client) = setup();
+    let contents = ramp_bytes(4, 2 * PART_SIZE + 17);
+
+    let params = PutObjectParams::new()
+        .if_none_match(Some("*".to_owned()))
+        .checksum_algorithm(Some(ChecksumAlgorithm::Crc32c));
+    let mut request = client
+        .put_object(BUCKET, "conditional", &params)
+        .await
+        .expect("put should start");
+    request.write(&contents).await.unwrap();
+    request.complete().await.expect("put should succeed");
+    let body = collect_body(&client, "conditional", &GetObjectParams::new()).await;
+    assert_eq!(body, contents);
+
+    // S3 only checks preconditions on CompleteMultipartUpload, so they mustn't be sent with the
+    // other requests of the upload
+    let requests = server.received_requests();
+    let upload_parts: Vec<_> = requests
+        .iter()
+        .filter(|r| r.method == "PUT" && r.has_query_param("uploadId"))
+        .collect();
+    assert_eq!(upload_parts.len(), 3);
+    for request in requests.iter().filter(|r| r.key.as_deref() == Some("conditional")) {
+        let is_complete = request.method == "POST" && request.has_query_param("uploadId");
+        assert_eq!(request.header("If-None-Match").is_some(), is_complete, "{request:?}");
+        assert!(request.header("If-Match").is_none(), "{request:?}");
+    }
+
+    // The object exists now, so the same precondition fails
+    let mut request = client
+        .put_object(BUCKET, "conditional", &params)
+        .await
+        .expect("put should start");
+    request.write(b"replacement").await.unwrap();
+    let result = request.complete().await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(PutObjectError::PreconditionFailed))
+    ));
+
+    let etag = mock_client
+        .head_object(BUCKET, "conditional", &HeadObjectParams::new())
+        .await
+        .unwrap()
+        .object
+        .etag;
+    let params = PutObjectParams::new().if_match(Some(ETag::from_str(&etag).unwrap()));
+    let mut request = client
+        .put_object(BUCKET, "conditional", &params)
+        .await
+        .expect("put should start");
+    request.write(b"replacement").await.unwrap();
+    request.complete().await.expect("put should succeed");
+    let body = collect_body(&client, "conditional", &GetObjectParams::new()).await;
+    assert_eq!(body, b"replacement");
+
+    let complete = server
+        .received_requests()
+        .into_iter()
+        .filter(|r| r.method == "POST" && r.has_query_param("uploadId"))
+        .last()
+        .expect("upload should be completed");
+    assert_eq!(
+        complete.header("If-Match").map(|e| e.trim_matches('"')),
+        Some(etag.trim_matches('"'))
+    );
+}
+
+#[tokio::test]
+async fn test_delete_objects() {
+    let (mock_client, _server, client) = setup();
+    for key in ["a", "b", "c"] {
+        mock_client.add_object(key, MockObject::constant(0, 10, ETag::for_tests()));
+    }
+
+    client.delete_object(BUCKET, "a").await.expect("delete should succeed");
+    assert!(!mock_client.contains_key("a"));
+
+    let keys = vec!["b".to_owned(), "c".to_owned()];
+    let objects = [
+        ObjectIdentifier::new("b"),
+        ObjectIdentifier::new("c").etag(ETag::for_tests()),
+    ];
+    let result = client
+        .delete_objects(BUCKET, &objects)
+        .await
+        .expect("delete should succeed");
+    assert_eq!(result.deleted, keys);
+    assert!(result.errors.is_empty());
+    assert!(!mock_client.contains_key("b"));
+    assert!(!mock_client.contains_key("c"));
+}
+
+#[tokio::test]
+async fn test_multipart_upload() {
+    let (mock_client, _server, client) = setup();
+    let params = PutObjectParams::new();
+
+    let upload = client
+        .create_multipart_upload(BUCKET, "mpu", &params)
+        .await
+        .expect("create should succeed");
+    let uploads = client
+        .list_multipart_uploads(BUCKET, None, None, "", 1000, "")
+        .await
+        .expect("list should succeed");
+    assert_eq!(uploads.uploads.len(), 1);
+    assert_eq!(uploads.uploads[0].upload_id, upload.upload_id);
+
+    let first = ramp_bytes(1, PART_SIZE);
+    let second = ramp_bytes(2, 100);
+    let mut parts = Vec::new();
+    for (part_number, contents) in [(1, &first), (2, &second)] {
+        let part = client
+            .upload_part(BUCKET, "mpu", &upload.upload_id, part_number, contents)
+            .await
+            .expect("upload part should succeed");
+        parts.push(mountpoint_s3_client::types::CompletedPart {
+            part_number,
+            etag: part.etag,
+        });
+    }
+    client
+        .complete_multipart_upload(BUCKET, "mpu", &upload.upload_id, &parts)
+        .await
+        .expect("complete should succeed");
+
+    assert!(mock_client.contains_key("mpu"));
+    let body = collect_body(&client, "mpu", &GetObjectParams::new()).await;
+    assert_eq!(body, [first, second].concat());
+
+    let upload = client
+        .create_multipart_upload(BUCKET, "aborted", &params)
+        .await
+        .expect("create should succeed");
+    client
+        .abort_multipart_upload(BUCKET, "aborted", &upload.upload_id)
+        .await
+        .expect("abort should succeed");
+    assert!(!mock_client.contains_key("aborted"));
+}
+
+#[tokio::test]
+async fn test_restore_object() {
+    let (mock_client, _server, client) = setup();
+    let mut object = MockObject::from(b"archived");
+    object.set_storage_class(Some("DEEP_ARCHIVE".to_owned()));
+    mock_client.add_object("archived", object);
+    mock_client.add_object("standard", MockObject::from(b"standard"));
+
+    let result = client
+        .restore_object(BUCKET, "archived", 3, RestoreTier::Bulk)
+        .await
+        .expect("restore should succeed");
+    assert!(!result.already_restored);
+    let head = client
+        .head_object(BUCKET, "archived", &HeadObjectParams::new())
+        .await
+        .expect("head should succeed");
+    assert!(matches!(head.object.restore_status, Some(RestoreStatus::InProgress)));
+
+    let result = client
+        .restore_object(BUCKET, "archived", 3, RestoreTier::Bulk)
+        .await
+        .expect_err("restore should fail while in progress");
+    assert!(matches!(
+        result,
+        ObjectClientError::ServiceError(RestoreObjectError::RestoreAlreadyInProgress)
+    ));
+
+    mock_client.complete_restore("archived").unwrap();
+    let result = client
+        .restore_object(BUCKET, "archived", 3, RestoreTier::Standard)
+        .await
+        .expect("restore should succeed");
+    assert!(result.already_restored);
+
+    let result = client
+        .restore_object(BUCKET, "standard", 3, RestoreTier::Standard)
+        .await
+        .expect_err("restore should fail for objects that aren't archived");
+    assert!(matches!(
+        result,
+        ObjectClientError::ServiceError(RestoreObjectError::InvalidObjectState)
+    ));
+    let result = client
+        .restore_object(BUCKET, "missing", 3, RestoreTier::Standard)
+        .await
+        .expect_err("restore should fail for missing keys");
+    assert!(matches!(
+        result,
+        ObjectClientError::ServiceError(RestoreObjectError::NoSuchKey)
+    ));
+}
+
+#[tokio::test]
+async fn test_tls_disabled() {
+    let (mock_client, _server, client) = setup_with_config(|config| config.tls_config(TlsConfig::Disabled));
+    let contents = ramp_bytes(5, 1024);
+    mock_client.add_object("plain", MockObject::from(&contents));
+
+    let body = collect_body(&client, "plain", &GetObjectParams::new()).await;
+    assert_eq!(body, contents);
+}
+
+/// A minimal HTTP proxy that only supports `CONNECT` tunnels. It records the `Proxy-Authorization`
+/// header of each tunnel it opens.
+struct ConnectProxy {
+    port: u16,
+    authorizations: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+impl ConnectProxy {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("proxy should bind");
+        let port = listener.local_addr().unwrap().port();
+        let authorizations = Arc::new(Mutex::new(Vec::new()));
+        let recorded = authorizations.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let recorded = recorded.clone();
+                thread::spawn(move || Self::tunnel(stream, &recorded));
+            }
+        });
+        Self { port, authorizations }
+    }
+
+    fn tunnel(mut client: TcpStream, recorded: &Mutex<Vec<Option<String>>>) -> io::Result<()> {
+        let mut reader = BufReader::new(client.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut words = request_line.split_whitespace();
+        if words.next() != Some("CONNECT") {
+            return client.write_all(b"HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\n\r\n");
+        }
+        let target = words.next().unwrap_or_default().to_owned();
+
+        let mut authorization = None;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("proxy-authorization") {
+                    authorization = Some(value.trim().to_owned());
+                }
+            }
+        }
+        recorded.lock().unwrap().push(authorization);
+
+        let mut upstream = TcpStream::connect(target)?;
+        client.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")?;
+        let mut upstream_reader = upstream.try_clone()?;
+        thread::spawn(move || {
+            let _ = io::copy(&mut upstream_reader, &mut client);
+            let _ = client.shutdown(Shutdown::Write);
+        });
+        io::copy(&mut reader, &mut upstream)?;
+        upstream.shutdown(Shutdown::Write)
+    }
+}
+
+#[tokio::test]
+async fn test_connect_proxy() {
+    let proxy = ConnectProxy::start();
+    let (mock_client, _server, client) = setup_with_config(|config| {
+        config.proxy_options(
+            ProxyOptions::new("127.0.0.1", proxy.port)
+                .connection_type(ProxyConnectionType::Tunneling)
+                .basic_auth("user", "secret"),
+        )
+    });
+    let contents = ramp_bytes(3, 1024);
+    mock_client.add_object("proxied", MockObject::from(&contents));
+
+    let body = collect_body(&client, "proxied", &GetObjectParams::new()).await;
+    assert_eq!(body, contents);
+
+    let authorizations = proxy.authorizations.lock().unwrap();
+    assert!(!authorizations.is_empty(), "requests should go through the proxy");
+    let expected = format!("Basic {}", Base64::encode_string(b"user:secret"));
+    for authorization in authorizations.iter() {
+        assert_eq!(authorization.as_deref(), Some(expected.as_str()));
+    }
+}
+
+/// Forward connections to `target`, except for the first `stalled` connections, which are accepted
+/// but never answered. Returns the address to connect to and a count of accepted connections.
+fn start_stalling_forwarder(target: SocketAddr, stalled: usize) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("forwarder should bind");
+    let addr = listener.local_addr().unwrap();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let accepted_clone = accepted.clone();
+    thread::spawn(move || {
+        let mut stalled_streams = Vec::new();
+        for stream in listener.incoming() {
+            let Ok(mut client) = stream else { break };
+            if accepted_clone.fetch_add(1, Ordering::SeqCst) < stalled {
+                // Keep the connection open without ever responding
+                stalled_streams.push(client);
+                continue;
+            }
+            thread::spawn(move || -> io::Result<()> {
+                let mut upstream = TcpStream::connect(target)?;
+                let mut client_reader = client.try_clone()?;
+                let mut upstream_reader = upstream.try_clone()?;
+                thread::spawn(move || {
+                    let _ = io::copy(&mut upstream_reader, &mut client);
+                    let _ = client.shutdown(Shutdown::Write);
+                });
+                io::copy(&mut client_reader, &mut upstream)?;
+                upstream.shutdown(Shutdown::Write)
+            });
+        }
+    });
+    (addr, accepted)
+}
+
+fn client_for_endpoint(addr: SocketAddr, config: S3ClientConfig) -> S3CrtClient {
+    let endpoint = Uri::new_from_str(&Allocator::default(), format!("http://{addr}")).unwrap();
+    let endpoint_config = EndpointConfig::new("us-east-1")
+        .endpoint(endpoint)
+        .addressing_style(AddressingStyle::Path);
+    let config = config
+        .endpoint_config(endpoint_config)
+        .auth_config(S3ClientAuthConfig::NoSigning);
+    S3CrtClient::new(config).expect("could not create test client")
+}
+
+#[tokio::test]
+async fn test_request_timeout() {
+    let (_mock_client, server, _client) = setup();
+    let (addr, _) = start_stalling_forwarder(server.addr(), usize::MAX);
+    let client = client_for_endpoint(addr, S3ClientConfig::new().request_timeout(Duration::from_millis(500)));
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        client.head_object(BUCKET, "stalled", &HeadObjectParams::new()),
+    )
+    .await
+    .expect("request should time out before the test does");
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ClientError(S3RequestError::RequestTimedOut(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_request_timeout_retries_stalled_request() {
+    let (mock_client, server, _client) = setup();
+    let contents = ramp_bytes(4, 1024);
+    mock_client.add_object("retried", MockObject::from(&contents));
+
+    let (addr, accepted) = start_stalling_forwarder(server.addr(), 1);
+    let client = client_for_endpoint(addr, S3ClientConfig::new().request_timeout(Duration::from_millis(500)));
+
+    let head = tokio::time::timeout(
+        Duration::from_secs(10),
+        client.head_object(BUCKET, "retried", &HeadObjectParams::new()),
+    )
+    .await
+    .expect("stalled request should be retried")
+    .expect("retried request should succeed");
+    assert_eq!(head.object.size, contents.len() as u64);
+    assert!(accepted.load(Ordering::SeqCst) >= 2);
+}
+
+#[tokio::test]
+async fn test_request_timeout_retries_stalled_get_object() {
+    let (mock_client, server, _client) = setup();
+    let contents = ramp_bytes(5, 1024);
+    mock_client.add_object("retried", MockObject::from(&contents));
+
+    // Both a single-part request and an auto-ranged one, whose timeout lasts until the first body part
+    let range = GetObjectParams::new().range(Some(0..contents.len() as u64));
+    for params in [range, GetObjectParams::new()] {
+        let (addr, accepted) = start_stalling_forwarder(server.addr(), 1);
+        let client = client_for_endpoint(addr, S3ClientConfig::new().request_timeout(Duration::from_millis(500)));
+
+        let body = tokio::time::timeout(Duration::from_secs(10), collect_body(&client, "retried", &params))
+            .await
+            .expect("stalled request should be retried");
+        assert_eq!(body, contents);
+        assert!(accepted.load(Ordering::SeqCst) >= 2);
+    }
+}
+
+#[tokio::test]
+async fn test_first_byte_timeout_retries_stalled_request() {
+    let (mock_client, server, _client) = setup();
+    let contents = ramp_bytes(9, 1024);
+    mock_client.add_object("retried", MockObject::from(&contents));
+
+    let (addr, accepted) = start_stalling_forwarder(server.addr(), 1);
+    let client = client_for_endpoint(addr, S3ClientConfig::new().first_byte_timeout(Duration::from_secs(1)));
+
+    let body = tokio::time::timeout(
+        Duration::from_secs(30),
+        collect_body(&client, "retried", &GetObjectParams::new()),
+    )
+    .await
+    .expect("stalled request should be retried");
+    assert_eq!(body, contents);
+    assert!(
+        accepted.load(Ordering::SeqCst) >= 2,
+        "request should be retried on a new connection"
+    );
+}
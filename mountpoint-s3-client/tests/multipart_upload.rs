@@ -0,0 +1,154 @@
+#![cfg(feature = "s3_tests")]
+
+pub mod common;
+
+use common::*;
+use mountpoint_s3_client::error::{
+    AbortMultipartUploadError, CompleteMultipartUploadError, ObjectClientError, UploadPartError,
+};
+use mountpoint_s3_client::types::CompletedPart;
+use mountpoint_s3_client::{ObjectClient, S3CrtClient};
+use rand::Rng;
+
+#[tokio::test]
+async fn test_multipart_upload() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_multipart_upload");
+    let key = format!("{prefix}hello");
+
+    let client: S3CrtClient = get_test_client();
+
+    let upload_id = client
+        .create_multipart_upload(&bucket, &key, &Default::default())
+        .await
+        .expect("create_multipart_upload failed")
+        .upload_id;
+    assert_eq!(
+        get_mpu_count_for_key(&sdk_client, &bucket, &prefix, &key)
+            .await
+            .unwrap(),
+        1
+    );
+
+    // Upload the parts concurrently, so they may complete out of order
+    let mut rng = rand::thread_rng();
+    let mut first_part = vec![0u8; 5 * 1024 * 1024];
+    rng.fill(&mut first_part[..]);
+    let mut second_part = vec![0u8; 1024];
+    rng.fill(&mut second_part[..]);
+    let (first, second) = futures::join!(
+        client.upload_part(&bucket, &key, &upload_id, 1, &first_part),
+        client.upload_part(&bucket, &key, &upload_id, 2, &second_part),
+    );
+    let parts = [
+        CompletedPart {
+            part_number: 1,
+            etag: first.expect("upload_part failed").etag,
+        },
+        CompletedPart {
+            part_number: 2,
+            etag: second.expect("upload_part failed").etag,
+        },
+    ];
+
+    client
+        .complete_multipart_upload(&bucket, &key, &upload_id, &parts)
+        .await
+        .expect("complete_multipart_upload failed");
+    assert_eq!(
+        get_mpu_count_for_key(&sdk_client, &bucket, &prefix, &key)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let result = sdk_client
+        .get_object()
+        .bucket(&bucket)
+        .key(&key)
+        .send()
+        .await
+        .expect("uploaded object should exist");
+    let body = result.body.collect().await.unwrap().into_bytes();
+    assert_eq!(&body[..], &[first_part, second_part].concat()[..]);
+}
+
+#[tokio::test]
+async fn test_complete_multipart_upload_invalid_part() {
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_complete_multipart_upload_invalid_part");
+    let key = format!("{prefix}hello");
+
+    let client: S3CrtClient = get_test_client();
+
+    let upload_id = client
+        .create_multipart_upload(&bucket, &key, &Default::default())
+        .await
+        .expect("create_multipart_upload failed")
+        .upload_id;
+    let part = client
+        .upload_part(&bucket, &key, &upload_id, 1, b"hello world")
+        .await
+        .expect("upload_part failed");
+
+    // Part 2 was never uploaded
+    let parts = [CompletedPart {
+        part_number: 2,
+        etag: part.etag,
+    }];
+    let result = client
+        .complete_multipart_upload(&bucket, &key, &upload_id, &parts)
+        .await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(
+            CompleteMultipartUploadError::InvalidPart
+        ))
+    ));
+
+    client
+        .abort_multipart_upload(&bucket, &key, &upload_id)
+        .await
+        .expect("abort_multipart_upload failed");
+}
+
+#[tokio::test]
+async fn test_abort_multipart_upload() {
+    let sdk_client = get_test_sdk_client().await;
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_abort_multipart_upload");
+    let key = format!("{prefix}hello");
+
+    let client: S3CrtClient = get_test_client();
+
+    let upload_id = client
+        .create_multipart_upload(&bucket, &key, &Default::default())
+        .await
+        .expect("create_multipart_upload failed")
+        .upload_id;
+    client
+        .upload_part(&bucket, &key, &upload_id, 1, b"hello world")
+        .await
+        .expect("upload_part failed");
+
+    client
+        .abort_multipart_upload(&bucket, &key, &upload_id)
+        .await
+        .expect("abort_multipart_upload failed");
+    assert_eq!(
+        get_mpu_count_for_key(&sdk_client, &bucket, &prefix, &key)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let result = client.upload_part(&bucket, &key, &upload_id, 2, b"hello world").await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(UploadPartError::NoSuchUpload))
+    ));
+
+    let result = client.abort_multipart_upload(&bucket, &key, &upload_id).await;
+    assert!(matches!(
+        result,
+        Err(ObjectClientError::ServiceError(AbortMultipartUploadError::NoSuchUpload))
+    ));
+}
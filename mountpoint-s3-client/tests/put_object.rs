@@ -4,12 +4,13 @@ pub mod common;
 
 use common::*;
 use futures::{pin_mut, StreamExt};
-use mountpoint_s3_client::checksums::crc32c_to_base64;
+use mountpoint_s3_client::checksums::ChecksumHasher;
 use mountpoint_s3_client::config::{EndpointConfig, S3ClientConfig};
-use mountpoint_s3_client::error::{GetObjectError, ObjectClientError};
-use mountpoint_s3_client::types::{ObjectClientResult, PutObjectParams, PutObjectResult};
-use mountpoint_s3_client::{ObjectClient, PutObjectRequest, S3CrtClient, S3RequestError};
-use mountpoint_s3_crt::checksums::crc32c;
+use mountpoint_s3_client::error::{GetObjectError, ObjectClientError, PutObjectError};
+use mountpoint_s3_client::types::{
+    ChecksumAlgorithm, ETag, ObjectClientResult, ObjectMetadata, PutObjectParams, PutObjectResult,
+};
+use mountpoint_s3_client::{GetObjectRequest, ObjectClient, PutObjectRequest, S3CrtClient, S3RequestError};
 use rand::Rng;
 use test_case::test_case;
 
@@ -35,7 +36,7 @@ async fn test_put_object(
     let put_object_result = request.complete().await.unwrap();
 
     let result = client
-        .get_object(bucket, key, None, None)
+        .get_object(bucket, key, &Default::default())
         .await
         .expect("get_object should succeed");
     check_get_result(result, None, &contents[..]).await;
@@ -61,7 +62,7 @@ async fn test_put_object_empty(
     let put_object_result = request.complete().await.unwrap();
 
     let result = client
-        .get_object(bucket, key, None, None)
+        .get_object(bucket, key, &Default::default())
         .await
         .expect("get_object should succeed");
     check_get_result(result, None, &[]).await;
@@ -96,7 +97,7 @@ async fn test_put_object_multi_part(
     let put_object_result = request.complete().await.unwrap();
 
     let result = client
-        .get_object(bucket, key, None, None)
+        .get_object(bucket, key, &Default::default())
         .await
         .expect("get_object failed");
     check_get_result(result, None, &contents[..]).await;
@@ -133,7 +134,7 @@ async fn test_put_object_large(
     let put_object_result = request.complete().await.unwrap();
 
     let result = client
-        .get_object(bucket, key, None, None)
+        .get_object(bucket, key, &Default::default())
         .await
         .expect("get_object failed");
     check_get_result(result, None, &contents[..]).await;
@@ -238,8 +239,12 @@ async fn test_put_object_initiate_failure() {
     assert_eq!(uploads_in_progress, 0);
 }
 
+#[test_case(ChecksumAlgorithm::Crc32c; "crc32c")]
+#[test_case(ChecksumAlgorithm::Crc32; "crc32")]
+#[test_case(ChecksumAlgorithm::Sha1; "sha1")]
+#[test_case(ChecksumAlgorithm::Sha256; "sha256")]
 #[tokio::test]
-async fn test_put_checksums() {
+async fn test_put_checksums(checksum_algorithm: ChecksumAlgorithm) {
     const PART_SIZE: usize = 5 * 1024 * 1024;
     let (bucket, prefix) = get_test_bucket_and_prefix("test_put_checksums");
     let client_config = S3ClientConfig::new()
@@ -252,14 +257,32 @@ async fn test_put_checksums() {
     let mut contents = vec![0u8; PART_SIZE * 2];
     rng.fill(&mut contents[..]);
 
-    let params = PutObjectParams::new().trailing_checksums(true);
+    let expected_checksums: Vec<_> = contents
+        .chunks(PART_SIZE)
+        .map(|part| {
+            let mut hasher = ChecksumHasher::new(checksum_algorithm);
+            hasher.update(part);
+            hasher.finalize_base64()
+        })
+        .collect();
+
+    let params = PutObjectParams::new().checksum_algorithm(Some(checksum_algorithm));
     let mut request = client
         .put_object(&bucket, &key, &params)
         .await
         .expect("put_object should succeed");
 
     request.write(&contents).await.unwrap();
-    request.complete().await.unwrap();
+    let expected_review_checksums = expected_checksums.clone();
+    request
+        .review_and_complete(move |review| {
+            assert_eq!(review.checksum_algorithm, Some(checksum_algorithm));
+            let review_checksums: Vec<_> = review.parts.into_iter().map(|p| p.checksum.unwrap()).collect();
+            assert_eq!(review_checksums, expected_review_checksums);
+            true
+        })
+        .await
+        .unwrap();
 
     let sdk_client = get_test_sdk_client().await;
     let attributes = sdk_client
@@ -271,14 +294,16 @@ async fn test_put_checksums() {
         .await
         .unwrap();
     let parts = attributes.object_parts().unwrap().parts();
-    let checksums: Vec<_> = parts.iter().map(|p| p.checksum_crc32_c().unwrap()).collect();
-    let expected_checksums: Vec<_> = contents.chunks(PART_SIZE).map(crc32c::checksum).collect();
-
-    assert_eq!(checksums.len(), expected_checksums.len());
-    for (checksum, expected_checksum) in checksums.into_iter().zip(expected_checksums.into_iter()) {
-        let encoded = crc32c_to_base64(&expected_checksum);
-        assert_eq!(checksum, encoded);
-    }
+    let checksums: Vec<_> = parts
+        .iter()
+        .map(|p| match checksum_algorithm {
+            ChecksumAlgorithm::Crc32c => p.checksum_crc32_c().unwrap(),
+            ChecksumAlgorithm::Crc32 => p.checksum_crc32().unwrap(),
+            ChecksumAlgorithm::Sha1 => p.checksum_sha1().unwrap(),
+            ChecksumAlgorithm::Sha256 => p.checksum_sha256().unwrap(),
+        })
+        .collect();
+    assert_eq!(checksums, expected_checksums);
 }
 
 #[test_case(true; "pass review")]
@@ -297,7 +322,7 @@ async fn test_put_review(pass_review: bool) {
     let mut contents = vec![0u8; PART_SIZE * 2];
     rng.fill(&mut contents[..]);
 
-    let params = PutObjectParams::new().trailing_checksums(true);
+    let params = PutObjectParams::new().checksum_algorithm(Some(ChecksumAlgorithm::Crc32c));
     let mut request = client
         .put_object(&bucket, &key, &params)
         .await
@@ -344,7 +369,7 @@ async fn check_get_object<Client: ObjectClient>(
     bucket: &str,
     key: &str,
 ) -> ObjectClientResult<(), GetObjectError, Client::ClientError> {
-    let result = client.get_object(bucket, key, None, None).await?;
+    let result = client.get_object(bucket, key, &Default::default()).await?;
     pin_mut!(result);
     result.next().await.unwrap()?;
     Ok(())
@@ -480,3 +505,144 @@ async fn test_put_object_sse(sse_type: Option<&str>, kms_key_id: Option<String>)
     let put_object_result = test_put_object_large(&client, &bucket, &key, request_params.clone()).await;
     check_sse(&bucket, &key, sse_type, &kms_key_id, put_object_result).await;
 }
+
+#[tokio::test]
+async fn test_put_object_preconditions() {
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_put_object_preconditions");
+    let client: S3CrtClient = get_test_client();
+    let key = format!("{prefix}hello");
+
+    async fn put(
+        client: &S3CrtClient,
+        bucket: &str,
+        key: &str,
+        params: PutObjectParams,
+    ) -> ObjectClientResult<PutObjectResult, PutObjectError, S3RequestError> {
+        let mut request = client.put_object(bucket, key, &params).await?;
+        request.write(b"hello").await?;
+        request.complete().await
+    }
+
+    let if_none_match = PutObjectParams::new().if_none_match(Some("*".to_owned()));
+    put(&client, &bucket, &key, if_none_match.clone())
+        .await
+        .expect("put with If-None-Match should succeed for a new key");
+    let err = put(&client, &bucket, &key, if_none_match)
+        .await
+        .expect_err("put with If-None-Match should fail for an existing key");
+    assert!(matches!(
+        err,
+        ObjectClientError::ServiceError(PutObjectError::PreconditionFailed)
+    ));
+
+    let etag = client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect("head_object should succeed")
+        .object
+        .etag;
+    let err = put(
+        &client,
+        &bucket,
+        &key,
+        PutObjectParams::new().if_match(Some(ETag::for_tests())),
+    )
+    .await
+    .expect_err("put with a stale If-Match should fail");
+    assert!(matches!(
+        err,
+        ObjectClientError::ServiceError(PutObjectError::PreconditionFailed)
+    ));
+    put(
+        &client,
+        &bucket,
+        &key,
+        PutObjectParams::new().if_match(Some(etag.parse().unwrap())),
+    )
+    .await
+    .expect("put with a matching If-Match should succeed");
+}
+
+#[tokio::test]
+async fn test_put_object_metadata() {
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_put_object_metadata");
+    let client = get_test_client();
+    let key = format!("{prefix}hello");
+
+    let object_metadata: ObjectMetadata = [("foo".to_owned(), "bar".to_owned())].into_iter().collect();
+    let params = PutObjectParams::new()
+        .object_metadata(object_metadata.clone())
+        .content_type("text/plain".to_owned())
+        .content_encoding("identity".to_owned())
+        .cache_control("no-cache".to_owned());
+    let mut request = client
+        .put_object(&bucket, &key, &params)
+        .await
+        .expect("put_object should succeed");
+    request.write(b"hello").await.unwrap();
+    request.complete().await.unwrap();
+
+    let head = client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect("head_object should succeed");
+    assert_eq!(head.object_metadata, object_metadata);
+    assert_eq!(head.content_type.as_deref(), Some("text/plain"));
+    assert_eq!(head.content_encoding.as_deref(), Some("identity"));
+    assert_eq!(head.cache_control.as_deref(), Some("no-cache"));
+
+    let mut get = client
+        .get_object(&bucket, &key, &Default::default())
+        .await
+        .expect("get_object should succeed");
+    // The response is only available once the headers have arrived
+    while get.next().await.is_some() {}
+    let response = get.get_object_response().expect("response headers should be captured");
+    assert_eq!(response.object_metadata, object_metadata);
+    assert_eq!(response.content_type.as_deref(), Some("text/plain"));
+    assert_eq!(response.content_encoding.as_deref(), Some("identity"));
+    assert_eq!(response.cache_control.as_deref(), Some("no-cache"));
+}
+
+#[cfg(not(feature = "s3express_tests"))]
+#[tokio::test]
+async fn test_put_object_sse_customer_key() {
+    use mountpoint_s3_client::types::{GetObjectParams, HeadObjectParams, SseCustomerKey};
+
+    let (bucket, prefix) = get_test_bucket_and_prefix("test_put_object_sse_customer_key");
+    let client = get_test_client();
+    let key = format!("{prefix}hello");
+
+    let mut rng = rand::thread_rng();
+    let mut key_bytes = [0u8; SseCustomerKey::KEY_LENGTH];
+    rng.fill(&mut key_bytes[..]);
+    let sse_customer_key = SseCustomerKey::new(&key_bytes).unwrap();
+
+    let params = PutObjectParams::new().sse_customer_key(Some(sse_customer_key.clone()));
+    let mut request = client
+        .put_object(&bucket, &key, &params)
+        .await
+        .expect("put_object should succeed");
+    request.write(b"hello").await.unwrap();
+    request.complete().await.unwrap();
+
+    // Reading the object requires the key
+    client
+        .head_object(&bucket, &key, &Default::default())
+        .await
+        .expect_err("head_object without the key should fail");
+
+    let params = HeadObjectParams::new().sse_customer_key(Some(sse_customer_key.clone()));
+    let head = client
+        .head_object(&bucket, &key, &params)
+        .await
+        .expect("head_object should succeed");
+    assert_eq!(head.object.size, 5);
+
+    let params = GetObjectParams::new().sse_customer_key(Some(sse_customer_key));
+    let request = client
+        .get_object(&bucket, &key, &params)
+        .await
+        .expect("get_object should succeed");
+    check_get_result(request, None, b"hello").await;
+}
@@ -38,6 +38,7 @@ const CRT_HEADERS: &[&str] = &[
     "common/error.h",
     "http/http.h",
     "http/connection.h",
+    "http/proxy.h",
     "http/request_response.h",
     "io/async_stream.h",
     "io/channel_bootstrap.h",
@@ -45,6 +46,7 @@ const CRT_HEADERS: &[&str] = &[
     "io/future.h",
     "io/host_resolver.h",
     "io/stream.h",
+    "io/tls_channel_handler.h",
     "io/uri.h",
     "s3/s3.h",
     "s3/s3_client.h",
@@ -1,20 +1,30 @@
 //! AWS credentials providers
 
 use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::ptr::NonNull;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use mountpoint_s3_crt_sys::{
-    aws_credentials_provider, aws_credentials_provider_acquire, aws_credentials_provider_chain_default_options,
-    aws_credentials_provider_new_anonymous, aws_credentials_provider_new_chain_default,
-    aws_credentials_provider_new_profile, aws_credentials_provider_new_static,
+    aws_allocator, aws_auth_errors, aws_credentials, aws_credentials_new, aws_credentials_provider,
+    aws_credentials_provider_acquire, aws_credentials_provider_cached_options,
+    aws_credentials_provider_chain_default_options, aws_credentials_provider_delegate_options,
+    aws_credentials_provider_ecs_options, aws_credentials_provider_new_anonymous, aws_credentials_provider_new_cached,
+    aws_credentials_provider_new_chain_default, aws_credentials_provider_new_delegate,
+    aws_credentials_provider_new_ecs, aws_credentials_provider_new_process, aws_credentials_provider_new_profile,
+    aws_credentials_provider_new_static, aws_credentials_provider_new_sts,
+    aws_credentials_provider_new_sts_web_identity, aws_credentials_provider_process_options,
     aws_credentials_provider_profile_options, aws_credentials_provider_release,
-    aws_credentials_provider_static_options,
+    aws_credentials_provider_shutdown_options, aws_credentials_provider_static_options,
+    aws_credentials_provider_sts_options, aws_credentials_provider_sts_web_identity_options, aws_credentials_release,
+    aws_on_get_credentials_callback_fn, AWS_OP_SUCCESS,
 };
 
 use crate::auth::auth_library_init;
 use crate::common::allocator::Allocator;
 use crate::common::error::Error;
 use crate::io::channel_bootstrap::ClientBootstrap;
+use crate::io::tls::TlsContext;
 use crate::{CrtError as _, ToAwsByteCursor as _};
 
 /// Options for creating a default credentials provider
@@ -53,6 +63,109 @@ impl Debug for CredentialsProviderStaticOptions<'_> {
     }
 }
 
+/// Options for creating an STS web identity credentials provider, which exchanges an OpenID Connect
+/// token (for example from IAM roles for service accounts on Amazon EKS) for credentials for a role.
+/// Options left unset are read from the `AWS_ROLE_ARN`, `AWS_ROLE_SESSION_NAME`,
+/// `AWS_WEB_IDENTITY_TOKEN_FILE`, and `AWS_REGION` environment variables or the config file.
+#[derive(Debug)]
+pub struct CredentialsProviderStsWebIdentityOptions<'a> {
+    /// The client bootstrap this credentials provider should use to setup channels
+    pub bootstrap: &'a mut ClientBootstrap,
+    /// The TLS context to use for connections to STS. If not set, a default one is created.
+    pub tls_context: Option<&'a TlsContext>,
+    /// The region of the STS endpoint to use
+    pub region: Option<&'a str>,
+    /// ARN of the role to assume
+    pub role_arn: Option<&'a str>,
+    /// Name of the session for the assumed role
+    pub role_session_name: Option<&'a str>,
+    /// Path to the file containing the web identity token
+    pub token_file_path: Option<&'a str>,
+}
+
+/// Options for creating an STS AssumeRole credentials provider, which uses credentials from another
+/// provider to assume a role
+#[derive(Debug)]
+pub struct CredentialsProviderStsOptions<'a> {
+    /// The client bootstrap this credentials provider should use to setup channels
+    pub bootstrap: &'a mut ClientBootstrap,
+    /// The TLS context to use for connections to STS
+    pub tls_context: &'a TlsContext,
+    /// The provider of the credentials used to call AssumeRole
+    pub source: &'a CredentialsProvider,
+    /// ARN of the role to assume
+    pub role_arn: &'a str,
+    /// Name of the session for the assumed role
+    pub session_name: &'a str,
+    /// How long the assumed role credentials are valid for. STS's default (one hour) if not set.
+    pub duration: Option<Duration>,
+}
+
+/// Options for creating a process credentials provider, which runs the `credential_process` command
+/// configured in a profile and parses credentials from its output
+#[derive(Debug)]
+pub struct CredentialsProviderProcessOptions<'a> {
+    /// The name of the profile to use. If not set, the `AWS_PROFILE` environment variable or the
+    /// default profile is used.
+    pub profile_name_override: Option<&'a str>,
+}
+
+/// Options for creating an ECS credentials provider, which fetches credentials from the container
+/// credentials endpoint of Amazon ECS or Amazon EKS Pod Identity
+#[derive(Debug)]
+pub struct CredentialsProviderEcsOptions<'a> {
+    /// The client bootstrap this credentials provider should use to setup channels
+    pub bootstrap: &'a mut ClientBootstrap,
+    /// The TLS context to use if the endpoint uses HTTPS
+    pub tls_context: Option<&'a TlsContext>,
+    /// Host name of the credentials endpoint
+    pub host: &'a str,
+    /// Path and query of the credentials endpoint
+    pub path_and_query: &'a str,
+    /// Authorization token to send to the endpoint
+    pub auth_token: Option<&'a str>,
+    /// Port of the credentials endpoint. The default HTTP or HTTPS port if not set.
+    pub port: Option<u16>,
+}
+
+/// Options for creating a cached credentials provider, which caches the credentials of another
+/// provider until shortly before they expire
+#[derive(Debug)]
+pub struct CredentialsProviderCachedOptions<'a> {
+    /// The provider whose credentials to cache
+    pub source: &'a CredentialsProvider,
+    /// How long to cache credentials that don't have an expiration time. The CRT's default if not set.
+    pub refresh_time: Option<Duration>,
+}
+
+/// A set of AWS credentials returned by a delegate credentials provider
+#[derive(Clone)]
+pub struct Credentials {
+    /// AWS access key ID
+    pub access_key_id: String,
+    /// AWS secret access key
+    pub secret_access_key: String,
+    /// AWS session token (only required for some credentials sources, e.g. STS)
+    pub session_token: Option<String>,
+    /// When these credentials expire. Credentials without an expiration time are cached for the
+    /// default refresh time of the cached credentials provider.
+    pub expiration: Option<SystemTime>,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &"** redacted **")
+            .field("secret_access_key", &"** redacted **")
+            .field("session_token", &self.session_token.as_ref().map(|_| "** redacted **"))
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+/// The error type returned by the callback of a delegate credentials provider
+pub type DelegateCredentialsError = Box<dyn std::error::Error + Send + Sync>;
+
 /// A credentials provider is an object that has an asynchronous query function for retrieving AWS
 /// credentials
 #[derive(Debug)]
@@ -141,6 +254,265 @@ impl CredentialsProvider {
     }
 }
 
+impl CredentialsProvider {
+    /// Creates an STS web identity credential provider.
+    pub fn new_sts_web_identity(
+        allocator: &Allocator,
+        options: CredentialsProviderStsWebIdentityOptions,
+    ) -> Result<Self, Error> {
+        auth_library_init(allocator);
+
+        // SAFETY: aws_credentials_provider_new_sts_web_identity acquires a reference to the bootstrap
+        // and TLS context, and makes a copy of the strings.
+        let inner = unsafe {
+            let inner_options = aws_credentials_provider_sts_web_identity_options {
+                bootstrap: options.bootstrap.inner.as_ptr(),
+                tls_ctx: options
+                    .tls_context
+                    .map(|tls_context| tls_context.inner.as_ptr())
+                    .unwrap_or(std::ptr::null_mut()),
+                region: options.region.map(|s| s.as_aws_byte_cursor()).unwrap_or_default(),
+                role_arn: options.role_arn.map(|s| s.as_aws_byte_cursor()).unwrap_or_default(),
+                role_session_name: options
+                    .role_session_name
+                    .map(|s| s.as_aws_byte_cursor())
+                    .unwrap_or_default(),
+                token_file_path: options
+                    .token_file_path
+                    .map(|s| s.as_aws_byte_cursor())
+                    .unwrap_or_default(),
+                ..Default::default()
+            };
+
+            aws_credentials_provider_new_sts_web_identity(allocator.inner.as_ptr(), &inner_options)
+                .ok_or_last_error()?
+        };
+
+        Ok(Self { inner })
+    }
+
+    /// Creates an STS AssumeRole credential provider.
+    pub fn new_sts(allocator: &Allocator, options: CredentialsProviderStsOptions) -> Result<Self, Error> {
+        auth_library_init(allocator);
+
+        // STS accepts durations of up to 12 hours, and rejects longer ones, so saturating is fine
+        let duration_seconds = options
+            .duration
+            .map(|duration| duration.as_secs().min(u16::MAX as u64) as u16)
+            .unwrap_or(0);
+
+        // SAFETY: aws_credentials_provider_new_sts acquires a reference to the bootstrap, TLS context
+        // and source provider, and makes a copy of the strings.
+        let inner = unsafe {
+            let inner_options = aws_credentials_provider_sts_options {
+                bootstrap: options.bootstrap.inner.as_ptr(),
+                tls_ctx: options.tls_context.inner.as_ptr(),
+                creds_provider: options.source.inner.as_ptr(),
+                role_arn: options.role_arn.as_aws_byte_cursor(),
+                session_name: options.session_name.as_aws_byte_cursor(),
+                duration_seconds,
+                ..Default::default()
+            };
+
+            aws_credentials_provider_new_sts(allocator.inner.as_ptr(), &inner_options).ok_or_last_error()?
+        };
+
+        Ok(Self { inner })
+    }
+
+    /// Creates a process credential provider.
+    pub fn new_process(allocator: &Allocator, options: CredentialsProviderProcessOptions) -> Result<Self, Error> {
+        auth_library_init(allocator);
+
+        // SAFETY: aws_credentials_provider_new_process makes a copy of the profile name.
+        let inner = unsafe {
+            let inner_options = aws_credentials_provider_process_options {
+                profile_to_use: options
+                    .profile_name_override
+                    .map(|s| s.as_aws_byte_cursor())
+                    .unwrap_or_default(),
+                ..Default::default()
+            };
+
+            aws_credentials_provider_new_process(allocator.inner.as_ptr(), &inner_options).ok_or_last_error()?
+        };
+
+        Ok(Self { inner })
+    }
+
+    /// Creates an ECS credential provider.
+    pub fn new_ecs(allocator: &Allocator, options: CredentialsProviderEcsOptions) -> Result<Self, Error> {
+        auth_library_init(allocator);
+
+        // SAFETY: aws_credentials_provider_new_ecs acquires a reference to the bootstrap and TLS
+        // context, and makes a copy of the strings.
+        let inner = unsafe {
+            let inner_options = aws_credentials_provider_ecs_options {
+                bootstrap: options.bootstrap.inner.as_ptr(),
+                tls_ctx: options
+                    .tls_context
+                    .map(|tls_context| tls_context.inner.as_ptr())
+                    .unwrap_or(std::ptr::null_mut()),
+                host: options.host.as_aws_byte_cursor(),
+                path_and_query: options.path_and_query.as_aws_byte_cursor(),
+                auth_token: options.auth_token.map(|s| s.as_aws_byte_cursor()).unwrap_or_default(),
+                port: options.port.unwrap_or(0).into(),
+                ..Default::default()
+            };
+
+            aws_credentials_provider_new_ecs(allocator.inner.as_ptr(), &inner_options).ok_or_last_error()?
+        };
+
+        Ok(Self { inner })
+    }
+
+    /// Creates a cached credential provider.
+    pub fn new_cached(allocator: &Allocator, options: CredentialsProviderCachedOptions) -> Result<Self, Error> {
+        auth_library_init(allocator);
+
+        let refresh_time_in_milliseconds = options
+            .refresh_time
+            .map(|refresh_time| refresh_time.as_millis().try_into().unwrap_or(u64::MAX))
+            .unwrap_or(0);
+
+        // SAFETY: aws_credentials_provider_new_cached acquires a reference to the source provider.
+        let inner = unsafe {
+            let inner_options = aws_credentials_provider_cached_options {
+                source: options.source.inner.as_ptr(),
+                refresh_time_in_milliseconds,
+                ..Default::default()
+            };
+
+            aws_credentials_provider_new_cached(allocator.inner.as_ptr(), &inner_options).ok_or_last_error()?
+        };
+
+        Ok(Self { inner })
+    }
+}
+
+impl CredentialsProvider {
+    /// Creates a credentials provider that gets credentials by calling the given function.
+    ///
+    /// The returned provider caches the credentials until shortly before their expiration time, so
+    /// `get_credentials` is only called again once the credentials need refreshing. It is called
+    /// synchronously on a CRT event loop thread, so it should avoid blocking for long. Errors and
+    /// panics are logged and reported to the CRT as a delegate failure.
+    pub fn new_delegate<F>(allocator: &Allocator, get_credentials: F) -> Result<Self, Error>
+    where
+        F: Fn() -> Result<Credentials, DelegateCredentialsError> + Send + Sync + 'static,
+    {
+        auth_library_init(allocator);
+
+        let user_data = Box::new(DelegateUserData {
+            allocator: allocator.inner,
+            get_credentials: Box::new(get_credentials),
+        });
+        let user_data = Box::into_raw(user_data) as *mut libc::c_void;
+
+        // SAFETY: `user_data` is a valid pointer to a `DelegateUserData`. It is freed by the shutdown
+        // callback once the provider is destroyed, or right here if the provider can't be created,
+        // since the CRT never calls the shutdown callback in that case.
+        let delegate = unsafe {
+            let inner_options = aws_credentials_provider_delegate_options {
+                shutdown_options: aws_credentials_provider_shutdown_options {
+                    shutdown_callback: Some(delegate_shutdown_callback),
+                    shutdown_user_data: user_data,
+                },
+                get_credentials: Some(delegate_get_credentials),
+                delegate_user_data: user_data,
+            };
+
+            match aws_credentials_provider_new_delegate(allocator.inner.as_ptr(), &inner_options).ok_or_last_error() {
+                Ok(inner) => Self { inner },
+                Err(e) => {
+                    drop(Box::from_raw(user_data as *mut DelegateUserData));
+                    return Err(e);
+                }
+            }
+        };
+
+        Self::new_cached(
+            allocator,
+            CredentialsProviderCachedOptions {
+                source: &delegate,
+                refresh_time: None,
+            },
+        )
+    }
+}
+
+struct DelegateUserData {
+    allocator: NonNull<aws_allocator>,
+    get_credentials: Box<dyn Fn() -> Result<Credentials, DelegateCredentialsError> + Send + Sync>,
+}
+
+/// SAFETY: not safe to call directly, only let the CRT call this function as a callback.
+unsafe extern "C" fn delegate_get_credentials(
+    delegate_user_data: *mut libc::c_void,
+    callback: aws_on_get_credentials_callback_fn,
+    callback_user_data: *mut libc::c_void,
+) -> libc::c_int {
+    let user_data = &*(delegate_user_data as *const DelegateUserData);
+    let callback = callback.expect("CRT should always provide a credentials callback");
+
+    // Unwinding across the FFI boundary is undefined behavior, so a panic in the user's function
+    // is reported to the CRT like any other delegate failure.
+    let result = catch_unwind(AssertUnwindSafe(|| (user_data.get_credentials)()))
+        .unwrap_or_else(|_| Err("credentials function panicked".into()));
+    let credentials = match result {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            log::warn!("delegate credentials provider failed: {e}");
+            let error_code = aws_auth_errors::AWS_AUTH_CREDENTIALS_PROVIDER_DELEGATE_FAILURE as i32;
+            callback(std::ptr::null_mut(), error_code, callback_user_data);
+            return AWS_OP_SUCCESS;
+        }
+    };
+
+    // The CRT represents credentials that never expire with an expiration of u64::MAX
+    let expiration_timepoint_seconds = credentials
+        .expiration
+        .map(|expiration| {
+            expiration
+                .duration_since(UNIX_EPOCH)
+                .map(|since_epoch| since_epoch.as_secs())
+                .unwrap_or(0)
+        })
+        .unwrap_or(u64::MAX);
+
+    let inner: *mut aws_credentials = aws_credentials_new(
+        user_data.allocator.as_ptr(),
+        credentials.access_key_id.as_aws_byte_cursor(),
+        credentials.secret_access_key.as_aws_byte_cursor(),
+        credentials
+            .session_token
+            .as_ref()
+            .map(|t| t.as_aws_byte_cursor())
+            .unwrap_or_default(),
+        expiration_timepoint_seconds,
+    );
+    if inner.is_null() {
+        callback(
+            std::ptr::null_mut(),
+            Error::last_error().raw_error(),
+            callback_user_data,
+        );
+        return AWS_OP_SUCCESS;
+    }
+
+    // The callback acquires its own reference to the credentials if it needs to keep them
+    callback(inner, AWS_OP_SUCCESS, callback_user_data);
+    aws_credentials_release(inner);
+
+    AWS_OP_SUCCESS
+}
+
+/// SAFETY: not safe to call directly, only let the CRT call this function as a callback.
+unsafe extern "C" fn delegate_shutdown_callback(user_data: *mut libc::c_void) {
+    assert!(!user_data.is_null());
+    drop(Box::from_raw(user_data as *mut DelegateUserData));
+}
+
 impl Clone for CredentialsProvider {
     fn clone(&self) -> Self {
         // SAFETY: `self.inner` is a valid `aws_credentials_provider` for as long as `self` exists
@@ -6,6 +6,7 @@ use mountpoint_s3_crt_sys::*;
 
 use crate::common::allocator::Allocator;
 
+pub mod proxy;
 pub mod request_response;
 
 static HTTP_LIBRARY_INIT: Once = Once::new();
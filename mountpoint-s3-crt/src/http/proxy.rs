@@ -0,0 +1,122 @@
+//! Configuration for making connections through an HTTP proxy
+
+use std::fmt::Debug;
+
+use mountpoint_s3_crt_sys::{
+    aws_http_proxy_authentication_type, aws_http_proxy_connection_type, aws_http_proxy_options,
+};
+
+use crate::ToAwsByteCursor as _;
+
+/// How requests are sent through an HTTP proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProxyConnectionType {
+    /// Tunnel HTTPS connections through the proxy with `CONNECT`, and forward plain HTTP requests
+    /// to it
+    #[default]
+    Automatic,
+    /// Send every request to the proxy, which forwards it to the destination. Only supported for
+    /// plain HTTP requests.
+    Forwarding,
+    /// Establish a tunnel to the destination through the proxy with `CONNECT`
+    Tunneling,
+}
+
+impl From<ProxyConnectionType> for aws_http_proxy_connection_type {
+    fn from(connection_type: ProxyConnectionType) -> Self {
+        match connection_type {
+            ProxyConnectionType::Automatic => aws_http_proxy_connection_type::AWS_HPCT_HTTP_LEGACY,
+            ProxyConnectionType::Forwarding => aws_http_proxy_connection_type::AWS_HPCT_HTTP_FORWARD,
+            ProxyConnectionType::Tunneling => aws_http_proxy_connection_type::AWS_HPCT_HTTP_TUNNEL,
+        }
+    }
+}
+
+/// Options for connecting through an HTTP proxy. Connections to the proxy itself are always plain
+/// HTTP; requests to HTTPS endpoints are still encrypted end-to-end when tunneled.
+#[derive(Clone)]
+pub struct ProxyOptions {
+    host: String,
+    port: u16,
+    connection_type: ProxyConnectionType,
+    basic_auth: Option<(String, String)>,
+}
+
+impl ProxyOptions {
+    /// Create options for a proxy at the given host and port
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_owned(),
+            port,
+            connection_type: Default::default(),
+            basic_auth: None,
+        }
+    }
+
+    /// Set how requests are sent through the proxy
+    pub fn connection_type(mut self, connection_type: ProxyConnectionType) -> Self {
+        self.connection_type = connection_type;
+        self
+    }
+
+    /// Authenticate to the proxy with HTTP basic authentication
+    pub fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.basic_auth = Some((username.to_owned(), password.to_owned()));
+        self
+    }
+
+    /// The host of the proxy
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The port of the proxy
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Convert these options into the CRT's struct.
+    ///
+    /// SAFETY: The returned struct holds pointers into `self`, so must not outlive it.
+    pub(crate) unsafe fn to_inner(&self) -> aws_http_proxy_options {
+        let mut inner = aws_http_proxy_options {
+            connection_type: self.connection_type.into(),
+            host: self.host.as_aws_byte_cursor(),
+            port: self.port as u32,
+            ..Default::default()
+        };
+        if let Some((username, password)) = &self.basic_auth {
+            inner.auth_type = aws_http_proxy_authentication_type::AWS_HPAT_BASIC;
+            inner.auth_username = username.as_aws_byte_cursor();
+            inner.auth_password = password.as_aws_byte_cursor();
+        }
+        inner
+    }
+}
+
+impl Debug for ProxyOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyOptions")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("connection_type", &self.connection_type)
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|(username, _)| (username, "<redacted>")),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_password() {
+        let options = ProxyOptions::new("proxy.example.com", 3128).basic_auth("user", "hunter2");
+        let debug = format!("{options:?}");
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
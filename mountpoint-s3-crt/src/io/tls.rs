@@ -0,0 +1,233 @@
+//! TLS configuration for connections made by the CRT
+
+use std::ffi::{CStr, CString};
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+use std::ptr::{self, NonNull};
+
+use mountpoint_s3_crt_sys::{
+    aws_common_error, aws_tls_client_ctx_new, aws_tls_connection_options, aws_tls_connection_options_clean_up,
+    aws_tls_connection_options_init_from_ctx, aws_tls_ctx, aws_tls_ctx_acquire, aws_tls_ctx_options,
+    aws_tls_ctx_options_clean_up, aws_tls_ctx_options_init_client_mtls_from_path,
+    aws_tls_ctx_options_init_default_client, aws_tls_ctx_options_override_default_trust_store_from_path,
+    aws_tls_ctx_options_set_alpn_list, aws_tls_ctx_release,
+};
+
+use crate::common::allocator::Allocator;
+use crate::common::error::Error;
+use crate::io::io_library_init;
+use crate::CrtError as _;
+
+/// A TLS context holds the configuration for TLS connections, and can be shared by many of them.
+#[derive(Debug)]
+pub struct TlsContext {
+    pub(crate) inner: NonNull<aws_tls_ctx>,
+}
+
+// SAFETY: aws_tls_ctx is immutable once created and reference counted, so is thread-safe.
+unsafe impl Send for TlsContext {}
+// SAFETY: aws_tls_ctx is immutable once created and reference counted, so is thread-safe.
+unsafe impl Sync for TlsContext {}
+
+impl TlsContext {
+    /// Create a TLS context for client connections that verifies peers with the system's default
+    /// trust store
+    pub fn new_client_default(allocator: &Allocator) -> Result<Self, Error> {
+        Self::new_client(allocator, &Default::default())
+    }
+
+    /// Create a TLS context for client connections with the given options
+    pub fn new_client(allocator: &Allocator, options: &TlsContextOptions) -> Result<Self, Error> {
+        io_library_init(allocator);
+
+        let ca_file = options.ca_file.as_deref().map(path_to_cstring).transpose()?;
+        let ca_dir = options.ca_dir.as_deref().map(path_to_cstring).transpose()?;
+        let client_cert = options
+            .client_cert
+            .as_ref()
+            .map(|(cert, key)| Ok::<_, Error>((path_to_cstring(cert)?, path_to_cstring(key)?)))
+            .transpose()?;
+        let alpn_list = if options.alpn_list.is_empty() {
+            None
+        } else {
+            Some(CString::new(options.alpn_list.join(";")).map_err(|_| invalid_argument())?)
+        };
+
+        let mut ctx_options: aws_tls_ctx_options = Default::default();
+
+        // SAFETY: `ctx_options` is a valid, zeroed `aws_tls_ctx_options`. The init functions clean
+        // up after themselves if they fail, and otherwise we clean it up below. The CRT copies what
+        // it needs from the strings we pass it, and `aws_tls_client_ctx_new` copies what it needs
+        // from `ctx_options`.
+        let inner = unsafe {
+            if let Some((cert, key)) = &client_cert {
+                aws_tls_ctx_options_init_client_mtls_from_path(
+                    &mut ctx_options,
+                    allocator.inner.as_ptr(),
+                    cert.as_ptr(),
+                    key.as_ptr(),
+                )
+                .ok_or_last_error()?;
+            } else {
+                aws_tls_ctx_options_init_default_client(&mut ctx_options, allocator.inner.as_ptr());
+            }
+
+            let inner = (|| {
+                if ca_file.is_some() || ca_dir.is_some() {
+                    aws_tls_ctx_options_override_default_trust_store_from_path(
+                        &mut ctx_options,
+                        ca_dir.as_deref().map_or(ptr::null(), CStr::as_ptr),
+                        ca_file.as_deref().map_or(ptr::null(), CStr::as_ptr),
+                    )
+                    .ok_or_last_error()?;
+                }
+                if let Some(alpn_list) = &alpn_list {
+                    aws_tls_ctx_options_set_alpn_list(&mut ctx_options, alpn_list.as_ptr()).ok_or_last_error()?;
+                }
+                aws_tls_client_ctx_new(allocator.inner.as_ptr(), &ctx_options).ok_or_last_error()
+            })();
+            aws_tls_ctx_options_clean_up(&mut ctx_options);
+            inner?
+        };
+
+        Ok(Self { inner })
+    }
+}
+
+/// Options for creating a client [TlsContext]. Follows the builder pattern.
+#[derive(Debug, Clone, Default)]
+pub struct TlsContextOptions {
+    ca_file: Option<PathBuf>,
+    ca_dir: Option<PathBuf>,
+    client_cert: Option<(PathBuf, PathBuf)>,
+    alpn_list: Vec<String>,
+}
+
+impl TlsContextOptions {
+    /// Create a new [TlsContextOptions] that verifies peers with the system's default trust store
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Trust the CA certificates in the given PEM file instead of the system's default trust store
+    pub fn ca_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.ca_file = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Trust the CA certificates in the given directory instead of the system's default trust
+    /// store. The directory must be in the format OpenSSL expects, with files named by the hash of
+    /// the certificate's subject, as created by `openssl rehash`.
+    pub fn ca_dir(mut self, path: impl AsRef<Path>) -> Self {
+        self.ca_dir = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Authenticate to peers with a client certificate (mutual TLS). Both files are PEM encoded, and
+    /// the certificate file can contain the rest of the certificate chain after the certificate.
+    pub fn client_cert(mut self, cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Self {
+        self.client_cert = Some((cert_path.as_ref().to_owned(), key_path.as_ref().to_owned()));
+        self
+    }
+
+    /// Set the protocols to offer with ALPN, in order of preference
+    pub fn alpn_list(mut self, protocols: &[&str]) -> Self {
+        self.alpn_list = protocols.iter().map(|protocol| protocol.to_string()).collect();
+        self
+    }
+}
+
+fn path_to_cstring(path: &Path) -> Result<CString, Error> {
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| invalid_argument())
+}
+
+fn invalid_argument() -> Error {
+    (aws_common_error::AWS_ERROR_INVALID_ARGUMENT as i32).into()
+}
+
+/// Options for TLS connections to a peer, created from a [TlsContext]
+#[derive(Debug)]
+pub struct TlsConnectionOptions {
+    pub(crate) inner: Box<aws_tls_connection_options>,
+}
+
+// SAFETY: the options are never mutated after creation, and the TLS context they hold a reference
+// to is thread-safe.
+unsafe impl Send for TlsConnectionOptions {}
+// SAFETY: the options are never mutated after creation, and the TLS context they hold a reference
+// to is thread-safe.
+unsafe impl Sync for TlsConnectionOptions {}
+
+impl TlsConnectionOptions {
+    /// Create options for connections that use the given [TlsContext]
+    pub fn new(context: &TlsContext) -> Self {
+        let mut inner: Box<aws_tls_connection_options> = Default::default();
+        // SAFETY: `context.inner` is a valid `aws_tls_ctx`, and this acquires a reference to it that
+        // we release in `drop`.
+        unsafe {
+            aws_tls_connection_options_init_from_ctx(&mut *inner, context.inner.as_ptr());
+        }
+        Self { inner }
+    }
+}
+
+impl Drop for TlsConnectionOptions {
+    fn drop(&mut self) {
+        // SAFETY: `self.inner` was initialized in `new`, and we're in drop so nothing else can use it
+        unsafe {
+            aws_tls_connection_options_clean_up(&mut *self.inner);
+        }
+    }
+}
+
+impl Clone for TlsContext {
+    fn clone(&self) -> Self {
+        // SAFETY: `self.inner` is a valid `aws_tls_ctx` for as long as `self` exists
+        unsafe {
+            aws_tls_ctx_acquire(self.inner.as_ptr());
+        }
+
+        Self { inner: self.inner }
+    }
+}
+
+impl Drop for TlsContext {
+    fn drop(&mut self) {
+        // SAFETY: `self.inner` is a valid `aws_tls_ctx` and we're in drop so it's safe to decrement
+        // the reference count.
+        unsafe {
+            aws_tls_ctx_release(self.inner.as_ptr());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_client_default() {
+        let allocator = Allocator::default();
+        let tls_context = TlsContext::new_client_default(&allocator).expect("TLS context should be created");
+        let cloned = tls_context.clone();
+        drop(tls_context);
+        drop(cloned);
+    }
+
+    #[test]
+    fn test_new_client_with_options() {
+        let allocator = Allocator::default();
+        let options = TlsContextOptions::new().alpn_list(&["http/1.1"]);
+        let tls_context = TlsContext::new_client(&allocator, &options).expect("TLS context should be created");
+        let connection_options = TlsConnectionOptions::new(&tls_context);
+        drop(tls_context);
+        drop(connection_options);
+    }
+
+    #[test]
+    fn test_missing_ca_file() {
+        let allocator = Allocator::default();
+        let options = TlsContextOptions::new().ca_file("/does/not/exist.pem");
+        TlsContext::new_client(&allocator, &options).expect_err("missing CA file should fail");
+    }
+}
@@ -6,9 +6,11 @@ use crate::common::allocator::Allocator;
 use crate::common::error::Error;
 use crate::common::thread::ThreadId;
 use crate::common::uri::Uri;
+use crate::http::proxy::ProxyOptions;
 use crate::http::request_response::{Headers, Message};
 use crate::io::channel_bootstrap::ClientBootstrap;
 use crate::io::retry_strategy::RetryStrategy;
+use crate::io::tls::TlsConnectionOptions;
 use crate::s3::s3_library_init;
 use crate::{aws_byte_cursor_as_slice, CrtError, ResultExt, ToAwsByteCursor};
 use mountpoint_s3_crt_sys::*;
@@ -56,6 +58,16 @@ pub struct ClientConfig {
 
     /// The region
     region: Option<String>,
+
+    /// Options for connecting through an HTTP proxy. The CRT's struct points into the
+    /// [ProxyOptions], and is boxed so that the pointer in `inner` stays valid if this config moves.
+    proxy_options: Option<(ProxyOptions, Box<aws_http_proxy_options>)>,
+
+    /// The TLS options `inner` points to, if set
+    tls_connection_options: Option<TlsConnectionOptions>,
+
+    /// The connection monitoring options `inner` points to, if set
+    monitoring_options: Option<Box<aws_http_connection_monitoring_options>>,
 }
 
 impl ClientConfig {
@@ -120,6 +132,79 @@ impl ClientConfig {
         self
     }
 
+    /// Connect to S3 through an HTTP proxy
+    pub fn proxy_options(&mut self, proxy_options: ProxyOptions) -> &mut Self {
+        // SAFETY: the CRT struct points into `proxy_options`, which we store alongside it and don't
+        // mutate further, so it lives as long as this `ClientConfig`, which outlives the client.
+        let mut inner = Box::new(unsafe { proxy_options.to_inner() });
+        self.inner.proxy_options = inner.as_mut();
+        self.proxy_options = Some((proxy_options, inner));
+        self
+    }
+
+    /// Timeout for establishing new connections to S3
+    pub fn connect_timeout(&mut self, connect_timeout: Duration) -> &mut Self {
+        self.inner.connect_timeout_ms = connect_timeout.as_millis().try_into().unwrap_or(u32::MAX);
+        self
+    }
+
+    /// Close connections whose throughput stays below `minimum_bytes_per_second` for longer than
+    /// `allowable_failure_interval` while a request is in flight on them, including while waiting
+    /// for the response to start. Requests on those connections fail with a retryable error, so
+    /// the client retries them on a new connection.
+    ///
+    /// The interval is rounded up to whole seconds.
+    pub fn throughput_monitoring(
+        &mut self,
+        minimum_bytes_per_second: u64,
+        allowable_failure_interval: Duration,
+    ) -> &mut Self {
+        let interval_seconds = allowable_failure_interval.as_secs_f64().ceil().max(1.0) as u32;
+        let mut options = Box::new(aws_http_connection_monitoring_options {
+            minimum_throughput_bytes_per_second: minimum_bytes_per_second.max(1),
+            allowable_throughput_failure_interval_seconds: interval_seconds,
+            ..Default::default()
+        });
+        self.inner.monitoring_options = options.as_mut();
+        self.monitoring_options = Some(options);
+        self
+    }
+
+    /// Whether to use TLS for connections to S3. When disabled, only `http://` endpoints work.
+    pub fn tls_mode(&mut self, tls_mode: TlsMode) -> &mut Self {
+        self.inner.tls_mode = tls_mode.into();
+        self
+    }
+
+    /// Options for TLS connections to S3, such as the trusted CA certificates. When not set, the
+    /// client verifies S3 with the system's default trust store.
+    pub fn tls_connection_options(&mut self, tls_connection_options: TlsConnectionOptions) -> &mut Self {
+        // The options are boxed, so `inner` can point to them for as long as we hold onto them.
+        // The CRT copies them when the client is created.
+        self.inner.tls_connection_options = &*tls_connection_options.inner as *const _ as *mut _;
+        self.tls_connection_options = Some(tls_connection_options);
+        self
+    }
+
+    /// Apply backpressure to downloads, so that each meta request only downloads response body
+    /// data up to the end of its flow-control window. Callers must then extend the window with
+    /// [MetaRequest::increment_read_window] to keep data flowing.
+    ///
+    /// The CRT currently only applies backpressure to GetObject meta requests that are split into
+    /// multiple parts, and may still deliver some data past the end of the window.
+    pub fn read_backpressure(&mut self, read_backpressure: bool) -> &mut Self {
+        self.inner.enable_read_backpressure = read_backpressure;
+        self
+    }
+
+    /// The initial size in bytes of each meta request's flow-control window. Ignored unless
+    /// [read_backpressure](Self::read_backpressure) is enabled. A meta request with an initial
+    /// window of 0 doesn't start until its window is incremented.
+    pub fn initial_read_window(&mut self, initial_read_window: usize) -> &mut Self {
+        self.inner.initial_read_window = initial_read_window;
+        self
+    }
+
     /// When set, this will cap the number of active connections. Otherwise, the client will
     /// determine this value based on throughput_target_gbps. (Recommended)
     pub fn max_active_connections_override(&mut self, max_active_connections_override: u32) -> &mut Self {
@@ -128,6 +213,25 @@ impl ClientConfig {
     }
 }
 
+/// Whether a [Client] uses TLS for its connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsMode {
+    /// Use TLS for connections to `https://` endpoints
+    #[default]
+    Enabled,
+    /// Never use TLS
+    Disabled,
+}
+
+impl From<TlsMode> for aws_s3_meta_request_tls_mode {
+    fn from(tls_mode: TlsMode) -> Self {
+        match tls_mode {
+            TlsMode::Enabled => aws_s3_meta_request_tls_mode::AWS_MR_TLS_ENABLED,
+            TlsMode::Disabled => aws_s3_meta_request_tls_mode::AWS_MR_TLS_DISABLED,
+        }
+    }
+}
+
 /// Callback for telemetry received as part of a successful meta request.
 type TelemetryCallback = Box<dyn Fn(&RequestMetrics) + Send>;
 
@@ -362,6 +466,16 @@ impl MetaRequestOptions {
         self
     }
 
+    /// Get the type of this request
+    pub fn get_request_type(&self) -> MetaRequestType {
+        match self.0.as_ref().inner.type_ {
+            aws_s3_meta_request_type::AWS_S3_META_REQUEST_TYPE_GET_OBJECT => MetaRequestType::GetObject,
+            aws_s3_meta_request_type::AWS_S3_META_REQUEST_TYPE_PUT_OBJECT => MetaRequestType::PutObject,
+            aws_s3_meta_request_type::AWS_S3_META_REQUEST_TYPE_COPY_OBJECT => MetaRequestType::CopyObject,
+            _ => MetaRequestType::Default,
+        }
+    }
+
     /// Set the part size of this request
     pub fn part_size(&mut self, part_size: u64) -> &mut Self {
         // SAFETY: we aren't moving out of the struct.
@@ -379,7 +493,7 @@ impl Default for MetaRequestOptions {
 
 /// What transformation to apply to a single [MetaRequest] to transform it into a collection of
 /// requests to S3.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MetaRequestType {
     /// Send the request as-is (no transformation)
     Default,
@@ -533,6 +647,17 @@ impl MetaRequest {
             aws_s3_meta_request_cancel(self.inner.as_ptr());
         }
     }
+
+    /// Extend the flow-control window of this meta request by `bytes`, so that more of the
+    /// response body can be downloaded. Does nothing unless the client was created with
+    /// [ClientConfig::read_backpressure] enabled.
+    pub fn increment_read_window(&self, bytes: u64) {
+        // SAFETY: `self.inner` is a valid `aws_s3_meta_request`, even if the request has otherwise
+        // finished, since we hold a ref count to it
+        unsafe {
+            aws_s3_meta_request_increment_read_window(self.inner.as_ptr(), bytes);
+        }
+    }
 }
 
 impl Drop for MetaRequest {
@@ -740,6 +865,13 @@ pub struct MetaRequestResult {
 
     /// Error HTTP response, if present.
     pub error_response_body: Option<OsString>,
+
+    /// Whether the CRT compared the checksum S3 returned with the checksum of the whole response
+    /// body. This is false for a multipart object even if its parts' checksums were validated.
+    pub did_validate: bool,
+
+    /// Checksum algorithm the response body was validated with, if it was validated.
+    pub validation_algorithm: Option<ChecksumAlgorithm>,
 }
 
 impl MetaRequestResult {
@@ -753,6 +885,12 @@ impl MetaRequestResult {
         self.crt_error.raw_error() == mountpoint_s3_crt_sys::aws_s3_errors::AWS_ERROR_S3_CANCELED as i32
     }
 
+    /// Return whether the response body didn't match its checksum according to the error code.
+    pub fn is_checksum_mismatch(&self) -> bool {
+        self.crt_error.raw_error()
+            == mountpoint_s3_crt_sys::aws_s3_errors::AWS_ERROR_S3_RESPONSE_CHECKSUM_MISMATCH as i32
+    }
+
     /// Convert the CRT's meta request result struct into a safe, owned result.
     /// SAFETY: This copies from the raw pointer inside of the request result, so only call on
     /// results given to us from the CRT.
@@ -773,6 +911,8 @@ impl MetaRequestResult {
             crt_error: inner.error_code.into(),
             error_response_headers,
             error_response_body,
+            did_validate: inner.did_validate,
+            validation_algorithm: ChecksumAlgorithm::from_aws_s3_checksum_algorithm(inner.validation_algorithm),
         }
     }
 }
@@ -1139,10 +1279,28 @@ pub struct ChecksumConfig {
 impl ChecksumConfig {
     /// Create a [ChecksumConfig] enabling Crc32c trailing checksums in PUT requests.
     pub fn trailing_crc32c() -> Self {
+        Self::trailing(ChecksumAlgorithm::Crc32c)
+    }
+
+    /// Create a [ChecksumConfig] enabling trailing checksums with the given algorithm in PUT requests.
+    pub fn trailing(algorithm: ChecksumAlgorithm) -> Self {
         Self {
             inner: aws_s3_checksum_config {
                 location: aws_s3_checksum_location::AWS_SCL_TRAILER,
-                checksum_algorithm: aws_s3_checksum_algorithm::AWS_SCA_CRC32C,
+                checksum_algorithm: algorithm.to_aws_s3_checksum_algorithm(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Create a [ChecksumConfig] that asks S3 for checksums in GET responses and validates the
+    /// response body against them. The CRT can only validate a body against a full-object checksum,
+    /// and not against the composite checksums of objects uploaded with multipart uploads.
+    pub fn validate_response() -> Self {
+        Self {
+            inner: aws_s3_checksum_config {
+                location: aws_s3_checksum_location::AWS_SCL_NONE,
+                validate_response_checksum: true,
                 ..Default::default()
             },
         }
@@ -1178,6 +1336,15 @@ impl ChecksumAlgorithm {
             _ => unreachable!("unknown aws_s3_checksum_algorithm"),
         }
     }
+
+    fn to_aws_s3_checksum_algorithm(self) -> aws_s3_checksum_algorithm {
+        match self {
+            ChecksumAlgorithm::Crc32c => aws_s3_checksum_algorithm::AWS_SCA_CRC32C,
+            ChecksumAlgorithm::Crc32 => aws_s3_checksum_algorithm::AWS_SCA_CRC32,
+            ChecksumAlgorithm::Sha1 => aws_s3_checksum_algorithm::AWS_SCA_SHA1,
+            ChecksumAlgorithm::Sha256 => aws_s3_checksum_algorithm::AWS_SCA_SHA256,
+        }
+    }
 }
 
 /// Info for the caller to review before an upload completes.
@@ -85,7 +85,7 @@ fn main() {
 
         let start = Instant::now();
 
-        let mut request = manager.prefetch(client.clone(), bucket, key, size, ETag::for_tests());
+        let mut request = manager.prefetch(client.clone(), bucket, key, size, ETag::for_tests(), None);
         block_on(async {
             loop {
                 let offset = received_size.load(Ordering::SeqCst);
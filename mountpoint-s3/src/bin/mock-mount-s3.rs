@@ -8,27 +8,75 @@
 //! sure we can't accidentally confuse this binary with a real `mount-s3` in any of our testing or
 //! release workflows, since real bucket names cannot start with this prefix.
 //!
+//! Instead of the pre-populated mock bucket, this binary can also serve a session recorded with
+//! `RecordingClient`, by setting the `MOCK_MOUNT_S3_REPLAY_FILE` environment variable to the path
+//! of the recording. Requests are only matched with recorded ones for the same bucket, so the
+//! recording must have been made against a bucket whose name also starts with "sthree-", or edited
+//! to use one.
+//!
 //! This binary is intended only for use in testing and development of Mountpoint.
 
+use std::ffi::OsString;
+
+use anyhow::Context as _;
 use futures::executor::ThreadPool;
-use mountpoint_s3::cli::{CliArgs, S3PersonalityArg};
+use mountpoint_s3::cli::{ClientArgs, ClientUsage, S3PersonalityArg};
 use mountpoint_s3::fs::S3Personality;
 use mountpoint_s3_client::mock_client::throughput_client::ThroughputMockClient;
 use mountpoint_s3_client::mock_client::{MockClientConfig, MockObject};
+use mountpoint_s3_client::rate_limited_client::RateLimitedClient;
+use mountpoint_s3_client::replay_client::ReplayClient;
 use mountpoint_s3_client::types::ETag;
 
+const REPLAY_FILE_ENV_VAR: &str = "MOCK_MOUNT_S3_REPLAY_FILE";
+
 fn main() -> anyhow::Result<()> {
-    mountpoint_s3::cli::main(create_mock_client)
+    match std::env::var_os(REPLAY_FILE_ENV_VAR) {
+        Some(replay_file) => mountpoint_s3::cli::main(|args, usage| create_replay_client(args, usage, replay_file)),
+        None => mountpoint_s3::cli::main(create_mock_client),
+    }
 }
 
-fn create_mock_client(args: &CliArgs) -> anyhow::Result<(ThroughputMockClient, ThreadPool, S3Personality)> {
-    // An extra little safety thing to make sure we can distinguish the real mount-s3 binary and
-    // this one. Buckets starting with "sthree-" are always invalid against real S3:
-    // https://docs.aws.amazon.com/AmazonS3/latest/userguide/bucketnamingrules.html
+/// An extra little safety thing to make sure we can distinguish the real mount-s3 binary and
+/// this one. Buckets starting with "sthree-" are always invalid against real S3:
+/// https://docs.aws.amazon.com/AmazonS3/latest/userguide/bucketnamingrules.html
+fn check_bucket_name(args: &ClientArgs) -> anyhow::Result<()> {
     anyhow::ensure!(
         args.bucket_name.starts_with("sthree-"),
         "mock-mount-s3 bucket names must start with `sthree-`"
     );
+    Ok(())
+}
+
+fn s3_personality(args: &ClientArgs) -> S3Personality {
+    if let Some(S3PersonalityArg(personality)) = args.bucket_type {
+        personality
+    } else {
+        S3Personality::Standard
+    }
+}
+
+fn create_replay_client(
+    args: &ClientArgs,
+    _usage: &ClientUsage,
+    replay_file: OsString,
+) -> anyhow::Result<(ReplayClient, ThreadPool, S3Personality)> {
+    check_bucket_name(args)?;
+
+    tracing::warn!(?replay_file, "using replay client");
+
+    let client = ReplayClient::open(args.part_size as usize, &replay_file)
+        .with_context(|| format!("failed to load recording from {replay_file:?}"))?;
+    let runtime = ThreadPool::builder().name_prefix("runtime").create()?;
+
+    Ok((client, runtime, s3_personality(args)))
+}
+
+fn create_mock_client(
+    args: &ClientArgs,
+    _usage: &ClientUsage,
+) -> anyhow::Result<(RateLimitedClient<ThroughputMockClient>, ThreadPool, S3Personality)> {
+    check_bucket_name(args)?;
 
     tracing::warn!("using mock client");
 
@@ -39,16 +87,13 @@ fn create_mock_client(args: &CliArgs) -> anyhow::Result<(ThroughputMockClient, T
         bucket: args.bucket_name.clone(),
         part_size: args.part_size as usize,
         unordered_list_seed: None,
+        ..Default::default()
     };
     let client = ThroughputMockClient::new(config, max_throughput_gbps);
 
     let runtime = ThreadPool::builder().name_prefix("runtime").create()?;
 
-    let s3_personality = if let Some(S3PersonalityArg(personality)) = args.bucket_type {
-        personality
-    } else {
-        S3Personality::Standard
-    };
+    let s3_personality = s3_personality(args);
 
     // Pre-populate the bucket with some interesting file sizes and a little structure
     for expt in 0..10 {
@@ -72,6 +117,7 @@ fn create_mock_client(args: &CliArgs) -> anyhow::Result<(ThroughputMockClient, T
         "dir/hello.txt",
         MockObject::from_bytes(b"hello world", ETag::for_tests()),
     );
+    let client = RateLimitedClient::new(client, args.rate_limit_config());
 
     Ok((client, runtime, s3_personality))
 }
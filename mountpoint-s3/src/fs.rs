@@ -13,8 +13,8 @@ use tracing::{debug, error, trace, Level};
 
 use fuser::consts::FOPEN_DIRECT_IO;
 use fuser::{FileAttr, KernelConfig};
-use mountpoint_s3_client::error::{GetObjectError, ObjectClientError};
-use mountpoint_s3_client::types::ETag;
+use mountpoint_s3_client::error::{GetObjectError, ObjectClientError, PutObjectError};
+use mountpoint_s3_client::types::{ChecksumAlgorithm, ETag, SseCustomerKey};
 use mountpoint_s3_client::ObjectClient;
 
 use crate::inode::{Inode, InodeError, InodeKind, LookedUp, ReaddirHandle, Superblock, SuperblockConfig, WriteHandle};
@@ -100,6 +100,12 @@ where
         fs: &S3Filesystem<Client, Prefetcher>,
     ) -> Result<FileHandleState<Client, Prefetcher>, Error> {
         let is_truncate = flags & libc::O_TRUNC != 0;
+        // Only replace the object we saw at lookup, or don't replace any object if this is a new file
+        let etag = lookup
+            .stat
+            .etag
+            .as_ref()
+            .map(|etag| ETag::from_str(etag).expect("E-Tag should be valid"));
         let handle = fs
             .superblock
             .write(
@@ -113,7 +119,7 @@ where
             .await
             .start_writing()?;
         let key = lookup.inode.full_key();
-        let handle = match fs.uploader.put(&fs.bucket, key).await {
+        let handle = match fs.uploader.put(&fs.bucket, key, etag).await {
             Err(e) => {
                 return Err(err!(libc::EIO, source:e, "put failed to start"));
             }
@@ -140,9 +146,14 @@ where
             None => return Err(err!(libc::EBADF, "no E-Tag for inode {}", lookup.inode.ino())),
             Some(etag) => ETag::from_str(etag).expect("E-Tag should be set"),
         };
-        let request = fs
-            .prefetcher
-            .prefetch(fs.client.clone(), &fs.bucket, &full_key, object_size, etag.clone());
+        let request = fs.prefetcher.prefetch(
+            fs.client.clone(),
+            &fs.bucket,
+            &full_key,
+            object_size,
+            etag.clone(),
+            fs.config.sse_customer_key.clone(),
+        );
         let handle = FileHandleState::Read(request);
         metrics::gauge!("fs.current_handles", "type" => "read").increment(1.0);
         Ok(handle)
@@ -235,6 +246,14 @@ impl<Client: ObjectClient> UploadState<Client> {
                 debug!(key, size, "put succeeded");
                 Ok(())
             }
+            Err(ObjectClientError::ServiceError(PutObjectError::PreconditionFailed)) => Err(err!(
+                libc::EEXIST,
+                "put failed because the object was created or replaced by someone else since it was opened"
+            )),
+            Err(ObjectClientError::ServiceError(PutObjectError::ConditionalRequestConflict)) => Err(err!(
+                libc::EBUSY,
+                "put failed because another conditional write to the same key was in progress"
+            )),
             Err(e) => Err(err!(libc::EIO, source:e, "put failed")),
         };
         if let Err(err) = handle.finish_writing() {
@@ -348,12 +367,21 @@ pub struct S3FilesystemConfig {
     pub allow_delete: bool,
     /// Allow overwrite
     pub allow_overwrite: bool,
+    /// Batch deletes into DeleteObjects requests and allow removing empty remote directories
+    /// (requires `allow_delete`)
+    pub batch_deletes: bool,
     /// Storage class to be used for new object uploads
     pub storage_class: Option<String>,
     /// S3 personality (for different S3 semantics)
     pub s3_personality: S3Personality,
     /// Server side encryption configuration to be used when creating new S3 object
     pub server_side_encryption: ServerSideEncryption,
+    /// Customer-provided key to encrypt new objects with and to read existing objects with (SSE-C)
+    pub sse_customer_key: Option<SseCustomerKey>,
+    /// Checksum algorithm to upload new objects with
+    pub upload_checksum_algorithm: ChecksumAlgorithm,
+    /// Fail uploads that would replace an object created or changed since the file was opened
+    pub conditional_writes: bool,
 }
 
 impl Default for S3FilesystemConfig {
@@ -370,9 +398,13 @@ impl Default for S3FilesystemConfig {
             file_mode: 0o644,
             allow_delete: false,
             allow_overwrite: false,
+            batch_deletes: false,
             storage_class: None,
             s3_personality: S3Personality::Standard,
             server_side_encryption: Default::default(),
+            sse_customer_key: None,
+            upload_checksum_algorithm: ChecksumAlgorithm::Crc32c,
+            conditional_writes: false,
         }
     }
 }
@@ -427,6 +459,11 @@ impl ServerSideEncryption {
         }
     }
 
+    /// Returns true if no SSE type or key was configured, so S3 applies the bucket's default encryption
+    pub fn is_none(&self) -> bool {
+        self.sse_type.is_none() && self.sse_kms_key_id.is_none()
+    }
+
     /// Computes the checksum of SSE settings by combining two strings containing the type and the key
     /// Note, that this implementation yields the same result for Some("") and None, but we may safely
     /// assume that it will never be called with an empty string as one of its parameters.
@@ -535,6 +572,8 @@ where
         let superblock_config = SuperblockConfig {
             cache_config: config.cache_config.clone(),
             s3_personality: config.s3_personality,
+            batch_deletes: config.allow_delete && config.batch_deletes,
+            sse_customer_key: config.sse_customer_key.clone(),
         };
         let superblock = Superblock::new(bucket, prefix, superblock_config);
 
@@ -544,6 +583,9 @@ where
             client.clone(),
             config.storage_class.to_owned(),
             config.server_side_encryption.clone(),
+            config.sse_customer_key.clone(),
+            config.upload_checksum_algorithm,
+            config.conditional_writes,
         );
 
         Self {
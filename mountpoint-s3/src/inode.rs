@@ -32,7 +32,9 @@ use anyhow::anyhow;
 use fuser::FileType;
 use futures::{select_biased, FutureExt};
 use mountpoint_s3_client::error::{HeadObjectError, ObjectClientError};
-use mountpoint_s3_client::types::{HeadObjectResult, RestoreStatus};
+use mountpoint_s3_client::types::{
+    HeadObjectParams, HeadObjectResult, ListObjectsParams, RestoreStatus, SseCustomerKey,
+};
 use mountpoint_s3_client::ObjectClient;
 use mountpoint_s3_crt::checksums::crc32c::{self, Crc32c};
 use thiserror::Error;
@@ -53,6 +55,9 @@ use expiry::Expiry;
 mod negative_cache;
 use negative_cache::NegativeCache;
 
+mod pending_deletes;
+use pending_deletes::PendingDeletes;
+
 mod readdir;
 pub use readdir::ReaddirHandle;
 
@@ -87,6 +92,7 @@ struct SuperblockInner {
     bucket: String,
     inodes: RwLock<InodeMap>,
     negative_cache: NegativeCache,
+    pending_deletes: PendingDeletes,
     next_ino: AtomicU64,
     mount_time: OffsetDateTime,
     config: SuperblockConfig,
@@ -97,6 +103,11 @@ struct SuperblockInner {
 pub struct SuperblockConfig {
     pub cache_config: CacheConfig,
     pub s3_personality: S3Personality,
+    /// Batch the deletes from unlink into DeleteObjects requests, and allow rmdir on empty remote
+    /// directories
+    pub batch_deletes: bool,
+    /// Customer-provided key that objects are encrypted with (SSE-C)
+    pub sse_customer_key: Option<SseCustomerKey>,
 }
 
 impl Superblock {
@@ -130,6 +141,7 @@ impl Superblock {
             bucket: bucket.to_owned(),
             inodes: RwLock::new(inodes),
             negative_cache,
+            pending_deletes: Default::default(),
             next_ino: AtomicU64::new(2),
             mount_time,
             config,
@@ -406,8 +418,11 @@ impl Superblock {
         Ok(lookup)
     }
 
-    /// Remove local-only empty directory, i.e., the ones created by mkdir.
-    /// It does not affect empty directories represented remotely with directory markers.
+    /// Remove an empty directory.
+    ///
+    /// Local directories (the ones created by mkdir) can always be removed if they are empty.
+    /// Directories that exist in S3 can only be removed if deletes are batched, in which case any
+    /// directory marker object is deleted too.
     pub async fn rmdir<OC: ObjectClient>(
         &self,
         client: &OC,
@@ -428,28 +443,44 @@ impl Superblock {
             return Err(InodeError::NotADirectory(inode.err()));
         }
 
+        let write_status = {
+            let inode_state = inode.get_inode_state()?;
+            match inode_state.write_status {
+                WriteStatus::LocalOpen => unreachable!("A directory cannot be in Local open state"),
+                WriteStatus::Remote if !self.inner.config.batch_deletes => {
+                    return Err(InodeError::CannotRemoveRemoteDirectory(inode.err()));
+                }
+                _ => {}
+            }
+            if let InodeKindData::Directory { writing_children, .. } = &inode_state.kind_data {
+                if !writing_children.is_empty() {
+                    return Err(InodeError::DirectoryNotEmpty(inode.err()));
+                }
+            }
+            inode_state.write_status
+        };
+
+        if write_status == WriteStatus::Remote {
+            self.inner.remove_remote_directory(client, &inode).await?;
+        }
+
         let parent = self.inner.get(parent_ino)?;
         let mut parent_state = parent.get_mut_inode_state()?;
         let mut inode_state = inode.get_mut_inode_state()?;
 
-        match &inode_state.write_status {
-            WriteStatus::LocalOpen => unreachable!("A directory cannot be in Local open state"),
-            WriteStatus::Remote => {
-                return Err(InodeError::CannotRemoveRemoteDirectory(inode.err()));
-            }
-            WriteStatus::LocalUnopened => match &mut inode_state.kind_data {
-                InodeKindData::File {} => unreachable!("Already checked that inode is a directory"),
-                InodeKindData::Directory {
-                    writing_children,
-                    deleted,
-                    ..
-                } => {
-                    if !writing_children.is_empty() {
-                        return Err(InodeError::DirectoryNotEmpty(inode.err()));
-                    }
-                    *deleted = true;
+        match &mut inode_state.kind_data {
+            InodeKindData::File {} => unreachable!("Already checked that inode is a directory"),
+            InodeKindData::Directory {
+                writing_children,
+                deleted,
+                ..
+            } => {
+                // A child could have been created while we were checking the remote directory
+                if !writing_children.is_empty() {
+                    return Err(InodeError::DirectoryNotEmpty(inode.err()));
                 }
-            },
+                *deleted = true;
+            }
         }
 
         match &mut parent_state.kind_data {
@@ -464,7 +495,7 @@ impl Superblock {
             } => {
                 let removed = writing_children.remove(&inode.ino());
                 debug_assert!(
-                    removed,
+                    removed || write_status == WriteStatus::Remote,
                     "should be able to remove the directory from its parents writing children as it was local"
                 );
                 children.remove(inode.name());
@@ -502,9 +533,9 @@ impl Superblock {
             return Err(InodeError::IsDirectory(inode.err()));
         }
 
-        let write_status = {
+        let (write_status, etag) = {
             let inode_state = inode.get_inode_state()?;
-            inode_state.write_status
+            (inode_state.write_status, inode_state.stat.etag.clone())
         };
 
         match write_status {
@@ -517,6 +548,18 @@ impl Superblock {
                 );
                 return Err(InodeError::UnlinkNotPermittedWhileWriting(inode.err()));
             }
+            WriteStatus::Remote if self.inner.config.batch_deletes => {
+                let s3_key = inode.full_key();
+                debug!(parent=?parent_ino, ?name, "unlink on remote file will batch delete key {}", s3_key);
+                self.inner
+                    .pending_deletes
+                    .delete(client, &self.inner.bucket, s3_key.to_owned(), etag.as_deref())
+                    .await
+                    .map_err(|e| {
+                        error!(inode=%inode.err(), error=?e, "DeleteObjects failed for unlink");
+                        InodeError::ClientError(anyhow!(e).context("DeleteObjects failed"))
+                    })?;
+            }
             WriteStatus::Remote => {
                 let (bucket, s3_key) = (self.inner.bucket.as_str(), inode.full_key());
                 debug!(parent=?parent_ino, ?name, "unlink on remote file will delete key {}", s3_key);
@@ -677,6 +720,36 @@ impl SuperblockInner {
         lookup
     }
 
+    /// Check that a remote directory is empty in S3 and delete its directory marker, if it has one.
+    async fn remove_remote_directory<OC: ObjectClient>(&self, client: &OC, inode: &Inode) -> Result<(), InodeError> {
+        let dir_key = inode.full_key();
+        assert!(dir_key.ends_with('/'));
+
+        // Two keys are enough to find both the directory marker and any other child
+        let result = client
+            .list_objects(
+                &self.bucket,
+                &ListObjectsParams::new().prefix(dir_key).delimiter("/").max_keys(2),
+            )
+            .await
+            .map_err(|e| InodeError::ClientError(anyhow!(e).context("ListObjectsV2 failed")))?;
+        let has_other_children =
+            !result.common_prefixes.is_empty() || result.objects.iter().any(|object| object.key != dir_key);
+        if has_other_children {
+            return Err(InodeError::DirectoryNotEmpty(inode.err()));
+        }
+
+        if result.objects.iter().any(|object| object.key == dir_key) {
+            debug!(?dir_key, "rmdir on remote directory will delete its directory marker");
+            client
+                .delete_object(&self.bucket, dir_key)
+                .await
+                .map_err(|e| InodeError::ClientError(anyhow!(e).context("DeleteObject failed")))?;
+        }
+
+        Ok(())
+    }
+
     /// Lookup an inode in the parent directory with the given name
     /// on the remote client.
     async fn remote_lookup<OC: ObjectClient>(
@@ -720,10 +793,18 @@ impl SuperblockInner {
         //       "/" to the prefix in the request, the first common prefix we'll get back will be
         //       "dir-1/", because that precedes "dir/" in lexicographic order. Doing the
         //       ListObjects with "/" appended makes sure we always observe the correct prefix.
-        let mut file_lookup = client.head_object(&self.bucket, &full_path).fuse();
-        let mut dir_lookup = client
-            .list_objects(&self.bucket, None, "/", 1, &full_path_suffixed)
+        let mut file_lookup = client
+            .head_object(
+                &self.bucket,
+                &full_path,
+                &HeadObjectParams::new().sse_customer_key(self.config.sse_customer_key.clone()),
+            )
             .fuse();
+        let dir_lookup_params = ListObjectsParams::new()
+            .prefix(&full_path_suffixed)
+            .delimiter("/")
+            .max_keys(1);
+        let mut dir_lookup = client.list_objects(&self.bucket, &dir_lookup_params).fuse();
 
         let mut file_state = None;
 
@@ -1525,7 +1606,8 @@ impl InodeStat {
                     matches!(restore_status, Some(RestoreStatus::Restored { expiry }) if expiry > SystemTime::now());
                 if !restored && !HAS_SENT_WARNING.swap(true, Ordering::SeqCst) {
                     tracing::warn!(
-                        "objects in the GLACIER and DEEP_ARCHIVE storage classes are only accessible if restored"
+                        "objects in the GLACIER and DEEP_ARCHIVE storage classes are only accessible if restored. \
+                        use `mount-s3 restore` to restore them"
                     );
                 }
                 restored
@@ -1650,7 +1732,7 @@ mod tests {
     use std::str::FromStr;
 
     use mountpoint_s3_client::{
-        mock_client::{MockClient, MockClientConfig, MockObject},
+        mock_client::{MockClient, MockClientConfig, MockObject, Operation},
         types::ETag,
     };
     use test_case::test_case;
@@ -1767,7 +1849,7 @@ mod tests {
                         .expect("inode should exist");
                     // Grab last modified time according to mock S3
                     let modified_time = client
-                        .head_object(bucket, file.inode.full_key())
+                        .head_object(bucket, file.inode.full_key(), &HeadObjectParams::new())
                         .await
                         .expect("object should exist")
                         .object
@@ -1827,6 +1909,8 @@ mod tests {
                     ..Default::default()
                 },
                 s3_personality: S3Personality::Standard,
+                batch_deletes: false,
+                sse_customer_key: None,
             },
         );
 
@@ -1882,6 +1966,8 @@ mod tests {
                     ..Default::default()
                 },
                 s3_personality: S3Personality::Standard,
+                batch_deletes: false,
+                sse_customer_key: None,
             },
         );
 
@@ -2308,6 +2394,7 @@ mod tests {
             bucket: "test_bucket".to_string(),
             part_size: 1024 * 1024,
             unordered_list_seed: (!ordered).then_some(123456),
+            ..Default::default()
         };
         let client = Arc::new(MockClient::new(client_config));
 
@@ -2546,6 +2633,124 @@ mod tests {
         assert_eq!(libc::ENOENT, err, "lookup should return no existing entry error");
     }
 
+    #[test_case(""; "unprefixed")]
+    #[test_case("test_prefix/"; "prefixed")]
+    #[tokio::test]
+    async fn test_batched_unlink_then_rmdir(prefix: &str) {
+        let client_config = MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024 * 1024,
+            ..Default::default()
+        };
+        let client = Arc::new(MockClient::new(client_config));
+        let prefix = Prefix::new(prefix).expect("valid prefix");
+        let superblock = Superblock::new(
+            "test_bucket",
+            &prefix,
+            SuperblockConfig {
+                batch_deletes: true,
+                ..Default::default()
+            },
+        );
+
+        let keys = ["dir/", "dir/a", "dir/b"].map(|name| format!("{prefix}{name}"));
+        for key in &keys {
+            client.add_object(key, MockObject::constant(0xaa, 30, ETag::for_tests()));
+        }
+
+        let dir = superblock
+            .lookup(&client, FUSE_ROOT_INODE, "dir".as_ref())
+            .await
+            .expect("directory should exist");
+        for name in ["a", "b"] {
+            superblock
+                .lookup(&client, dir.inode.ino(), name.as_ref())
+                .await
+                .expect("file should exist");
+        }
+
+        let delete_object_counter = client.new_counter(Operation::DeleteObject);
+        let delete_objects_counter = client.new_counter(Operation::DeleteObjects);
+        for name in ["a", "b"] {
+            superblock
+                .unlink(&client, dir.inode.ino(), name.as_ref())
+                .await
+                .expect("unlink should succeed");
+        }
+
+        // Each unlink waits for its own DeleteObjects request, since they weren't concurrent
+        assert_eq!(delete_object_counter.count(), 0);
+        assert_eq!(delete_objects_counter.count(), 2);
+        assert!(!client.contains_key(&keys[1]));
+        assert!(!client.contains_key(&keys[2]));
+        let entries = superblock
+            .readdir(&client, dir.inode.ino(), 10)
+            .await
+            .unwrap()
+            .collect(&client)
+            .await
+            .unwrap();
+        assert!(entries.is_empty());
+
+        superblock
+            .rmdir(&client, FUSE_ROOT_INODE, "dir".as_ref())
+            .await
+            .expect("rmdir on empty remote directory should succeed");
+        for key in &keys {
+            assert!(!client.contains_key(key));
+        }
+        assert_eq!(
+            delete_object_counter.count(),
+            1,
+            "rmdir should delete the directory marker"
+        );
+
+        let err = superblock
+            .lookup(&client, FUSE_ROOT_INODE, "dir".as_ref())
+            .await
+            .expect_err("directory should be gone");
+        assert_eq!(err.to_errno(), libc::ENOENT);
+    }
+
+    #[test_case(false; "without batch deletes")]
+    #[test_case(true; "with batch deletes")]
+    #[tokio::test]
+    async fn test_rmdir_remote_dir(batch_deletes: bool) {
+        let client_config = MockClientConfig {
+            bucket: "test_bucket".to_string(),
+            part_size: 1024 * 1024,
+            ..Default::default()
+        };
+        let client = Arc::new(MockClient::new(client_config));
+        let superblock = Superblock::new(
+            "test_bucket",
+            &Default::default(),
+            SuperblockConfig {
+                batch_deletes,
+                ..Default::default()
+            },
+        );
+        client.add_object("empty/", MockObject::constant(0xaa, 0, ETag::for_tests()));
+        client.add_object("full/file", MockObject::constant(0xaa, 30, ETag::for_tests()));
+
+        let err = superblock
+            .rmdir(&client, FUSE_ROOT_INODE, "full".as_ref())
+            .await
+            .expect_err("rmdir on a non-empty remote directory should fail");
+        let expected = if batch_deletes { libc::ENOTEMPTY } else { libc::EPERM };
+        assert_eq!(err.to_errno(), expected);
+        assert!(client.contains_key("full/file"));
+
+        let result = superblock.rmdir(&client, FUSE_ROOT_INODE, "empty".as_ref()).await;
+        if batch_deletes {
+            result.expect("rmdir on an empty remote directory should succeed");
+            assert!(!client.contains_key("empty/"));
+        } else {
+            assert_eq!(result.expect_err("rmdir should fail").to_errno(), libc::EPERM);
+            assert!(client.contains_key("empty/"));
+        }
+    }
+
     #[tokio::test]
     async fn test_unlink_verify_checksum() {
         let client_config = MockClientConfig {
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use futures::channel::oneshot;
+use mountpoint_s3_client::types::{ETag, ObjectIdentifier, MAX_DELETE_OBJECTS_KEYS};
+use mountpoint_s3_client::ObjectClient;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::sync::{AsyncMutex, Mutex};
+
+/// Keys of unlinked files waiting to be deleted from S3.
+///
+/// Each unlink queues its key and then waits for the DeleteObjects request that deletes it, so an
+/// unlink still only succeeds once its object is gone. Only one request is in flight at a time, and
+/// the keys queued while it runs are all deleted by the next one, so removing many files from
+/// several threads needs far fewer requests than sending one DeleteObject request per file.
+///
+/// Keys are queued with the ETag of the unlinked object, so that an object another client replaced
+/// since it was looked up isn't deleted.
+#[derive(Debug, Default)]
+pub struct PendingDeletes {
+    queue: Mutex<Vec<PendingDelete>>,
+    /// Held while a DeleteObjects request is in flight
+    request_lock: AsyncMutex<()>,
+}
+
+#[derive(Debug)]
+struct PendingDelete {
+    key: String,
+    etag: Option<ETag>,
+    sender: oneshot::Sender<Result<(), DeleteError>>,
+}
+
+/// Reasons a queued key could not be deleted
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DeleteError {
+    #[error("DeleteObjects request failed: {0}")]
+    RequestFailed(String),
+
+    #[error("DeleteObjects failed to delete the key: {code}: {message}")]
+    KeyFailed { code: String, message: String },
+
+    #[error("DeleteObjects response did not include the key")]
+    MissingFromResponse,
+
+    #[error("DeleteObjects request was cancelled")]
+    Cancelled,
+}
+
+impl PendingDeletes {
+    /// Delete `key` from S3 if its object still has the given ETag, together with any other keys
+    /// queued meanwhile. Returns once the DeleteObjects request that included the key completes.
+    pub async fn delete<OC: ObjectClient>(
+        &self,
+        client: &OC,
+        bucket: &str,
+        key: String,
+        etag: Option<&str>,
+    ) -> Result<(), DeleteError> {
+        let etag = etag.map(|etag| ETag::from_str(etag).expect("ETag parsing is infallible"));
+        let (sender, mut receiver) = oneshot::channel();
+        self.queue.lock().unwrap().push(PendingDelete { key, etag, sender });
+
+        loop {
+            let _guard = self.request_lock.lock().await;
+            // The request that held the lock before us may already have included our key
+            match receiver.try_recv() {
+                Ok(Some(result)) => return result,
+                Ok(None) => self.send_batch(client, bucket).await,
+                Err(oneshot::Canceled) => return Err(DeleteError::Cancelled),
+            }
+        }
+    }
+
+    /// Delete up to [MAX_DELETE_OBJECTS_KEYS] queued keys with one DeleteObjects request, and send
+    /// each key's outcome to the unlink waiting for it. Must be called with `request_lock` held.
+    async fn send_batch<OC: ObjectClient>(&self, client: &OC, bucket: &str) {
+        let batch: Vec<PendingDelete> = {
+            let mut queue = self.queue.lock().unwrap();
+            let len = queue.len().min(MAX_DELETE_OBJECTS_KEYS);
+            queue.drain(..len).collect()
+        };
+        if batch.is_empty() {
+            return;
+        }
+        debug!(num_keys = batch.len(), "deleting batch of unlinked keys");
+        metrics::counter!("fs.batched_deletes").increment(batch.len() as u64);
+
+        let objects: Vec<_> = batch
+            .iter()
+            .map(|pending| {
+                let object = ObjectIdentifier::new(&pending.key);
+                match &pending.etag {
+                    Some(etag) => object.etag(etag.clone()),
+                    None => object,
+                }
+            })
+            .collect();
+        let mut outcomes = HashMap::new();
+        match client.delete_objects(bucket, &objects).await {
+            Ok(result) => {
+                for key in result.deleted {
+                    outcomes.insert(key, Ok(()));
+                }
+                for failure in result.errors {
+                    let outcome = match failure.code.as_str() {
+                        // The object was replaced or deleted since it was looked up, so leave it alone
+                        "PreconditionFailed" | "NoSuchKey" => {
+                            debug!(key=?failure.key, code=?failure.code, "object changed since lookup, not deleting it");
+                            Ok(())
+                        }
+                        _ => {
+                            warn!(
+                                key=?failure.key,
+                                code=?failure.code,
+                                message=?failure.message,
+                                "DeleteObjects failed to delete key for unlink",
+                            );
+                            Err(DeleteError::KeyFailed {
+                                code: failure.code,
+                                message: failure.message,
+                            })
+                        }
+                    };
+                    outcomes.insert(failure.key, outcome);
+                }
+            }
+            Err(e) => {
+                warn!(error=?e, num_keys = batch.len(), "DeleteObjects failed for unlink");
+                let error = DeleteError::RequestFailed(e.to_string());
+                for pending in &batch {
+                    outcomes.insert(pending.key.clone(), Err(error.clone()));
+                }
+            }
+        }
+
+        for pending in batch {
+            let outcome = outcomes
+                .get(&pending.key)
+                .cloned()
+                .unwrap_or(Err(DeleteError::MissingFromResponse));
+            // The unlink may have given up waiting, in which case nobody needs the outcome
+            let _ = pending.sender.send(outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+
+    use futures::future::join_all;
+    use futures::poll;
+    use mountpoint_s3_client::mock_client::{MockClient, MockClientConfig, MockObject, Operation};
+
+    use super::*;
+
+    fn new_client(bucket: &str) -> MockClient {
+        MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 1024,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_deletes_are_batched() {
+        let bucket = "bucket";
+        let client = new_client(bucket);
+        let keys: Vec<_> = (0..MAX_DELETE_OBJECTS_KEYS + 1).map(|i| format!("key{i}")).collect();
+        for key in &keys {
+            client.add_object(key, MockObject::constant(0u8, 1, ETag::for_tests()));
+        }
+        let delete_objects_counter = client.new_counter(Operation::DeleteObjects);
+
+        // Queue every key while another request is in flight
+        let pending = PendingDeletes::default();
+        let guard = pending.request_lock.lock().await;
+        let deletes = join_all(
+            keys.iter()
+                .map(|key| pending.delete(&client, bucket, key.clone(), None)),
+        );
+        let mut deletes = pin!(deletes);
+        assert!(poll!(&mut deletes).is_pending());
+        drop(guard);
+
+        let results = deletes.await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(delete_objects_counter.count(), 2);
+        for key in &keys {
+            assert!(!client.contains_key(key));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_replaced_object() {
+        let bucket = "bucket";
+        let client = new_client(bucket);
+        client.add_object("key", MockObject::constant(0u8, 1, ETag::from_str("new").unwrap()));
+
+        // Another client replaced the object after we looked it up, so the new object must survive
+        let pending = PendingDeletes::default();
+        pending
+            .delete(&client, bucket, "key".to_owned(), Some("old"))
+            .await
+            .expect("unlink of a replaced object should succeed");
+        assert!(client.contains_key("key"));
+
+        pending
+            .delete(&client, bucket, "key".to_owned(), Some("new"))
+            .await
+            .expect("delete should succeed");
+        assert!(!client.contains_key("key"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_failure() {
+        let client = new_client("bucket");
+        client.add_object("key", MockObject::constant(0u8, 1, ETag::for_tests()));
+
+        // Every DeleteObjects request to a bucket the mock doesn't have fails
+        let pending = PendingDeletes::default();
+        let result = pending.delete(&client, "other_bucket", "key".to_owned(), None).await;
+        assert!(matches!(result, Err(DeleteError::RequestFailed(_))));
+        assert!(client.contains_key("key"));
+        assert!(pending.queue.lock().unwrap().is_empty());
+    }
+}
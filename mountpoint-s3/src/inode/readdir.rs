@@ -38,7 +38,7 @@
 use std::cmp::Ordering;
 use std::collections::VecDeque;
 
-use mountpoint_s3_client::types::ObjectInfo;
+use mountpoint_s3_client::types::{ListObjectsParams, ObjectInfo};
 use mountpoint_s3_client::ObjectClient;
 use tracing::{error, trace, warn};
 
@@ -347,10 +347,11 @@ impl RemoteIter {
             let result = client
                 .list_objects(
                     &self.bucket,
-                    continuation_token.as_deref(),
-                    "/",
-                    self.page_size,
-                    self.full_path.as_str(),
+                    &ListObjectsParams::new()
+                        .prefix(self.full_path.as_str())
+                        .delimiter("/")
+                        .max_keys(self.page_size)
+                        .continuation_token(continuation_token.as_deref()),
                 )
                 .await
                 .map_err(|e| InodeError::ClientError(anyhow::Error::new(e)))?;
@@ -12,6 +12,9 @@ use metrics::{Key, Metadata, Recorder};
 use crate::sync::mpsc::{channel, RecvTimeoutError, Sender};
 use crate::sync::Arc;
 
+mod cost;
+pub use cost::{CostModel, PriceTable};
+
 mod data;
 use data::*;
 
@@ -28,9 +31,12 @@ pub const TARGET_NAME: &str = "mountpoint_s3::metrics";
 /// the sink down. The sink should only be shut down after any threads that generate metrics are
 /// done with their work; metrics generated after shutting down the sink will be lost.
 ///
+/// If a [CostModel] is provided, the sink also estimates the cost of S3 requests, and logs the
+/// estimate with the other metrics and when it's shut down.
+///
 /// Panics if a sink has already been installed.
-pub fn install() -> MetricsSinkHandle {
-    let sink = Arc::new(MetricsSink::new());
+pub fn install(cost_model: Option<CostModel>) -> MetricsSinkHandle {
+    let sink = Arc::new(MetricsSink::new(cost_model));
 
     let (tx, rx) = channel();
 
@@ -47,6 +53,7 @@ pub fn install() -> MetricsSinkHandle {
             // any new metrics data after the sink shuts down, but we assume a clean shutdown
             // stops generating new metrics before shutting down the sink.
             inner.publish();
+            inner.publish_cost_summary();
         })
     };
 
@@ -64,18 +71,24 @@ pub fn install() -> MetricsSinkHandle {
 #[derive(Debug)]
 struct MetricsSink {
     metrics: DashMap<Key, Metric>,
+    cost_model: Option<CostModel>,
 }
 
 impl MetricsSink {
-    fn new() -> Self {
+    fn new(cost_model: Option<CostModel>) -> Self {
         Self {
             metrics: DashMap::with_capacity(64),
+            cost_model,
         }
     }
 
     fn counter(&self, key: &Key) -> metrics::Counter {
         let entry = self.metrics.entry(key.clone()).or_insert_with(Metric::counter);
-        entry.as_counter()
+        let counter = entry.as_counter();
+        match &self.cost_model {
+            Some(cost_model) => cost_model.wrap_counter(key, counter),
+            None => counter,
+        }
     }
 
     fn gauge(&self, key: &Key) -> metrics::Gauge {
@@ -112,12 +125,23 @@ impl MetricsSink {
             metrics.push(format!("{}{}: {}", key.name(), labels, metric));
         }
 
+        if let Some(cost_model) = &self.cost_model {
+            metrics.extend(cost_model.report());
+        }
+
         metrics.sort();
 
         for metric in metrics {
             tracing::info!(target: TARGET_NAME, "{}", metric);
         }
     }
+
+    /// Publish the estimated cost of all S3 requests since the sink was installed
+    fn publish_cost_summary(&self) {
+        if let Some(cost_model) = &self.cost_model {
+            tracing::info!(target: TARGET_NAME, "{}", cost_model.summary());
+        }
+    }
 }
 
 /// The actual recorder that will be installed for the metrics facade. Just a wrapper around a
@@ -193,7 +217,7 @@ mod tests {
 
     #[test]
     fn basic_metrics() {
-        let sink = Arc::new(MetricsSink::new());
+        let sink = Arc::new(MetricsSink::new(None));
         let recorder = MetricsRecorder { sink: sink.clone() };
         with_local_recorder(&recorder, || {
             // Run twice to check reset works
@@ -284,4 +308,37 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn cost_model_counts_requests() {
+        let cost_model = CostModel::new("bucket", "", None, PriceTable::default());
+        let sink = Arc::new(MetricsSink::new(Some(cost_model)));
+        let recorder = MetricsRecorder { sink: sink.clone() };
+        with_local_recorder(&recorder, || {
+            for _ in 0..2000 {
+                metrics::counter!("s3.requests.billed", "op" => "list_objects", "type" => "Default", "storage_class" => "")
+                    .increment(1);
+            }
+        });
+
+        // The request counter is still published as usual
+        let key = Key::from_parts(
+            "s3.requests.billed",
+            vec![
+                Label::new("op", "list_objects"),
+                Label::new("type", "Default"),
+                Label::new("storage_class", ""),
+            ],
+        );
+        let Metric::Counter(counter) = &*sink.metrics.get(&key).unwrap() else {
+            panic!("should be a counter");
+        };
+        assert_eq!(counter.load_and_reset(), Some((2000, 2000)));
+
+        let summary = sink.cost_model.as_ref().unwrap().summary();
+        assert_eq!(
+            summary,
+            "estimated S3 request charges for bucket bucket: $0.010000 for 2000 requests"
+        );
+    }
 }
@@ -0,0 +1,424 @@
+//! Estimates of what Mountpoint's S3 requests cost.
+//!
+//! The S3 client reports every request S3 responded to in the `s3.requests.billed` counter,
+//! labelled with the operation, the CRT request type, and (for reads of non-Standard objects) the
+//! storage class. It also reports the bytes downloaded and uploaded. The [CostModel] classifies
+//! these requests into price tiers and multiplies them by a [PriceTable] to estimate the request
+//! and retrieval charges for the mounted bucket and prefix. It doesn't include storage or data
+//! transfer charges, which depend on things Mountpoint can't see.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Context as _;
+use dashmap::DashMap;
+use metrics::{CounterFn, Key};
+use serde::Deserialize;
+
+use crate::sync::atomic::{AtomicU64, Ordering};
+use crate::sync::Arc;
+
+/// The storage class of objects that S3 doesn't report one for
+const DEFAULT_STORAGE_CLASS: &str = "STANDARD";
+
+/// Prices for requests against one storage class, in USD
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StorageClassPrices {
+    /// Price per 1,000 PUT, COPY, POST, and LIST requests
+    pub write_requests_per_thousand: f64,
+    /// Price per 1,000 GET, HEAD, and other requests
+    pub read_requests_per_thousand: f64,
+    /// Price per GB of data read
+    #[serde(default)]
+    pub retrieval_per_gb: f64,
+}
+
+impl StorageClassPrices {
+    const fn new(write_requests_per_thousand: f64, read_requests_per_thousand: f64, retrieval_per_gb: f64) -> Self {
+        Self {
+            write_requests_per_thousand,
+            read_requests_per_thousand,
+            retrieval_per_gb,
+        }
+    }
+}
+
+/// Request prices for each storage class. The default table has the public prices for the
+/// us-east-1 Region; prices for other Regions and negotiated discounts can be loaded from a file
+/// with [PriceTable::from_file].
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    prices: HashMap<String, StorageClassPrices>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        let prices = [
+            ("STANDARD", StorageClassPrices::new(0.005, 0.0004, 0.0)),
+            ("REDUCED_REDUNDANCY", StorageClassPrices::new(0.005, 0.0004, 0.0)),
+            ("INTELLIGENT_TIERING", StorageClassPrices::new(0.005, 0.0004, 0.0)),
+            ("STANDARD_IA", StorageClassPrices::new(0.01, 0.001, 0.01)),
+            ("ONEZONE_IA", StorageClassPrices::new(0.01, 0.001, 0.01)),
+            ("GLACIER_IR", StorageClassPrices::new(0.02, 0.01, 0.03)),
+            ("GLACIER", StorageClassPrices::new(0.03, 0.0004, 0.0)),
+            ("DEEP_ARCHIVE", StorageClassPrices::new(0.05, 0.0004, 0.0)),
+            ("EXPRESS_ONEZONE", StorageClassPrices::new(0.0025, 0.0002, 0.0)),
+        ];
+        Self {
+            prices: prices
+                .into_iter()
+                .map(|(storage_class, prices)| (storage_class.to_owned(), prices))
+                .collect(),
+        }
+    }
+}
+
+impl PriceTable {
+    /// Load a price table from a JSON file that maps storage class names to
+    /// [StorageClassPrices]. Storage classes missing from the file keep their default prices.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| format!("failed to read price table {path:?}"))?;
+        Self::from_json(&contents).with_context(|| format!("invalid price table {path:?}"))
+    }
+
+    fn from_json(json: &str) -> anyhow::Result<Self> {
+        let overrides: HashMap<String, StorageClassPrices> = serde_json::from_str(json)?;
+        let mut table = Self::default();
+        table.prices.extend(overrides);
+        Ok(table)
+    }
+
+    /// The prices for a storage class. Unknown storage classes are priced like Standard.
+    fn prices(&self, storage_class: &str) -> StorageClassPrices {
+        self.prices
+            .get(storage_class)
+            .or_else(|| self.prices.get(DEFAULT_STORAGE_CLASS))
+            .copied()
+            .unwrap_or(StorageClassPrices::new(0.0, 0.0, 0.0))
+    }
+}
+
+/// The price tier of an S3 request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum RequestTier {
+    /// PUT, COPY, and POST requests, priced by the storage class of the new object
+    Write,
+    /// LIST requests, priced like writes to the Standard storage class
+    List,
+    /// GET, HEAD, and other requests, priced by the storage class of the object
+    Read,
+}
+
+impl RequestTier {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RequestTier::Write => "write",
+            RequestTier::List => "list",
+            RequestTier::Read => "read",
+        }
+    }
+}
+
+/// Classify a request by the name of the client operation it was part of and the CRT request type.
+/// Returns `None` for requests S3 doesn't charge for.
+fn classify(op: &str, request_type: &str) -> Option<RequestTier> {
+    match request_type {
+        "GetObject" | "HeadObject" => Some(RequestTier::Read),
+        "ListParts" => Some(RequestTier::List),
+        "CreateMultipartUpload" | "UploadPart" | "CompleteMultipartUpload" | "UploadPartCopy" => {
+            Some(RequestTier::Write)
+        }
+        "AbortMultipartUpload" => None,
+        // Requests the CRT doesn't know about, so we need the operation to tell what they were
+        _ => match op {
+            "delete_object" | "delete_objects" | "abort_multipart_upload" => None,
+            "list_objects" | "list_object_versions" | "list_multipart_uploads" => Some(RequestTier::List),
+            "put_object"
+            | "copy_object"
+            | "create_multipart_upload"
+            | "upload_part"
+            | "complete_multipart_upload"
+            | "restore_object" => Some(RequestTier::Write),
+            _ => Some(RequestTier::Read),
+        },
+    }
+}
+
+/// Accumulates S3 requests and bytes transferred for one mounted bucket and prefix, and estimates
+/// their cost.
+#[derive(Debug)]
+pub struct CostModel {
+    bucket: String,
+    prefix: String,
+    /// The storage class new objects are written with, which S3 doesn't echo back in responses
+    write_storage_class: String,
+    prices: PriceTable,
+    requests: DashMap<(RequestTier, String), Arc<AtomicU64>>,
+    downloaded_bytes: DashMap<String, Arc<AtomicU64>>,
+    uploaded_bytes: Arc<AtomicU64>,
+    /// Number of requests at the last report, so we only log the estimate when it changes
+    last_reported: AtomicU64,
+}
+
+impl CostModel {
+    pub fn new(bucket: &str, prefix: &str, write_storage_class: Option<&str>, prices: PriceTable) -> Self {
+        Self {
+            bucket: bucket.to_owned(),
+            prefix: prefix.to_owned(),
+            write_storage_class: write_storage_class.unwrap_or(DEFAULT_STORAGE_CLASS).to_owned(),
+            prices,
+            requests: DashMap::new(),
+            downloaded_bytes: DashMap::new(),
+            uploaded_bytes: Default::default(),
+            last_reported: AtomicU64::new(0),
+        }
+    }
+
+    /// Wrap the counter for `key` so that its increments are also accounted to this model, if it's
+    /// one of the S3 client's request counters.
+    pub fn wrap_counter(&self, key: &Key, counter: metrics::Counter) -> metrics::Counter {
+        let Some(usage) = self.usage_for(key) else {
+            return counter;
+        };
+        metrics::Counter::from_arc(Arc::new(UsageCounter { counter, usage }))
+    }
+
+    fn usage_for(&self, key: &Key) -> Option<Arc<AtomicU64>> {
+        let label = |name: &str| {
+            key.labels()
+                .find(|label| label.key() == name)
+                .map(|label| label.value())
+                .unwrap_or_default()
+        };
+        let storage_class = |default: &str| match label("storage_class") {
+            "" => default.to_owned(),
+            storage_class => storage_class.to_owned(),
+        };
+        match key.name() {
+            "s3.requests.billed" => {
+                let tier = classify(label("op"), label("type"))?;
+                let storage_class = match tier {
+                    RequestTier::Write => storage_class(&self.write_storage_class),
+                    RequestTier::List => DEFAULT_STORAGE_CLASS.to_owned(),
+                    RequestTier::Read => storage_class(DEFAULT_STORAGE_CLASS),
+                };
+                Some(self.requests.entry((tier, storage_class)).or_default().clone())
+            }
+            "s3.requests.downloaded_bytes" => Some(
+                self.downloaded_bytes
+                    .entry(storage_class(DEFAULT_STORAGE_CLASS))
+                    .or_default()
+                    .clone(),
+            ),
+            "s3.requests.uploaded_bytes" => Some(self.uploaded_bytes.clone()),
+            _ => None,
+        }
+    }
+
+    /// The estimated cost so far, broken down by request tier and storage class, and the total
+    fn estimate(&self) -> (Vec<(RequestTier, String, u64, f64)>, f64) {
+        let mut lines = Vec::new();
+        let mut total = 0.0;
+        for entry in self.requests.iter() {
+            let (tier, storage_class) = entry.key();
+            let count = entry.value().load(Ordering::SeqCst);
+            let prices = self.prices.prices(storage_class);
+            let per_thousand = match tier {
+                RequestTier::Write => prices.write_requests_per_thousand,
+                RequestTier::List => self.prices.prices(DEFAULT_STORAGE_CLASS).write_requests_per_thousand,
+                RequestTier::Read => prices.read_requests_per_thousand,
+            };
+            let cost = count as f64 / 1000.0 * per_thousand;
+            total += cost;
+            lines.push((*tier, storage_class.clone(), count, cost));
+        }
+        for entry in self.downloaded_bytes.iter() {
+            let bytes = entry.value().load(Ordering::SeqCst);
+            total += bytes as f64 / GIB * self.prices.prices(entry.key()).retrieval_per_gb;
+        }
+        lines.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        (lines, total)
+    }
+
+    /// Format the estimated cost so far as metrics lines, if there have been new requests since
+    /// the last report
+    pub fn report(&self) -> Vec<String> {
+        let total_requests = self
+            .requests
+            .iter()
+            .map(|entry| entry.value().load(Ordering::SeqCst))
+            .sum::<u64>();
+        if self.last_reported.swap(total_requests, Ordering::SeqCst) == total_requests {
+            return vec![];
+        }
+
+        let (lines, total) = self.estimate();
+        let mut report = lines
+            .into_iter()
+            .map(|(tier, storage_class, count, cost)| {
+                format!(
+                    "s3.estimated_cost[{},storage_class={},tier={}]: requests={} cost=${:.6}",
+                    self.labels(),
+                    storage_class,
+                    tier.as_str(),
+                    count,
+                    cost
+                )
+            })
+            .collect::<Vec<_>>();
+        report.push(format!(
+            "s3.estimated_cost[{}]: downloaded_bytes={} uploaded_bytes={} total=${:.6}",
+            self.labels(),
+            self.downloaded_bytes
+                .iter()
+                .map(|entry| entry.value().load(Ordering::SeqCst))
+                .sum::<u64>(),
+            self.uploaded_bytes.load(Ordering::SeqCst),
+            total
+        ));
+        report
+    }
+
+    /// A one-line summary of the estimated cost, for when the file system is unmounted
+    pub fn summary(&self) -> String {
+        let (lines, total) = self.estimate();
+        let requests = lines.iter().map(|(_, _, count, _)| count).sum::<u64>();
+        format!(
+            "estimated S3 request charges for {}: ${:.6} for {} requests",
+            self.description(),
+            total,
+            requests
+        )
+    }
+
+    fn labels(&self) -> String {
+        format!("bucket={},prefix={}", self.bucket, self.prefix)
+    }
+
+    fn description(&self) -> String {
+        if self.prefix.is_empty() {
+            format!("bucket {}", self.bucket)
+        } else {
+            format!("prefix {} of bucket {}", self.prefix, self.bucket)
+        }
+    }
+}
+
+const GIB: f64 = (1024 * 1024 * 1024) as f64;
+
+/// A counter that also adds its increments to a [CostModel]'s usage
+struct UsageCounter {
+    counter: metrics::Counter,
+    usage: Arc<AtomicU64>,
+}
+
+impl CounterFn for UsageCounter {
+    fn increment(&self, value: u64) {
+        self.counter.increment(value);
+        self.usage.fetch_add(value, Ordering::SeqCst);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.counter.absolute(value);
+        self.usage.store(value, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics::Label;
+
+    fn key(name: &'static str, labels: &[(&'static str, &'static str)]) -> Key {
+        let labels = labels
+            .iter()
+            .map(|(key, value)| Label::new(*key, *value))
+            .collect::<Vec<_>>();
+        Key::from_parts(name, labels)
+    }
+
+    fn increment(model: &CostModel, key: Key, value: u64) {
+        let counter = model.wrap_counter(&key, metrics::Counter::noop());
+        counter.increment(value);
+    }
+
+    #[test]
+    fn classify_requests() {
+        assert_eq!(classify("get_object", "GetObject"), Some(RequestTier::Read));
+        assert_eq!(classify("head_object", "Default"), Some(RequestTier::Read));
+        assert_eq!(classify("put_object", "UploadPart"), Some(RequestTier::Write));
+        assert_eq!(classify("put_object", "Default"), Some(RequestTier::Write));
+        assert_eq!(classify("put_object", "AbortMultipartUpload"), None);
+        assert_eq!(classify("list_objects", "Default"), Some(RequestTier::List));
+        assert_eq!(classify("delete_objects", "Default"), None);
+        assert_eq!(classify("get_object_attributes", "Default"), Some(RequestTier::Read));
+        assert_eq!(classify("restore_object", "Default"), Some(RequestTier::Write));
+    }
+
+    #[test]
+    fn estimate_cost() {
+        let model = CostModel::new("bucket", "prefix/", Some("STANDARD_IA"), PriceTable::default());
+
+        let get = key(
+            "s3.requests.billed",
+            &[("op", "get_object"), ("type", "GetObject"), ("storage_class", "")],
+        );
+        increment(&model, get, 10_000);
+        let put = key(
+            "s3.requests.billed",
+            &[("op", "put_object"), ("type", "UploadPart"), ("storage_class", "")],
+        );
+        increment(&model, put, 1000);
+        let list = key(
+            "s3.requests.billed",
+            &[("op", "list_objects"), ("type", "Default"), ("storage_class", "")],
+        );
+        increment(&model, list, 2000);
+        let delete = key(
+            "s3.requests.billed",
+            &[("op", "delete_object"), ("type", "Default"), ("storage_class", "")],
+        );
+        increment(&model, delete, 1000);
+        let glacier_ir_bytes = key("s3.requests.downloaded_bytes", &[("storage_class", "GLACIER_IR")]);
+        increment(&model, glacier_ir_bytes, 2 * 1024 * 1024 * 1024);
+        // Unrelated counters aren't accounted
+        increment(&model, key("s3.requests", &[]), 1000);
+
+        let (lines, total) = model.estimate();
+        assert_eq!(
+            lines
+                .iter()
+                .map(|(tier, sc, count, _)| (*tier, sc.as_str(), *count))
+                .collect::<Vec<_>>(),
+            vec![
+                (RequestTier::Write, "STANDARD_IA", 1000),
+                (RequestTier::List, "STANDARD", 2000),
+                (RequestTier::Read, "STANDARD", 10_000),
+            ]
+        );
+        // 1000 writes to STANDARD_IA, 2000 LISTs, 10,000 Standard GETs, and 2GiB from GLACIER_IR
+        let expected = 0.01 + 2.0 * 0.005 + 10.0 * 0.0004 + 2.0 * 0.03;
+        assert!((total - expected).abs() < 1e-9, "{total} != {expected}");
+
+        let report = model.report();
+        assert_eq!(report.len(), 4);
+        assert!(report[3].starts_with("s3.estimated_cost[bucket=bucket,prefix=prefix/]: downloaded_bytes=2147483648"));
+        // Nothing changed, so nothing to report
+        assert!(model.report().is_empty());
+    }
+
+    #[test]
+    fn price_table_overrides() {
+        let table = PriceTable::from_json(
+            r#"{"STANDARD": {"write_requests_per_thousand": 0.0054, "read_requests_per_thousand": 0.00043}}"#,
+        )
+        .unwrap();
+        assert_eq!(table.prices("STANDARD"), StorageClassPrices::new(0.0054, 0.00043, 0.0));
+        assert_eq!(table.prices("GLACIER_IR"), StorageClassPrices::new(0.02, 0.01, 0.03));
+        // Unknown storage classes are priced like Standard
+        assert_eq!(table.prices("MYSTERY"), StorageClassPrices::new(0.0054, 0.00043, 0.0));
+
+        assert!(PriceTable::from_json(r#"{"STANDARD": {"read_requests_per_thousand": 1.0}}"#).is_err());
+    }
+}
@@ -22,7 +22,7 @@ use async_trait::async_trait;
 use futures::task::Spawn;
 use metrics::{counter, histogram};
 use mountpoint_s3_client::error::{GetObjectError, ObjectClientError};
-use mountpoint_s3_client::types::ETag;
+use mountpoint_s3_client::types::{ETag, SseCustomerKey};
 use mountpoint_s3_client::ObjectClient;
 use thiserror::Error;
 use tracing::trace;
@@ -40,7 +40,8 @@ use crate::sync::Arc;
 pub trait Prefetch {
     type PrefetchResult<Client: ObjectClient + Send + Sync + 'static>: PrefetchResult<Client>;
 
-    /// Start a new prefetch request to the specified object.
+    /// Start a new prefetch request to the specified object. If the object is encrypted with a
+    /// customer-provided key, `sse_customer_key` must be that key.
     fn prefetch<Client>(
         &self,
         client: Arc<Client>,
@@ -48,6 +49,7 @@ pub trait Prefetch {
         key: &str,
         size: u64,
         etag: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
     ) -> Self::PrefetchResult<Client>
     where
         Client: ObjectClient + Send + Sync + 'static;
@@ -183,6 +185,7 @@ where
         key: &str,
         size: u64,
         etag: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
     ) -> Self::PrefetchResult<Client>
     where
         Client: ObjectClient + Send + Sync + 'static,
@@ -195,6 +198,7 @@ where
             key,
             size,
             etag,
+            sse_customer_key,
         )
     }
 }
@@ -216,6 +220,7 @@ pub struct PrefetchGetObject<Stream: ObjectPartStream, Client: ObjectClient> {
     backward_seek_window: SeekWindow,
     bucket: String,
     object_id: ObjectId,
+    sse_customer_key: Option<SseCustomerKey>,
     // preferred part size in the prefetcher's part queue, not the object part
     preferred_part_size: usize,
     /// Start offset for sequential read, used for calculating contiguous read metric
@@ -349,6 +354,7 @@ where
         key: &str,
         size: u64,
         etag: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
     ) -> Self {
         PrefetchGetObject {
             client,
@@ -364,6 +370,7 @@ where
             next_request_offset: 0,
             bucket: bucket.to_owned(),
             object_id: ObjectId::new(key.to_owned(), etag),
+            sse_customer_key,
             size,
         }
     }
@@ -407,6 +414,7 @@ where
             &self.bucket,
             self.object_id.key(),
             self.object_id.etag().clone(),
+            self.sse_customer_key.clone(),
             range,
             self.preferred_part_size,
         );
@@ -626,7 +634,7 @@ mod tests {
         };
 
         let prefetcher = Prefetcher::new(part_stream, prefetcher_config);
-        let mut request = prefetcher.prefetch(client, "test-bucket", "hello", size, etag);
+        let mut request = prefetcher.prefetch(client, "test-bucket", "hello", size, etag, None);
 
         let mut next_offset = 0;
         loop {
@@ -722,7 +730,7 @@ mod tests {
         };
 
         let prefetcher = Prefetcher::new(part_stream, prefetcher_config);
-        let mut request = prefetcher.prefetch(Arc::new(client), "test-bucket", "hello", size, etag);
+        let mut request = prefetcher.prefetch(Arc::new(client), "test-bucket", "hello", size, etag, None);
 
         let mut next_offset = 0;
         loop {
@@ -849,7 +857,7 @@ mod tests {
         };
 
         let prefetcher = Prefetcher::new(part_stream, prefetcher_config);
-        let mut request = prefetcher.prefetch(client, "test-bucket", "hello", object_size, etag);
+        let mut request = prefetcher.prefetch(client, "test-bucket", "hello", object_size, etag, None);
 
         for (offset, length) in reads {
             assert!(offset < object_size);
@@ -995,8 +1003,14 @@ mod tests {
 
         // Try every possible seek from first_read_size
         for offset in first_read_size + 1..OBJECT_SIZE {
-            let mut request =
-                prefetcher.prefetch(client.clone(), "test-bucket", "hello", OBJECT_SIZE as u64, etag.clone());
+            let mut request = prefetcher.prefetch(
+                client.clone(),
+                "test-bucket",
+                "hello",
+                OBJECT_SIZE as u64,
+                etag.clone(),
+                None,
+            );
             if first_read_size > 0 {
                 let _first_read = block_on(request.read(0, first_read_size)).unwrap();
             }
@@ -1033,8 +1047,14 @@ mod tests {
 
         // Try every possible seek from first_read_size
         for offset in 0..first_read_size {
-            let mut request =
-                prefetcher.prefetch(client.clone(), "test-bucket", "hello", OBJECT_SIZE as u64, etag.clone());
+            let mut request = prefetcher.prefetch(
+                client.clone(),
+                "test-bucket",
+                "hello",
+                OBJECT_SIZE as u64,
+                etag.clone(),
+                None,
+            );
             if first_read_size > 0 {
                 let _first_read = block_on(request.read(0, first_read_size)).unwrap();
             }
@@ -1092,7 +1112,7 @@ mod tests {
             };
 
             let prefetcher = Prefetcher::new(ClientPartStream::new(ShuttleRuntime), prefetcher_config);
-            let mut request = prefetcher.prefetch(client, "test-bucket", "hello", object_size, file_etag);
+            let mut request = prefetcher.prefetch(client, "test-bucket", "hello", object_size, file_etag, None);
 
             let mut next_offset = 0;
             loop {
@@ -1149,7 +1169,7 @@ mod tests {
             };
 
             let prefetcher = Prefetcher::new(ClientPartStream::new(ShuttleRuntime), prefetcher_config);
-            let mut request = prefetcher.prefetch(client, "test-bucket", "hello", object_size, file_etag);
+            let mut request = prefetcher.prefetch(client, "test-bucket", "hello", object_size, file_etag, None);
 
             let num_reads = rng.gen_range(10usize..50);
             for _ in 0..num_reads {
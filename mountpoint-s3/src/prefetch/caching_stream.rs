@@ -4,7 +4,8 @@ use std::{ops::Range, sync::Arc};
 use bytes::Bytes;
 use futures::task::{Spawn, SpawnExt};
 use futures::{pin_mut, StreamExt};
-use mountpoint_s3_client::{types::ETag, ObjectClient};
+use mountpoint_s3_client::types::{ETag, GetObjectParams, SseCustomerKey};
+use mountpoint_s3_client::ObjectClient;
 use tracing::{debug_span, trace, warn, Instrument};
 
 use crate::checksums::ChecksummedBytes;
@@ -44,6 +45,7 @@ where
         bucket: &str,
         key: &str,
         if_match: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
         range: RequestRange,
         _preferred_part_size: usize,
     ) -> RequestTask<<Client as ObjectClient>::ClientError>
@@ -65,6 +67,7 @@ where
                 bucket.to_owned(),
                 key.to_owned(),
                 if_match,
+                sse_customer_key,
                 part_queue_producer,
             );
             let span = debug_span!("prefetch", ?range);
@@ -83,6 +86,7 @@ struct CachingRequest<Client: ObjectClient, Cache> {
     cache: Arc<Cache>,
     bucket: String,
     cache_key: ObjectId,
+    sse_customer_key: Option<SseCustomerKey>,
     part_queue_producer: PartQueueProducer<Client::ClientError>,
 }
 
@@ -97,6 +101,7 @@ where
         bucket: String,
         key: String,
         etag: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
         part_queue_producer: PartQueueProducer<Client::ClientError>,
     ) -> Self {
         let cache_key = ObjectId::new(key, etag);
@@ -105,6 +110,7 @@ where
             cache,
             bucket,
             cache_key,
+            sse_customer_key,
             part_queue_producer,
         }
     }
@@ -164,16 +170,11 @@ where
             original_range =? range,
             "fetching data from client"
         );
-        let get_object_result = match self
-            .client
-            .get_object(
-                &self.bucket,
-                key,
-                Some(block_aligned_byte_range),
-                Some(self.cache_key.etag().clone()),
-            )
-            .await
-        {
+        let params = GetObjectParams::new()
+            .range(Some(block_aligned_byte_range))
+            .if_match(Some(self.cache_key.etag().clone()))
+            .sse_customer_key(self.sse_customer_key.clone());
+        let get_object_result = match self.client.get_object(&self.bucket, key, &params).await {
             Ok(get_object_result) => get_object_result,
             Err(e) => {
                 warn!(key, error=?e, "GetObject request failed");
@@ -366,7 +367,7 @@ mod tests {
         let first_read_count = {
             // First request (from client)
             let get_object_counter = mock_client.new_counter(Operation::GetObject);
-            let request_task = stream.spawn_get_object_request(&mock_client, bucket, key, etag.clone(), range, 0);
+            let request_task = stream.spawn_get_object_request(&mock_client, bucket, key, etag.clone(), None, range, 0);
             compare_read(&id, &object, request_task);
             get_object_counter.count()
         };
@@ -375,7 +376,7 @@ mod tests {
         let second_read_count = {
             // Second request (from cache)
             let get_object_counter = mock_client.new_counter(Operation::GetObject);
-            let request_task = stream.spawn_get_object_request(&mock_client, bucket, key, etag.clone(), range, 0);
+            let request_task = stream.spawn_get_object_request(&mock_client, bucket, key, etag.clone(), None, range, 0);
             compare_read(&id, &object, request_task);
             get_object_counter.count()
         };
@@ -410,7 +411,8 @@ mod tests {
         for offset in [0, 512 * KB, 1 * MB, 4 * MB, 9 * MB] {
             for preferred_size in [1 * KB, 512 * KB, 4 * MB, 12 * MB, 16 * MB] {
                 let range = RequestRange::new(object_size, offset as u64, preferred_size);
-                let request_task = stream.spawn_get_object_request(&mock_client, bucket, key, etag.clone(), range, 0);
+                let request_task =
+                    stream.spawn_get_object_request(&mock_client, bucket, key, etag.clone(), None, range, 0);
                 compare_read(&id, &object, request_task);
             }
         }
@@ -3,7 +3,8 @@ use std::{fmt::Debug, ops::Range};
 use bytes::Bytes;
 use futures::task::SpawnExt;
 use futures::{pin_mut, task::Spawn, StreamExt};
-use mountpoint_s3_client::{types::ETag, ObjectClient};
+use mountpoint_s3_client::types::{ETag, GetObjectParams, SseCustomerKey};
+use mountpoint_s3_client::ObjectClient;
 use tracing::{debug_span, error, trace, Instrument};
 
 use crate::checksums::ChecksummedBytes;
@@ -24,6 +25,7 @@ pub trait ObjectPartStream {
         bucket: &str,
         key: &str,
         if_match: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
         range: RequestRange,
         preferred_part_size: usize,
     ) -> RequestTask<Client::ClientError>
@@ -169,6 +171,7 @@ where
         bucket: &str,
         key: &str,
         if_match: ETag,
+        sse_customer_key: Option<SseCustomerKey>,
         range: RequestRange,
         preferred_part_size: usize,
     ) -> RequestTask<Client::ClientError>
@@ -190,10 +193,11 @@ where
             let span = debug_span!("prefetch", range=?request_range);
 
             async move {
-                let get_object_result = match client
-                    .get_object(&bucket, id.key(), Some(request_range.into()), Some(id.etag().clone()))
-                    .await
-                {
+                let params = GetObjectParams::new()
+                    .range(Some(request_range.into()))
+                    .if_match(Some(id.etag().clone()))
+                    .sse_customer_key(sse_customer_key);
+                let get_object_result = match client.get_object(&bucket, id.key(), &params).await {
                     Ok(get_object_result) => get_object_result,
                     Err(e) => {
                         error!(key=id.key(), error=?e, "GetObject request failed");
@@ -1,21 +1,30 @@
+use std::time::Duration;
 use std::{fmt::Debug, sync::Arc};
 
-use mountpoint_s3_client::checksums::crc32c_from_base64;
-use mountpoint_s3_client::error::{ObjectClientError, PutObjectError};
-use mountpoint_s3_client::types::{PutObjectParams, PutObjectResult, UploadReview};
+use mountpoint_s3_client::checksums::{crc32c_from_base64, ChecksumHasher};
+use mountpoint_s3_client::error::{ListMultipartUploadsError, ObjectClientError, PutObjectError};
+use mountpoint_s3_client::types::{
+    ChecksumAlgorithm, ETag, MultipartUploadInfo, PutObjectParams, PutObjectResult, SseCustomerKey, UploadReview,
+};
 use mountpoint_s3_client::{ObjectClient, PutObjectRequest};
 
 use mountpoint_s3_crt::checksums::crc32c::{Crc32c, Hasher};
 use thiserror::Error;
+use time::OffsetDateTime;
 use tracing::error;
 
 use crate::checksums::combine_checksums;
 use crate::fs::{ServerSideEncryption, SseCorruptedError};
 
 type PutRequestError<Client> = ObjectClientError<PutObjectError, <Client as ObjectClient>::ClientError>;
+type ListUploadsError<Client> = ObjectClientError<ListMultipartUploadsError, <Client as ObjectClient>::ClientError>;
 
 const MAX_S3_MULTIPART_UPLOAD_PARTS: usize = 10000;
 
+/// Multipart uploads that have been in progress for longer than this are assumed to be abandoned,
+/// for example because a previous mount-s3 process was killed while a file was being written.
+pub const DEFAULT_STALE_UPLOAD_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// An [Uploader] creates and manages streaming PutObject requests.
 #[derive(Debug)]
 pub struct Uploader<Client> {
@@ -27,6 +36,9 @@ struct UploaderInner<Client> {
     client: Arc<Client>,
     storage_class: Option<String>,
     server_side_encryption: ServerSideEncryption,
+    sse_customer_key: Option<SseCustomerKey>,
+    checksum_algorithm: ChecksumAlgorithm,
+    conditional_writes: bool,
 }
 
 #[derive(Debug, Error)]
@@ -38,27 +50,37 @@ pub enum UploadPutError<S, C> {
 }
 
 impl<Client: ObjectClient> Uploader<Client> {
-    /// Create a new [Uploader] that will make requests to the given client.
+    /// Create a new [Uploader] that will make requests to the given client. If `conditional_writes`
+    /// is set, uploads will fail rather than replace an object that changed since it was opened.
     pub fn new(
         client: Arc<Client>,
         storage_class: Option<String>,
         server_side_encryption: ServerSideEncryption,
+        sse_customer_key: Option<SseCustomerKey>,
+        checksum_algorithm: ChecksumAlgorithm,
+        conditional_writes: bool,
     ) -> Self {
         let inner = UploaderInner {
             client,
             storage_class,
             server_side_encryption,
+            sse_customer_key,
+            checksum_algorithm,
+            conditional_writes,
         };
         Self { inner: Arc::new(inner) }
     }
 
-    /// Start a new put request to the specified object.
+    /// Start a new put request to the specified object. With conditional writes enabled, the upload
+    /// will only replace an object with the given `etag`, or if `etag` is not set, it will fail if
+    /// the object already exists.
     pub async fn put(
         &self,
         bucket: &str,
         key: &str,
+        etag: Option<ETag>,
     ) -> Result<UploadRequest<Client>, UploadPutError<PutObjectError, Client::ClientError>> {
-        UploadRequest::new(Arc::clone(&self.inner), bucket, key).await
+        UploadRequest::new(Arc::clone(&self.inner), bucket, key, etag).await
     }
 
     #[cfg(test)]
@@ -89,7 +111,7 @@ pub struct UploadRequest<Client: ObjectClient> {
     bucket: String,
     key: String,
     next_request_offset: u64,
-    hasher: Hasher,
+    checksums: UploadChecksums,
     request: Client::PutObjectRequest,
     maximum_upload_size: Option<usize>,
     sse: ServerSideEncryption,
@@ -100,8 +122,17 @@ impl<Client: ObjectClient> UploadRequest<Client> {
         inner: Arc<UploaderInner<Client>>,
         bucket: &str,
         key: &str,
+        etag: Option<ETag>,
     ) -> Result<UploadRequest<Client>, UploadPutError<PutObjectError, Client::ClientError>> {
-        let mut params = PutObjectParams::new().trailing_checksums(true);
+        let mut params = PutObjectParams::new().checksum_algorithm(Some(inner.checksum_algorithm));
+
+        // Don't silently clobber an object that someone else created or replaced in the meantime
+        if inner.conditional_writes {
+            params = match etag {
+                Some(etag) => params.if_match(Some(etag)),
+                None => params.if_none_match(Some("*".to_owned())),
+            };
+        }
 
         if let Some(storage_class) = &inner.storage_class {
             params = params.storage_class(storage_class.clone());
@@ -113,15 +144,17 @@ impl<Client: ObjectClient> UploadRequest<Client> {
         let (sse_type, key_id) = inner.server_side_encryption.clone().into_inner()?;
         params = params.server_side_encryption(sse_type);
         params = params.ssekms_key_id(key_id);
+        params = params.sse_customer_key(inner.sse_customer_key.clone());
 
         let request = inner.client.put_object(bucket, key, &params).await?;
-        let maximum_upload_size = inner.client.part_size().map(|ps| ps * MAX_S3_MULTIPART_UPLOAD_PARTS);
+        let part_size = inner.client.part_size();
+        let maximum_upload_size = part_size.map(|ps| ps * MAX_S3_MULTIPART_UPLOAD_PARTS);
 
         Ok(Self {
             bucket: bucket.to_owned(),
             key: key.to_owned(),
             next_request_offset: 0,
-            hasher: Hasher::new(),
+            checksums: UploadChecksums::new(inner.checksum_algorithm, part_size),
             request,
             maximum_upload_size,
             sse: inner.server_side_encryption.clone(),
@@ -150,7 +183,7 @@ impl<Client: ObjectClient> UploadRequest<Client> {
             }
         }
 
-        self.hasher.update(data);
+        self.checksums.update(data);
         self.request.write(data).await?;
         self.next_request_offset += data.len() as u64;
         Ok(data.len())
@@ -158,10 +191,10 @@ impl<Client: ObjectClient> UploadRequest<Client> {
 
     pub async fn complete(self) -> Result<PutObjectResult, PutRequestError<Client>> {
         let size = self.size();
-        let checksum = self.hasher.finalize();
+        let checksums = self.checksums;
         let result = self
             .request
-            .review_and_complete(move |review| verify_checksums(review, size, checksum))
+            .review_and_complete(move |review| checksums.verify(review, size))
             .await?;
         if let Err(err) = self
             .sse
@@ -185,12 +218,100 @@ impl<Client: ObjectClient> Debug for UploadRequest<Client> {
             .field("bucket", &self.bucket)
             .field("key", &self.key)
             .field("next_request_offset", &self.next_request_offset)
-            .field("hasher", &self.hasher)
+            .field("checksums", &self.checksums)
             .finish()
     }
 }
 
-fn verify_checksums(review: UploadReview, expected_size: u64, expected_checksum: Crc32c) -> bool {
+/// Checksums of the data written to an upload, used to verify the checksums the client reports in
+/// its review of the upload before completing it
+#[derive(Debug)]
+enum UploadChecksums {
+    /// CRC32C checksums of parts can be combined, so we only need the checksum of the whole object
+    /// and don't depend on how the client splits it into parts.
+    Crc32c(Hasher),
+    /// Other checksums can't be combined, so we compute them for each part, assuming the client
+    /// splits the object into parts of exactly `part_size` bytes like the CRT does.
+    Parts {
+        part_size: usize,
+        current: ChecksumHasher,
+        current_size: usize,
+        completed: Vec<String>,
+    },
+}
+
+impl UploadChecksums {
+    fn new(algorithm: ChecksumAlgorithm, part_size: Option<usize>) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32c => Self::Crc32c(Hasher::new()),
+            _ => Self::Parts {
+                // A client that doesn't report a part size uploads the object as a single part
+                part_size: part_size.unwrap_or(usize::MAX),
+                current: ChecksumHasher::new(algorithm),
+                current_size: 0,
+                completed: Vec::new(),
+            },
+        }
+    }
+
+    fn algorithm(&self) -> ChecksumAlgorithm {
+        match self {
+            Self::Crc32c(_) => ChecksumAlgorithm::Crc32c,
+            Self::Parts { current, .. } => current.algorithm(),
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        match self {
+            Self::Crc32c(hasher) => hasher.update(data),
+            Self::Parts {
+                part_size,
+                current,
+                current_size,
+                completed,
+            } => {
+                while !data.is_empty() {
+                    let len = data.len().min(*part_size - *current_size);
+                    current.update(&data[..len]);
+                    *current_size += len;
+                    data = &data[len..];
+                    if *current_size == *part_size {
+                        let next = ChecksumHasher::new(current.algorithm());
+                        completed.push(std::mem::replace(current, next).finalize_base64());
+                        *current_size = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    fn verify(self, review: UploadReview, expected_size: u64) -> bool {
+        if review.checksum_algorithm != Some(self.algorithm()) {
+            error!(
+                uploaded_algorithm = ?review.checksum_algorithm,
+                expected_algorithm = ?self.algorithm(),
+                "Checksum algorithm of uploaded parts differs from expected algorithm"
+            );
+            return false;
+        }
+        match self {
+            Self::Crc32c(hasher) => verify_crc32c_checksums(review, expected_size, hasher.finalize()),
+            Self::Parts {
+                current,
+                current_size,
+                mut completed,
+                ..
+            } => {
+                if current_size > 0 {
+                    completed.push(current.finalize_base64());
+                }
+                verify_part_checksums(review, expected_size, completed)
+            }
+        }
+    }
+}
+
+fn verify_crc32c_checksums(review: UploadReview, expected_size: u64, expected_checksum: Crc32c) -> bool {
     let mut uploaded_size = 0u64;
     let mut uploaded_checksum = Crc32c::new(0);
     for part in review.parts {
@@ -231,14 +352,97 @@ fn verify_checksums(review: UploadReview, expected_size: u64, expected_checksum:
     true
 }
 
+fn verify_part_checksums(review: UploadReview, expected_size: u64, expected_checksums: Vec<String>) -> bool {
+    let mut uploaded_size = 0u64;
+    let mut uploaded_checksums = Vec::with_capacity(review.parts.len());
+    // Empty parts carry no data, so we don't expect a checksum for them
+    for part in review.parts.into_iter().filter(|part| part.size > 0) {
+        uploaded_size += part.size;
+
+        let Some(checksum) = part.checksum else {
+            error!("missing part checksum");
+            return false;
+        };
+        uploaded_checksums.push(checksum);
+    }
+
+    if uploaded_size != expected_size {
+        error!(
+            uploaded_size,
+            expected_size, "Total uploaded size differs from expected size"
+        );
+        return false;
+    }
+
+    if uploaded_checksums != expected_checksums {
+        error!(
+            ?uploaded_checksums,
+            ?expected_checksums,
+            "Checksums of uploaded parts differ from expected checksums"
+        );
+        return false;
+    }
+
+    true
+}
+
+/// Find the in-progress multipart uploads under `prefix` that were initiated at least `older_than`
+/// ago. At most `max_pages` ListMultipartUploads requests are made, or as many as needed to list
+/// every upload if `None`.
+pub async fn find_stale_uploads<Client: ObjectClient>(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+    older_than: Duration,
+    max_pages: Option<usize>,
+) -> Result<Vec<MultipartUploadInfo>, ListUploadsError<Client>> {
+    const MAX_UPLOADS_PER_PAGE: usize = 1000;
+
+    let Some(cutoff) = time::Duration::try_from(older_than)
+        .ok()
+        .and_then(|age| OffsetDateTime::now_utc().checked_sub(age))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut stale_uploads = Vec::new();
+    let mut key_marker = None;
+    let mut upload_id_marker = None;
+    let mut pages = 0;
+    loop {
+        let result = client
+            .list_multipart_uploads(
+                bucket,
+                key_marker.as_deref(),
+                upload_id_marker.as_deref(),
+                "",
+                MAX_UPLOADS_PER_PAGE,
+                prefix,
+            )
+            .await?;
+        stale_uploads.extend(result.uploads.into_iter().filter(|upload| upload.initiated <= cutoff));
+
+        pages += 1;
+        if result.next_key_marker.is_none() || max_pages.is_some_and(|max_pages| pages >= max_pages) {
+            break;
+        }
+        key_marker = result.next_key_marker;
+        upload_id_marker = result.next_upload_id_marker;
+    }
+
+    Ok(stale_uploads)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::str::FromStr;
 
     use super::*;
+    use mountpoint_s3_client::types::UploadReviewPart;
     use mountpoint_s3_client::{
         failure_client::countdown_failure_client,
-        mock_client::{MockClient, MockClientConfig, MockClientError},
+        mock_client::{MockClient, MockClientConfig, MockClientError, MockObject},
     };
     use test_case::test_case;
 
@@ -253,8 +457,15 @@ mod tests {
             part_size: 32,
             ..Default::default()
         }));
-        let uploader = Uploader::new(client.clone(), None, ServerSideEncryption::default());
-        let request = uploader.put(bucket, key).await.unwrap();
+        let uploader = Uploader::new(
+            client.clone(),
+            None,
+            ServerSideEncryption::default(),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
+        );
+        let request = uploader.put(bucket, key, None).await.unwrap();
 
         assert!(!client.contains_key(key));
         assert!(client.is_upload_in_progress(key));
@@ -265,6 +476,73 @@ mod tests {
         assert!(!client.is_upload_in_progress(key));
     }
 
+    #[test_case(ChecksumAlgorithm::Crc32c; "crc32c")]
+    #[test_case(ChecksumAlgorithm::Crc32; "crc32")]
+    #[test_case(ChecksumAlgorithm::Sha1; "sha1")]
+    #[test_case(ChecksumAlgorithm::Sha256; "sha256")]
+    #[tokio::test]
+    async fn checksum_algorithm_test(checksum_algorithm: ChecksumAlgorithm) {
+        let bucket = "bucket";
+        let key = "hello";
+
+        let client = Arc::new(MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 32,
+            ..Default::default()
+        }));
+        let uploader = Uploader::new(
+            client.clone(),
+            None,
+            ServerSideEncryption::default(),
+            None,
+            checksum_algorithm,
+            true,
+        );
+        let mut request = uploader.put(bucket, key, None).await.unwrap();
+
+        // Writes that don't line up with part boundaries
+        let data = [0xaa; 7];
+        let mut offset = 0;
+        for _ in 0..15 {
+            offset += request.write(offset, &data).await.unwrap() as i64;
+        }
+
+        request.complete().await.expect("checksums should match");
+        assert!(client.contains_key(key));
+    }
+
+    #[test_case(ChecksumAlgorithm::Crc32c; "crc32c")]
+    #[test_case(ChecksumAlgorithm::Sha256; "sha256")]
+    fn verify_checksums_mismatch_test(checksum_algorithm: ChecksumAlgorithm) {
+        let part_checksum = |data: &[u8]| {
+            let mut hasher = ChecksumHasher::new(checksum_algorithm);
+            hasher.update(data);
+            hasher.finalize_base64()
+        };
+        let review = |parts: &[&[u8]], algorithm| UploadReview {
+            checksum_algorithm: Some(algorithm),
+            parts: parts
+                .iter()
+                .map(|part| UploadReviewPart {
+                    size: part.len() as u64,
+                    checksum: Some(part_checksum(part)),
+                })
+                .collect(),
+        };
+
+        let mut checksums = UploadChecksums::new(checksum_algorithm, Some(4));
+        checksums.update(b"hello world");
+        assert!(checksums.verify(review(&[b"hell", b"o wo", b"rld"], checksum_algorithm), 11));
+
+        let mut checksums = UploadChecksums::new(checksum_algorithm, Some(4));
+        checksums.update(b"hello world");
+        assert!(!checksums.verify(review(&[b"hell", b"o wo", b"rlD"], checksum_algorithm), 11));
+
+        let mut checksums = UploadChecksums::new(checksum_algorithm, Some(4));
+        checksums.update(b"hello world");
+        assert!(!checksums.verify(review(&[b"hell", b"o wo", b"rld"], ChecksumAlgorithm::Crc32), 11));
+    }
+
     #[tokio::test]
     async fn write_order_test() {
         let bucket = "bucket";
@@ -281,9 +559,12 @@ mod tests {
             client.clone(),
             Some(storage_class.to_owned()),
             ServerSideEncryption::default(),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
         );
 
-        let mut request = uploader.put(bucket, key).await.unwrap();
+        let mut request = uploader.put(bucket, key, None).await.unwrap();
 
         let data = b"foo";
         let mut offset = 0;
@@ -330,11 +611,18 @@ mod tests {
             put_failures,
         ));
 
-        let uploader = Uploader::new(failure_client.clone(), None, ServerSideEncryption::default());
+        let uploader = Uploader::new(
+            failure_client.clone(),
+            None,
+            ServerSideEncryption::default(),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
+        );
 
         // First request fails on first write.
         {
-            let mut request = uploader.put(bucket, key).await.unwrap();
+            let mut request = uploader.put(bucket, key, None).await.unwrap();
 
             let data = b"foo";
             request.write(0, data).await.expect_err("first write should fail");
@@ -344,7 +632,7 @@ mod tests {
 
         // Second request fails on complete (after one write).
         {
-            let mut request = uploader.put(bucket, key).await.unwrap();
+            let mut request = uploader.put(bucket, key, None).await.unwrap();
 
             let data = b"foo";
             _ = request.write(0, data).await.unwrap();
@@ -355,6 +643,82 @@ mod tests {
         assert!(!client.contains_key(key));
     }
 
+    #[tokio::test]
+    async fn precondition_test() {
+        let bucket = "bucket";
+        let key = "hello";
+
+        let client = Arc::new(MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 32,
+            ..Default::default()
+        }));
+        let uploader = Uploader::new(
+            client.clone(),
+            None,
+            ServerSideEncryption::default(),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
+        );
+
+        // Someone else creates the object while we're uploading a new one
+        let request = uploader.put(bucket, key, None).await.unwrap();
+        client.add_object(
+            key,
+            MockObject::constant(0xaa, 30, ETag::from_str("other_etag").unwrap()),
+        );
+        let err = request.complete().await.expect_err("object already exists");
+        assert!(matches!(
+            err,
+            ObjectClientError::ServiceError(PutObjectError::PreconditionFailed)
+        ));
+
+        // Someone else replaces the object while we're overwriting it
+        let request = uploader.put(bucket, key, Some(ETag::for_tests())).await.unwrap();
+        let err = request.complete().await.expect_err("object has a different ETag");
+        assert!(matches!(
+            err,
+            ObjectClientError::ServiceError(PutObjectError::PreconditionFailed)
+        ));
+
+        let request = uploader
+            .put(bucket, key, Some(ETag::from_str("other_etag").unwrap()))
+            .await
+            .unwrap();
+        request.complete().await.expect("object has the expected ETag");
+    }
+
+    #[tokio::test]
+    async fn precondition_disabled_test() {
+        let bucket = "bucket";
+        let key = "hello";
+
+        let client = Arc::new(MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 32,
+            ..Default::default()
+        }));
+        let uploader = Uploader::new(
+            client.clone(),
+            None,
+            ServerSideEncryption::default(),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            false,
+        );
+
+        let request = uploader.put(bucket, key, None).await.unwrap();
+        client.add_object(
+            key,
+            MockObject::constant(0xaa, 30, ETag::from_str("other_etag").unwrap()),
+        );
+        request.complete().await.expect("last writer should win");
+
+        let request = uploader.put(bucket, key, Some(ETag::for_tests())).await.unwrap();
+        request.complete().await.expect("last writer should win");
+    }
+
     #[test_case(8000; "divisible by max size")]
     #[test_case(7000; "not divisible by max size")]
     #[test_case(320001; "single write too big")]
@@ -371,8 +735,15 @@ mod tests {
             part_size: PART_SIZE,
             ..Default::default()
         }));
-        let uploader = Uploader::new(client.clone(), None, ServerSideEncryption::default());
-        let mut request = uploader.put(bucket, key).await.unwrap();
+        let uploader = Uploader::new(
+            client.clone(),
+            None,
+            ServerSideEncryption::default(),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
+        );
+        let mut request = uploader.put(bucket, key, None).await.unwrap();
 
         let successful_writes = PART_SIZE * MAX_S3_MULTIPART_UPLOAD_PARTS / write_size;
         let data = vec![0xaa; write_size];
@@ -404,13 +775,16 @@ mod tests {
             client,
             None,
             ServerSideEncryption::new(Some("aws:kms".to_string()), Some("some_key_alias".to_string())),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
         );
         std::sync::Arc::<UploaderInner<MockClient>>::get_mut(&mut uploader.inner)
             .unwrap()
             .server_side_encryption
             .corrupt_data(sse_type_corrupted.map(String::from), key_id_corrupted.map(String::from));
         let err = uploader
-            .put("bucket", "hello")
+            .put("bucket", "hello", None)
             .await
             .expect_err("sse checksum must be checked");
         assert!(matches!(
@@ -434,7 +808,48 @@ mod tests {
             client,
             None,
             ServerSideEncryption::new(Some("aws:kms".to_string()), Some("some_key".to_string())),
+            None,
+            ChecksumAlgorithm::Crc32c,
+            true,
         );
-        uploader.put(bucket, key).await.expect("put with sse should succeed");
+        uploader
+            .put(bucket, key, None)
+            .await
+            .expect("put with sse should succeed");
+    }
+
+    #[tokio::test]
+    async fn find_stale_uploads_test() {
+        let bucket = "bucket";
+        let client = MockClient::new(MockClientConfig {
+            bucket: bucket.to_owned(),
+            part_size: 32,
+            ..Default::default()
+        });
+
+        for key in ["dir/a", "dir/b", "other"] {
+            client
+                .create_multipart_upload(bucket, key, &Default::default())
+                .await
+                .unwrap();
+        }
+
+        let uploads = find_stale_uploads(&client, bucket, "dir/", Duration::ZERO, None)
+            .await
+            .unwrap();
+        let keys: Vec<_> = uploads.iter().map(|upload| upload.key.as_str()).collect();
+        assert_eq!(keys, ["dir/a", "dir/b"]);
+
+        // Uploads that were only just started aren't stale yet
+        let uploads = find_stale_uploads(&client, bucket, "", DEFAULT_STALE_UPLOAD_AGE, None)
+            .await
+            .unwrap();
+        assert!(uploads.is_empty());
+
+        // An age too large to subtract from the current time can't match anything
+        let uploads = find_stale_uploads(&client, bucket, "", Duration::MAX, None)
+            .await
+            .unwrap();
+        assert!(uploads.is_empty());
     }
 }
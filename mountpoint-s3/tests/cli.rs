@@ -142,6 +142,84 @@ fn s3_uri_as_bucket_name() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn cleanup_uploads_help() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("cleanup-uploads").arg("--help");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Usage: mount-s3 cleanup-uploads"))
+        .stdout(predicate::str::contains("--older-than <SECONDS>"));
+
+    Ok(())
+}
+
+#[test]
+fn cleanup_uploads_s3_uri_as_bucket_name() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("cleanup-uploads").arg("s3://test-bucket/");
+    let error_message = "bucket name should not be an s3:// URI";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn mount_bucket_named_like_command() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("--").arg("cleanup-uploads").arg("test/dir");
+    let error_message = "mount point test/dir does not exist";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("--").arg("restore").arg("test/dir");
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn restore_help() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("restore").arg("--help");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Usage: mount-s3 restore"))
+        .stdout(predicate::str::contains("--tier <TIER>"));
+
+    Ok(())
+}
+
+#[test]
+fn restore_requires_keys_or_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("restore").arg("test-bucket");
+    let error_message = "the following required arguments were not provided";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn restore_invalid_tier() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("restore")
+        .arg("test-bucket")
+        .arg("key")
+        .arg("--tier")
+        .arg("instant");
+    let error_message = "invalid value 'instant' for '--tier <TIER>'";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
 #[test]
 fn invalid_profile() -> Result<(), Box<dyn std::error::Error>> {
     let dir = assert_fs::TempDir::new()?;
@@ -154,6 +232,37 @@ fn invalid_profile() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn role_session_name_requires_role_arn() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--role-session-name")
+        .arg("my-session");
+    let error_message = "the following required arguments were not provided";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn role_arn_conflicts_with_no_sign_request() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--role-arn")
+        .arg("arn:aws:iam::123456789012:role/example")
+        .arg("--no-sign-request");
+    let error_message = "cannot be used with";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
 #[test]
 fn validate_log_files_permissions() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("mount-s3")?;
@@ -211,6 +320,18 @@ fn allow_other_conflict() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn batch_delete_requires_allow_delete() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+
+    cmd.arg("test-bucket").arg(dir.path()).arg("--batch-delete");
+    let error_message = "the following required arguments were not provided:\n  --allow-delete";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
 #[test]
 fn max_ttl_exceeded() -> Result<(), Box<dyn std::error::Error>> {
     let dir = assert_fs::TempDir::new()?;
@@ -268,3 +389,112 @@ fn sse_args_non_empty() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn invalid_sse_customer_key_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let key_file = assert_fs::NamedTempFile::new("key.bin")?;
+    fs::write(key_file.path(), [0x42; 16])?;
+
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--sse-customer-key-file")
+        .arg(key_file.path());
+    let error_message = "SSE-C key must be 32 bytes, but was 16 bytes";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[cfg(feature = "sse_kms")]
+#[test]
+fn sse_customer_key_conflicts_with_sse_kms() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let key_file = assert_fs::NamedTempFile::new("key.bin")?;
+    fs::write(key_file.path(), [0x42; 32])?;
+
+    for sse_args in [
+        &["--sse=aws:kms"][..],
+        &["--sse=aws:kms", "--sse-kms-key-id=some_key"][..],
+        &["--sse-kms-key-id=some_key"][..],
+    ] {
+        let mut cmd = Command::cargo_bin("mount-s3")?;
+        cmd.arg("test-bucket")
+            .arg(dir.path())
+            .arg("--sse-customer-key-file")
+            .arg(key_file.path())
+            .args(sse_args);
+        let error_message = "cannot be used with";
+        cmd.assert().failure().stderr(predicate::str::contains(error_message));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invalid_upload_checksums() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--upload-checksums")
+        .arg("md5");
+    let error_message = "invalid value 'md5' for '--upload-checksums <ALGORITHM>'";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn zero_max_request_rate() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--max-request-rate")
+        .arg("0");
+    let error_message = "invalid value '0' for '--max-request-rate <N>'";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_retry_jitter() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--retry-jitter")
+        .arg("random");
+    let error_message = "invalid value 'random' for '--retry-jitter <MODE>'";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn client_key_requires_client_cert() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("test-bucket")
+        .arg(dir.path())
+        .arg("--client-key")
+        .arg("key.pem");
+    let error_message = "the following required arguments were not provided";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
+
+#[test]
+fn record_bodies_requires_record_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let mut cmd = Command::cargo_bin("mount-s3")?;
+    cmd.arg("test-bucket").arg(dir.path()).arg("--record-bodies");
+    let error_message = "the following required arguments were not provided";
+    cmd.assert().failure().stderr(predicate::str::contains(error_message));
+
+    Ok(())
+}
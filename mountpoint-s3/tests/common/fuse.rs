@@ -241,7 +241,7 @@ pub mod mock_session {
 
         fn restore_object(&mut self, key: &str, _expedited: bool) -> Result<(), Box<dyn std::error::Error>> {
             let full_key = format!("{}{}", self.prefix, key);
-            Ok(self.client.restore_object(&full_key)?)
+            Ok(self.client.complete_restore(&full_key)?)
         }
 
         fn is_object_restored(&mut self, key: &str) -> Result<bool, Box<dyn std::error::Error>> {
@@ -360,8 +360,14 @@ pub mod s3_session {
             if let Some(storage_class) = params.storage_class {
                 request = request.set_storage_class(Some(storage_class.as_str().into()));
             }
-            if params.trailing_checksums {
-                request = request.set_checksum_algorithm(Some(ChecksumAlgorithm::Crc32C));
+            if let Some(algorithm) = params.checksum_algorithm {
+                let algorithm = match algorithm {
+                    mountpoint_s3_client::types::ChecksumAlgorithm::Crc32c => ChecksumAlgorithm::Crc32C,
+                    mountpoint_s3_client::types::ChecksumAlgorithm::Crc32 => ChecksumAlgorithm::Crc32,
+                    mountpoint_s3_client::types::ChecksumAlgorithm::Sha1 => ChecksumAlgorithm::Sha1,
+                    mountpoint_s3_client::types::ChecksumAlgorithm::Sha256 => ChecksumAlgorithm::Sha256,
+                };
+                request = request.set_checksum_algorithm(Some(algorithm));
             }
             Ok(tokio_block_on(request.send()).map(|_| ())?)
         }
@@ -7,7 +7,7 @@ use mountpoint_s3::prefix::Prefix;
 use mountpoint_s3::S3FilesystemConfig;
 use mountpoint_s3_client::failure_client::countdown_failure_client;
 use mountpoint_s3_client::mock_client::{MockClient, MockClientConfig, MockClientError, MockObject, Operation};
-use mountpoint_s3_client::types::{ETag, RestoreStatus};
+use mountpoint_s3_client::types::{ETag, RestoreStatus, SseCustomerKey};
 use mountpoint_s3_client::ObjectClient;
 use nix::unistd::{getgid, getuid};
 use rand::{Rng, SeedableRng};
@@ -585,7 +585,7 @@ async fn test_sequential_write(write_size: usize) {
 
     // Check that the object made it to S3 as we expected
     let get = client
-        .get_object(BUCKET_NAME, "dir1/file2.bin", None, None)
+        .get_object(BUCKET_NAME, "dir1/file2.bin", &Default::default())
         .await
         .unwrap();
     let actual = get.collect().await.unwrap();
@@ -707,6 +707,144 @@ async fn test_duplicate_write_fails() {
     assert_eq!(err, libc::EPERM);
 }
 
+#[tokio::test]
+async fn test_create_fails_if_key_created_concurrently() {
+    const BUCKET_NAME: &str = "test_create_fails_if_key_created_concurrently";
+
+    let fs_config = S3FilesystemConfig {
+        conditional_writes: true,
+        ..Default::default()
+    };
+    let (client, fs) = make_test_filesystem(BUCKET_NAME, &Default::default(), fs_config);
+
+    let mode = libc::S_IFREG | libc::S_IRWXU; // regular file + 0700 permissions
+    let dentry = fs
+        .mknod(FUSE_ROOT_INODE, "file.bin".as_ref(), mode, 0, 0)
+        .await
+        .unwrap();
+    let file_ino = dentry.attr.ino;
+
+    let fh = fs
+        .open(file_ino, libc::S_IFREG as i32 | libc::O_WRONLY, 0)
+        .await
+        .unwrap()
+        .fh;
+    fs.write(file_ino, fh, 0, &[0xaa; 27], 0, 0, None).await.unwrap();
+
+    // Another client creates the same key before we complete our upload
+    client.add_object("file.bin", MockObject::constant(0xbb, 15, ETag::for_tests()));
+
+    let err = fs
+        .release(file_ino, fh, 0, None, false)
+        .await
+        .expect_err("upload should not replace the other object")
+        .to_errno();
+    assert_eq!(err, libc::EEXIST);
+
+    let object = client
+        .head_object(BUCKET_NAME, "file.bin", &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(object.object.size, 15);
+}
+
+#[tokio::test]
+async fn test_overwrite_fails_if_etag_changed() {
+    const BUCKET_NAME: &str = "test_overwrite_fails_if_etag_changed";
+
+    let fs_config = S3FilesystemConfig {
+        allow_overwrite: true,
+        conditional_writes: true,
+        ..Default::default()
+    };
+    let (client, fs) = make_test_filesystem(BUCKET_NAME, &Default::default(), fs_config);
+    client.add_object(
+        "file.bin",
+        MockObject::constant(0xaa, 15, ETag::from_str("etag_at_open").unwrap()),
+    );
+
+    let entry = fs.lookup(FUSE_ROOT_INODE, "file.bin".as_ref()).await.unwrap();
+    let file_ino = entry.attr.ino;
+    let fh = fs
+        .open(file_ino, libc::S_IFREG as i32 | libc::O_WRONLY | libc::O_TRUNC, 0)
+        .await
+        .unwrap()
+        .fh;
+    fs.write(file_ino, fh, 0, &[0xcc; 27], 0, 0, None).await.unwrap();
+
+    // Another client replaces the object before we complete our upload
+    client.add_object(
+        "file.bin",
+        MockObject::constant(0xbb, 15, ETag::from_str("other_etag").unwrap()),
+    );
+
+    let err = fs
+        .release(file_ino, fh, 0, None, false)
+        .await
+        .expect_err("upload should only replace the object that was opened")
+        .to_errno();
+    assert_eq!(err, libc::EEXIST);
+
+    let object = client
+        .head_object(BUCKET_NAME, "file.bin", &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(object.object.etag, "other_etag");
+}
+
+#[tokio::test]
+async fn test_sse_customer_key() {
+    const BUCKET_NAME: &str = "test_sse_customer_key";
+
+    let sse_customer_key = SseCustomerKey::new(&[0x42; SseCustomerKey::KEY_LENGTH]).unwrap();
+    let fs_config = S3FilesystemConfig {
+        sse_customer_key: Some(sse_customer_key.clone()),
+        ..Default::default()
+    };
+    let (client, fs) = make_test_filesystem(BUCKET_NAME, &Default::default(), fs_config);
+
+    let mode = libc::S_IFREG | libc::S_IRWXU; // regular file + 0700 permissions
+    let dentry = fs
+        .mknod(FUSE_ROOT_INODE, "file.bin".as_ref(), mode, 0, 0)
+        .await
+        .unwrap();
+    let file_ino = dentry.attr.ino;
+    let fh = fs
+        .open(file_ino, libc::S_IFREG as i32 | libc::O_WRONLY, 0)
+        .await
+        .unwrap()
+        .fh;
+    fs.write(file_ino, fh, 0, &[0xaa; 27], 0, 0, None).await.unwrap();
+    fs.release(file_ino, fh, 0, None, false).await.unwrap();
+
+    // The object can only be read with the key it was uploaded with
+    client
+        .head_object(BUCKET_NAME, "file.bin", &Default::default())
+        .await
+        .expect_err("object should be encrypted with the key");
+
+    // A new file system has to look up and read the object from S3 again
+    let fs_config = S3FilesystemConfig {
+        sse_customer_key: Some(sse_customer_key),
+        ..Default::default()
+    };
+    let fs = make_test_filesystem_with_client(client, BUCKET_NAME, &Default::default(), fs_config);
+    let entry = fs.lookup(FUSE_ROOT_INODE, "file.bin".as_ref()).await.unwrap();
+    assert_eq!(entry.attr.size, 27);
+    let file_ino = entry.attr.ino;
+    let fh = fs
+        .open(file_ino, libc::S_IFREG as i32 | libc::O_RDONLY, 0)
+        .await
+        .unwrap()
+        .fh;
+    let bytes_read = fs
+        .read(file_ino, fh, 0, 4096, 0, None)
+        .await
+        .expect("fs read should succeed");
+    assert_eq!(&bytes_read[..], &[0xaa; 27]);
+    fs.release(file_ino, fh, 0, None, true).await.unwrap();
+}
+
 #[tokio::test]
 async fn test_upload_aborted_on_write_failure() {
     const BUCKET_NAME: &str = "test_upload_aborted_on_write_failure";